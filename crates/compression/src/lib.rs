@@ -121,14 +121,22 @@ impl From<CompressionAlgo> for HeaderValue {
     }
 }
 
-/// Compression
+/// Compression middleware.
+///
+/// The algorithm is negotiated with the request's `Accept-Encoding` header: the one with the
+/// highest quality value is used, and algorithms with the same quality are picked in the order
+/// they are enabled. Each algorithm has its own [`CompressionLevel`], for example
+/// `Compression::new().disable_all().enable_brotli(CompressionLevel::Precise(5)).enable_gzip(CompressionLevel::Fastest)`
+/// prefers Brotli at quality 5 and falls back to fast Gzip.
 #[derive(Clone, Debug)]
 #[non_exhaustive]
 pub struct Compression {
-    /// Compression algorithms to use.
+    /// Compression algorithms to use, in priority order.
     pub algos: IndexMap<CompressionAlgo, CompressionLevel>,
-    /// Content types to compress.
+    /// Content types to compress, an empty list compresses every content type.
     pub content_types: Vec<Mime>,
+    /// Content types never to compress, even if they match `content_types`.
+    pub skip_content_types: Vec<Mime>,
     /// Sets minimum compression size, if body less than this value, no compression.
     pub min_length: usize,
     /// Ignore request algorithms order in `Accept-Encoding` header and always server's config.
@@ -158,7 +166,8 @@ impl Default for Compression {
                 "application/xml".parse().expect("invalid mime type"),
                 "application/rss+xml".parse().expect("invalid mime type"),
             ],
-            min_length: 0,
+            skip_content_types: vec![],
+            min_length: 1024,
             force_priority: false,
         }
     }
@@ -245,8 +254,11 @@ impl Compression {
         self
     }
 
-    /// Sets minimum compression size, if body less than this value, no compression
-    /// default is 1kb
+    /// Sets minimum compression size, if body less than this value, no compression.
+    ///
+    /// The compressed output of small bodies is often bigger than the input. The length is known
+    /// for bodies set as bytes and for responses with a `Content-Length` header, other streams are
+    /// always compressed. Default is 1 KiB.
     #[inline]
     pub fn min_length(mut self, size: usize) -> Self {
         self.min_length = size;
@@ -260,12 +272,29 @@ impl Compression {
     }
 
     /// Sets `Compression` with content types list.
+    ///
+    /// A subtype of `*`, such as `text/*`, matches every subtype. The default list is `text/*`,
+    /// `application/javascript`, `application/json`, `image/svg+xml`, `application/wasm`,
+    /// `application/xml` and `application/rss+xml`, so already compressed formats such as
+    /// images, video and archives are not compressed again. Responses without `Content-Type` are
+    /// only compressed if the list is empty.
     #[inline]
     pub fn content_types(mut self, content_types: &[Mime]) -> Self {
         self.content_types = content_types.to_vec();
         self
     }
 
+    /// Sets content types which are never compressed, checked before
+    /// [`content_types`](Self::content_types).
+    ///
+    /// This is useful with an empty `content_types` list to compress everything except some
+    /// types, for example `image/*` and `video/*`.
+    #[inline]
+    pub fn skip_content_types(mut self, content_types: &[Mime]) -> Self {
+        self.skip_content_types = content_types.to_vec();
+        self
+    }
+
     fn negotiate(
         &self,
         req: &Request,
@@ -275,24 +304,20 @@ impl Compression {
             return None;
         }
 
-        if !self.content_types.is_empty() {
-            let content_type = res
-                .headers()
-                .get(CONTENT_TYPE)
-                .and_then(|v| v.to_str().ok())
-                .unwrap_or_default();
-            if content_type.is_empty() {
+        let content_type = res
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<Mime>().ok());
+        if let Some(content_type) = &content_type {
+            if mime_matches(&self.skip_content_types, content_type) {
                 return None;
             }
-            if let Ok(content_type) = content_type.parse::<Mime>() {
-                if !self.content_types.iter().any(|citem| {
-                    citem.type_() == content_type.type_()
-                        && (citem.subtype() == "*" || citem.subtype() == content_type.subtype())
-                }) {
-                    return None;
-                }
-            } else {
-                return None;
+        }
+        if !self.content_types.is_empty() {
+            match &content_type {
+                Some(content_type) if mime_matches(&self.content_types, content_type) => {}
+                _ => return None,
             }
         }
         let header = req
@@ -300,33 +325,45 @@ impl Compression {
             .get(ACCEPT_ENCODING)
             .and_then(|v| v.to_str().ok())?;
 
-        let accept_algos = http::parse_accept_encoding(header)
-            .into_iter()
-            .filter_map(|(algo, level)| {
-                if let Ok(algo) = algo.parse::<CompressionAlgo>() {
-                    Some((algo, level))
-                } else {
-                    None
-                }
-            })
-            .collect::<Vec<_>>();
-        if self.force_priority {
-            let accept_algos = accept_algos
-                .into_iter()
-                .map(|(algo, _)| algo)
-                .collect::<Vec<_>>();
-            self.algos
-                .iter()
-                .find(|(algo, _level)| accept_algos.contains(algo))
-                .map(|(algo, level)| (*algo, *level))
-        } else {
+        let accept_algos = http::parse_accept_encoding(header);
+        // `identity` and unknown algorithms are ignored, `*` matches every algorithm which is not
+        // listed explicitly, and a quality of `0` means not acceptable.
+        let quality = |algo: &CompressionAlgo| {
             accept_algos
-                .into_iter()
-                .find_map(|(algo, _)| self.algos.get(&algo).map(|level| (algo, *level)))
+                .iter()
+                .find(|(name, _)| name.parse::<CompressionAlgo>().ok().as_ref() == Some(algo))
+                .or_else(|| accept_algos.iter().find(|(name, _)| name == "*"))
+                .map(|(_, q)| *q)
+                .unwrap_or(0)
+        };
+        let mut selected: Option<(u8, CompressionAlgo, CompressionLevel)> = None;
+        for (algo, level) in &self.algos {
+            let q = quality(algo);
+            if q == 0 {
+                continue;
+            }
+            if self.force_priority {
+                return Some((*algo, *level));
+            }
+            // Algorithms with the same quality are chosen by the server's priority order.
+            if selected
+                .map(|(selected_q, _, _)| q > selected_q)
+                .unwrap_or(true)
+            {
+                selected = Some((q, *algo, *level));
+            }
         }
+        selected.map(|(_, algo, level)| (algo, level))
     }
 }
 
+fn mime_matches(list: &[Mime], content_type: &Mime) -> bool {
+    list.iter().any(|item| {
+        item.type_() == content_type.type_()
+            && (item.subtype() == "*" || item.subtype() == content_type.subtype())
+    })
+}
+
 #[async_trait]
 impl Handler for Compression {
     async fn handle(
@@ -346,6 +383,17 @@ impl Handler for Compression {
                 return;
             }
         }
+        let content_length = res
+            .headers()
+            .get(CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<usize>().ok());
+        if content_length
+            .map(|len| len < self.min_length)
+            .unwrap_or(false)
+        {
+            return;
+        }
 
         match res.take_body() {
             ResBody::None => {
@@ -458,6 +506,100 @@ mod tests {
         assert_eq!(content, "hello");
     }
 
+    #[tokio::test]
+    async fn test_zstd() {
+        let comp_handler = Compression::new().min_length(1);
+        let router = Router::with_hoop(comp_handler).push(Router::with_path("hello").get(hello));
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/hello")
+            .add_header(ACCEPT_ENCODING, "zstd", true)
+            .send(router)
+            .await;
+        assert_eq!(res.headers().get(CONTENT_ENCODING).unwrap(), "zstd");
+        let content = res.take_string().await.unwrap();
+        assert_eq!(content, "hello");
+    }
+
+    async fn negotiated(
+        comp_handler: Compression,
+        accept_encoding: &'static str,
+    ) -> Option<String> {
+        let router = Router::with_hoop(comp_handler.min_length(1))
+            .push(Router::with_path("hello").get(hello));
+        let res = TestClient::get("http://127.0.0.1:5801/hello")
+            .add_header(ACCEPT_ENCODING, accept_encoding, true)
+            .send(router)
+            .await;
+        res.headers()
+            .get(CONTENT_ENCODING)
+            .map(|v| v.to_str().unwrap().to_owned())
+    }
+
+    #[tokio::test]
+    async fn test_negotiate() {
+        let comp_handler = Compression::new()
+            .disable_all()
+            .enable_brotli(CompressionLevel::Precise(5))
+            .enable_zstd(CompressionLevel::Fastest)
+            .enable_gzip(CompressionLevel::Default);
+        let negotiate = |accept_encoding| negotiated(comp_handler.clone(), accept_encoding);
+        // Same quality, the server's priority order wins.
+        assert_eq!(negotiate("gzip, zstd, br").await.as_deref(), Some("br"));
+        assert_eq!(negotiate("gzip, zstd").await.as_deref(), Some("zstd"));
+        // Higher quality wins.
+        assert_eq!(negotiate("br;q=0.5, gzip").await.as_deref(), Some("gzip"));
+        // Not acceptable.
+        assert_eq!(negotiate("br;q=0, gzip;q=0").await, None);
+        assert_eq!(negotiate("identity").await, None);
+        assert_eq!(negotiate("*;q=0.5, br;q=0").await.as_deref(), Some("zstd"));
+
+        let comp_handler = comp_handler.force_priority(true);
+        assert_eq!(
+            negotiated(comp_handler, "gzip, br;q=0.1").await.as_deref(),
+            Some("br")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_content_types() {
+        #[handler]
+        async fn image(res: &mut Response) {
+            res.add_header(CONTENT_TYPE, "image/png", true).unwrap();
+            res.write_body(vec![0u8; 2048]).unwrap();
+        }
+        #[handler]
+        async fn json(res: &mut Response) {
+            res.render(Text::Json("[1, 2, 3]".repeat(512)));
+        }
+        let negotiate = |comp_handler: Compression, path: &'static str| async move {
+            let router = Router::with_hoop(comp_handler)
+                .push(Router::with_path("image").get(image))
+                .push(Router::with_path("json").get(json))
+                .push(Router::with_path("hello").get(hello));
+            let res = TestClient::get(format!("http://127.0.0.1:5801/{path}"))
+                .add_header(ACCEPT_ENCODING, "gzip", true)
+                .send(router)
+                .await;
+            res.headers().contains_key(CONTENT_ENCODING)
+        };
+
+        assert!(negotiate(Compression::new(), "json").await);
+        assert!(!negotiate(Compression::new(), "image").await);
+        // Below the default minimum length.
+        assert!(!negotiate(Compression::new().content_types(&[]), "hello").await);
+
+        let comp_handler = Compression::new()
+            .content_types(&[])
+            .min_length(1)
+            .skip_content_types(&["image/*".parse().unwrap()]);
+        assert!(!negotiate(comp_handler.clone(), "image").await);
+        assert!(negotiate(comp_handler.clone(), "json").await);
+        assert!(negotiate(comp_handler, "hello").await);
+
+        let comp_handler = Compression::new().skip_content_types(&[mime::APPLICATION_JSON]);
+        assert!(!negotiate(comp_handler, "json").await);
+    }
+
     #[tokio::test]
     async fn test_deflate() {
         let comp_handler = Compression::new().min_length(1);
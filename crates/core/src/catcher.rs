@@ -33,6 +33,10 @@
 //!
 //! You can add multiple custom error catching handlers to [`Catcher`] through [`Catcher::hoop`]. The custom error
 //! handler can call [`FlowCtrl::skip_rest()`] method to skip next error handlers and return early.
+//!
+//! To handle the requests which match no route with your own handler and the middlewares of the
+//! routers, such as rendering a JSON error under `/api`, use [`Router::fallback`](crate::Router::fallback).
+//! The catcher still runs after the fallback if it leaves the body empty.
 
 use std::borrow::Cow;
 use std::sync::{Arc, LazyLock};
@@ -275,7 +275,8 @@ cfg_feature! {
     }
 }
 
-/// AcmeAcceptor
+/// A wrapper of `Acceptor` which serves TLS with the certificates issued by ACME, it's created by
+/// binding an [`AcmeListener`].
 pub struct AcmeAcceptor<T> {
     config: Arc<AcmeConfig>,
     server_config: Arc<ServerConfig>,
@@ -343,6 +344,9 @@ where
         });
         Ok(acceptor)
     }
+
+    /// Get the rustls server config, its certificate is replaced when it is renewed.
+    #[inline]
     pub fn server_config(&self) -> Arc<ServerConfig> {
         self.server_config.clone()
     }
@@ -376,4 +380,9 @@ where
             http_scheme: Scheme::HTTPS,
         })
     }
+
+    #[inline]
+    async fn shutdown(&mut self) {
+        self.inner.shutdown().await;
+    }
 }
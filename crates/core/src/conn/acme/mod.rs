@@ -3,6 +3,8 @@
 //! Reference: <https://datatracker.ietf.org/doc/html/rfc8555>
 //! Reference: <https://datatracker.ietf.org/doc/html/rfc8737>
 //!
+//! [`Server::bind_acme`](crate::Server::bind_acme) is a shortcut of the TLS-ALPN-01 example below.
+//!
 //! * HTTP-01
 //!
 //! # Example
@@ -75,7 +77,7 @@ use crate::http::StatusError;
 use crate::{async_trait, Depot, FlowCtrl, Handler, Request, Response};
 use cache::AcmeCache;
 pub use config::{AcmeConfig, AcmeConfigBuilder};
-pub use listener::AcmeListener;
+pub use listener::{AcmeAcceptor, AcmeListener};
 cfg_feature! {
     #![feature = "quinn"]
     pub use listener::AcmeQuinnListener;
@@ -1,5 +1,6 @@
 //! Socket Address module.
 use std::fmt::{self, Display, Formatter};
+use std::hash::{Hash, Hasher};
 #[cfg(unix)]
 use std::sync::Arc;
 
@@ -18,6 +19,40 @@ pub enum SocketAddr {
     #[cfg_attr(docsrs, doc(cfg(unix)))]
     Unix(Arc<tokio::net::unix::SocketAddr>),
 }
+impl PartialEq for SocketAddr {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (SocketAddr::Unknown, SocketAddr::Unknown) => true,
+            (SocketAddr::IPv4(a), SocketAddr::IPv4(b)) => a == b,
+            (SocketAddr::IPv6(a), SocketAddr::IPv6(b)) => a == b,
+            // Unnamed unix sockets have no path to compare, so they are only equal to themselves.
+            #[cfg(unix)]
+            (SocketAddr::Unix(a), SocketAddr::Unix(b)) => {
+                match (a.as_pathname(), b.as_pathname()) {
+                    (Some(a), Some(b)) => a == b,
+                    (None, None) => Arc::ptr_eq(a, b),
+                    _ => false,
+                }
+            }
+            _ => false,
+        }
+    }
+}
+impl Eq for SocketAddr {}
+impl Hash for SocketAddr {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            SocketAddr::Unknown => {}
+            SocketAddr::IPv4(addr) => addr.hash(state),
+            SocketAddr::IPv6(addr) => addr.hash(state),
+            #[cfg(unix)]
+            SocketAddr::Unix(addr) => addr.as_pathname().hash(state),
+        }
+    }
+}
+
 impl From<std::net::SocketAddr> for SocketAddr {
     #[inline]
     fn from(addr: std::net::SocketAddr) -> Self {
@@ -102,6 +137,29 @@ impl SocketAddr {
         }
     }
 
+    /// Returns the port number of an ip socket address.
+    ///
+    /// Returns `None` for unknown and unix socket addresses.
+    #[inline]
+    pub fn port(&self) -> Option<u16> {
+        match self {
+            SocketAddr::IPv4(addr) => Some(addr.port()),
+            SocketAddr::IPv6(addr) => Some(addr.port()),
+            _ => None,
+        }
+    }
+    /// Returns the ip address of an ip socket address.
+    ///
+    /// Returns `None` for unknown and unix socket addresses.
+    #[inline]
+    pub fn ip(&self) -> Option<std::net::IpAddr> {
+        match self {
+            SocketAddr::IPv4(addr) => Some((*addr.ip()).into()),
+            SocketAddr::IPv6(addr) => Some((*addr.ip()).into()),
+            _ => None,
+        }
+    }
+
     cfg_feature! {
         #![unix]
         /// Returns unix socket address.
@@ -143,6 +201,8 @@ mod tests {
         #[cfg(target_os = "linux")]
         assert!(!ipv4.is_unix());
         assert_eq!(ipv4.as_ipv4().unwrap().to_string(), "127.0.0.1:8080");
+        assert_eq!(ipv4.port(), Some(8080));
+        assert_eq!(ipv4.ip().unwrap().to_string(), "127.0.0.1");
         assert!(ipv4.as_ipv6().is_none());
         #[cfg(target_os = "linux")]
         assert!(ipv4.as_unix().is_none());
@@ -161,7 +221,64 @@ mod tests {
         assert!(!ipv6.is_unix());
         assert!(ipv6.as_ipv4().is_none());
         assert_eq!(ipv6.as_ipv6().unwrap().to_string(), "[::ffff:0.0.0.1]:8080");
+        assert_eq!(ipv6.port(), Some(8080));
+        assert_eq!(ipv6.ip().unwrap().to_string(), "::ffff:0.0.0.1");
         #[cfg(target_os = "linux")]
         assert!(ipv6.as_unix().is_none());
     }
+
+    #[test]
+    fn test_addr_unknown() {
+        let addr = SocketAddr::Unknown;
+        assert!(addr.port().is_none());
+        assert!(addr.ip().is_none());
+    }
+
+    #[test]
+    fn test_addr_eq_and_hash() {
+        use std::collections::HashSet;
+
+        let a: SocketAddr = "127.0.0.1:8080"
+            .parse::<std::net::SocketAddr>()
+            .unwrap()
+            .into();
+        let b: SocketAddr = "127.0.0.1:8080"
+            .parse::<std::net::SocketAddr>()
+            .unwrap()
+            .into();
+        let c: SocketAddr = "[fe80::1%2]:8080"
+            .parse::<std::net::SocketAddr>()
+            .unwrap()
+            .into();
+        let d: SocketAddr = "[fe80::1%3]:8080"
+            .parse::<std::net::SocketAddr>()
+            .unwrap()
+            .into();
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_ne!(c, d);
+        assert_eq!(SocketAddr::Unknown, SocketAddr::Unknown);
+
+        let set: HashSet<SocketAddr> = [a, b, c, d, SocketAddr::Unknown].into_iter().collect();
+        assert_eq!(set.len(), 4);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_addr_unix_eq() {
+        let sock_file = "/tmp/test-salvo-addr-eq.sock";
+        let _ = std::fs::remove_file(sock_file);
+        let listener = tokio::net::UnixListener::bind(sock_file).unwrap();
+        let a: SocketAddr = listener.local_addr().unwrap().into();
+        let b: SocketAddr = listener.local_addr().unwrap().into();
+        assert_eq!(a, b);
+
+        let (left, right) = tokio::net::UnixStream::pair().unwrap();
+        let unnamed: SocketAddr = left.local_addr().unwrap().into();
+        let other: SocketAddr = right.local_addr().unwrap().into();
+        assert_eq!(unnamed, unnamed.clone());
+        assert_ne!(unnamed, other);
+        assert_ne!(a, unnamed);
+        let _ = std::fs::remove_file(sock_file);
+    }
 }
@@ -0,0 +1,212 @@
+//! Idle timeout of keep-alive connections.
+use std::future::Future;
+use std::pin::{pin, Pin};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use http::{Request, Response, StatusCode};
+use hyper::service::Service;
+use pin_project::pin_project;
+use tokio::sync::Notify;
+
+use crate::http::body::{Body, Frame, SizeHint};
+
+/// Requests in flight on a connection, used to detect when the connection is idle.
+#[derive(Debug, Default)]
+pub(crate) struct IdleState {
+    in_flight: AtomicUsize,
+    served: AtomicBool,
+    upgraded: AtomicBool,
+    notify: Notify,
+}
+impl IdleState {
+    fn enter(self: &Arc<Self>) -> InFlight {
+        self.in_flight.fetch_add(1, Ordering::AcqRel);
+        self.notify.notify_waiters();
+        InFlight(self.clone())
+    }
+
+    /// Returns `true` if the connection has served a response, all the responses have been
+    /// sent and it is not upgraded.
+    fn is_idle(&self) -> bool {
+        self.served.load(Ordering::Acquire)
+            && !self.upgraded.load(Ordering::Acquire)
+            && self.in_flight.load(Ordering::Acquire) == 0
+    }
+
+    /// Completes when the connection has been idle for `timeout`.
+    ///
+    /// The timeout starts when the last response is sent, a new request resets it. The time before
+    /// the first request is not covered, it is limited by the accept timeout of the listener.
+    pub(crate) async fn timed_out(&self, timeout: Duration) {
+        loop {
+            let mut notified = pin!(self.notify.notified());
+            notified.as_mut().enable();
+            if !self.is_idle() {
+                notified.await;
+                continue;
+            }
+            if tokio::time::timeout(timeout, notified).await.is_err() && self.is_idle() {
+                return;
+            }
+        }
+    }
+}
+
+/// Guard of a request in flight, it is dropped when the response body is sent or dropped.
+#[derive(Debug)]
+struct InFlight(Arc<IdleState>);
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.0.served.store(true, Ordering::Release);
+        self.0.in_flight.fetch_sub(1, Ordering::AcqRel);
+        self.0.notify.notify_waiters();
+    }
+}
+
+/// Service which tracks the requests in flight of a connection.
+pub(crate) struct IdleService<S> {
+    inner: S,
+    state: Option<Arc<IdleState>>,
+}
+impl<S> IdleService<S> {
+    pub(crate) fn new(inner: S, state: Option<Arc<IdleState>>) -> Self {
+        Self { inner, state }
+    }
+}
+impl<S, ReqBody, B> Service<Request<ReqBody>> for IdleService<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<B>>,
+{
+    type Response = Response<IdleBody<B>>;
+    type Error = S::Error;
+    type Future = IdleFuture<S::Future>;
+
+    fn call(&self, req: Request<ReqBody>) -> Self::Future {
+        IdleFuture {
+            inner: self.inner.call(req),
+            in_flight: self.state.as_ref().map(IdleState::enter),
+        }
+    }
+}
+
+#[pin_project]
+pub(crate) struct IdleFuture<F> {
+    #[pin]
+    inner: F,
+    in_flight: Option<InFlight>,
+}
+impl<F, B, E> Future for IdleFuture<F>
+where
+    F: Future<Output = Result<Response<B>, E>>,
+{
+    type Output = Result<Response<IdleBody<B>>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let res = match this.inner.poll(cx) {
+            Poll::Ready(res) => res,
+            Poll::Pending => return Poll::Pending,
+        };
+        let in_flight = this.in_flight.take();
+        Poll::Ready(res.map(|res| {
+            // WebSocket and other upgraded connections are never idle.
+            if let Some(in_flight) = &in_flight {
+                if res.status() == StatusCode::SWITCHING_PROTOCOLS {
+                    in_flight.0.upgraded.store(true, Ordering::Release);
+                }
+            }
+            res.map(|inner| IdleBody {
+                inner,
+                _in_flight: in_flight,
+            })
+        }))
+    }
+}
+
+/// Response body which keeps its request in flight until it is sent, so SSE and other streamed
+/// responses don't make the connection idle.
+#[pin_project]
+pub(crate) struct IdleBody<B> {
+    #[pin]
+    inner: B,
+    _in_flight: Option<InFlight>,
+}
+impl<B> Body for IdleBody<B>
+where
+    B: Body,
+{
+    type Data = B::Data;
+    type Error = B::Error;
+
+    #[inline]
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        self.project().inner.poll_frame(cx)
+    }
+    #[inline]
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+    #[inline]
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use http_body_util::{BodyExt, Empty};
+
+    use super::*;
+
+    struct Respond(StatusCode);
+    impl Service<Request<()>> for Respond {
+        type Response = Response<Empty<bytes::Bytes>>;
+        type Error = Infallible;
+        type Future = std::future::Ready<Result<Self::Response, Infallible>>;
+
+        fn call(&self, _req: Request<()>) -> Self::Future {
+            let mut res = Response::new(Empty::new());
+            *res.status_mut() = self.0;
+            std::future::ready(Ok(res))
+        }
+    }
+
+    async fn is_timed_out(state: &IdleState) -> bool {
+        tokio::time::timeout(
+            Duration::from_millis(200),
+            state.timed_out(Duration::from_millis(50)),
+        )
+        .await
+        .is_ok()
+    }
+
+    #[tokio::test]
+    async fn test_idle_state() {
+        let state = Arc::new(IdleState::default());
+        let service = IdleService::new(Respond(StatusCode::OK), Some(state.clone()));
+        // Not idle before the first response.
+        assert!(!is_timed_out(&state).await);
+
+        let res = service.call(Request::new(())).await.unwrap();
+        // The response body is not sent yet.
+        assert!(!is_timed_out(&state).await);
+        res.into_body().collect().await.unwrap();
+        assert!(is_timed_out(&state).await);
+
+        let state = Arc::new(IdleState::default());
+        let service = IdleService::new(
+            Respond(StatusCode::SWITCHING_PROTOCOLS),
+            Some(state.clone()),
+        );
+        drop(service.call(Request::new(())).await.unwrap());
+        assert!(!is_timed_out(&state).await);
+    }
+}
@@ -15,7 +15,7 @@ use crate::service::HyperHandler;
 
 use super::{Accepted, Acceptor, Listener};
 
-/// A I/O stream for JoinedListener.
+/// A I/O stream for JoinedListener, it is either a connection of the first or the second acceptor.
 pub enum JoinedStream<A, B> {
     #[allow(missing_docs)]
     A(A),
@@ -109,6 +109,11 @@ where
     }
 }
 
+/// `JoinedAcceptor` is an acceptor that accepts connections from two acceptors.
+///
+/// Both acceptors are polled at the same time and, when both have a connection ready, one is
+/// picked at random so neither side is starved. Join more than two by nesting, for example
+/// `a.join(b).join(c)`; the holdings of all of them are reported together.
 pub struct JoinedAcceptor<A, B> {
     a: A,
     b: B,
@@ -116,6 +121,7 @@ pub struct JoinedAcceptor<A, B> {
 }
 
 impl<A, B> JoinedAcceptor<A, B> {
+    /// Create a new `JoinedAcceptor`.
     pub fn new(a: A, b: B, holdings: Vec<Holding>) -> Self {
         JoinedAcceptor { a, b, holdings }
     }
@@ -138,7 +144,10 @@ where
         }
     }
     fn fusewire(&self) -> Option<ArcFusewire> {
-        None
+        match self {
+            JoinedStream::A(a) => a.fusewire(),
+            JoinedStream::B(b) => b.fusewire(),
+        }
     }
 }
 
@@ -170,6 +179,21 @@ where
             }
         }
     }
+
+    #[inline]
+    async fn shutdown(&mut self) {
+        self.a.shutdown().await;
+        self.b.shutdown().await;
+    }
+
+    #[inline]
+    fn try_shard(&self) -> IoResult<Self> {
+        Ok(JoinedAcceptor::new(
+            self.a.try_shard()?,
+            self.b.try_shard()?,
+            self.holdings.clone(),
+        ))
+    }
 }
 
 #[cfg(test)]
@@ -202,4 +226,36 @@ mod tests {
         let second = conn.read_i32().await.unwrap();
         assert_eq!(first + second, 150);
     }
+
+    #[tokio::test]
+    async fn test_joined_acceptor() {
+        let addr1 = std::net::SocketAddr::from(([127, 0, 0, 1], 6980));
+        let addr2 = std::net::SocketAddr::from(([127, 0, 0, 1], 6981));
+        let addr3 = std::net::SocketAddr::from(([127, 0, 0, 1], 6982));
+
+        let mut acceptor = TcpListener::new(addr1)
+            .bind()
+            .await
+            .join(TcpListener::new(addr2).bind().await)
+            .join(TcpListener::new(addr3).bind().await);
+        let ports = acceptor
+            .holdings()
+            .iter()
+            .map(|h| h.local_addr.port().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(ports, vec![6980, 6981, 6982]);
+
+        tokio::spawn(async move {
+            for (addr, value) in [(addr1, 1), (addr2, 10), (addr3, 100)] {
+                let mut stream = TcpStream::connect(addr).await.unwrap();
+                stream.write_i32(value).await.unwrap();
+            }
+        });
+        let mut sum = 0;
+        for _ in 0..3 {
+            let Accepted { mut conn, .. } = acceptor.accept(None).await.unwrap();
+            sum += conn.read_i32().await.unwrap();
+        }
+        assert_eq!(sum, 111);
+    }
 }
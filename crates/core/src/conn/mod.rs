@@ -5,18 +5,30 @@
 //! Additionally, it includes implementations for Unix domain sockets.
 use std::fmt::{self, Display, Formatter};
 use std::future::Future;
-use std::io::Result as IoResult;
+use std::io::{Error as IoError, ErrorKind, Result as IoResult};
 
 use http::uri::Scheme;
 use tokio::io::{AsyncRead, AsyncWrite};
 
-use crate::fuse::ArcFuseFactory;
+use crate::fuse::{ArcFuseFactory, TransProto};
 use crate::http::{HttpConnection, Version};
 
+#[cfg(any(feature = "http1", feature = "http2"))]
+mod idle;
 mod proto;
 pub use proto::HttpBuilder;
 mod stream;
 pub use stream::*;
+mod tls_info;
+pub use tls_info::TlsInfo;
+pub(crate) use tls_info::TlsInfoSlot;
+#[cfg(any(
+    feature = "native-tls",
+    feature = "rustls",
+    feature = "openssl",
+    feature = "acme"
+))]
+pub(crate) use tls_info::{TlsInfoSource, PEER_VERIFIED};
 
 cfg_feature! {
     #![feature = "acme"]
@@ -55,14 +67,21 @@ cfg_feature! {
     #![unix]
     pub mod unix;
 }
+cfg_feature! {
+    #![feature = "systemd"]
+    pub mod systemd;
+}
 pub mod addr;
 pub use addr::SocketAddr;
 
 pub mod tcp;
 pub use tcp::TcpListener;
 
+pub mod proxy_protocol;
+pub use proxy_protocol::ProxyProtocolListener;
+
 mod joined;
-pub use joined::JoinedListener;
+pub use joined::{JoinedAcceptor, JoinedListener, JoinedStream};
 
 cfg_feature! {
     #![unix]
@@ -131,11 +150,64 @@ pub trait Acceptor {
     /// Returns the holding information that this listener is bound to.
     fn holdings(&self) -> &[Holding];
 
+    /// Returns the local addresses that this listener is bound to.
+    ///
+    /// They are the addresses assigned by the OS, so the real port is returned when the listener
+    /// is bound to port `0`.
+    #[inline]
+    fn local_addrs(&self) -> Vec<&SocketAddr> {
+        self.holdings()
+            .iter()
+            .map(|holding| &holding.local_addr)
+            .collect()
+    }
+
     /// Accepts a new incoming connection from this listener.
     fn accept(
         &mut self,
         fuse_factory: Option<ArcFuseFactory>,
     ) -> impl Future<Output = IoResult<Accepted<Self::Conn>>> + Send;
+
+    /// Stops accepting new incoming connections.
+    ///
+    /// Connections which have already been returned by [`Acceptor::accept`] are not affected and
+    /// keep being served until they finish. Once `shutdown` has completed, the underlying listener
+    /// is released and any further call to `accept` returns an error, which is expected.
+    ///
+    /// The default implementation does nothing.
+    fn shutdown(&mut self) -> impl Future<Output = ()> + Send {
+        async {}
+    }
+
+    /// Creates another acceptor which accepts connections on the same addresses with its own
+    /// sockets, used by [`Server::with_accept_shards`](crate::Server::with_accept_shards).
+    ///
+    /// The default implementation returns an `ErrorKind::Unsupported` error.
+    fn try_shard(&self) -> IoResult<Self>
+    where
+        Self: Sized,
+    {
+        Err(IoError::new(
+            ErrorKind::Unsupported,
+            "accept shards are not supported by this acceptor",
+        ))
+    }
+
+    /// Join current Acceptor with the other, so that one server accepts connections from both.
+    #[inline]
+    fn join<T>(self, other: T) -> JoinedAcceptor<Self, T>
+    where
+        Self: Sized + Send,
+        T: Acceptor,
+    {
+        let holdings = self
+            .holdings()
+            .iter()
+            .chain(other.holdings())
+            .cloned()
+            .collect();
+        JoinedAcceptor::new(self, other, holdings)
+    }
 }
 
 /// Holding information.
@@ -149,6 +221,17 @@ pub struct Holding {
     /// Http scheme.
     pub http_scheme: Scheme,
 }
+impl Holding {
+    /// Transport protocol of this holding, only QUIC listeners serve HTTP/3.
+    #[inline]
+    pub fn trans_proto(&self) -> TransProto {
+        if self.http_versions.contains(&Version::HTTP_3) {
+            TransProto::Quic
+        } else {
+            TransProto::Tcp
+        }
+    }
+}
 impl Display for Holding {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(
@@ -178,4 +178,9 @@ where
             http_scheme: Scheme::HTTPS,
         })
     }
+
+    #[inline]
+    async fn shutdown(&mut self) {
+        self.inner.shutdown().await;
+    }
 }
@@ -4,12 +4,15 @@ use std::fs::File;
 use std::future::{Ready, ready};
 use std::io::{Error as IoError, Read, Result as IoResult};
 use std::path::Path;
+use std::pin::Pin;
+use std::task::{Context, Poll};
 
 use futures_util::stream::{once, Once, Stream};
 use openssl::pkey::PKey;
 use openssl::ssl::{SslAcceptor, SslMethod, SslRef};
 use openssl::x509::X509;
 use tokio::io::ErrorKind;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
 
 use crate::conn::IntoConfigStream;
 
@@ -211,3 +214,71 @@ where
         self
     }
 }
+
+/// A handle to replace the tls config of a running [`OpensslAcceptor`](super::OpensslAcceptor).
+///
+/// New handshakes use the latest config, while connections that are already established keep
+/// the certificate they were accepted with.
+///
+/// ```no_run
+/// use salvo_core::conn::openssl::{Keycert, OpensslConfig, OpensslReloader};
+/// use salvo_core::prelude::*;
+///
+/// # async fn run() -> std::io::Result<()> {
+/// let keycert = Keycert::new().key_from_path("key.pem")?.cert_from_path("cert.pem")?;
+/// let (reloader, config_stream) = OpensslReloader::new(OpensslConfig::new(keycert))?;
+/// let acceptor = TcpListener::new("0.0.0.0:443").openssl(config_stream).bind().await;
+/// tokio::spawn(async move {
+///     // Later, when the certificate files are renewed:
+///     let keycert = Keycert::new().key_from_path("key.pem")?.cert_from_path("cert.pem")?;
+///     reloader.reload(OpensslConfig::new(keycert))
+/// });
+/// Server::new(acceptor).serve(Router::new()).await;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct OpensslReloader {
+    sender: UnboundedSender<SslAcceptorBuilder>,
+}
+
+impl OpensslReloader {
+    /// Create a new `OpensslReloader` and the config stream to pass to the listener.
+    pub fn new(config: OpensslConfig) -> IoResult<(Self, OpensslReloadStream)> {
+        let (sender, receiver) = unbounded_channel();
+        let reloader = Self { sender };
+        reloader.reload(config)?;
+        Ok((reloader, OpensslReloadStream { receiver }))
+    }
+
+    /// Build `config` and use it for all following handshakes.
+    ///
+    /// Returns an error if the config is invalid, in which case the current config is kept, or if
+    /// the acceptor has been dropped.
+    pub fn reload(&self, mut config: OpensslConfig) -> IoResult<()> {
+        let builder = config.create_acceptor_builder()?;
+        self.sender
+            .send(builder)
+            .map_err(|_| IoError::new(ErrorKind::BrokenPipe, "openssl acceptor is closed"))
+    }
+}
+
+/// Config stream created by [`OpensslReloader::new`].
+pub struct OpensslReloadStream {
+    receiver: UnboundedReceiver<SslAcceptorBuilder>,
+}
+
+impl Debug for OpensslReloadStream {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("OpensslReloadStream").finish()
+    }
+}
+
+impl Stream for OpensslReloadStream {
+    type Item = SslAcceptorBuilder;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx)
+    }
+}
@@ -114,21 +114,13 @@ where
     }
 }
 
-impl<S, C, T, E> Acceptor for OpensslAcceptor<S, C, T, E>
+impl<S, C, T, E> OpensslAcceptor<S, C, T, E>
 where
     S: Stream<Item = C> + Send + Unpin + 'static,
     C: TryInto<SslAcceptorBuilder, Error = E> + Send + 'static,
-    T: Acceptor + Send + 'static,
     E: StdError + Send,
 {
-    type Conn = HandshakeStream<SslStream<T::Conn>>;
-
-    /// Get the local address bound to this listener.
-    fn holdings(&self) -> &[Holding] {
-        &self.holdings
-    }
-
-    async fn accept(&mut self, fuse_factory: Option<ArcFuseFactory>) -> IoResult<Accepted<Self::Conn>> {
+    fn update_config(&mut self) {
         let config = {
             let mut config = None;
             while let Poll::Ready(Some(item)) = self
@@ -152,10 +144,28 @@ where
                 Err(e) => tracing::error!(error = ?e, "openssl: invalid tls config."),
             }
         }
-        let tls_acceptor = match &self.tls_acceptor {
-            Some(tls_acceptor) => tls_acceptor.clone(),
-            None => return Err(IoError::new(ErrorKind::Other, "openssl: tls_acceptor is none.")),
-        };
+    }
+}
+
+impl<S, C, T, E> Acceptor for OpensslAcceptor<S, C, T, E>
+where
+    S: Stream<Item = C> + Send + Unpin + 'static,
+    C: TryInto<SslAcceptorBuilder, Error = E> + Send + 'static,
+    T: Acceptor + Send + 'static,
+    E: StdError + Send,
+{
+    type Conn = HandshakeStream<SslStream<T::Conn>>;
+
+    /// Get the local address bound to this listener.
+    fn holdings(&self) -> &[Holding] {
+        &self.holdings
+    }
+
+    async fn accept(&mut self, fuse_factory: Option<ArcFuseFactory>) -> IoResult<Accepted<Self::Conn>> {
+        self.update_config();
+        if self.tls_acceptor.is_none() {
+            return Err(IoError::new(ErrorKind::Other, "openssl: tls_acceptor is none."));
+        }
 
         let Accepted {
             conn,
@@ -163,6 +173,12 @@ where
             remote_addr,
             ..
         } = self.inner.accept(fuse_factory).await?;
+        // Pick up configs pushed while waiting, so that a reload applies to the very next handshake.
+        self.update_config();
+        let tls_acceptor = self
+            .tls_acceptor
+            .clone()
+            .ok_or_else(|| IoError::new(ErrorKind::Other, "openssl: tls_acceptor is none."))?;
         let fusewire = conn.fusewire();
         let conn = async move {
             let ssl =
@@ -183,4 +199,9 @@ where
             http_scheme: Scheme::HTTPS,
         })
     }
+
+    #[inline]
+    async fn shutdown(&mut self) {
+        self.inner.shutdown().await;
+    }
 }
@@ -1,6 +1,6 @@
 //! OpensslListener and utils.
 mod config;
-pub use config::{Keycert, OpensslConfig, SslAcceptorBuilder};
+pub use config::{Keycert, OpensslConfig, OpensslReloadStream, OpensslReloader, SslAcceptorBuilder};
 
 mod listener;
 pub use listener::{OpensslAcceptor, OpensslListener};
@@ -52,4 +52,52 @@ mod tests {
         let Accepted { mut conn, .. } = acceptor.accept(None).await.unwrap();
         assert_eq!(conn.read_i32().await.unwrap(), 518);
     }
+
+    #[tokio::test]
+    async fn test_openssl_reloader() {
+        let keycert = || {
+            Keycert::new()
+                .key_from_path("certs/key.pem")
+                .unwrap()
+                .cert_from_path("certs/cert.pem")
+                .unwrap()
+        };
+        let (reloader, config_stream) =
+            OpensslReloader::new(OpensslConfig::new(keycert()).alpn_protocols(b"\x08http/1.1".as_slice())).unwrap();
+        let mut acceptor = TcpListener::new("127.0.0.1:0").openssl(config_stream).bind().await;
+        let addr = acceptor.holdings()[0].local_addr.clone().into_std().unwrap();
+
+        let connect = move || async move {
+            let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+            connector.set_ca_file("certs/chain.pem").unwrap();
+            connector.set_alpn_protos(b"\x02h2\x08http/1.1").unwrap();
+            let ssl = connector
+                .build()
+                .configure()
+                .unwrap()
+                .into_ssl("testserver.com")
+                .unwrap();
+            let stream = TcpStream::connect(addr).await.unwrap();
+            let mut tls_stream = SslStream::new(ssl, stream).unwrap();
+            Pin::new(&mut tls_stream).connect().await.unwrap();
+            let negotiated = tls_stream.ssl().selected_alpn_protocol().map(|p| p.to_vec());
+            tls_stream.write_i32(518).await.unwrap();
+            negotiated
+        };
+
+        let client = tokio::spawn(connect());
+        let Accepted { mut conn, .. } = acceptor.accept(None).await.unwrap();
+        assert_eq!(conn.read_i32().await.unwrap(), 518);
+        assert_eq!(client.await.unwrap().as_deref(), Some(&b"http/1.1"[..]));
+
+        reloader
+            .reload(OpensslConfig::new(keycert()).alpn_protocols(b"\x02h2".as_slice()))
+            .unwrap();
+        assert!(reloader.reload(OpensslConfig::new(Keycert::new())).is_err());
+
+        let client = tokio::spawn(connect());
+        let Accepted { mut conn, .. } = acceptor.accept(None).await.unwrap();
+        assert_eq!(conn.read_i32().await.unwrap(), 518);
+        assert_eq!(client.await.unwrap().as_deref(), Some(&b"h2"[..]));
+    }
 }
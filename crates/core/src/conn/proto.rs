@@ -4,7 +4,10 @@ use std::future::Future;
 use std::io::{Error as IoError, ErrorKind, IoSlice, Result as IoResult};
 use std::marker::PhantomPinned;
 use std::pin::Pin;
+#[cfg(any(feature = "http1", feature = "http2"))]
+use std::sync::Arc;
 use std::task::{self, ready, Context, Poll};
+use std::time::Duration;
 
 use bytes::{Buf, Bytes};
 
@@ -14,6 +17,8 @@ use pin_project::pin_project;
 use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
 use tokio_util::sync::CancellationToken;
 
+#[cfg(any(feature = "http1", feature = "http2"))]
+use crate::conn::idle::{IdleService, IdleState};
 use crate::fuse::ArcFusewire;
 use crate::http::body::{Body, HyperBody};
 #[cfg(any(feature = "http1", feature = "http2"))]
@@ -33,7 +38,8 @@ use crate::conn::quinn;
 
 const H2_PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
 
-#[doc(hidden)]
+/// Builder of the HTTP protocols used to serve the connections, pass it to
+/// [`Server::with_http_builder`](crate::Server::with_http_builder).
 pub struct HttpBuilder {
     #[cfg(feature = "http1")]
     pub(crate) http1: http1::Builder,
@@ -41,6 +47,7 @@ pub struct HttpBuilder {
     pub(crate) http2: http2::Builder<TokioExecutor>,
     #[cfg(feature = "quinn")]
     pub(crate) quinn: quinn::Builder,
+    pub(crate) idle_timeout: Option<Duration>,
 }
 impl Default for HttpBuilder {
     fn default() -> Self {
@@ -49,6 +56,7 @@ impl Default for HttpBuilder {
 }
 
 impl HttpBuilder {
+    /// Create a new `HttpBuilder` with the default settings.
     pub fn new() -> Self {
         Self {
             #[cfg(feature = "http1")]
@@ -57,6 +65,35 @@ impl HttpBuilder {
             http2: http2::Builder::new(crate::rt::tokio::TokioExecutor::new()),
             #[cfg(feature = "quinn")]
             quinn: crate::conn::quinn::Builder::new(),
+            idle_timeout: None,
+        }
+    }
+
+    /// Sets the idle timeout of keep-alive connections, see [`Server::idle_timeout`](crate::Server::idle_timeout).
+    pub fn idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = Some(timeout);
+        self
+    }
+
+    cfg_feature! {
+        #![feature = "http1"]
+        /// Sets the maximum size of the head of HTTP/1 requests, which is the request line and the
+        /// headers, default is about 400 KiB.
+        ///
+        /// Requests with a larger head are rejected with `431 Request Header Fields Too Large`
+        /// before they reach the handlers. The minimum is 8 KiB, smaller values are raised to it.
+        pub fn http1_max_header_size(mut self, size: usize) -> Self {
+            self.http1.max_buf_size(size.max(8192));
+            self
+        }
+
+        /// Sets the maximum number of headers of HTTP/1 requests, default is `100`.
+        ///
+        /// Requests with more headers are rejected with `431 Request Header Fields Too Large`
+        /// before they reach the handlers.
+        pub fn http1_max_headers(mut self, max: usize) -> Self {
+            self.http1.max_headers(max);
+            self
         }
     }
 
@@ -99,6 +136,11 @@ impl HttpBuilder {
         #[cfg(all(not(feature = "http1"), feature = "http2"))]
         let version = Version::HTTP_2;
 
+        #[cfg(any(feature = "http1", feature = "http2"))]
+        let idle_state = self.idle_timeout.map(|_| Arc::new(IdleState::default()));
+        #[cfg(any(feature = "http1", feature = "http2"))]
+        let service = IdleService::new(service, idle_state.clone());
+
         match version {
             Version::HTTP_10 | Version::HTTP_11 => {
                 #[cfg(not(feature = "http1"))]
@@ -114,52 +156,25 @@ impl HttpBuilder {
                         .serve_connection(TokioIo::new(socket), service)
                         .with_upgrades();
 
-                    match (fusewire, graceful_stop_token) {
-                        (Some(fusewire), Some(graceful_stop_token)) => {
-                            tokio::select! {
-                                _ = &mut conn => {
-                                    // Connection completed successfully.
-                                    return Ok(());
-                                },
-                                _ = fusewire.fused() => {
-                                    tracing::info!("closing connection due to fused");
-                                },
-                                _ = graceful_stop_token.cancelled() => {
-                                    tracing::info!("closing connection due to inactivity");
-
-                                    // Init graceful shutdown for connection (`GOAWAY` for `HTTP/2` or disabling `keep-alive` for `HTTP/1`)
-                                    Pin::new(&mut conn).graceful_shutdown();
-                                    let _ = conn.await;
-                                }
-                            }
-                        }
-                        (None, Some(graceful_stop_token)) => {
-                            tokio::select! {
-                                _ = &mut conn => {
-                                    // Connection completed successfully.
-                                    return Ok(());
-                                },
-                                _ = graceful_stop_token.cancelled() => {
-                                    tracing::info!("closing connection due to inactivity");
-
-                                    // Init graceful shutdown for connection (`GOAWAY` for `HTTP/2` or disabling `keep-alive` for `HTTP/1`)
-                                    Pin::new(&mut conn).graceful_shutdown();
-                                    let _ = conn.await;
-                                }
-                            }
-                        }
-                        (Some(fusewire), None) => {
-                            tokio::select! {
-                                _ = &mut conn => {
-                                    // Connection completed successfully.
-                                    return Ok(());
-                                },
-                                _ = fusewire.fused() => {
-                                    tracing::info!("closing connection due to fused");
-                                }
-                            }
+                    tokio::select! {
+                        _ = &mut conn => {
+                            // Connection completed successfully.
+                            return Ok(());
+                        },
+                        _ = fused(fusewire.as_ref()) => {
+                            tracing::info!("closing connection due to fused");
+                        },
+                        _ = cancelled(graceful_stop_token.as_ref()) => {
+                            tracing::info!("closing connection due to inactivity");
+
+                            // Init graceful shutdown for connection (`GOAWAY` for `HTTP/2` or disabling `keep-alive` for `HTTP/1`)
+                            Pin::new(&mut conn).graceful_shutdown();
+                            let _ = conn.await;
                         }
-                        (None, None) => {
+                        _ = idle(idle_state.as_deref(), self.idle_timeout) => {
+                            tracing::debug!("closing connection due to idle timeout");
+
+                            Pin::new(&mut conn).graceful_shutdown();
                             let _ = conn.await;
                         }
                     }
@@ -176,52 +191,25 @@ impl HttpBuilder {
                 {
                     let mut conn = self.http2.serve_connection(TokioIo::new(socket), service);
 
-                    match (fusewire, graceful_stop_token) {
-                        (Some(fusewire), Some(graceful_stop_token)) => {
-                            tokio::select! {
-                                _ = &mut conn => {
-                                    // Connection completed successfully.
-                                    return Ok(());
-                                },
-                                _ = fusewire.fused() => {
-                                    tracing::info!("closing connection due to fused");
-                                },
-                                _ = graceful_stop_token.cancelled() => {
-                                    tracing::info!("closing connection due to inactivity");
-
-                                    // Init graceful shutdown for connection (`GOAWAY` for `HTTP/2` or disabling `keep-alive` for `HTTP/1`)
-                                    Pin::new(&mut conn).graceful_shutdown();
-                                    let _ = conn.await;
-                                }
-                            }
-                        }
-                        (None, Some(graceful_stop_token)) => {
-                            tokio::select! {
-                                _ = &mut conn => {
-                                    // Connection completed successfully.
-                                    return Ok(());
-                                },
-                                _ = graceful_stop_token.cancelled() => {
-                                    tracing::info!("closing connection due to inactivity");
-
-                                    // Init graceful shutdown for connection (`GOAWAY` for `HTTP/2` or disabling `keep-alive` for `HTTP/1`)
-                                    Pin::new(&mut conn).graceful_shutdown();
-                                    let _ = conn.await;
-                                }
-                            }
-                        }
-                        (Some(fusewire), None) => {
-                            tokio::select! {
-                                _ = &mut conn => {
-                                    // Connection completed successfully.
-                                    return Ok(());
-                                },
-                                _ = fusewire.fused() => {
-                                    tracing::info!("closing connection due to fused");
-                                }
-                            }
+                    tokio::select! {
+                        _ = &mut conn => {
+                            // Connection completed successfully.
+                            return Ok(());
+                        },
+                        _ = fused(fusewire.as_ref()) => {
+                            tracing::info!("closing connection due to fused");
+                        },
+                        _ = cancelled(graceful_stop_token.as_ref()) => {
+                            tracing::info!("closing connection due to inactivity");
+
+                            // Init graceful shutdown for connection (`GOAWAY` for `HTTP/2` or disabling `keep-alive` for `HTTP/1`)
+                            Pin::new(&mut conn).graceful_shutdown();
+                            let _ = conn.await;
                         }
-                        (None, None) => {
+                        _ = idle(idle_state.as_deref(), self.idle_timeout) => {
+                            tracing::debug!("closing connection due to idle timeout");
+
+                            Pin::new(&mut conn).graceful_shutdown();
                             let _ = conn.await;
                         }
                     }
@@ -236,6 +224,33 @@ impl HttpBuilder {
     }
 }
 
+/// Completes when the fusewire is fused, never completes if there is no fusewire.
+#[cfg(any(feature = "http1", feature = "http2"))]
+async fn fused(fusewire: Option<&ArcFusewire>) {
+    match fusewire {
+        Some(fusewire) => fusewire.fused().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Completes when the token is cancelled, never completes if there is no token.
+#[cfg(any(feature = "http1", feature = "http2"))]
+async fn cancelled(token: Option<&CancellationToken>) {
+    match token {
+        Some(token) => token.cancelled().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Completes when the connection is idle for the timeout, never completes if there is no timeout.
+#[cfg(any(feature = "http1", feature = "http2"))]
+async fn idle(state: Option<&IdleState>, timeout: Option<Duration>) {
+    match (state, timeout) {
+        (Some(state), Some(timeout)) => state.timed_out(timeout).await,
+        _ => std::future::pending().await,
+    }
+}
+
 #[allow(dead_code)]
 #[allow(clippy::future_not_send)]
 pub(crate) async fn read_version<A>(mut reader: A) -> IoResult<(Version, Rewind<A>)>
@@ -368,3 +383,38 @@ where
         self.inner.is_write_vectored()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::AsyncReadExt;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_read_version_h2_preface() {
+        let mut data = H2_PREFACE.to_vec();
+        data.extend_from_slice(b"\x00\x00\x00\x04\x00\x00\x00\x00\x00");
+        let (version, mut rewind) = read_version(&data[..]).await.unwrap();
+        assert_eq!(version, Version::HTTP_2);
+
+        let mut read = Vec::new();
+        rewind.read_to_end(&mut read).await.unwrap();
+        assert_eq!(read, data);
+    }
+
+    #[tokio::test]
+    async fn test_read_version_http1() {
+        let data = b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n";
+        let (version, mut rewind) = read_version(&data[..]).await.unwrap();
+        assert_eq!(version, Version::HTTP_11);
+
+        let mut read = Vec::new();
+        rewind.read_to_end(&mut read).await.unwrap();
+        assert_eq!(read, data);
+    }
+
+    #[tokio::test]
+    async fn test_read_version_early_eof() {
+        assert!(read_version(&b"PRI * HTTP"[..]).await.is_err());
+    }
+}
@@ -0,0 +1,339 @@
+//! ProxyProtocolListener and it's implements.
+//!
+//! Supports both the human-readable v1 header and the binary v2 header of the
+//! [PROXY protocol](https://www.haproxy.org/download/2.9/doc/proxy-protocol.txt).
+use std::io::{Error as IoError, ErrorKind, Result as IoResult};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr as StdSocketAddr};
+use std::time::Duration;
+
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+use crate::conn::{Accepted, Acceptor, Holding, Listener};
+use crate::fuse::ArcFuseFactory;
+
+const V1_PREFIX: &[u8] = b"PROXY ";
+const V1_MAX_LENGTH: usize = 107;
+const V2_SIGNATURE: &[u8; 12] = b"\r\n\r\n\0\r\nQUIT\n";
+
+/// A wrapper of `Listener` which reads the PROXY protocol header sent by a load balancer
+/// and reports the real client address as the remote address.
+///
+/// It should directly wrap a plain transport listener such as [`TcpListener`](crate::conn::TcpListener),
+/// TLS listeners can be built on top of it.
+pub struct ProxyProtocolListener<T> {
+    inner: T,
+    timeout: Duration,
+}
+
+impl<T> ProxyProtocolListener<T> {
+    /// Create a new `ProxyProtocolListener`.
+    #[inline]
+    pub fn new(inner: T) -> Self {
+        ProxyProtocolListener {
+            inner,
+            timeout: Duration::from_secs(5),
+        }
+    }
+
+    /// Sets the time allowed for a client to send a complete header, default is 5 seconds.
+    ///
+    /// Connections which do not send a valid header in time are dropped.
+    #[inline]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+impl<T> Listener for ProxyProtocolListener<T>
+where
+    T: Listener + Send,
+    T::Acceptor: Send + 'static,
+{
+    type Acceptor = ProxyProtocolAcceptor<T::Acceptor>;
+
+    async fn try_bind(self) -> crate::Result<Self::Acceptor> {
+        Ok(ProxyProtocolAcceptor::new(
+            self.inner.try_bind().await?,
+            self.timeout,
+        ))
+    }
+}
+
+/// A wrapper of `Acceptor` which reads the PROXY protocol header of each connection.
+pub struct ProxyProtocolAcceptor<T: Acceptor> {
+    inner: T,
+    timeout: Duration,
+    tx: UnboundedSender<IoResult<Accepted<T::Conn>>>,
+    rx: UnboundedReceiver<IoResult<Accepted<T::Conn>>>,
+}
+
+impl<T: Acceptor> ProxyProtocolAcceptor<T> {
+    /// Create a new `ProxyProtocolAcceptor`.
+    pub fn new(inner: T, timeout: Duration) -> Self {
+        let (tx, rx) = unbounded_channel();
+        ProxyProtocolAcceptor {
+            inner,
+            timeout,
+            tx,
+            rx,
+        }
+    }
+
+    /// Get the inner `Acceptor`.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<T> Acceptor for ProxyProtocolAcceptor<T>
+where
+    T: Acceptor + Send + 'static,
+{
+    type Conn = T::Conn;
+
+    #[inline]
+    fn holdings(&self) -> &[Holding] {
+        self.inner.holdings()
+    }
+
+    async fn accept(
+        &mut self,
+        fuse_factory: Option<ArcFuseFactory>,
+    ) -> IoResult<Accepted<Self::Conn>> {
+        loop {
+            tokio::select! {
+                accepted = self.inner.accept(fuse_factory.clone()) => {
+                    // Read the header in a separate task, so slow clients do not block the accept loop.
+                    let mut accepted = accepted?;
+                    let tx = self.tx.clone();
+                    let timeout = self.timeout;
+                    tokio::spawn(async move {
+                        let result = match tokio::time::timeout(timeout, read_header(&mut accepted.conn)).await {
+                            Ok(Ok(remote_addr)) => {
+                                if let Some(remote_addr) = remote_addr {
+                                    accepted.remote_addr = remote_addr.into();
+                                }
+                                Ok(accepted)
+                            }
+                            Ok(Err(e)) => Err(e),
+                            Err(_) => Err(IoError::new(ErrorKind::TimedOut, "proxy protocol: read header timed out")),
+                        };
+                        let _ = tx.send(result);
+                    });
+                }
+                Some(accepted) = self.rx.recv() => {
+                    return accepted;
+                }
+            }
+        }
+    }
+
+    #[inline]
+    async fn shutdown(&mut self) {
+        self.inner.shutdown().await;
+    }
+}
+
+/// Reads the PROXY protocol header from the front of the stream.
+///
+/// Returns `None` if the header does not carry the client address, for example the v1 `UNKNOWN`
+/// protocol or the v2 `LOCAL` command. No byte after the header is consumed.
+async fn read_header<C>(conn: &mut C) -> IoResult<Option<StdSocketAddr>>
+where
+    C: AsyncRead + Unpin,
+{
+    let mut buf = [0u8; 16];
+    conn.read_exact(&mut buf[..V1_PREFIX.len()]).await?;
+    if &buf[..V1_PREFIX.len()] == V1_PREFIX {
+        let mut line = V1_PREFIX.to_vec();
+        while !line.ends_with(b"\r\n") {
+            if line.len() >= V1_MAX_LENGTH {
+                return Err(invalid_header("v1 header is too long"));
+            }
+            line.push(conn.read_u8().await?);
+        }
+        return parse_v1(&line[V1_PREFIX.len()..line.len() - 2]);
+    }
+
+    conn.read_exact(&mut buf[V1_PREFIX.len()..]).await?;
+    if &buf[..V2_SIGNATURE.len()] != V2_SIGNATURE {
+        return Err(invalid_header("missing header"));
+    }
+    let version_command = buf[12];
+    if version_command >> 4 != 0x2 {
+        return Err(invalid_header("unsupported version"));
+    }
+    let family = buf[13];
+    let len = u16::from_be_bytes([buf[14], buf[15]]) as usize;
+    let mut payload = vec![0u8; len];
+    conn.read_exact(&mut payload).await?;
+    match version_command & 0x0F {
+        0x0 => Ok(None),
+        0x1 => parse_v2_addr(family, &payload),
+        _ => Err(invalid_header("unsupported command")),
+    }
+}
+
+fn parse_v1(line: &[u8]) -> IoResult<Option<StdSocketAddr>> {
+    let line =
+        std::str::from_utf8(line).map_err(|_| invalid_header("v1 header is not valid utf-8"))?;
+    let mut parts = line.split(' ');
+    let ip: IpAddr = match parts.next() {
+        Some("TCP4") => parts
+            .next()
+            .and_then(|ip| ip.parse::<Ipv4Addr>().ok())
+            .ok_or_else(|| invalid_header("invalid v1 source address"))?
+            .into(),
+        Some("TCP6") => parts
+            .next()
+            .and_then(|ip| ip.parse::<Ipv6Addr>().ok())
+            .ok_or_else(|| invalid_header("invalid v1 source address"))?
+            .into(),
+        Some("UNKNOWN") => return Ok(None),
+        _ => return Err(invalid_header("invalid v1 protocol")),
+    };
+    let _destination = parts
+        .next()
+        .ok_or_else(|| invalid_header("missing v1 destination address"))?;
+    let port = parts
+        .next()
+        .and_then(|port| port.parse::<u16>().ok())
+        .ok_or_else(|| invalid_header("invalid v1 source port"))?;
+    let _destination_port = parts
+        .next()
+        .ok_or_else(|| invalid_header("missing v1 destination port"))?;
+    if parts.next().is_some() {
+        return Err(invalid_header("unexpected v1 header fields"));
+    }
+    Ok(Some(StdSocketAddr::new(ip, port)))
+}
+
+fn parse_v2_addr(family: u8, payload: &[u8]) -> IoResult<Option<StdSocketAddr>> {
+    match family >> 4 {
+        // AF_INET
+        0x1 => {
+            if payload.len() < 12 {
+                return Err(invalid_header("v2 ipv4 address is truncated"));
+            }
+            let ip = Ipv4Addr::new(payload[0], payload[1], payload[2], payload[3]);
+            let port = u16::from_be_bytes([payload[8], payload[9]]);
+            Ok(Some(StdSocketAddr::new(ip.into(), port)))
+        }
+        // AF_INET6
+        0x2 => {
+            if payload.len() < 36 {
+                return Err(invalid_header("v2 ipv6 address is truncated"));
+            }
+            let mut octets = [0u8; 16];
+            octets.copy_from_slice(&payload[..16]);
+            let port = u16::from_be_bytes([payload[32], payload[33]]);
+            Ok(Some(StdSocketAddr::new(
+                Ipv6Addr::from(octets).into(),
+                port,
+            )))
+        }
+        // AF_UNSPEC, AF_UNIX
+        _ => Ok(None),
+    }
+}
+
+fn invalid_header(msg: &str) -> IoError {
+    IoError::new(ErrorKind::InvalidData, format!("proxy protocol: {msg}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+
+    use super::*;
+    use crate::conn::TcpListener;
+
+    #[tokio::test]
+    async fn test_proxy_protocol_v1() {
+        let addr = std::net::SocketAddr::from(([127, 0, 0, 1], 6883));
+        let mut acceptor = ProxyProtocolListener::new(TcpListener::new(addr))
+            .bind()
+            .await;
+        tokio::spawn(async move {
+            let mut stream = TcpStream::connect(addr).await.unwrap();
+            stream
+                .write_all(b"PROXY TCP4 192.168.0.1 192.168.0.11 56324 443\r\n")
+                .await
+                .unwrap();
+            stream.write_i32(150).await.unwrap();
+        });
+
+        let Accepted {
+            mut conn,
+            remote_addr,
+            ..
+        } = acceptor.accept(None).await.unwrap();
+        assert_eq!(remote_addr.to_string(), "socket://192.168.0.1:56324");
+        assert_eq!(conn.read_i32().await.unwrap(), 150);
+    }
+
+    #[tokio::test]
+    async fn test_proxy_protocol_v2() {
+        let addr = std::net::SocketAddr::from(([127, 0, 0, 1], 6884));
+        let mut acceptor = ProxyProtocolListener::new(TcpListener::new(addr))
+            .bind()
+            .await;
+        tokio::spawn(async move {
+            let mut header = V2_SIGNATURE.to_vec();
+            header.extend_from_slice(&[0x21, 0x21, 0, 36]);
+            header.extend_from_slice(&"2001:db8::1".parse::<Ipv6Addr>().unwrap().octets());
+            header.extend_from_slice(&"2001:db8::2".parse::<Ipv6Addr>().unwrap().octets());
+            header.extend_from_slice(&8080u16.to_be_bytes());
+            header.extend_from_slice(&443u16.to_be_bytes());
+            let mut stream = TcpStream::connect(addr).await.unwrap();
+            stream.write_all(&header).await.unwrap();
+            stream.write_i32(150).await.unwrap();
+        });
+
+        let Accepted {
+            mut conn,
+            remote_addr,
+            ..
+        } = acceptor.accept(None).await.unwrap();
+        assert_eq!(remote_addr.to_string(), "socket://[2001:db8::1]:8080");
+        assert_eq!(conn.read_i32().await.unwrap(), 150);
+    }
+
+    #[tokio::test]
+    async fn test_proxy_protocol_malformed() {
+        let addr = std::net::SocketAddr::from(([127, 0, 0, 1], 6885));
+        let mut acceptor = ProxyProtocolListener::new(TcpListener::new(addr))
+            .timeout(Duration::from_millis(200))
+            .bind()
+            .await;
+        tokio::spawn(async move {
+            let mut stream = TcpStream::connect(addr).await.unwrap();
+            stream
+                .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n")
+                .await
+                .unwrap();
+            let mut stream = TcpStream::connect(addr).await.unwrap();
+            stream.write_all(b"PROXY ").await.unwrap();
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        });
+
+        let err = acceptor.accept(None).await.err().unwrap();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        let err = acceptor.accept(None).await.err().unwrap();
+        assert_eq!(err.kind(), ErrorKind::TimedOut);
+    }
+
+    #[test]
+    fn test_parse_v1() {
+        assert_eq!(
+            parse_v1(b"TCP6 ::1 ::1 1234 80").unwrap(),
+            Some("[::1]:1234".parse().unwrap())
+        );
+        assert_eq!(parse_v1(b"UNKNOWN").unwrap(), None);
+        assert!(parse_v1(b"TCP4 ::1 ::1 1234 80").is_err());
+        assert!(parse_v1(b"TCP4 127.0.0.1 127.0.0.1 99999 80").is_err());
+    }
+}
@@ -15,11 +15,15 @@ use salvo_http3::http3_quinn::{self, Endpoint};
 
 use super::H3Connection;
 use crate::conn::quinn::ServerConfig;
-use crate::conn::{Accepted, Acceptor, Holding, IntoConfigStream, Listener};
+use crate::conn::{Accepted, Acceptor, Holding, IntoConfigStream, Listener, TlsInfoSource};
 use crate::fuse::{ArcFuseFactory, FuseInfo, TransProto};
 use crate::http::Version;
 
 /// A wrapper of `Listener` with quinn.
+///
+/// It serves HTTP/3 over QUIC with the same `Router` and `Service` as the TCP listeners, so it is usually
+/// joined with a TLS `TcpListener` on the same port. The server then adds an `Alt-Svc` header to the
+/// HTTP/1.1 and HTTP/2 responses, which lets browsers upgrade to HTTP/3.
 pub struct QuinnListener<S, C, T, E> {
     config_stream: S,
     local_addr: T,
@@ -141,13 +145,14 @@ where
             let local_addr = self.holdings[0].local_addr.clone();
             match new_conn.await {
                 Ok(conn) => {
+                    let tls_info = conn.tls_info();
                     let conn = http3_quinn::Connection::new(conn);
                     return Ok(Accepted {
                         conn: H3Connection::new(conn, fuse_factory.map(|f|f.create(FuseInfo {
                             trans_proto: TransProto::Quic,
                             remote_addr: remote_addr.into(),
                             local_addr: local_addr.clone()
-                        }))),
+                        })), tls_info),
                         local_addr: self.holdings[0].local_addr.clone(),
                         remote_addr: remote_addr.into(),
                         http_scheme: self.holdings[0].http_scheme.clone(),
@@ -158,4 +163,55 @@ where
         }
         Err(IoError::new(ErrorKind::Other, "quinn accept error"))
     }
+
+    #[inline]
+    async fn shutdown(&mut self) {
+        // Refuse new incoming connections but keep the endpoint alive for existing ones.
+        if let Some(endpoint) = self.endpoint.take() {
+            endpoint.set_server_config(None);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conn::rustls::{Keycert, RustlsConfig};
+    use crate::conn::TcpListener;
+
+    fn rustls_config() -> RustlsConfig {
+        RustlsConfig::new(
+            Keycert::new()
+                .key_from_path("certs/key.pem")
+                .unwrap()
+                .cert_from_path("certs/cert.pem")
+                .unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_quinn_holdings() {
+        let acceptor = QuinnListener::new(rustls_config().build_quinn_config().unwrap(), "127.0.0.1:6891")
+            .bind()
+            .await;
+        let holding = &acceptor.holdings()[0];
+        assert_eq!(holding.local_addr.port(), Some(6891));
+        assert_eq!(holding.http_versions, vec![Version::HTTP_3]);
+        assert_eq!(holding.http_scheme, Scheme::HTTPS);
+        assert_eq!(holding.trans_proto(), TransProto::Quic);
+    }
+
+    #[tokio::test]
+    async fn test_quinn_join_tcp() {
+        let acceptor = QuinnListener::new(rustls_config().build_quinn_config().unwrap(), "127.0.0.1:6892")
+            .join(TcpListener::new("127.0.0.1:6892").rustls(rustls_config()))
+            .bind()
+            .await;
+        let holdings = acceptor.holdings();
+        assert_eq!(holdings.len(), 2);
+        assert_eq!(holdings[0].http_versions, vec![Version::HTTP_3]);
+        assert!(!holdings[1].http_versions.contains(&Version::HTTP_3));
+        assert_eq!(holdings[1].trans_proto(), TransProto::Tcp);
+        assert!(holdings.iter().all(|h| h.local_addr.port() == Some(6892)));
+    }
 }
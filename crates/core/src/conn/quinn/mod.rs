@@ -3,7 +3,7 @@ use std::io::Result as IoResult;
 use std::ops::{Deref, DerefMut};
 use std::future::{ready, Ready};
 use std::pin::Pin;
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 use std::task::{Context, Poll};
 
 use salvo_http3::http3_quinn;
@@ -12,7 +12,7 @@ use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
 use tokio_util::sync::CancellationToken;
 use futures_util::stream::{once, Once};
 
-use crate::conn::{HttpBuilder, IntoConfigStream};
+use crate::conn::{HttpBuilder, IntoConfigStream, TlsInfo, TlsInfoSlot};
 use crate::fuse::ArcFusewire;
 use crate::http::HttpConnection;
 use crate::service::HyperHandler;
@@ -26,10 +26,15 @@ pub use listener::{QuinnAcceptor, QuinnListener};
 pub struct H3Connection {
     inner: http3_quinn::Connection,
     fusewire: Option<ArcFusewire>,
+    tls_info: TlsInfoSlot,
 }
 impl H3Connection {
-    pub(crate) fn new(inner: http3_quinn::Connection, fusewire: Option<ArcFusewire>) -> Self {
-        Self { inner, fusewire }
+    pub(crate) fn new(inner: http3_quinn::Connection, fusewire: Option<ArcFusewire>, tls_info: TlsInfo) -> Self {
+        Self {
+            inner,
+            fusewire,
+            tls_info: Arc::new(OnceLock::from(Arc::new(tls_info))),
+        }
     }
     /// Get inner quinn connection.
     pub fn into_inner(self) -> http3_quinn::Connection {
@@ -70,10 +75,11 @@ impl AsyncWrite for H3Connection {
 impl HttpConnection for H3Connection {
     async fn serve(
         self,
-        handler: HyperHandler,
+        mut handler: HyperHandler,
         builder: Arc<HttpBuilder>,
         graceful_stop_token: Option<CancellationToken>,
     ) -> IoResult<()> {
+        handler.tls_info = Some(self.tls_info.clone());
         builder.quinn.serve_connection(self, handler, graceful_stop_token).await
     }
     fn fusewire(&self) -> Option<ArcFusewire> {
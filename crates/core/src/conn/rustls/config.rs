@@ -1,20 +1,29 @@
 //! rustls module
 use std::collections::HashMap;
+use std::fmt::{self, Debug, Formatter};
 use std::fs::File;
 use std::future::{ready, Ready};
 use std::io::{Error as IoError, ErrorKind, Read, Result as IoResult};
 use std::path::Path;
+use std::pin::Pin;
 use std::sync::Arc;
+use std::task::{Context, Poll};
 
 use futures_util::stream::{once, Once, Stream};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tokio_rustls::rustls::client::danger::HandshakeSignatureValid;
 use tokio_rustls::rustls::crypto::ring::sign::any_supported_type;
-use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer, UnixTime};
+use tokio_rustls::rustls::server::danger::{ClientCertVerified, ClientCertVerifier};
 use tokio_rustls::rustls::server::{ClientHello, ResolvesServerCert, WebPkiClientVerifier};
-use tokio_rustls::rustls::sign::CertifiedKey;
+use tokio_rustls::rustls::{
+    CertificateError, DigitallySignedStruct, DistinguishedName, Error as RustlsError, SignatureScheme,
+};
 
 pub use tokio_rustls::rustls::server::ServerConfig;
+pub use tokio_rustls::rustls::sign::CertifiedKey;
 
-use crate::conn::IntoConfigStream;
+use crate::conn::{IntoConfigStream, PEER_VERIFIED};
 
 use super::read_trust_anchor;
 
@@ -82,7 +91,8 @@ impl Keycert {
         &self.ocsp_resp
     }
 
-    fn build_certified_key(&mut self) -> IoResult<CertifiedKey> {
+    /// Build a [`CertifiedKey`] from the key and certificate, useful with [`RustlsConfig::sni_resolver`].
+    pub fn build_certified_key(&self) -> IoResult<CertifiedKey> {
         let cert = rustls_pemfile::certs(&mut self.cert.as_ref())
             .flat_map(|certs| certs.into_iter().collect::<Vec<CertificateDer<'static>>>())
             .collect::<Vec<_>>();
@@ -133,6 +143,9 @@ pub enum TlsClientAuth {
     /// No client auth.
     Off,
     /// Allow any anonymous or authenticated client.
+    ///
+    /// Clients with an invalid certificate are accepted too, their certificate is marked as not
+    /// verified in [`TlsInfo`](crate::conn::TlsInfo).
     Optional(Vec<u8>),
     /// Allow any authenticated client.
     Required(Vec<u8>),
@@ -150,6 +163,28 @@ fn alpn_protocols() -> Vec<Vec<u8>> {
     alpn_protocols
 }
 
+type SniResolveFn = dyn Fn(&str) -> Option<Arc<CertifiedKey>> + Send + Sync;
+
+/// Custom certificate resolver used by [`RustlsConfig::sni_resolver`].
+#[derive(Clone)]
+pub struct SniResolver(Arc<SniResolveFn>);
+impl Debug for SniResolver {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SniResolver").finish()
+    }
+}
+
+type ClientCertCheckFn = dyn Fn(&CertificateDer<'_>, &[CertificateDer<'_>]) -> bool + Send + Sync;
+
+/// Custom client certificate check used by [`RustlsConfig::client_cert_checker`].
+#[derive(Clone)]
+pub struct ClientCertChecker(Arc<ClientCertCheckFn>);
+impl Debug for ClientCertChecker {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientCertChecker").finish()
+    }
+}
+
 /// Builder to set the configuration for the Tls server.
 #[derive(Clone, Debug)]
 pub struct RustlsConfig {
@@ -161,6 +196,10 @@ pub struct RustlsConfig {
     pub client_auth: TlsClientAuth,
     /// Protocols through ALPN (Application-Layer Protocol Negotiation).
     pub alpn_protocols: Vec<Vec<u8>>,
+    /// Custom SNI resolver, checked before `keycerts` and `fallback`.
+    pub sni_resolver: Option<SniResolver>,
+    /// Custom client certificate check, applied after the certificate is verified by the trust anchor.
+    pub client_cert_checker: Option<ClientCertChecker>,
 }
 
 
@@ -173,6 +212,8 @@ impl RustlsConfig {
             keycerts: HashMap::new(),
             client_auth: TlsClientAuth::Off,
             alpn_protocols: alpn_protocols(),
+            sni_resolver: None,
+            client_cert_checker: None,
         }
     }
 
@@ -219,6 +260,21 @@ impl RustlsConfig {
         self
     }
 
+    /// Set a custom check of the client certificates, such as matching their subject with an
+    /// allow list.
+    ///
+    /// It's called with the certificate of the client and its intermediates after they are
+    /// verified by the trust anchor of the `client_auth_` methods, and the certificate is
+    /// rejected if it returns `false`. It has no effect if client authentication is disabled.
+    #[inline]
+    pub fn client_cert_checker<F>(mut self, checker: F) -> Self
+    where
+        F: Fn(&CertificateDer<'_>, &[CertificateDer<'_>]) -> bool + Send + Sync + 'static,
+    {
+        self.client_cert_checker = Some(ClientCertChecker(Arc::new(checker)));
+        self
+    }
+
     /// Add a new keycert to be used for the given SNI `name`.
     #[inline]
     pub fn keycert(mut self, name: impl Into<String>, keycert: Keycert) -> Self {
@@ -226,45 +282,67 @@ impl RustlsConfig {
         self
     }
 
+    /// Set a custom resolver which picks the certificate by the SNI name sent in the ClientHello.
+    ///
+    /// If the resolver returns `None`, or the client sends no SNI name, the keycerts added by
+    /// [`RustlsConfig::keycert`] and then the fallback are tried. When none of them matches, the
+    /// handshake is aborted with a TLS alert.
+    #[inline]
+    pub fn sni_resolver<F>(mut self, resolver: F) -> Self
+    where
+        F: Fn(&str) -> Option<Arc<CertifiedKey>> + Send + Sync + 'static,
+    {
+        self.sni_resolver = Some(SniResolver(Arc::new(resolver)));
+        self
+    }
+
     /// Set specific protocols through ALPN (Application-Layer Protocol Negotiation).
+    ///
+    /// The protocols are offered in the given order, for example `["h2"]` only accepts HTTP/2 clients.
+    /// Clients which do not support any of them are rejected during the handshake.
     #[inline]
-    pub fn alpn_protocols(mut self, alpn_protocols: impl Into<Vec<Vec<u8>>>) -> Self {
-        self.alpn_protocols = alpn_protocols.into();
+    pub fn alpn_protocols(mut self, alpn_protocols: impl IntoIterator<Item = impl Into<Vec<u8>>>) -> Self {
+        self.alpn_protocols = alpn_protocols.into_iter().map(Into::into).collect();
         self
     }
 
     /// ServerConfig
-    pub(crate) fn build_server_config(mut self) -> IoResult<ServerConfig> {
+    pub(crate) fn build_server_config(self) -> IoResult<ServerConfig> {
         let fallback = self
             .fallback
-            .as_mut()
+            .as_ref()
             .map(|fallback| fallback.build_certified_key())
             .transpose()?
             .map(Arc::new);
         let mut certified_keys = HashMap::new();
 
-        for (name, keycert) in &mut self.keycerts {
+        for (name, keycert) in &self.keycerts {
             certified_keys.insert(name.clone(), Arc::new(keycert.build_certified_key()?));
         }
 
-        let client_auth = match &self.client_auth {
+        let client_auth: Arc<dyn ClientCertVerifier> = match &self.client_auth {
             TlsClientAuth::Off => WebPkiClientVerifier::no_client_auth(),
-            TlsClientAuth::Optional(trust_anchor) => {
-                WebPkiClientVerifier::builder(read_trust_anchor(trust_anchor)?.into())
+            TlsClientAuth::Optional(trust_anchor) => Arc::new(ClientAuthVerifier {
+                inner: WebPkiClientVerifier::builder(read_trust_anchor(trust_anchor)?.into())
                     .allow_unauthenticated()
                     .build()
-                    .map_err(|e| IoError::new(ErrorKind::Other, format!("failed to build server config: {}", e)))?
-            }
-            TlsClientAuth::Required(trust_anchor) => {
-                WebPkiClientVerifier::builder(read_trust_anchor(trust_anchor)?.into())
+                    .map_err(|e| IoError::new(ErrorKind::Other, format!("failed to build server config: {}", e)))?,
+                checker: self.client_cert_checker,
+                optional: true,
+            }),
+            TlsClientAuth::Required(trust_anchor) => Arc::new(ClientAuthVerifier {
+                inner: WebPkiClientVerifier::builder(read_trust_anchor(trust_anchor)?.into())
                     .build()
-                    .map_err(|e| IoError::new(ErrorKind::Other, format!("failed to build server config: {}", e)))?
-            }
+                    .map_err(|e| IoError::new(ErrorKind::Other, format!("failed to build server config: {}", e)))?,
+                checker: self.client_cert_checker,
+                optional: false,
+            }),
         };
 
         let mut config = ServerConfig::builder()
             .with_client_cert_verifier(client_auth)
             .with_cert_resolver(Arc::new(CertResolver {
+                sni_resolver: self.sni_resolver,
                 certified_keys,
                 fallback,
             }));
@@ -303,19 +381,91 @@ cfg_feature! {
 
 #[derive(Debug)]
 pub(crate) struct CertResolver {
+    sni_resolver: Option<SniResolver>,
     fallback: Option<Arc<CertifiedKey>>,
     certified_keys: HashMap<String, Arc<CertifiedKey>>,
 }
 
 impl ResolvesServerCert for CertResolver {
     fn resolve(&self, client_hello: ClientHello) -> Option<Arc<CertifiedKey>> {
-        client_hello
-            .server_name()
-            .and_then(|name| self.certified_keys.get(name).cloned())
+        let name = client_hello.server_name();
+        name.and_then(|name| self.sni_resolver.as_ref().and_then(|resolver| (resolver.0)(name)))
+            .or_else(|| name.and_then(|name| self.certified_keys.get(name).cloned()))
             .or_else(|| self.fallback.clone())
     }
 }
 
+/// Client certificate verifier which applies the custom check and records the verification result
+/// for [`TlsInfo`](crate::conn::TlsInfo).
+#[derive(Debug)]
+struct ClientAuthVerifier {
+    inner: Arc<dyn ClientCertVerifier>,
+    checker: Option<ClientCertChecker>,
+    optional: bool,
+}
+
+impl ClientCertVerifier for ClientAuthVerifier {
+    fn offer_client_auth(&self) -> bool {
+        self.inner.offer_client_auth()
+    }
+
+    fn client_auth_mandatory(&self) -> bool {
+        self.inner.client_auth_mandatory()
+    }
+
+    fn root_hint_subjects(&self) -> &[DistinguishedName] {
+        self.inner.root_hint_subjects()
+    }
+
+    fn verify_client_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        now: UnixTime,
+    ) -> Result<ClientCertVerified, RustlsError> {
+        let result = self
+            .inner
+            .verify_client_cert(end_entity, intermediates, now)
+            .and_then(|verified| match &self.checker {
+                Some(checker) if !(checker.0)(end_entity, intermediates) => Err(RustlsError::InvalidCertificate(
+                    CertificateError::ApplicationVerificationFailure,
+                )),
+                _ => Ok(verified),
+            });
+        let recorded = PEER_VERIFIED.try_with(|verified| verified.set(Some(result.is_ok())));
+        match result {
+            // An invalid certificate can only be accepted if it is marked as not verified.
+            Err(e) if !self.optional || recorded.is_err() => {
+                tracing::debug!(error = ?e, "client certificate rejected");
+                Err(e)
+            }
+            _ => Ok(ClientCertVerified::assertion()),
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, RustlsError> {
+        self.inner.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, RustlsError> {
+        self.inner.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.inner.supported_verify_schemes()
+    }
+}
+
 impl IntoConfigStream<RustlsConfig> for RustlsConfig {
     type Stream = Once<Ready<RustlsConfig>>;
 
@@ -333,3 +483,73 @@ where
         self
     }
 }
+
+/// A handle to replace the tls config of a running [`RustlsAcceptor`](super::RustlsAcceptor).
+///
+/// New handshakes use the latest config, while connections that are already established keep
+/// the certificate they were accepted with.
+///
+/// ```no_run
+/// use salvo_core::conn::rustls::{Keycert, RustlsConfig, RustlsReloader};
+/// use salvo_core::prelude::*;
+///
+/// # async fn run() -> std::io::Result<()> {
+/// let keycert = Keycert::new().key_from_path("key.pem")?.cert_from_path("cert.pem")?;
+/// let (reloader, config_stream) = RustlsReloader::new(RustlsConfig::new(keycert))?;
+/// let acceptor = TcpListener::new("0.0.0.0:443").rustls(config_stream).bind().await;
+/// tokio::spawn(async move {
+///     // Later, when the certificate files are renewed:
+///     let keycert = Keycert::new().key_from_path("key.pem")?.cert_from_path("cert.pem")?;
+///     reloader.reload(RustlsConfig::new(keycert))
+/// });
+/// Server::new(acceptor).serve(Router::new()).await;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct RustlsReloader {
+    sender: UnboundedSender<ServerConfig>,
+}
+
+impl RustlsReloader {
+    /// Create a new `RustlsReloader` and the config stream to pass to the listener.
+    pub fn new(config: RustlsConfig) -> IoResult<(Self, RustlsReloadStream)> {
+        let (sender, receiver) = unbounded_channel();
+        let reloader = Self { sender };
+        reloader.reload(config)?;
+        Ok((reloader, RustlsReloadStream { receiver }))
+    }
+
+    /// Build `config` and use it for all following handshakes.
+    ///
+    /// Returns an error if the config is invalid, in which case the current config is kept, or if
+    /// the acceptor has been dropped.
+    pub fn reload(&self, config: RustlsConfig) -> IoResult<()> {
+        let config = config.build_server_config()?;
+        self.sender
+            .send(config)
+            .map_err(|_| IoError::new(ErrorKind::BrokenPipe, "rustls acceptor is closed"))
+    }
+}
+
+/// Config stream created by [`RustlsReloader::new`].
+#[derive(Debug)]
+pub struct RustlsReloadStream {
+    receiver: UnboundedReceiver<ServerConfig>,
+}
+
+impl Stream for RustlsReloadStream {
+    type Item = ServerConfig;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+impl IntoConfigStream<ServerConfig> for RustlsReloadStream {
+    type Stream = Self;
+
+    fn into_stream(self) -> Self {
+        self
+    }
+}
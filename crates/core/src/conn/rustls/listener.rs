@@ -114,21 +114,13 @@ where
     }
 }
 
-impl<S, C, T, E> Acceptor for RustlsAcceptor<S, C, T, E>
+impl<S, C, T, E> RustlsAcceptor<S, C, T, E>
 where
     S: Stream<Item = C> + Send + Unpin + 'static,
     C: TryInto<ServerConfig, Error = E> + Send + 'static,
-    T: Acceptor + Send + 'static,
-    <T as Acceptor>::Conn: AsyncRead + AsyncWrite + Send + Unpin + 'static,
     E: StdError + Send,
 {
-    type Conn = HandshakeStream<TlsStream<T::Conn>>;
-
-    fn holdings(&self) -> &[Holding] {
-        &self.holdings
-    }
-
-    async fn accept(&mut self, fuse_factory: Option<ArcFuseFactory>) -> IoResult<Accepted<Self::Conn>> {
+    fn update_config(&mut self) -> IoResult<()> {
         let config = {
             let mut config = None;
             while let Poll::Ready(Some(item)) =
@@ -150,10 +142,29 @@ where
             }
             self.tls_acceptor = Some(tls_acceptor);
         }
-        let tls_acceptor = match &self.tls_acceptor {
-            Some(tls_acceptor) => tls_acceptor,
-            None => return Err(IoError::new(ErrorKind::Other, "rustls: invalid tls config.")),
-        };
+        Ok(())
+    }
+}
+
+impl<S, C, T, E> Acceptor for RustlsAcceptor<S, C, T, E>
+where
+    S: Stream<Item = C> + Send + Unpin + 'static,
+    C: TryInto<ServerConfig, Error = E> + Send + 'static,
+    T: Acceptor + Send + 'static,
+    <T as Acceptor>::Conn: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+    E: StdError + Send,
+{
+    type Conn = HandshakeStream<TlsStream<T::Conn>>;
+
+    fn holdings(&self) -> &[Holding] {
+        &self.holdings
+    }
+
+    async fn accept(&mut self, fuse_factory: Option<ArcFuseFactory>) -> IoResult<Accepted<Self::Conn>> {
+        self.update_config()?;
+        if self.tls_acceptor.is_none() {
+            return Err(IoError::new(ErrorKind::Other, "rustls: invalid tls config."));
+        }
 
         let Accepted {
             conn,
@@ -161,6 +172,12 @@ where
             remote_addr,
             ..
         } = self.inner.accept(fuse_factory).await?;
+        // Pick up configs pushed while waiting, so that a reload applies to the very next handshake.
+        self.update_config()?;
+        let tls_acceptor = self
+            .tls_acceptor
+            .as_ref()
+            .ok_or_else(|| IoError::other("rustls: invalid tls config."))?;
         let fusewire = conn.fusewire();
         Ok(Accepted {
             conn: HandshakeStream::new(tls_acceptor.accept(conn), fusewire),
@@ -169,4 +186,9 @@ where
             http_scheme: Scheme::HTTPS,
         })
     }
+
+    #[inline]
+    async fn shutdown(&mut self) {
+        self.inner.shutdown().await;
+    }
 }
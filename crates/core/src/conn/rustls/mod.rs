@@ -4,7 +4,10 @@ use std::io::{Error as IoError, ErrorKind, Result as IoResult};
 use tokio_rustls::rustls::RootCertStore;
 
 pub(crate) mod config;
-pub use config::{Keycert, RustlsConfig, ServerConfig};
+pub use config::{
+    CertifiedKey, ClientCertChecker, Keycert, RustlsConfig, RustlsReloadStream, RustlsReloader, ServerConfig,
+    SniResolver, TlsClientAuth,
+};
 
 mod listener;
 pub use listener::{RustlsAcceptor, RustlsListener};
@@ -63,4 +66,275 @@ mod tests {
         let Accepted { mut conn, .. } = acceptor.accept(None).await.unwrap();
         assert_eq!(conn.read_i32().await.unwrap(), 518);
     }
+
+    #[tokio::test]
+    async fn test_rustls_alpn_protocols() {
+        let mut acceptor = TcpListener::new("127.0.0.1:0")
+            .rustls(
+                RustlsConfig::new(
+                    Keycert::new()
+                        .key_from_path("certs/key.pem")
+                        .unwrap()
+                        .cert_from_path("certs/cert.pem")
+                        .unwrap(),
+                )
+                .alpn_protocols(["h2"]),
+            )
+            .bind()
+            .await;
+        let addr = acceptor.holdings()[0].local_addr.clone().into_std().unwrap();
+
+        let connect = move |alpn_protocols: Vec<Vec<u8>>| async move {
+            let stream = TcpStream::connect(addr).await.unwrap();
+            let trust_anchor = include_bytes!("../../../certs/chain.pem");
+            let mut client_config = ClientConfig::builder()
+                .with_root_certificates(read_trust_anchor(trust_anchor.as_slice()).unwrap())
+                .with_no_client_auth();
+            client_config.alpn_protocols = alpn_protocols;
+            let connector = TlsConnector::from(Arc::new(client_config));
+            connector
+                .connect(ServerName::try_from("testserver.com").unwrap(), stream)
+                .await
+        };
+        let client = tokio::spawn(async move {
+            let rejected = connect(vec![b"http/1.1".to_vec()]).await;
+            let tls_stream = connect(vec![b"http/1.1".to_vec(), b"h2".to_vec()]).await.unwrap();
+            (rejected.is_err(), tls_stream.get_ref().1.alpn_protocol().map(|p| p.to_vec()))
+        });
+
+        for _ in 0..2 {
+            let Accepted { mut conn, .. } = acceptor.accept(None).await.unwrap();
+            tokio::spawn(async move {
+                let _ = conn.read_u8().await;
+            });
+        }
+        let (rejected, negotiated) = client.await.unwrap();
+        assert!(rejected);
+        assert_eq!(negotiated.as_deref(), Some(&b"h2"[..]));
+    }
+
+    #[tokio::test]
+    async fn test_rustls_sni_resolver() {
+        let certified_key = Arc::new(
+            Keycert::new()
+                .key_from_path("certs/key.pem")
+                .unwrap()
+                .cert_from_path("certs/cert.pem")
+                .unwrap()
+                .build_certified_key()
+                .unwrap(),
+        );
+        let mut acceptor = TcpListener::new("127.0.0.1:0")
+            .rustls(
+                RustlsConfig::new(None)
+                    .sni_resolver(move |name| (name == "testserver.com").then(|| certified_key.clone())),
+            )
+            .bind()
+            .await;
+        let addr = acceptor.holdings()[0].local_addr.clone().into_std().unwrap();
+
+        let connect = move |name: &'static str| async move {
+            let stream = TcpStream::connect(addr).await.unwrap();
+            let trust_anchor = include_bytes!("../../../certs/chain.pem");
+            let client_config = ClientConfig::builder()
+                .with_root_certificates(read_trust_anchor(trust_anchor.as_slice()).unwrap())
+                .with_no_client_auth();
+            let connector = TlsConnector::from(Arc::new(client_config));
+            connector.connect(ServerName::try_from(name).unwrap(), stream).await
+        };
+        let client = tokio::spawn(async move {
+            let unknown = connect("unknown.com").await;
+            let known = connect("testserver.com").await;
+            (unknown.is_err(), known.is_ok())
+        });
+
+        for _ in 0..2 {
+            let Accepted { mut conn, .. } = acceptor.accept(None).await.unwrap();
+            tokio::spawn(async move {
+                let _ = conn.read_u8().await;
+            });
+        }
+        assert_eq!(client.await.unwrap(), (true, true));
+    }
+
+    #[tokio::test]
+    async fn test_rustls_reloader() {
+        let (reloader, config_stream) = RustlsReloader::new(RustlsConfig::new(None)).unwrap();
+        let mut acceptor = TcpListener::new("127.0.0.1:0").rustls(config_stream).bind().await;
+        let addr = acceptor.holdings()[0].local_addr.clone().into_std().unwrap();
+
+        let connect = move || async move {
+            let stream = TcpStream::connect(addr).await.unwrap();
+            let trust_anchor = include_bytes!("../../../certs/chain.pem");
+            let client_config = ClientConfig::builder()
+                .with_root_certificates(read_trust_anchor(trust_anchor.as_slice()).unwrap())
+                .with_no_client_auth();
+            let connector = TlsConnector::from(Arc::new(client_config));
+            connector
+                .connect(ServerName::try_from("testserver.com").unwrap(), stream)
+                .await
+        };
+
+        // No certificate configured yet, so the handshake is rejected.
+        let client = tokio::spawn(connect());
+        let Accepted { mut conn, .. } = acceptor.accept(None).await.unwrap();
+        assert!(conn.read_u8().await.is_err());
+        assert!(client.await.unwrap().is_err());
+
+        reloader
+            .reload(RustlsConfig::new(
+                Keycert::new()
+                    .key_from_path("certs/key.pem")
+                    .unwrap()
+                    .cert_from_path("certs/cert.pem")
+                    .unwrap(),
+            ))
+            .unwrap();
+        assert!(reloader.reload(RustlsConfig::new(Keycert::new())).is_err());
+
+        let client = tokio::spawn(async move {
+            let mut tls_stream = connect().await.unwrap();
+            tls_stream.write_i32(518).await.unwrap();
+        });
+        let Accepted { mut conn, .. } = acceptor.accept(None).await.unwrap();
+        assert_eq!(conn.read_i32().await.unwrap(), 518);
+        client.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_rustls_tls_info() {
+        use crate::prelude::*;
+
+        #[handler]
+        async fn tls_info(req: &mut Request) -> String {
+            let info = req.tls_info().unwrap();
+            format!(
+                "{:?} {:?} {:?} {}",
+                info.server_name,
+                info.protocol_version,
+                info.alpn_protocol.as_deref().map(String::from_utf8_lossy),
+                info.peer_certificate().is_none()
+            )
+        }
+        let acceptor = TcpListener::new("127.0.0.1:0")
+            .rustls(RustlsConfig::new(
+                Keycert::new()
+                    .key_from_path("certs/key.pem")
+                    .unwrap()
+                    .cert_from_path("certs/cert.pem")
+                    .unwrap(),
+            ))
+            .bind()
+            .await;
+        let addr = acceptor.holdings()[0].local_addr.clone().into_std().unwrap();
+        tokio::spawn(Server::new(acceptor).serve(Router::new().get(tls_info)));
+
+        let stream = TcpStream::connect(addr).await.unwrap();
+        let trust_anchor = include_bytes!("../../../certs/chain.pem");
+        let mut client_config = ClientConfig::builder()
+            .with_root_certificates(read_trust_anchor(trust_anchor.as_slice()).unwrap())
+            .with_no_client_auth();
+        client_config.alpn_protocols = vec![b"http/1.1".to_vec()];
+        let connector = TlsConnector::from(Arc::new(client_config));
+        let mut tls_stream = connector
+            .connect(ServerName::try_from("testserver.com").unwrap(), stream)
+            .await
+            .unwrap();
+        tls_stream
+            .write_all(b"GET / HTTP/1.1\r\nHost: testserver.com\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        let _ = tls_stream.read_to_string(&mut response).await;
+        assert!(
+            response.ends_with(r#"Some("testserver.com") Some("TLSv1.3") Some("http/1.1") true"#),
+            "{response}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rustls_client_auth() {
+        use crate::prelude::*;
+
+        #[handler]
+        async fn peer(req: &mut Request) -> String {
+            let info = req.tls_info().unwrap();
+            format!(
+                "{} {} {}",
+                info.peer_certificates.len(),
+                info.peer_verified,
+                info.peer_certificate().is_some()
+            )
+        }
+        let serve = |config: RustlsConfig| async move {
+            let keycert = Keycert::new()
+                .key_from_path("certs/key.pem")
+                .unwrap()
+                .cert_from_path("certs/cert.pem")
+                .unwrap();
+            let acceptor = TcpListener::new("127.0.0.1:0")
+                .rustls(RustlsConfig { fallback: Some(keycert), ..config })
+                .bind()
+                .await;
+            let addr = acceptor.holdings()[0].local_addr.clone().into_std().unwrap();
+            tokio::spawn(Server::new(acceptor).serve(Router::new().get(peer)));
+            addr
+        };
+        let request = |addr, with_cert: bool| async move {
+            let stream = TcpStream::connect(addr).await.unwrap();
+            let trust_anchor = include_bytes!("../../../certs/chain.pem");
+            let builder =
+                ClientConfig::builder().with_root_certificates(read_trust_anchor(trust_anchor.as_slice()).unwrap());
+            let client_config = if with_cert {
+                let certified_key = Keycert::new()
+                    .key_from_path("certs/key.pem")
+                    .unwrap()
+                    .cert_from_path("certs/cert.pem")
+                    .unwrap()
+                    .build_certified_key()
+                    .unwrap();
+                let key = rustls_pemfile::private_key(&mut include_bytes!("../../../certs/key.pem").as_slice())
+                    .unwrap()
+                    .unwrap();
+                builder.with_client_auth_cert(certified_key.cert, key).unwrap()
+            } else {
+                builder.with_no_client_auth()
+            };
+            let connector = TlsConnector::from(Arc::new(client_config));
+            let mut tls_stream = connector
+                .connect(ServerName::try_from("testserver.com").unwrap(), stream)
+                .await?;
+            tls_stream
+                .write_all(b"GET / HTTP/1.1\r\nHost: testserver.com\r\nConnection: close\r\n\r\n")
+                .await?;
+            let mut response = String::new();
+            tls_stream.read_to_string(&mut response).await?;
+            IoResult::Ok(response)
+        };
+
+        let trust_anchor = include_bytes!("../../../certs/chain.pem");
+        let addr = serve(RustlsConfig::new(None).client_auth_required(trust_anchor.as_slice())).await;
+        let response = request(addr, true).await.unwrap();
+        assert!(response.ends_with("1 true true"), "{response}");
+        assert!(request(addr, false).await.is_err());
+
+        let addr = serve(
+            RustlsConfig::new(None)
+                .client_auth_required(trust_anchor.as_slice())
+                .client_cert_checker(|_, _| false),
+        )
+        .await;
+        assert!(request(addr, true).await.is_err());
+
+        let addr = serve(
+            RustlsConfig::new(None)
+                .client_auth_optional(trust_anchor.as_slice())
+                .client_cert_checker(|_, _| false),
+        )
+        .await;
+        let response = request(addr, true).await.unwrap();
+        assert!(response.ends_with("1 false false"), "{response}");
+        let response = request(addr, false).await.unwrap();
+        assert!(response.ends_with("0 false false"), "{response}");
+    }
 }
@@ -1,3 +1,4 @@
+use std::cell::Cell;
 use std::future::Future;
 use std::io::{Error as IoError, ErrorKind, Result as IoResult};
 use std::pin::Pin;
@@ -8,7 +9,7 @@ use futures_util::{future::BoxFuture, FutureExt};
 use tokio::io::{AsyncRead, AsyncWrite, ReadBuf, Result};
 use tokio_util::sync::CancellationToken;
 
-use crate::conn::HttpBuilder;
+use crate::conn::{HttpBuilder, TlsInfoSlot, TlsInfoSource, PEER_VERIFIED};
 use crate::fuse::{ArcFusewire, FuseEvent};
 use crate::http::HttpConnection;
 use crate::service::HyperHandler;
@@ -23,19 +24,29 @@ enum State<S> {
 pub struct HandshakeStream<S> {
     state: State<S>,
     fusewire: Option<ArcFusewire>,
+    tls_info: TlsInfoSlot,
 }
 
 impl<S> HandshakeStream<S> {
     pub(crate) fn new<F>(handshake: F, fusewire: Option<ArcFusewire>) -> Self
     where
         F: Future<Output = Result<S>> + Send + 'static,
+        S: TlsInfoSource,
     {
         if let Some(fusewire) = &fusewire {
             fusewire.event(FuseEvent::TlsHandshaking);
         }
+        let tls_info = TlsInfoSlot::default();
+        let handshaked = tls_info.clone();
+        let handshake = PEER_VERIFIED.scope(Cell::new(None), async move {
+            let stream = handshake.await?;
+            let _ = handshaked.set(Arc::new(stream.tls_info()));
+            Ok(stream)
+        });
         Self {
             state: State::Handshaking(handshake.boxed()),
             fusewire,
+            tls_info,
         }
     }
 
@@ -52,7 +63,7 @@ where
 {
     async fn serve(
         self,
-        handler: HyperHandler,
+        mut handler: HyperHandler,
         builder: Arc<HttpBuilder>,
         graceful_stop_token: Option<CancellationToken>,
     ) -> IoResult<()> {
@@ -60,6 +71,7 @@ where
         if let Some(fusewire) = &fusewire {
             fusewire.event(FuseEvent::Alive);
         }
+        handler.tls_info = Some(self.tls_info.clone());
         builder
             .serve_connection(self, handler, fusewire, graceful_stop_token)
             .await
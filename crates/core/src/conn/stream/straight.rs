@@ -1,12 +1,16 @@
 use tokio_util::sync::CancellationToken;
 
+use std::future::Future;
 use std::io::{Error as IoError, ErrorKind, IoSlice, Result as IoResult};
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
+use std::time::Duration;
 
 use pin_project::pin_project;
 use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::sync::OwnedSemaphorePermit;
+use tokio::time::Sleep;
 
 use crate::conn::HttpBuilder;
 use crate::fuse::{ArcFusewire, FuseEvent};
@@ -19,6 +23,8 @@ pub struct StraightStream<C> {
     #[pin]
     inner: C,
     fusewire: Option<ArcFusewire>,
+    permit: Option<OwnedSemaphorePermit>,
+    first_read_deadline: Option<Pin<Box<Sleep>>>,
 }
 
 impl<C> StraightStream<C>
@@ -27,7 +33,24 @@ where
 {
     /// Create a new `StraightStream`.
     pub fn new(inner: C, fusewire: Option<ArcFusewire>) -> Self {
-        Self { inner, fusewire }
+        Self {
+            inner,
+            fusewire,
+            permit: None,
+            first_read_deadline: None,
+        }
+    }
+
+    /// Holds a connection limit permit, it is released when the stream is dropped.
+    pub(crate) fn with_permit(mut self, permit: Option<OwnedSemaphorePermit>) -> Self {
+        self.permit = permit;
+        self
+    }
+
+    /// Fails reading with `ErrorKind::TimedOut` if the first bytes are not received within `timeout`.
+    pub(crate) fn with_first_read_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.first_read_deadline = timeout.map(|timeout| Box::pin(tokio::time::sleep(timeout)));
+        self
     }
 }
 
@@ -68,6 +91,7 @@ where
         let remaining = buf.remaining();
         match this.inner.poll_read(cx, buf) {
             Poll::Ready(Ok(())) => {
+                *this.first_read_deadline = None;
                 if let Some(fusewire) = &this.fusewire {
                     fusewire.event(FuseEvent::ReadData(remaining - buf.remaining()));
                 }
@@ -75,6 +99,14 @@ where
             }
             Poll::Ready(Err(e)) => Poll::Ready(Err(e)),
             Poll::Pending => {
+                if let Some(deadline) = this.first_read_deadline {
+                    if deadline.as_mut().poll(cx).is_ready() {
+                        return Poll::Ready(Err(IoError::new(
+                            ErrorKind::TimedOut,
+                            "no data received before accept timeout",
+                        )));
+                    }
+                }
                 if let Some(fusewire) = &this.fusewire {
                     fusewire.event(FuseEvent::Alive);
                 }
@@ -0,0 +1,59 @@
+//! Socket activation of systemd.
+//!
+//! With socket activation systemd binds the sockets and passes them to the service with the
+//! `LISTEN_FDS` and `LISTEN_PID` environment variables, so the port is held during restarts and
+//! no connection is refused.
+//!
+//! Reference: <https://www.freedesktop.org/software/systemd/man/latest/sd_listen_fds.html>
+//!
+//! # Example
+//!
+//! ```no_run
+//! use salvo_core::conn::systemd;
+//! use salvo_core::prelude::*;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let acceptor = systemd::tcp_acceptor().unwrap();
+//!     Server::new(acceptor).serve(Router::new()).await;
+//! }
+//! ```
+use std::io::{Error as IoError, ErrorKind, Result as IoResult};
+
+use listenfd::ListenFd;
+
+use crate::conn::tcp::TcpAcceptor;
+
+/// Create [`TcpAcceptor`]s over all the TCP sockets passed by systemd, in the order of the
+/// `ListenStream` settings. Their local addresses are the addresses bound by systemd. The other
+/// sockets, such as UDP or Unix sockets, are skipped.
+///
+/// The environment variables are removed when they are read, so the sockets are only taken by the
+/// first call. It's empty if the process is not socket activated.
+pub fn tcp_acceptors() -> IoResult<Vec<TcpAcceptor>> {
+    let mut listen_fd = ListenFd::from_env();
+    let mut acceptors = Vec::with_capacity(listen_fd.len());
+    for idx in 0..listen_fd.len() {
+        match listen_fd.take_tcp_listener(idx) {
+            Ok(Some(listener)) => acceptors.push(TcpAcceptor::from_std(listener)?),
+            Ok(None) => {}
+            Err(e) => tracing::debug!(error = ?e, idx, "skip the socket which is not a TCP socket"),
+        }
+    }
+    Ok(acceptors)
+}
+
+/// Create a [`TcpAcceptor`] over the first socket passed by systemd, its local address is the
+/// address bound by systemd.
+///
+/// Only the first socket is used, the other sockets can't be taken after it is called, because
+/// the environment variables are removed. Use [`tcp_acceptors`] to serve all of them.
+///
+/// Returns an error if the process is not socket activated, the socket is not a TCP socket, or
+/// the sockets have already been taken.
+pub fn tcp_acceptor() -> IoResult<TcpAcceptor> {
+    ListenFd::from_env()
+        .take_tcp_listener(0)?
+        .ok_or_else(|| IoError::new(ErrorKind::NotFound, "no socket is passed by systemd"))
+        .and_then(TcpAcceptor::from_std)
+}
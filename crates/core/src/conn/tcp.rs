@@ -1,15 +1,20 @@
 //! TcpListener and it's implements.
-use std::io::{Error as IoError, Result as IoResult};
+use std::io::{Error as IoError, ErrorKind, Result as IoResult};
 use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
 use std::vec;
 
 use tokio::net::{TcpListener as TokioTcpListener, TcpStream, ToSocketAddrs};
+use tokio::sync::Semaphore;
 
-use crate::conn::{Holding, StraightStream};
+use crate::conn::{Holding, ProxyProtocolListener, StraightStream};
 use crate::fuse::{ArcFuseFactory, FuseInfo, TransProto};
 use crate::http::uri::Scheme;
 use crate::http::Version;
 
+#[cfg(feature = "socket2")]
+use super::JoinedListener;
 use super::{Accepted, Acceptor, Listener};
 
 #[cfg(any(feature = "rustls", feature = "native-tls", feature = "openssl"))]
@@ -28,11 +33,22 @@ use crate::conn::openssl::OpensslListener;
 use crate::conn::acme::AcmeListener;
 
 /// `TcpListener` is used to create a TCP connection listener.
+///
+/// When both `http1` and `http2` features are enabled, the first bytes of each connection are
+/// inspected: clients sending the HTTP/2 connection preface (prior knowledge h2c) are served with
+/// HTTP/2, all others with HTTP/1. The inspected bytes are replayed, so no data is lost.
 pub struct TcpListener<T> {
     local_addr: T,
     ttl: Option<u32>,
+    nodelay: Option<bool>,
+    max_connections: Option<usize>,
+    accept_timeout: Option<Duration>,
     #[cfg(feature = "socket2")]
     backlog: Option<u32>,
+    #[cfg(feature = "socket2")]
+    keepalive: Option<Duration>,
+    #[cfg(feature = "socket2")]
+    bind_options: BindOptions,
 }
 impl<T: ToSocketAddrs + Send> TcpListener<T> {
     /// Bind to socket address.
@@ -43,6 +59,9 @@ impl<T: ToSocketAddrs + Send> TcpListener<T> {
         TcpListener {
             local_addr,
             ttl: None,
+            nodelay: None,
+            max_connections: None,
+            accept_timeout: None,
         }
     }
     /// Bind to socket address.
@@ -52,7 +71,12 @@ impl<T: ToSocketAddrs + Send> TcpListener<T> {
         TcpListener {
             local_addr,
             ttl: None,
+            nodelay: None,
+            max_connections: None,
+            accept_timeout: None,
             backlog: None,
+            keepalive: None,
+            bind_options: BindOptions::default(),
         }
     }
 
@@ -111,6 +135,15 @@ impl<T: ToSocketAddrs + Send> TcpListener<T> {
         }
     }
 
+    /// Creates a new `ProxyProtocolListener` from current `TcpListener`.
+    ///
+    /// The real client address sent by the load balancer in the PROXY protocol header will be
+    /// reported as the remote address.
+    #[inline]
+    pub fn proxy_protocol(self) -> ProxyProtocolListener<Self> {
+        ProxyProtocolListener::new(self)
+    }
+
     /// Sets the value for the `IP_TTL` option on this socket.
     ///
     /// This value sets the time-to-live field that is used in every packet sent
@@ -120,6 +153,33 @@ impl<T: ToSocketAddrs + Send> TcpListener<T> {
         self
     }
 
+    /// Sets the value of the `TCP_NODELAY` option on each accepted stream.
+    ///
+    /// If set, this option disables the Nagle algorithm. This means that segments are always
+    /// sent as soon as possible, even if there is only a small amount of data.
+    pub fn nodelay(mut self, nodelay: bool) -> Self {
+        self.nodelay = Some(nodelay);
+        self
+    }
+
+    /// Sets the maximum number of concurrent connections.
+    ///
+    /// When the limit is reached, `accept` waits until one of the served connections is closed
+    /// before accepting a new one. By default the number of connections is unlimited.
+    pub fn max_connections(mut self, max: usize) -> Self {
+        self.max_connections = Some(max);
+        self
+    }
+
+    /// Sets the time allowed for an accepted connection to send its first bytes.
+    ///
+    /// Connections which stay silent longer are closed with an `ErrorKind::TimedOut` error. The timeout
+    /// only covers the phase before the first request, it does not limit the lifetime of the connection.
+    pub fn accept_timeout(mut self, timeout: Duration) -> Self {
+        self.accept_timeout = Some(timeout);
+        self
+    }
+
     cfg_feature! {
         #![feature = "socket2"]
         /// Set backlog capacity.
@@ -128,6 +188,73 @@ impl<T: ToSocketAddrs + Send> TcpListener<T> {
             self.backlog = Some(backlog);
             self
         }
+
+        /// Enables `SO_KEEPALIVE` on each accepted stream and sets the idle time before the
+        /// first keepalive probe is sent.
+        ///
+        /// The option is checked against the listening socket when binding, so `try_bind` returns
+        /// an error if the platform does not support it.
+        #[inline]
+        pub fn keepalive(mut self, time: Duration) -> Self {
+            self.keepalive = Some(time);
+            self
+        }
+
+        /// Sets the value for the `IPV6_V6ONLY` option on this socket before binding.
+        ///
+        /// If this is set to `true`, an IPv6 socket only accepts IPv6 connections, so another
+        /// socket can be bound to the IPv4 address with the same port.
+        #[inline]
+        pub fn only_v6(mut self, only_v6: bool) -> Self {
+            self.bind_options.only_v6 = Some(only_v6);
+            self
+        }
+
+        /// Sets the value for the `SO_REUSEADDR` option on this socket before binding.
+        #[inline]
+        pub fn reuse_address(mut self, reuse_address: bool) -> Self {
+            self.bind_options.reuse_address = Some(reuse_address);
+            self
+        }
+
+        /// Sets the value for the `SO_REUSEPORT` option on this socket before binding.
+        ///
+        /// This allows a new process to bind the same address before the old one exits, which is
+        /// useful for zero-downtime restarts. It is only supported on unix platforms, `try_bind`
+        /// returns an error on other platforms.
+        #[inline]
+        pub fn reuse_port(mut self, reuse_port: bool) -> Self {
+            self.bind_options.reuse_port = Some(reuse_port);
+            self
+        }
+    }
+}
+
+#[cfg(feature = "socket2")]
+impl TcpListener<SocketAddr> {
+    /// Creates a listener which binds to both `0.0.0.0:port` and `[::]:port`.
+    ///
+    /// The returned acceptor holds both addresses and fairly accepts connections from
+    /// either of them.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use salvo_core::prelude::*;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let acceptor = TcpListener::dual_stack(5800).bind().await;
+    ///     Server::new(acceptor).serve(Router::new()).await;
+    /// }
+    /// ```
+    pub fn dual_stack(
+        port: u16,
+    ) -> JoinedListener<TcpListener<SocketAddr>, TcpListener<SocketAddr>> {
+        let ipv4 = TcpListener::new(SocketAddr::from((std::net::Ipv4Addr::UNSPECIFIED, port)));
+        let ipv6 = TcpListener::new(SocketAddr::from((std::net::Ipv6Addr::UNSPECIFIED, port)))
+            .only_v6(true);
+        ipv4.join(ipv6)
     }
 }
 impl<T> Listener for TcpListener<T>
@@ -137,7 +264,14 @@ where
     type Acceptor = TcpAcceptor;
 
     async fn try_bind(self) -> crate::Result<Self::Acceptor> {
+        #[cfg(not(feature = "socket2"))]
         let inner = TokioTcpListener::bind(self.local_addr).await?;
+        #[cfg(feature = "socket2")]
+        let inner = if self.bind_options.is_set() {
+            bind_socket2(self.local_addr, self.bind_options).await?
+        } else {
+            TokioTcpListener::bind(self.local_addr).await?
+        };
 
         #[cfg(feature = "socket2")]
         if let Some(backlog) = self.backlog {
@@ -147,20 +281,160 @@ where
         if let Some(ttl) = self.ttl {
             inner.set_ttl(ttl)?;
         }
+        #[cfg(feature = "socket2")]
+        let keepalive = match self.keepalive {
+            Some(time) => {
+                let keepalive = socket2::TcpKeepalive::new().with_time(time);
+                socket2::SockRef::from(&inner).set_tcp_keepalive(&keepalive)?;
+                Some(keepalive)
+            }
+            None => None,
+        };
+
+        let mut acceptor: TcpAcceptor = inner.try_into()?;
+        acceptor.nodelay = self.nodelay;
+        acceptor.accept_timeout = self.accept_timeout;
+        acceptor.limit = self
+            .max_connections
+            .map(|max| (max, Arc::new(Semaphore::new(max))));
+        #[cfg(feature = "socket2")]
+        {
+            acceptor.keepalive = keepalive;
+            acceptor.backlog = self.backlog;
+        }
+        Ok(acceptor)
+    }
+}
+/// Socket options which must be applied before binding.
+#[cfg(feature = "socket2")]
+#[derive(Clone, Copy, Debug, Default)]
+struct BindOptions {
+    only_v6: Option<bool>,
+    reuse_address: Option<bool>,
+    reuse_port: Option<bool>,
+}
+#[cfg(feature = "socket2")]
+impl BindOptions {
+    fn is_set(&self) -> bool {
+        self.only_v6.is_some() || self.reuse_address.is_some() || self.reuse_port.is_some()
+    }
+
+    fn apply(&self, socket: &socket2::Socket, addr: &SocketAddr) -> IoResult<()> {
+        match self.reuse_address {
+            Some(reuse_address) => socket.set_reuse_address(reuse_address)?,
+            // Keep the same behavior as `TokioTcpListener::bind`.
+            #[cfg(not(windows))]
+            None => socket.set_reuse_address(true)?,
+            #[cfg(windows)]
+            None => {}
+        }
+        if let Some(reuse_port) = self.reuse_port {
+            #[cfg(all(
+                unix,
+                not(any(target_os = "solaris", target_os = "illumos", target_os = "cygwin"))
+            ))]
+            socket.set_reuse_port(reuse_port)?;
+            #[cfg(not(all(
+                unix,
+                not(any(target_os = "solaris", target_os = "illumos", target_os = "cygwin"))
+            )))]
+            if reuse_port {
+                return Err(IoError::new(
+                    ErrorKind::Unsupported,
+                    "`SO_REUSEPORT` is not supported on this platform",
+                ));
+            }
+        }
+        if let (Some(only_v6), true) = (self.only_v6, addr.is_ipv6()) {
+            socket.set_only_v6(only_v6)?;
+        }
+        Ok(())
+    }
+}
 
-        Ok(inner.try_into()?)
+/// Builds the listening socket with `socket2`, so options which must be applied before binding can be set.
+#[cfg(feature = "socket2")]
+async fn bind_socket2<T: ToSocketAddrs>(
+    local_addr: T,
+    options: BindOptions,
+) -> IoResult<TokioTcpListener> {
+    use socket2::{Domain, Protocol, Socket, Type};
+
+    let mut last_err = None;
+    for addr in tokio::net::lookup_host(local_addr).await? {
+        let bind = || {
+            let socket = Socket::new(Domain::for_address(addr), Type::STREAM, Some(Protocol::TCP))?;
+            options.apply(&socket, &addr)?;
+            socket.set_nonblocking(true)?;
+            socket.bind(&addr.into())?;
+            socket.listen(1024)?;
+            TokioTcpListener::from_std(socket.into())
+        };
+        match bind() {
+            Ok(listener) => return Ok(listener),
+            Err(e) => last_err = Some(e),
+        }
     }
+    Err(last_err.unwrap_or_else(|| {
+        IoError::new(ErrorKind::InvalidInput, "could not resolve to any address")
+    }))
 }
+
+type OnAccept = Arc<dyn Fn(&TcpStream) + Send + Sync + 'static>;
+
 /// `TcpAcceptor` is used to accept a TCP connection.
 pub struct TcpAcceptor {
-    inner: TokioTcpListener,
+    inner: Option<TokioTcpListener>,
     holdings: Vec<Holding>,
+    nodelay: Option<bool>,
+    #[cfg(feature = "socket2")]
+    keepalive: Option<socket2::TcpKeepalive>,
+    #[cfg(feature = "socket2")]
+    backlog: Option<u32>,
+    on_accept: Option<OnAccept>,
+    limit: Option<(usize, Arc<Semaphore>)>,
+    accept_timeout: Option<Duration>,
 }
 
 impl TcpAcceptor {
+    /// Creates a `TcpAcceptor` from a listener which is already bound, such as one inherited from
+    /// the parent process, so the address is not bound again.
+    ///
+    /// A raw file descriptor can be converted with `std::os::fd::FromRawFd` first. The listener is
+    /// switched to non-blocking mode, this function panics if it is not called from within a tokio
+    /// runtime.
+    pub fn from_std(inner: std::net::TcpListener) -> IoResult<Self> {
+        inner.set_nonblocking(true)?;
+        TokioTcpListener::from_std(inner)?.try_into()
+    }
+
+    /// Sets a callback which is invoked with every accepted `TcpStream` before it is served.
+    ///
+    /// This is useful to inspect the raw socket, for example with `as_raw_fd`, or to apply
+    /// socket options which are not exposed by [`TcpListener`].
+    #[inline]
+    pub fn on_accept<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&TcpStream) + Send + Sync + 'static,
+    {
+        self.on_accept = Some(Arc::new(f));
+        self
+    }
+
     /// Get the inner `TokioTcpListener`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the acceptor has been shut down, use [`TcpAcceptor::try_inner`] to check it.
     pub fn inner(&self) -> &TokioTcpListener {
-        &self.inner
+        self.inner
+            .as_ref()
+            .expect("tcp listener has been shut down")
+    }
+
+    /// Get the inner `TokioTcpListener`, returns `None` if the acceptor has been shut down.
+    pub fn try_inner(&self) -> Option<&TokioTcpListener> {
+        self.inner.as_ref()
     }
 
     /// Get the local address that this listener is bound to.
@@ -168,12 +442,12 @@ impl TcpAcceptor {
     /// This can be useful, for example, when binding to port 0 to figure out
     /// which port was actually bound.
     pub fn local_addr(&self) -> IoResult<SocketAddr> {
-        self.inner.local_addr()
+        self.listener()?.local_addr()
     }
 
     /// Gets the value of the `IP_TTL` option for this socket.
     pub fn ttl(&self) -> IoResult<u32> {
-        self.inner.ttl()
+        self.listener()?.ttl()
     }
 
     /// Sets the value for the `IP_TTL` option on this socket.
@@ -181,7 +455,23 @@ impl TcpAcceptor {
     /// This value sets the time-to-live field that is used in every packet sent
     /// from this socket.
     pub fn set_ttl(&self, ttl: u32) -> IoResult<()> {
-        self.inner.set_ttl(ttl)
+        self.listener()?.set_ttl(ttl)
+    }
+
+    /// Returns the number of currently alive connections.
+    ///
+    /// Connections are only counted when a limit is set by [`TcpListener::max_connections`],
+    /// otherwise `None` is returned.
+    pub fn alive_connections(&self) -> Option<usize> {
+        self.limit
+            .as_ref()
+            .map(|(max, semaphore)| max - semaphore.available_permits())
+    }
+
+    fn listener(&self) -> IoResult<&TokioTcpListener> {
+        self.inner
+            .as_ref()
+            .ok_or_else(|| IoError::new(ErrorKind::NotConnected, "tcp listener has been shut down"))
     }
 }
 
@@ -197,7 +487,18 @@ impl TryFrom<TokioTcpListener> for TcpAcceptor {
             http_scheme: Scheme::HTTP,
         }];
 
-        Ok(TcpAcceptor { inner, holdings })
+        Ok(TcpAcceptor {
+            inner: Some(inner),
+            holdings,
+            nodelay: None,
+            #[cfg(feature = "socket2")]
+            keepalive: None,
+            #[cfg(feature = "socket2")]
+            backlog: None,
+            on_accept: None,
+            limit: None,
+            accept_timeout: None,
+        })
     }
 }
 
@@ -214,23 +515,95 @@ impl Acceptor for TcpAcceptor {
         &mut self,
         fuse_factory: Option<ArcFuseFactory>,
     ) -> IoResult<Accepted<Self::Conn>> {
-        self.inner.accept().await.map(move |(conn, remote_addr)| {
-            let local_addr = self.holdings[0].local_addr.clone();
-            Accepted {
-                conn: StraightStream::new(
-                    conn,
-                    fuse_factory.map(|f| {
-                        f.create(FuseInfo {
-                            trans_proto: TransProto::Tcp,
-                            remote_addr: remote_addr.into(),
-                            local_addr: local_addr.clone(),
-                        })
-                    }),
-                ),
-                remote_addr: remote_addr.into(),
-                local_addr,
-                http_scheme: Scheme::HTTP,
-            }
+        let permit = match &self.limit {
+            Some((_, semaphore)) => Some(
+                semaphore
+                    .clone()
+                    .acquire_owned()
+                    .await
+                    .map_err(IoError::other)?,
+            ),
+            None => None,
+        };
+        let (conn, remote_addr) = self.listener()?.accept().await?;
+        if let Some(nodelay) = self.nodelay {
+            conn.set_nodelay(nodelay)?;
+        }
+        #[cfg(feature = "socket2")]
+        if let Some(keepalive) = &self.keepalive {
+            socket2::SockRef::from(&conn).set_tcp_keepalive(keepalive)?;
+        }
+        if let Some(on_accept) = &self.on_accept {
+            on_accept(&conn);
+        }
+        let local_addr = self.holdings[0].local_addr.clone();
+        Ok(Accepted {
+            conn: StraightStream::new(
+                conn,
+                fuse_factory.map(|f| {
+                    f.create(FuseInfo {
+                        trans_proto: TransProto::Tcp,
+                        remote_addr: remote_addr.into(),
+                        local_addr: local_addr.clone(),
+                    })
+                }),
+            )
+            .with_permit(permit)
+            .with_first_read_timeout(self.accept_timeout),
+            remote_addr: remote_addr.into(),
+            local_addr,
+            http_scheme: Scheme::HTTP,
+        })
+    }
+
+    #[inline]
+    async fn shutdown(&mut self) {
+        self.inner.take();
+    }
+
+    /// Binds another socket to the address of this acceptor with `SO_REUSEPORT`, so the kernel
+    /// distributes the incoming connections between them.
+    ///
+    /// The listener must be bound with [`TcpListener::reuse_port`], the shard has the same
+    /// options and shares the [`max_connections`](TcpListener::max_connections) limit and the
+    /// [`on_accept`](TcpAcceptor::on_accept) callback.
+    #[cfg(all(
+        feature = "socket2",
+        unix,
+        not(any(target_os = "solaris", target_os = "illumos", target_os = "cygwin"))
+    ))]
+    fn try_shard(&self) -> IoResult<Self> {
+        use socket2::{Domain, Protocol, SockRef, Socket, Type};
+
+        let listener = self.listener()?;
+        let origin = SockRef::from(listener);
+        if !origin.reuse_port()? {
+            return Err(IoError::new(
+                ErrorKind::InvalidInput,
+                "accept shards require the listener to be bound with `TcpListener::reuse_port(true)`",
+            ));
+        }
+        let addr = listener.local_addr()?;
+        let socket = Socket::new(Domain::for_address(addr), Type::STREAM, Some(Protocol::TCP))?;
+        socket.set_reuse_address(origin.reuse_address()?)?;
+        socket.set_reuse_port(true)?;
+        if addr.is_ipv6() {
+            socket.set_only_v6(origin.only_v6()?)?;
+        }
+        socket.set_nonblocking(true)?;
+        socket.bind(&addr.into())?;
+        socket.listen(self.backlog.map(|backlog| backlog as _).unwrap_or(1024))?;
+        let inner = TokioTcpListener::from_std(socket.into())?;
+        inner.set_ttl(listener.ttl()?)?;
+        Ok(TcpAcceptor {
+            inner: Some(inner),
+            holdings: self.holdings.clone(),
+            nodelay: self.nodelay,
+            keepalive: self.keepalive.clone(),
+            backlog: self.backlog,
+            on_accept: self.on_accept.clone(),
+            limit: self.limit.clone(),
+            accept_timeout: self.accept_timeout,
         })
     }
 }
@@ -245,6 +618,10 @@ mod tests {
     async fn test_tcp_listener() {
         let addr = std::net::SocketAddr::from(([127, 0, 0, 1], 6878));
         let mut acceptor = TcpListener::new(addr).bind().await;
+        assert_eq!(
+            acceptor.holdings()[0].trans_proto(),
+            crate::fuse::TransProto::Tcp
+        );
         let addr = acceptor.holdings()[0]
             .local_addr
             .clone()
@@ -258,4 +635,203 @@ mod tests {
         let Accepted { mut conn, .. } = acceptor.accept(None).await.unwrap();
         assert_eq!(conn.read_i32().await.unwrap(), 150);
     }
+
+    #[tokio::test]
+    async fn test_tcp_acceptor_from_std() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let mut acceptor = TcpAcceptor::from_std(listener).unwrap();
+        assert_eq!(
+            acceptor.holdings()[0].local_addr.clone().into_std(),
+            Some(addr)
+        );
+        tokio::spawn(async move {
+            let mut stream = TcpStream::connect(addr).await.unwrap();
+            stream.write_i32(150).await.unwrap();
+        });
+        let Accepted { mut conn, .. } = acceptor.accept(None).await.unwrap();
+        assert_eq!(conn.read_i32().await.unwrap(), 150);
+    }
+
+    #[tokio::test]
+    async fn test_tcp_listener_socket_options() {
+        let addr = std::net::SocketAddr::from(([127, 0, 0, 1], 6879));
+        let listener = TcpListener::new(addr).nodelay(true);
+        #[cfg(feature = "socket2")]
+        let listener = listener.keepalive(std::time::Duration::from_secs(30));
+        let mut acceptor = listener.bind().await;
+        tokio::spawn(async move {
+            let mut stream = TcpStream::connect(addr).await.unwrap();
+            stream.write_i32(150).await.unwrap();
+        });
+
+        let Accepted { mut conn, .. } = acceptor.accept(None).await.unwrap();
+        assert_eq!(conn.read_i32().await.unwrap(), 150);
+    }
+
+    #[tokio::test]
+    async fn test_tcp_acceptor_on_accept() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let addr = std::net::SocketAddr::from(([127, 0, 0, 1], 6880));
+        let accepted = Arc::new(AtomicUsize::new(0));
+        let counter = accepted.clone();
+        let mut acceptor = TcpListener::new(addr)
+            .bind()
+            .await
+            .on_accept(move |stream| {
+                assert!(stream.peer_addr().is_ok());
+                counter.fetch_add(1, Ordering::SeqCst);
+            });
+        tokio::spawn(async move {
+            let mut stream = TcpStream::connect(addr).await.unwrap();
+            stream.write_i32(150).await.unwrap();
+        });
+
+        let Accepted { mut conn, .. } = acceptor.accept(None).await.unwrap();
+        assert_eq!(conn.read_i32().await.unwrap(), 150);
+        assert_eq!(accepted.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_tcp_acceptor_shutdown() {
+        let addr = std::net::SocketAddr::from(([127, 0, 0, 1], 6881));
+        let mut acceptor = TcpListener::new(addr).bind().await;
+        acceptor.shutdown().await;
+        assert!(acceptor.try_inner().is_none());
+        assert!(acceptor.accept(None).await.is_err());
+        assert!(TcpStream::connect(addr).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_tcp_listener_max_connections() {
+        let addr = std::net::SocketAddr::from(([127, 0, 0, 1], 6882));
+        let mut acceptor = TcpListener::new(addr).max_connections(1).bind().await;
+        assert_eq!(acceptor.alive_connections(), Some(0));
+        tokio::spawn(async move {
+            let mut first = TcpStream::connect(addr).await.unwrap();
+            first.write_i32(1).await.unwrap();
+            let mut second = TcpStream::connect(addr).await.unwrap();
+            second.write_i32(2).await.unwrap();
+            tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+        });
+
+        let Accepted { mut conn, .. } = acceptor.accept(None).await.unwrap();
+        assert_eq!(conn.read_i32().await.unwrap(), 1);
+        assert_eq!(acceptor.alive_connections(), Some(1));
+        let pending =
+            tokio::time::timeout(std::time::Duration::from_millis(100), acceptor.accept(None))
+                .await;
+        assert!(pending.is_err());
+
+        drop(conn);
+        assert_eq!(acceptor.alive_connections(), Some(0));
+        let Accepted { mut conn, .. } = acceptor.accept(None).await.unwrap();
+        assert_eq!(conn.read_i32().await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_tcp_listener_accept_timeout() {
+        let addr = std::net::SocketAddr::from(([127, 0, 0, 1], 6886));
+        let mut acceptor = TcpListener::new(addr)
+            .accept_timeout(Duration::from_millis(100))
+            .bind()
+            .await;
+        tokio::spawn(async move {
+            let _silent = TcpStream::connect(addr).await.unwrap();
+            let mut stream = TcpStream::connect(addr).await.unwrap();
+            stream.write_i32(150).await.unwrap();
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            stream.write_i32(300).await.unwrap();
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        });
+
+        let Accepted { mut conn, .. } = acceptor.accept(None).await.unwrap();
+        assert_eq!(
+            conn.read_i32().await.unwrap_err().kind(),
+            ErrorKind::TimedOut
+        );
+        let Accepted { mut conn, .. } = acceptor.accept(None).await.unwrap();
+        assert_eq!(conn.read_i32().await.unwrap(), 150);
+        assert_eq!(conn.read_i32().await.unwrap(), 300);
+    }
+
+    #[cfg(feature = "socket2")]
+    #[tokio::test]
+    async fn test_tcp_listener_dual_stack() {
+        let mut acceptor = TcpListener::dual_stack(6887).bind().await;
+        assert_eq!(acceptor.holdings().len(), 2);
+        assert!(acceptor.holdings()[0].local_addr.is_ipv4());
+        assert!(acceptor.holdings()[1].local_addr.is_ipv6());
+        tokio::spawn(async move {
+            let mut stream = TcpStream::connect("127.0.0.1:6887").await.unwrap();
+            stream.write_i32(50).await.unwrap();
+            let mut stream = TcpStream::connect("[::1]:6887").await.unwrap();
+            stream.write_i32(100).await.unwrap();
+        });
+
+        let Accepted { mut conn, .. } = acceptor.accept(None).await.unwrap();
+        let first = conn.read_i32().await.unwrap();
+        let Accepted { mut conn, .. } = acceptor.accept(None).await.unwrap();
+        let second = conn.read_i32().await.unwrap();
+        assert_eq!(first + second, 150);
+    }
+
+    #[cfg(all(feature = "socket2", target_os = "linux"))]
+    #[tokio::test]
+    async fn test_tcp_listener_reuse_port() {
+        let addr = std::net::SocketAddr::from(([127, 0, 0, 1], 6888));
+        let first = TcpListener::new(addr).reuse_port(true).bind().await;
+        let second = TcpListener::new(addr).reuse_port(true).try_bind().await;
+        assert!(second.is_ok());
+        drop(first);
+
+        let addr = std::net::SocketAddr::from(([127, 0, 0, 1], 6889));
+        let _first = TcpListener::new(addr).bind().await;
+        assert!(TcpListener::new(addr)
+            .reuse_port(true)
+            .try_bind()
+            .await
+            .is_err());
+    }
+
+    #[cfg(all(feature = "socket2", target_os = "linux"))]
+    #[tokio::test]
+    async fn test_tcp_acceptor_shard() {
+        let addr = std::net::SocketAddr::from(([127, 0, 0, 1], 6904));
+        let acceptor = TcpListener::new(addr).bind().await;
+        assert_eq!(
+            acceptor.try_shard().err().map(|e| e.kind()),
+            Some(ErrorKind::InvalidInput)
+        );
+        drop(acceptor);
+
+        let acceptor = TcpListener::new(addr)
+            .reuse_port(true)
+            .max_connections(4)
+            .bind()
+            .await;
+        let shard = acceptor.try_shard().unwrap();
+        assert_eq!(shard.local_addr().unwrap(), addr);
+        let mut acceptors = [acceptor, shard];
+        let clients = (0..8).map(|_| TcpStream::connect(addr)).collect::<Vec<_>>();
+        let mut streams = Vec::new();
+        for client in clients {
+            streams.push(client.await.unwrap());
+        }
+
+        // The kernel distributes the connections between the sockets, the limit is shared.
+        let mut conns = Vec::new();
+        for _ in 0..4 {
+            let [first, second] = &mut acceptors;
+            let Accepted { conn, .. } = tokio::select! {
+                accepted = first.accept(None) => accepted.unwrap(),
+                accepted = second.accept(None) => accepted.unwrap(),
+            };
+            conns.push(conn);
+        }
+        assert_eq!(acceptors[0].alive_connections(), Some(4));
+        assert_eq!(acceptors[1].alive_connections(), Some(4));
+    }
 }
@@ -0,0 +1,197 @@
+//! TLS session information of connections.
+#[cfg(any(
+    feature = "native-tls",
+    feature = "rustls",
+    feature = "openssl",
+    feature = "acme"
+))]
+use std::cell::Cell;
+use std::sync::{Arc, OnceLock};
+
+/// TLS session information of a connection, captured when the handshake completes, see
+/// [`Request::tls_info`](crate::http::Request::tls_info).
+///
+/// It is available for the connections of [`RustlsListener`](crate::conn::RustlsListener),
+/// [`NativeTlsListener`](crate::conn::NativeTlsListener),
+/// [`OpensslListener`](crate::conn::OpensslListener), [`AcmeListener`](crate::conn::AcmeListener)
+/// and [`QuinnListener`](crate::conn::QuinnListener). `native-tls` only exposes the certificate
+/// of the client without verifying it, the other fields are always `None` with it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct TlsInfo {
+    /// DER encoded certificates sent by the client, the certificate of the client comes first,
+    /// followed by its chain. It's empty if the client sent no certificate.
+    pub peer_certificates: Vec<Vec<u8>>,
+    /// Whether `peer_certificates` is verified by the TLS config. It's `false` if the client sent
+    /// no certificate, or sent an invalid one to a server with optional client authentication.
+    pub peer_verified: bool,
+    /// The protocol negotiated with ALPN, such as `h2`.
+    pub alpn_protocol: Option<Vec<u8>>,
+    /// The negotiated cipher suite, its name depends on the TLS library, such as
+    /// `TLS13_AES_128_GCM_SHA256` for rustls and `TLS_AES_128_GCM_SHA256` for OpenSSL.
+    pub cipher_suite: Option<String>,
+    /// The negotiated protocol version, such as `TLSv1.3`.
+    pub protocol_version: Option<String>,
+    /// The server name sent by the client with SNI.
+    pub server_name: Option<String>,
+}
+
+impl TlsInfo {
+    /// Get the DER encoded certificate of the client if it is verified, parse it with a crate
+    /// such as `x509-parser` to read its subject.
+    #[inline]
+    pub fn peer_certificate(&self) -> Option<&[u8]> {
+        if self.peer_verified {
+            self.peer_certificates.first().map(Vec::as_slice)
+        } else {
+            None
+        }
+    }
+}
+
+/// [`TlsInfo`] of a connection, it's set when the handshake completes.
+pub(crate) type TlsInfoSlot = Arc<OnceLock<Arc<TlsInfo>>>;
+
+#[cfg(any(
+    feature = "native-tls",
+    feature = "rustls",
+    feature = "openssl",
+    feature = "acme"
+))]
+tokio::task_local! {
+    /// The result of the client certificate verification of the handshake running in the current
+    /// task, it's set by the rustls client verifier of [`RustlsConfig`](crate::conn::RustlsConfig).
+    pub(crate) static PEER_VERIFIED: Cell<Option<bool>>;
+}
+
+/// TLS streams which can tell their [`TlsInfo`] after the handshake.
+#[cfg(any(
+    feature = "native-tls",
+    feature = "rustls",
+    feature = "openssl",
+    feature = "acme"
+))]
+pub(crate) trait TlsInfoSource {
+    fn tls_info(&self) -> TlsInfo;
+}
+
+#[cfg(any(feature = "rustls", feature = "acme"))]
+impl<S> TlsInfoSource for tokio_rustls::server::TlsStream<S> {
+    fn tls_info(&self) -> TlsInfo {
+        use tokio_rustls::rustls::ProtocolVersion;
+
+        let (_, conn) = self.get_ref();
+        let peer_certificates: Vec<_> = conn
+            .peer_certificates()
+            .unwrap_or_default()
+            .iter()
+            .map(|cert| cert.to_vec())
+            .collect();
+        // Certificates are only accepted without being verified when the verifier could record it.
+        let peer_verified = PEER_VERIFIED
+            .try_with(Cell::get)
+            .ok()
+            .flatten()
+            .unwrap_or(!peer_certificates.is_empty());
+        TlsInfo {
+            peer_certificates,
+            peer_verified,
+            alpn_protocol: conn.alpn_protocol().map(ToOwned::to_owned),
+            cipher_suite: conn.negotiated_cipher_suite().map(|suite| {
+                let suite = suite.suite();
+                suite
+                    .as_str()
+                    .map_or_else(|| format!("{suite:?}"), ToOwned::to_owned)
+            }),
+            protocol_version: conn.protocol_version().map(|version| match version {
+                ProtocolVersion::TLSv1_2 => "TLSv1.2".to_owned(),
+                ProtocolVersion::TLSv1_3 => "TLSv1.3".to_owned(),
+                version => format!("{version:?}"),
+            }),
+            server_name: conn.server_name().map(ToOwned::to_owned),
+        }
+    }
+}
+
+#[cfg(feature = "native-tls")]
+impl<S> TlsInfoSource for tokio_native_tls::TlsStream<S>
+where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    fn tls_info(&self) -> TlsInfo {
+        let peer_certificate = self
+            .get_ref()
+            .peer_certificate()
+            .ok()
+            .flatten()
+            .and_then(|cert| cert.to_der().ok());
+        TlsInfo {
+            peer_certificates: peer_certificate.into_iter().collect(),
+            // native-tls doesn't verify the certificates of the clients.
+            peer_verified: false,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(feature = "openssl")]
+impl<S> TlsInfoSource for tokio_openssl::SslStream<S> {
+    fn tls_info(&self) -> TlsInfo {
+        use openssl::ssl::NameType;
+        use openssl::x509::X509VerifyResult;
+
+        let ssl = self.ssl();
+        // The chain of the server side doesn't include the certificate of the client.
+        let peer_certificates = ssl
+            .peer_certificate()
+            .into_iter()
+            .chain(
+                ssl.peer_cert_chain()
+                    .into_iter()
+                    .flatten()
+                    .map(ToOwned::to_owned),
+            )
+            .filter_map(|cert| cert.to_der().ok())
+            .collect::<Vec<_>>();
+        TlsInfo {
+            peer_verified: !peer_certificates.is_empty()
+                && ssl.verify_result() == X509VerifyResult::OK,
+            peer_certificates,
+            alpn_protocol: ssl.selected_alpn_protocol().map(ToOwned::to_owned),
+            cipher_suite: ssl
+                .current_cipher()
+                .map(|cipher| cipher.standard_name().unwrap_or(cipher.name()).to_owned()),
+            protocol_version: Some(ssl.version_str().to_owned()),
+            server_name: ssl.servername(NameType::HOST_NAME).map(ToOwned::to_owned),
+        }
+    }
+}
+
+#[cfg(feature = "quinn")]
+impl TlsInfoSource for quinn::Connection {
+    fn tls_info(&self) -> TlsInfo {
+        use tokio_rustls::rustls::pki_types::CertificateDer;
+
+        let handshake_data = self
+            .handshake_data()
+            .and_then(|data| data.downcast::<quinn::crypto::rustls::HandshakeData>().ok());
+        let peer_certificates: Vec<_> = self
+            .peer_identity()
+            .and_then(|identity| identity.downcast::<Vec<CertificateDer<'static>>>().ok())
+            .map(|certs| certs.iter().map(|cert| cert.to_vec()).collect())
+            .unwrap_or_default();
+        TlsInfo {
+            // The handshakes of quinn run out of the `PEER_VERIFIED` scope, so invalid
+            // certificates are always rejected.
+            peer_verified: !peer_certificates.is_empty(),
+            peer_certificates,
+            alpn_protocol: handshake_data
+                .as_ref()
+                .and_then(|data| data.protocol.clone()),
+            cipher_suite: None,
+            // QUIC always uses TLS 1.3.
+            protocol_version: Some("TLSv1.3".to_owned()),
+            server_name: handshake_data.and_then(|data| data.server_name),
+        }
+    }
+}
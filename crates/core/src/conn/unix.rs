@@ -1,6 +1,7 @@
 //! UnixListener module
 use std::fs::{set_permissions, Permissions};
-use std::io::Result as IoResult;
+use std::io::{Error as IoError, ErrorKind, Result as IoResult};
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
 use std::path::Path;
 use std::sync::Arc;
 
@@ -21,6 +22,7 @@ pub struct UnixListener<T> {
     path: T,
     permissions: Option<Permissions>,
     owner: Option<(Option<Uid>, Option<Gid>)>,
+    remove_on_bind: bool,
     #[cfg(feature = "socket2")]
     backlog: Option<u32>,
 }
@@ -34,6 +36,7 @@ impl<T> UnixListener<T> {
             path,
             permissions: None,
             owner: None,
+            remove_on_bind: false,
         }
     }
     /// Creates a new `UnixListener` bind to the specified path.
@@ -43,7 +46,9 @@ impl<T> UnixListener<T> {
         UnixListener {
             path,
             permissions: None,
-            owner: None, backlog: None
+            owner: None,
+            remove_on_bind: false,
+            backlog: None,
         }
     }
 
@@ -54,6 +59,21 @@ impl<T> UnixListener<T> {
         self
     }
 
+    /// Provides permission mode bits, such as `0o660`, to be set on actual bind.
+    #[inline]
+    pub fn mode(self, mode: u32) -> Self {
+        self.permissions(Permissions::from_mode(mode))
+    }
+
+    /// Removes a stale socket file left at the path before binding, default is `false`.
+    ///
+    /// Only socket files are removed, binding still fails if the path is any other kind of file.
+    #[inline]
+    pub fn remove_on_bind(mut self, remove_on_bind: bool) -> Self {
+        self.remove_on_bind = remove_on_bind;
+        self
+    }
+
     #[inline]
     /// Provides owner to be set on actual bind.
     pub fn owner(mut self, uid: Option<u32>, gid: Option<u32>) -> Self {
@@ -79,6 +99,14 @@ where
     type Acceptor = UnixAcceptor;
 
     async fn try_bind(self) -> crate::Result<Self::Acceptor> {
+        if self.remove_on_bind {
+            match std::fs::symlink_metadata(self.path.as_ref()) {
+                Ok(metadata) if metadata.file_type().is_socket() => std::fs::remove_file(self.path.as_ref())?,
+                Ok(_) => {}
+                Err(e) if e.kind() == ErrorKind::NotFound => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
         let inner = match (self.permissions, self.owner) {
             (Some(permissions), Some((uid, gid))) => {
                 let inner = TokioUnixListener::bind(self.path.clone())?;
@@ -114,7 +142,7 @@ where
             http_scheme: Scheme::HTTP,
         }];
         Ok(UnixAcceptor {
-            inner,
+            inner: Some(inner),
             holdings,
         })
     }
@@ -122,13 +150,22 @@ where
 
 /// `UnixAcceptor` is used to accept a Unix socket connection.
 pub struct UnixAcceptor {
-    inner: TokioUnixListener,
+    inner: Option<TokioUnixListener>,
     holdings: Vec<Holding>,
 }
 impl UnixAcceptor {
     /// Get the inner `TokioUnixListener`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the acceptor has been shut down, use [`UnixAcceptor::try_inner`] to check it.
     pub fn inner(&self) -> &TokioUnixListener {
-        &self.inner
+        self.inner.as_ref().expect("unix listener has been shut down")
+    }
+
+    /// Get the inner `TokioUnixListener`, returns `None` if the acceptor has been shut down.
+    pub fn try_inner(&self) -> Option<&TokioUnixListener> {
+        self.inner.as_ref()
     }
 }
 
@@ -143,7 +180,10 @@ impl Acceptor for UnixAcceptor {
 
     #[inline]
     async fn accept(&mut self, fuse_factory: Option<ArcFuseFactory>) -> IoResult<Accepted<Self::Conn>> {
-        self.inner.accept().await.map(move |(conn, remote_addr)|{
+        let Some(inner) = &self.inner else {
+            return Err(IoError::new(ErrorKind::NotConnected, "unix listener has been shut down"));
+        };
+        inner.accept().await.map(move |(conn, remote_addr)|{
             let remote_addr = Arc::new(remote_addr);
             let local_addr = self.holdings[0].local_addr.clone();
             Accepted {
@@ -158,6 +198,11 @@ impl Acceptor for UnixAcceptor {
             }
         })
     }
+
+    #[inline]
+    async fn shutdown(&mut self) {
+        self.inner.take();
+    }
 }
 
 #[cfg(test)]
@@ -181,4 +226,28 @@ mod tests {
         assert_eq!(conn.read_i32().await.unwrap(), 518);
         std::fs::remove_file(sock_file).unwrap();
     }
+
+    #[tokio::test]
+    async fn test_unix_listener_mode_and_remove_on_bind() {
+        let sock_file = "/tmp/test-salvo-remove-on-bind.sock";
+        let _ = std::fs::remove_file(sock_file);
+        let stale = std::os::unix::net::UnixListener::bind(sock_file).unwrap();
+        drop(stale);
+
+        assert!(UnixListener::new(sock_file).try_bind().await.is_err());
+        let _acceptor = UnixListener::new(sock_file)
+            .remove_on_bind(true)
+            .mode(0o660)
+            .bind()
+            .await;
+        let mode = std::fs::metadata(sock_file).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o660);
+        std::fs::remove_file(sock_file).unwrap();
+
+        let regular_file = "/tmp/test-salvo-remove-on-bind.txt";
+        std::fs::write(regular_file, "salvo").unwrap();
+        assert!(UnixListener::new(regular_file).remove_on_bind(true).try_bind().await.is_err());
+        assert!(std::path::Path::new(regular_file).exists());
+        std::fs::remove_file(regular_file).unwrap();
+    }
 }
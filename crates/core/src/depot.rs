@@ -2,6 +2,11 @@ use std::any::{Any, TypeId};
 use std::collections::HashMap;
 use std::fmt::{self, Debug, Formatter};
 
+use async_trait::async_trait;
+
+use crate::http::{Request, Response};
+use crate::{FlowCtrl, Handler};
+
 /// Store temp data for current request.
 ///
 /// A `Depot` created when server process a request from client. It will dropped when all process
@@ -30,10 +35,58 @@ use std::fmt::{self, Debug, Formatter};
 ///     Server::new(acceptor).serve(router).await;
 /// }
 /// ```
-
+///
+/// # Typed keys
+///
+/// String keys may collide when middlewares from different crates use the same name. Library
+/// authors can define a private key type implementing [`DepotKey`] instead, values stored with
+/// [`Depot::insert_typed`] are looked up by the [`TypeId`] of the key type, so they can not be
+/// replaced by other crates, and the value type is checked at compile time.
+///
+/// ```
+/// use salvo_core::{Depot, DepotKey};
+///
+/// struct CurrentUser;
+/// impl DepotKey for CurrentUser {
+///     type Value = String;
+/// }
+///
+/// let mut depot = Depot::new();
+/// depot.insert_typed::<CurrentUser>("client".to_owned());
+/// assert_eq!(depot.get_typed::<CurrentUser>().map(String::as_str), Some("client"));
+/// ```
+///
+/// # Scopes
+///
+/// Values inserted after [`Depot::push_scope`] are removed by [`Depot::pop_scope`], and the values
+/// they replaced are restored. Use [`Router::depot_scope`] to clear the values inserted by the
+/// handlers of a router when they finish, so the same key can be reused by modules mounted under
+/// different routers.
+///
+/// [`Router::depot_scope`]: crate::Router::depot_scope
 #[derive(Default)]
 pub struct Depot {
     map: HashMap<String, Box<dyn Any + Send + Sync>>,
+    typed: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
+    scopes: Vec<Scope>,
+}
+
+/// The keys inserted in a scope, with the values they had before the scope.
+///
+/// Only the first insertion of a key in the scope is recorded, so its size is bounded by the
+/// number of distinct keys.
+#[derive(Default)]
+struct Scope {
+    map: HashMap<String, Option<Box<dyn Any + Send + Sync>>>,
+    typed: HashMap<TypeId, Option<Box<dyn Any + Send + Sync>>>,
+}
+
+/// Key type for values stored in [`Depot`] with the typed-key API.
+///
+/// The key type itself is never stored, it is usually an empty struct private to the crate.
+pub trait DepotKey: 'static {
+    /// Type of the value stored with this key.
+    type Value: Any + Send + Sync;
 }
 
 #[inline]
@@ -49,6 +102,8 @@ impl Depot {
     pub fn new() -> Depot {
         Depot {
             map: HashMap::new(),
+            typed: HashMap::new(),
+            scopes: Vec::new(),
         }
     }
 
@@ -65,6 +120,8 @@ impl Depot {
     pub fn with_capacity(capacity: usize) -> Self {
         Depot {
             map: HashMap::with_capacity(capacity),
+            typed: HashMap::new(),
+            scopes: Vec::new(),
         }
     }
     /// Returns the number of elements the depot can hold without reallocating.
@@ -76,8 +133,7 @@ impl Depot {
     /// Inject a value into the depot.
     #[inline]
     pub fn inject<V: Any + Send + Sync>(&mut self, value: V) -> &mut Self {
-        self.map.insert(type_key::<V>(), Box::new(value));
-        self
+        self.insert(type_key::<V>(), value)
     }
 
     /// Obtain a reference to a value previous inject to the depot.
@@ -107,7 +163,13 @@ impl Depot {
         K: Into<String>,
         V: Any + Send + Sync,
     {
-        self.map.insert(key.into(), Box::new(value));
+        let key = key.into();
+        if let Some(scope) = self.scopes.last_mut() {
+            let old = self.map.insert(key.clone(), Box::new(value));
+            scope.map.entry(key).or_insert(old);
+        } else {
+            self.map.insert(key, Box::new(value));
+        }
         self
     }
 
@@ -187,12 +249,112 @@ impl Depot {
     ) -> Result<T, Option<Box<dyn Any + Send + Sync>>> {
         self.remove(&type_key::<T>())
     }
+
+    /// Inserts a value with the typed key `K`, returns the previous value of this key.
+    ///
+    /// In a [scope](Depot::push_scope), a value inserted before the scope is only shadowed: it is
+    /// kept to be restored when the scope is popped, and `None` is returned. A value inserted in
+    /// the same scope is returned.
+    #[inline]
+    pub fn insert_typed<K: DepotKey>(&mut self, value: K::Value) -> Option<K::Value> {
+        let key = TypeId::of::<K>();
+        let old = self.typed.insert(key, Box::new(value));
+        let old = match self.scopes.last_mut() {
+            Some(scope) if !scope.typed.contains_key(&key) => {
+                scope.typed.insert(key, old);
+                None
+            }
+            _ => old,
+        };
+        old.and_then(|old| old.downcast().ok()).map(|old| *old)
+    }
+
+    /// Check is there a value stored in depot with the typed key `K`.
+    #[inline]
+    pub fn contains_typed<K: DepotKey>(&self) -> bool {
+        self.typed.contains_key(&TypeId::of::<K>())
+    }
+
+    /// Immutably borrows the value of the typed key `K`, returns `None` if it is not present.
+    #[inline]
+    pub fn get_typed<K: DepotKey>(&self) -> Option<&K::Value> {
+        self.typed
+            .get(&TypeId::of::<K>())
+            .and_then(|value| value.downcast_ref())
+    }
+
+    /// Mutably borrows the value of the typed key `K`, returns `None` if it is not present.
+    #[inline]
+    pub fn get_typed_mut<K: DepotKey>(&mut self) -> Option<&mut K::Value> {
+        self.typed
+            .get_mut(&TypeId::of::<K>())
+            .and_then(|value| value.downcast_mut())
+    }
+
+    /// Removes the value of the typed key `K` and returns it, returns `None` if it is not present.
+    #[inline]
+    pub fn remove_typed<K: DepotKey>(&mut self) -> Option<K::Value> {
+        self.typed
+            .remove(&TypeId::of::<K>())
+            .and_then(|value| value.downcast().ok())
+            .map(|value| *value)
+    }
+
+    /// Starts a new scope, the values inserted from now on are removed by [`Depot::pop_scope`].
+    ///
+    /// Values inserted before the scope and removed or mutated in it are not restored.
+    #[inline]
+    pub fn push_scope(&mut self) {
+        self.scopes.push(Scope::default());
+    }
+
+    /// Ends the current scope, removes the values inserted in it and restores the values they
+    /// replaced. Returns `false` if there is no scope.
+    pub fn pop_scope(&mut self) -> bool {
+        let Some(scope) = self.scopes.pop() else {
+            return false;
+        };
+        for (key, old) in scope.map {
+            match old {
+                Some(value) => self.map.insert(key, value),
+                None => self.map.remove(&key),
+            };
+        }
+        for (key, old) in scope.typed {
+            match old {
+                Some(value) => self.typed.insert(key, value),
+                None => self.typed.remove(&key),
+            };
+        }
+        true
+    }
+}
+
+/// Handler which runs the next handlers in a new [`Depot`] scope.
+///
+/// It is added by [`Router::depot_scope`](crate::Router::depot_scope).
+#[derive(Default, Debug, Clone, Copy)]
+pub struct DepotScope;
+#[async_trait]
+impl Handler for DepotScope {
+    async fn handle(
+        &self,
+        req: &mut Request,
+        depot: &mut Depot,
+        res: &mut Response,
+        ctrl: &mut FlowCtrl,
+    ) {
+        depot.push_scope();
+        ctrl.call_next(req, depot, res).await;
+        depot.pop_scope();
+    }
 }
 
 impl Debug for Depot {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         f.debug_struct("Depot")
             .field("keys", &self.map.keys())
+            .field("typed_keys", &self.typed.len())
             .finish()
     }
 }
@@ -219,6 +381,117 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_depot_typed() {
+        struct UserKey;
+        impl DepotKey for UserKey {
+            type Value = String;
+        }
+        struct OtherUserKey;
+        impl DepotKey for OtherUserKey {
+            type Value = u32;
+        }
+
+        let mut depot = Depot::new();
+        assert!(!depot.contains_typed::<UserKey>());
+        assert_eq!(depot.insert_typed::<UserKey>("one".to_owned()), None);
+        depot.insert_typed::<OtherUserKey>(1);
+        depot.insert("user", 2u8);
+        depot.inject(3u32);
+
+        assert_eq!(depot.get_typed::<UserKey>().unwrap(), "one");
+        assert_eq!(depot.get_typed::<OtherUserKey>(), Some(&1));
+        assert_eq!(depot.obtain::<u32>().unwrap(), &3);
+        depot.get_typed_mut::<UserKey>().unwrap().push_str("!");
+        assert_eq!(
+            depot.insert_typed::<UserKey>("two".to_owned()).as_deref(),
+            Some("one!")
+        );
+        assert_eq!(depot.remove_typed::<UserKey>().as_deref(), Some("two"));
+        assert_eq!(depot.get_typed::<UserKey>(), None);
+        assert!(depot.contains_typed::<OtherUserKey>());
+    }
+
+    #[test]
+    fn test_depot_scope() {
+        struct UserKey;
+        impl DepotKey for UserKey {
+            type Value = String;
+        }
+
+        let mut depot = Depot::new();
+        depot.insert("user", "outer");
+        depot.insert("keep", 1u8);
+        assert!(!depot.pop_scope());
+
+        depot.push_scope();
+        depot.insert("user", "inner");
+        depot.insert("temp", 2u8);
+        depot.insert_typed::<UserKey>("inner".to_owned());
+        depot.push_scope();
+        depot.insert("user", "innermost");
+        assert_eq!(*depot.get::<&str>("user").unwrap(), "innermost");
+        assert!(depot.pop_scope());
+        assert_eq!(*depot.get::<&str>("user").unwrap(), "inner");
+        assert!(depot.pop_scope());
+
+        assert_eq!(*depot.get::<&str>("user").unwrap(), "outer");
+        assert!(!depot.contains_key("temp"));
+        assert!(!depot.contains_typed::<UserKey>());
+        assert_eq!(*depot.get::<u8>("keep").unwrap(), 1);
+
+        depot.insert_typed::<UserKey>("outer".to_owned());
+        depot.push_scope();
+        assert_eq!(depot.insert_typed::<UserKey>("one".to_owned()), None);
+        assert_eq!(
+            depot.insert_typed::<UserKey>("two".to_owned()).as_deref(),
+            Some("one")
+        );
+        assert!(depot.pop_scope());
+        assert_eq!(
+            depot.get_typed::<UserKey>().map(String::as_str),
+            Some("outer")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_router_depot_scope() {
+        #[handler]
+        async fn set_module(depot: &mut Depot) {
+            depot.insert("module", "admin");
+        }
+        #[handler]
+        async fn check_after(
+            req: &mut Request,
+            depot: &mut Depot,
+            res: &mut Response,
+            ctrl: &mut FlowCtrl,
+        ) {
+            ctrl.call_next(req, depot, res).await;
+            let module = depot.get::<&str>("module").copied().unwrap_or("none");
+            res.render(format!(" after: {module}"));
+        }
+        #[handler]
+        async fn hello(depot: &mut Depot, res: &mut Response) {
+            let module = depot.get::<&str>("module").copied().unwrap_or("none");
+            res.render(format!("in: {module}"));
+        }
+
+        let router = Router::new().hoop(check_after).push(
+            Router::with_path("admin")
+                .depot_scope()
+                .hoop(set_module)
+                .get(hello),
+        );
+        let content = TestClient::get("http://127.0.0.1:5800/admin")
+            .send(router)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, "in: admin after: none");
+    }
+
     #[tokio::test]
     async fn test_middleware_use_depot() {
         #[handler]
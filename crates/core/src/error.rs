@@ -177,6 +177,21 @@ cfg_feature! {
     }
 }
 
+impl Scribe for IoError {
+    #[inline]
+    fn render(self, res: &mut Response) {
+        tracing::error!(error = ?self, "io error occurred");
+        res.render(StatusError::internal_server_error().cause(self));
+    }
+}
+impl Scribe for BoxedError {
+    #[inline]
+    fn render(self, res: &mut Response) {
+        tracing::error!(error = ?self, "error occurred");
+        res.render(StatusError::internal_server_error().cause(self));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::http::*;
@@ -216,4 +231,20 @@ mod tests {
         e.write(&mut req, &mut depot, &mut res).await;
         assert_eq!(res.status_code, Some(StatusCode::INTERNAL_SERVER_ERROR));
     }
+
+    #[tokio::test]
+    async fn test_io_and_boxed_error() {
+        let mut req = Request::default();
+        let mut depot = Depot::new();
+
+        let mut res = Response::default();
+        let e = IoError::new(std::io::ErrorKind::NotFound, "config not found");
+        Err::<(), _>(e).write(&mut req, &mut depot, &mut res).await;
+        assert_eq!(res.status_code, Some(StatusCode::INTERNAL_SERVER_ERROR));
+
+        let mut res = Response::default();
+        let e: BoxedError = "detail message".into();
+        Err::<(), _>(e).write(&mut req, &mut depot, &mut res).await;
+        assert_eq!(res.status_code, Some(StatusCode::INTERNAL_SERVER_ERROR));
+    }
 }
@@ -67,6 +67,8 @@ pub mod metadata;
 pub use metadata::Metadata;
 mod case;
 pub use case::RenameRule;
+mod typed_header;
+pub use typed_header::TypedHeader;
 
 use std::fmt::Debug;
 use std::future::Future;
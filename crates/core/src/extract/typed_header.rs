@@ -0,0 +1,117 @@
+use std::ops::{Deref, DerefMut};
+
+use headers::Header;
+
+use super::{Extractible, Metadata};
+use crate::http::{Request, StatusError};
+
+/// Extractor for a header parsed by the [`headers`] crate, such as [`headers::Range`] or
+/// [`headers::IfModifiedSince`].
+///
+/// `400 Bad Request` is rendered if the header is missing or malformed. Use
+/// [`Request::typed_header`] if the header is optional, and [`Response::typed_header`] to insert
+/// a typed header into the response.
+///
+/// [`Response::typed_header`]: crate::http::Response::typed_header
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::extract::TypedHeader;
+/// use salvo_core::http::headers::UserAgent;
+/// use salvo_core::prelude::*;
+///
+/// #[handler]
+/// async fn hello(user_agent: TypedHeader<UserAgent>) -> String {
+///     format!("Hello {}", user_agent.as_str())
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TypedHeader<H>(pub H);
+
+impl<H> TypedHeader<H> {
+    /// Consumes self and returns the inner header.
+    #[inline]
+    pub fn into_inner(self) -> H {
+        self.0
+    }
+}
+
+impl<H> Deref for TypedHeader<H> {
+    type Target = H;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+impl<H> DerefMut for TypedHeader<H> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<'ex, H> Extractible<'ex> for TypedHeader<H>
+where
+    H: Header + Send,
+{
+    fn metadata() -> &'ex Metadata {
+        static METADATA: Metadata = Metadata::new("");
+        &METADATA
+    }
+    #[allow(refining_impl_trait)]
+    async fn extract(req: &'ex mut Request) -> Result<Self, StatusError> {
+        match req.typed_header::<H>() {
+            Ok(Some(header)) => Ok(Self(header)),
+            Ok(None) => Err(StatusError::bad_request()
+                .brief(format!("Missing request header `{}`.", H::name()))),
+            Err(_) => Err(StatusError::bad_request()
+                .brief(format!("Malformed request header `{}`.", H::name()))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use headers::{ContentLength, IfModifiedSince};
+
+    use super::*;
+    use crate::prelude::*;
+    use crate::test::{ResponseExt, TestClient};
+
+    #[handler]
+    async fn show_length(length: TypedHeader<ContentLength>) -> String {
+        length.0 .0.to_string()
+    }
+
+    #[tokio::test]
+    async fn test_typed_header() {
+        let service = Service::new(Router::new().get(show_length));
+        let access = |content_length: Option<&'static str>| {
+            let service = &service;
+            async move {
+                let mut client = TestClient::get("http://127.0.0.1:5801/");
+                if let Some(content_length) = content_length {
+                    client = client.add_header("content-length", content_length, true);
+                }
+                let mut res = client.send(service).await;
+                (res.status_code, res.take_string().await.unwrap())
+            }
+        };
+
+        assert_eq!(access(Some("5")).await, (Some(StatusCode::OK), "5".into()));
+        let (status_code, body) = access(Some("five")).await;
+        assert_eq!(status_code, Some(StatusCode::BAD_REQUEST));
+        assert!(body.contains("Malformed request header `content-length`."));
+        let (status_code, body) = access(None).await;
+        assert_eq!(status_code, Some(StatusCode::BAD_REQUEST));
+        assert!(body.contains("Missing request header `content-length`."));
+
+        let mut req = Request::default();
+        assert_eq!(req.typed_header::<IfModifiedSince>().unwrap(), None);
+        req.headers_mut()
+            .insert("if-modified-since", "yesterday".parse().unwrap());
+        assert!(req.typed_header::<IfModifiedSince>().is_err());
+    }
+}
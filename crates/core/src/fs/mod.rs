@@ -87,7 +87,9 @@ mod test {
     use mime::Mime;
 
     use super::*;
-    use crate::http::header::HeaderValue;
+    use crate::http::header::*;
+    use crate::http::{HeaderMap, Response, StatusCode};
+    use crate::test::ResponseExt;
 
     #[tokio::test]
     async fn test_chunk_read() {
@@ -131,4 +133,199 @@ mod test {
             ))
         );
     }
+
+    static DIGITS: std::sync::LazyLock<tempfile::TempDir> = std::sync::LazyLock::new(|| {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("digits.txt"), "0123456789").unwrap();
+        dir
+    });
+
+    async fn send_file(headers: &[(HeaderName, &str)]) -> Response {
+        let path = DIGITS.path().join("digits.txt");
+        let mut req_headers = HeaderMap::new();
+        for (name, value) in headers {
+            req_headers.insert(name, HeaderValue::from_str(value).unwrap());
+        }
+        let mut res = Response::new();
+        NamedFile::open(&path)
+            .await
+            .unwrap()
+            .send(&req_headers, &mut res)
+            .await;
+        res
+    }
+
+    #[tokio::test]
+    async fn test_named_file_range() {
+        let mut res = send_file(&[(RANGE, "bytes=2-4")]).await;
+        assert_eq!(res.status_code, Some(StatusCode::PARTIAL_CONTENT));
+        assert_eq!(res.headers().get(CONTENT_RANGE).unwrap(), "bytes 2-4/10");
+        assert_eq!(res.headers().get(CONTENT_LENGTH).unwrap(), "3");
+        assert_eq!(res.take_string().await.unwrap(), "234");
+
+        let mut res = send_file(&[(RANGE, "bytes=-3")]).await;
+        assert_eq!(res.headers().get(CONTENT_RANGE).unwrap(), "bytes 7-9/10");
+        assert_eq!(res.take_string().await.unwrap(), "789");
+
+        let res = send_file(&[(RANGE, "bytes=20-30")]).await;
+        assert_eq!(res.status_code, Some(StatusCode::RANGE_NOT_SATISFIABLE));
+        assert_eq!(res.headers().get(CONTENT_RANGE).unwrap(), "bytes */10");
+
+        // An empty range set is served as the whole file.
+        let mut res = send_file(&[(RANGE, "bytes= , ")]).await;
+        assert_eq!(res.status_code, Some(StatusCode::OK));
+        assert_eq!(res.take_string().await.unwrap(), "0123456789");
+    }
+
+    #[tokio::test]
+    async fn test_named_file_multi_range() {
+        let mut res = send_file(&[(RANGE, "bytes=0-1, 8-")]).await;
+        assert_eq!(res.status_code, Some(StatusCode::PARTIAL_CONTENT));
+        let content_type = res
+            .headers()
+            .get(CONTENT_TYPE)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_owned();
+        let boundary = content_type
+            .strip_prefix("multipart/byteranges; boundary=")
+            .unwrap();
+        let content_length: usize = res
+            .headers()
+            .get(CONTENT_LENGTH)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        let body = res.take_string().await.unwrap();
+        assert_eq!(
+            body,
+            format!(
+                "--{boundary}\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Range: bytes 0-1/10\r\n\r\n01\r\n\
+                --{boundary}\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Range: bytes 8-9/10\r\n\r\n89\r\n\
+                --{boundary}--\r\n"
+            )
+        );
+        assert_eq!(body.len(), content_length);
+    }
+
+    #[tokio::test]
+    async fn test_named_file_conditional() {
+        let res = send_file(&[]).await;
+        let etag = res
+            .headers()
+            .get(ETAG)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_owned();
+        let last_modified = res
+            .headers()
+            .get(LAST_MODIFIED)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_owned();
+
+        let res = send_file(&[(IF_NONE_MATCH, &etag)]).await;
+        assert_eq!(res.status_code, Some(StatusCode::NOT_MODIFIED));
+        let res = send_file(&[(IF_MODIFIED_SINCE, &last_modified)]).await;
+        assert_eq!(res.status_code, Some(StatusCode::NOT_MODIFIED));
+        // `If-None-Match` takes precedence over `If-Modified-Since`.
+        let res = send_file(&[
+            (IF_NONE_MATCH, "\"outdated\""),
+            (IF_MODIFIED_SINCE, &last_modified),
+        ])
+        .await;
+        assert_eq!(res.status_code, Some(StatusCode::OK));
+    }
+
+    #[cfg(feature = "content-hash")]
+    #[tokio::test]
+    async fn test_named_file_etag_strategy() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "same content").unwrap();
+        std::fs::write(dir.path().join("b.txt"), "same content").unwrap();
+        let content_etag = |name: &str| {
+            let path = dir.path().join(name);
+            async move {
+                let file = NamedFile::builder(path)
+                    .etag_strategy(EtagStrategy::ContentHash)
+                    .build()
+                    .await
+                    .unwrap();
+                file.etag().unwrap()
+            }
+        };
+        assert_eq!(content_etag("a.txt").await, content_etag("b.txt").await);
+        assert_ne!(
+            NamedFile::open(dir.path().join("a.txt"))
+                .await
+                .unwrap()
+                .etag(),
+            NamedFile::open(dir.path().join("b.txt"))
+                .await
+                .unwrap()
+                .etag()
+        );
+
+        let mut res = Response::new();
+        NamedFile::builder(dir.path().join("a.txt"))
+            .use_etag(false)
+            .build()
+            .await
+            .unwrap()
+            .send(&HeaderMap::new(), &mut res)
+            .await;
+        assert!(!res.headers().contains_key(ETAG));
+    }
+
+    #[tokio::test]
+    async fn test_named_file_if_range() {
+        let res = send_file(&[]).await;
+        let etag = res
+            .headers()
+            .get(ETAG)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_owned();
+
+        let mut res = send_file(&[(RANGE, "bytes=0-1"), (IF_RANGE, &etag)]).await;
+        assert_eq!(res.status_code, Some(StatusCode::PARTIAL_CONTENT));
+        assert_eq!(res.take_string().await.unwrap(), "01");
+
+        let mut res = send_file(&[(RANGE, "bytes=0-1"), (IF_RANGE, "\"outdated\"")]).await;
+        assert_eq!(res.status_code, Some(StatusCode::OK));
+        assert_eq!(res.take_string().await.unwrap(), "0123456789");
+    }
+
+    #[tokio::test]
+    async fn test_response_send_file() {
+        use crate::prelude::*;
+        use crate::test::TestClient;
+
+        #[handler]
+        async fn digits(req: &mut Request, res: &mut Response) {
+            res.send_file(DIGITS.path().join("digits.txt"), req).await;
+        }
+        let service = Service::new(Router::with_path("digits").get(digits).head(digits));
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/digits")
+            .add_header(RANGE, "bytes=2-4", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::PARTIAL_CONTENT));
+        assert_eq!(res.headers()[CONTENT_TYPE], "text/plain; charset=utf-8");
+        assert_eq!(res.take_string().await.unwrap(), "234");
+
+        let mut res = TestClient::head("http://127.0.0.1:5801/digits")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::OK));
+        assert_eq!(res.headers()[CONTENT_LENGTH], "10");
+        assert!(res.take_string().await.unwrap().is_empty());
+    }
 }
@@ -1,22 +1,31 @@
 use std::borrow::Cow;
 use std::cmp;
+#[cfg(feature = "content-hash")]
+use std::collections::HashMap;
 use std::fs::Metadata;
 use std::ops::{Deref, DerefMut};
 use std::path::{Path, PathBuf};
+#[cfg(feature = "content-hash")]
+use std::sync::LazyLock;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 #[cfg(unix)]
 use std::os::unix::fs::MetadataExt;
 
+use bytes::Bytes;
 use enumflags2::{bitflags, BitFlags};
+use futures_util::future;
+use futures_util::stream::{self, BoxStream, StreamExt};
 use headers::*;
+#[cfg(feature = "content-hash")]
+use parking_lot::Mutex;
 use tokio::fs::File;
 
 use super::{ChunkedFile, ChunkedState};
 use crate::http::header::{
     CONTENT_DISPOSITION, CONTENT_ENCODING, CONTENT_TYPE, IF_NONE_MATCH, RANGE,
 };
-use crate::http::{HttpRange, Mime, Request, Response, StatusCode, StatusError};
+use crate::http::{HttpRange, Method, Mime, Request, ResBody, Response, StatusCode, StatusError};
 use crate::{async_trait, Depot, Error, Result, Writer};
 
 const CHUNK_SIZE: u64 = 1024 * 1024;
@@ -30,6 +39,25 @@ pub(crate) enum Flag {
     ContentDisposition = 0b0100,
 }
 
+/// Strategy to compute the `ETag` of a [`NamedFile`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum EtagStrategy {
+    /// From the inode, size and modification time of the file, like Apache.
+    ///
+    /// It is cheap, but changes when an unchanged file is deployed again with a new modification
+    /// time, and differs between servers serving the same file.
+    #[default]
+    Metadata,
+    /// From a SHA-256 hash of the file content.
+    ///
+    /// It only changes when the content changes. The whole file is read to compute it the first
+    /// time it is opened, the hash is then cached until the path, size or modification time of
+    /// the file changes.
+    #[cfg(feature = "content-hash")]
+    ContentHash,
+}
+
 /// A file with an associated name.
 ///
 /// This struct represents a file with an associated name. It provides methods for opening and sending the file,
@@ -54,6 +82,7 @@ pub struct NamedFile {
     content_type: mime::Mime,
     content_disposition: Option<HeaderValue>,
     content_encoding: Option<HeaderValue>,
+    content_hash: Option<String>,
 }
 
 /// Builder for build [`NamedFile`].
@@ -66,6 +95,7 @@ pub struct NamedFileBuilder {
     content_encoding: Option<String>,
     buffer_size: Option<u64>,
     flags: BitFlags<Flag>,
+    etag_strategy: EtagStrategy,
 }
 impl NamedFileBuilder {
     /// Sets attached filename and returns `Self`.
@@ -126,6 +156,15 @@ impl NamedFileBuilder {
         self
     }
 
+    /// Sets how the `ETag` is computed.
+    ///
+    /// Default is [`EtagStrategy::Metadata`].
+    #[inline]
+    pub fn etag_strategy(mut self, etag_strategy: EtagStrategy) -> Self {
+        self.etag_strategy = etag_strategy;
+        self
+    }
+
     ///Specifies whether to use Last-Modified or not.
     ///
     ///Default is true.
@@ -161,6 +200,7 @@ impl NamedFileBuilder {
             disposition_type,
             attached_name,
             flags,
+            etag_strategy,
         } = self;
 
         let file = File::open(&path).await?;
@@ -181,6 +221,18 @@ impl NamedFileBuilder {
         });
         let metadata = file.metadata().await?;
         let modified = metadata.modified().ok();
+        #[cfg(feature = "content-hash")]
+        let content_hash =
+            if flags.contains(Flag::Etag) && etag_strategy == EtagStrategy::ContentHash {
+                Some(content_hash(&path, &file, &metadata).await?)
+            } else {
+                None
+            };
+        #[cfg(not(feature = "content-hash"))]
+        let content_hash = {
+            let _ = etag_strategy;
+            None
+        };
         let content_encoding = match content_encoding {
             Some(content_encoding) => Some(
                 content_encoding
@@ -207,11 +259,61 @@ impl NamedFileBuilder {
             metadata,
             modified,
             content_encoding,
+            content_hash,
             buffer_size: buffer_size.unwrap_or(CHUNK_SIZE),
             flags,
         })
     }
 }
+cfg_feature! {
+    #![feature = "content-hash"]
+
+    type ContentHashKey = (PathBuf, Option<SystemTime>, u64);
+    static CONTENT_HASHES: LazyLock<Mutex<HashMap<ContentHashKey, String>>> =
+        LazyLock::new(Default::default);
+
+    /// Returns the content hash of the file, from the cache if the file is unchanged.
+    async fn content_hash(path: &Path, file: &File, metadata: &Metadata) -> Result<String> {
+        let key = (path.to_owned(), metadata.modified().ok(), metadata.len());
+        if let Some(hash) = CONTENT_HASHES.lock().get(&key) {
+            return Ok(hash.clone());
+        }
+        let file = file.try_clone().await?.into_std().await;
+        let hash = tokio::task::spawn_blocking(move || hash_content(file))
+            .await
+            .map_err(Error::other)??;
+        let mut hashes = CONTENT_HASHES.lock();
+        // Drop the entries of the older versions of the file.
+        hashes.retain(|(p, ..), _| p != &key.0);
+        hashes.insert(key, hash.clone());
+        Ok(hash)
+    }
+
+    /// Returns the hex encoded first 128 bits of the SHA-256 hash of the content.
+    fn hash_content(mut file: std::fs::File) -> std::io::Result<String> {
+        use sha2::{Digest, Sha256};
+        use std::fmt::Write;
+        use std::io::{Read, Seek};
+
+        let mut hasher = Sha256::new();
+        let mut buf = vec![0; 64 * 1024];
+        loop {
+            let len = file.read(&mut buf)?;
+            if len == 0 {
+                break;
+            }
+            hasher.update(&buf[..len]);
+        }
+        // The handle shares its cursor with the `NamedFile`.
+        file.rewind()?;
+        Ok(hasher.finalize()[..16]
+            .iter()
+            .fold(String::with_capacity(32), |mut hex, b| {
+                let _ = write!(hex, "{b:02x}");
+                hex
+            }))
+    }
+}
 fn build_content_disposition(
     file_path: impl AsRef<Path>,
     content_type: &Mime,
@@ -261,6 +363,7 @@ impl NamedFile {
             content_encoding: None,
             buffer_size: None,
             flags: BitFlags::default(),
+            etag_strategy: EtagStrategy::default(),
         }
     }
 
@@ -347,6 +450,9 @@ impl NamedFile {
 
     /// Get ETag value.
     pub fn etag(&self) -> Option<ETag> {
+        if let Some(content_hash) = &self.content_hash {
+            return format!("\"{content_hash}\"").parse::<ETag>().ok();
+        }
         // This etag format is similar to Apache's.
         self.modified.as_ref().and_then(|mtime| {
             let ino = {
@@ -407,7 +513,23 @@ impl NamedFile {
             self.flags.remove(Flag::LastModified);
         }
     }
+    /// Consume self and send content to [`Response`] as the response of `req`.
+    ///
+    /// It is the same as [`NamedFile::send`], except that the body is dropped for `HEAD`
+    /// requests, the headers including `Content-Length` are kept.
+    pub async fn send_to(self, req: &Request, res: &mut Response) {
+        self.send(req.headers(), res).await;
+        if req.method() == Method::HEAD {
+            res.body(ResBody::None);
+        }
+    }
+
     ///Consume self and send content to [`Response`].
+    ///
+    /// Conditional headers are checked first, then `Range` requests are answered with
+    /// `206 Partial Content`, multiple ranges as `multipart/byteranges`. The range is ignored if
+    /// `If-Range` does not match the current file, and `416 Range Not Satisfiable` is sent when
+    /// no range overlaps the file.
     pub async fn send(mut self, req_headers: &HeaderMap, res: &mut Response) {
         let etag = if self.flags.contains(Flag::Etag) {
             self.etag()
@@ -468,36 +590,15 @@ impl NamedFile {
         if let Some(lm) = last_modified {
             res.headers_mut().typed_insert(LastModified::from(lm));
         }
-        if let Some(etag) = self.etag() {
-            res.headers_mut().typed_insert(etag);
+        if let Some(etag) = &etag {
+            res.headers_mut().typed_insert(etag.clone());
         }
         res.headers_mut().typed_insert(AcceptRanges::bytes());
 
-        let mut length = self.metadata.len();
         if let Some(content_encoding) = &self.content_encoding {
             res.headers_mut()
                 .insert(CONTENT_ENCODING, content_encoding.clone());
         }
-        let mut offset = 0;
-
-        // check for range header
-        let range = req_headers.get(RANGE);
-        if let Some(range) = range {
-            if let Ok(range) = range.to_str() {
-                if let Ok(range) = HttpRange::parse(range, length) {
-                    length = range[0].length;
-                    offset = range[0].start;
-                } else {
-                    res.headers_mut()
-                        .typed_insert(ContentRange::unsatisfied_bytes(length));
-                    res.status_code(StatusCode::RANGE_NOT_SATISFIABLE);
-                    return;
-                };
-            } else {
-                res.status_code(StatusCode::BAD_REQUEST);
-                return;
-            };
-        }
 
         if precondition_failed {
             res.status_code(StatusCode::PRECONDITION_FAILED);
@@ -507,37 +608,127 @@ impl NamedFile {
             return;
         }
 
-        if offset != 0 || length != self.metadata.len() || range.is_some() {
-            res.status_code(StatusCode::PARTIAL_CONTENT);
-            match ContentRange::bytes(offset..offset + length - 1, self.metadata.len()) {
-                Ok(content_range) => {
-                    res.headers_mut().typed_insert(content_range);
+        // If the file changed since the client got its partial copy, `If-Range` makes the range
+        // ignored and the whole file is sent.
+        let size = self.metadata.len();
+        let range_allowed = req_headers
+            .typed_get::<IfRange>()
+            .map(|if_range| {
+                !if_range.is_modified(
+                    etag.as_ref(),
+                    last_modified.map(LastModified::from).as_ref(),
+                )
+            })
+            .unwrap_or(true);
+        let ranges = match req_headers.get(RANGE) {
+            Some(range) if range_allowed => match range.to_str() {
+                Ok(range) => match HttpRange::parse(range, size) {
+                    Ok(ranges) => ranges,
+                    Err(_) => {
+                        res.headers_mut()
+                            .typed_insert(ContentRange::unsatisfied_bytes(size));
+                        res.status_code(StatusCode::RANGE_NOT_SATISFIABLE);
+                        return;
+                    }
+                },
+                Err(_) => {
+                    res.status_code(StatusCode::BAD_REQUEST);
+                    return;
                 }
-                Err(e) => {
-                    tracing::error!(error = ?e, "set file's content ranage failed");
+            },
+            _ => Vec::new(),
+        };
+
+        let buffer_size = self.buffer_size;
+        let file = self.file.into_std().await;
+        match ranges.as_slice() {
+            [] => {
+                res.status_code(StatusCode::OK);
+                let reader = ChunkedFile {
+                    offset: 0,
+                    state: ChunkedState::File(Some(file)),
+                    total_size: size,
+                    read_size: 0,
+                    buffer_size,
+                };
+                res.headers_mut().typed_insert(ContentLength(size));
+                res.stream(reader);
+            }
+            [range] => {
+                res.status_code(StatusCode::PARTIAL_CONTENT);
+                match ContentRange::bytes(range.start..range.start + range.length, size) {
+                    Ok(content_range) => {
+                        res.headers_mut().typed_insert(content_range);
+                    }
+                    Err(e) => {
+                        tracing::error!(error = ?e, "set file's content ranage failed");
+                    }
                 }
+                let reader = ChunkedFile {
+                    offset: range.start,
+                    total_size: cmp::min(range.length, size),
+                    read_size: 0,
+                    state: ChunkedState::File(Some(file)),
+                    buffer_size,
+                };
+                res.headers_mut()
+                    .typed_insert(ContentLength(reader.total_size));
+                res.stream(reader);
+            }
+            ranges => {
+                // Multiple ranges are sent as `multipart/byteranges`, each part has its own
+                // `Content-Type` and `Content-Range` headers.
+                let boundary = format!("{:016x}", rand::random::<u64>());
+                let mut content_length = 0;
+                let mut parts: Vec<BoxStream<'static, std::io::Result<Bytes>>> =
+                    Vec::with_capacity(ranges.len() + 1);
+                for range in ranges {
+                    let file = match file.try_clone() {
+                        Ok(file) => file,
+                        Err(e) => {
+                            tracing::error!(error = ?e, "clone file for byte ranges failed");
+                            res.status_code(StatusCode::INTERNAL_SERVER_ERROR);
+                            return;
+                        }
+                    };
+                    let head = format!(
+                        "--{boundary}\r\nContent-Type: {}\r\nContent-Range: bytes {}-{}/{size}\r\n\r\n",
+                        self.content_type,
+                        range.start,
+                        range.start + range.length - 1
+                    );
+                    content_length += head.len() as u64 + range.length + 2;
+                    let reader = ChunkedFile {
+                        offset: range.start,
+                        total_size: range.length,
+                        read_size: 0,
+                        state: ChunkedState::File(Some(file)),
+                        buffer_size,
+                    };
+                    parts.push(
+                        stream::once(future::ready(Ok(Bytes::from(head))))
+                            .chain(reader)
+                            .chain(stream::once(future::ready(Ok(Bytes::from_static(b"\r\n")))))
+                            .boxed(),
+                    );
+                }
+                let tail = format!("--{boundary}--\r\n");
+                content_length += tail.len() as u64;
+                parts.push(stream::once(future::ready(Ok(Bytes::from(tail)))).boxed());
+
+                res.status_code(StatusCode::PARTIAL_CONTENT);
+                match HeaderValue::from_str(&format!("multipart/byteranges; boundary={boundary}")) {
+                    Ok(content_type) => {
+                        res.headers_mut().insert(CONTENT_TYPE, content_type);
+                    }
+                    Err(e) => {
+                        tracing::error!(error = ?e, "set multipart content type failed");
+                    }
+                }
+                res.headers_mut()
+                    .typed_insert(ContentLength(content_length));
+                res.stream(stream::iter(parts).flatten());
             }
-            let reader = ChunkedFile {
-                offset,
-                total_size: cmp::min(length, self.metadata.len()),
-                read_size: 0,
-                state: ChunkedState::File(Some(self.file.into_std().await)),
-                buffer_size: self.buffer_size,
-            };
-            res.headers_mut()
-                .typed_insert(ContentLength(reader.total_size));
-            res.stream(reader);
-        } else {
-            res.status_code(StatusCode::OK);
-            let reader = ChunkedFile {
-                offset,
-                state: ChunkedState::File(Some(self.file.into_std().await)),
-                total_size: length,
-                read_size: 0,
-                buffer_size: self.buffer_size,
-            };
-            res.headers_mut().typed_insert(ContentLength(length));
-            res.stream(reader);
         }
     }
 }
@@ -545,7 +736,7 @@ impl NamedFile {
 #[async_trait]
 impl Writer for NamedFile {
     async fn write(self, req: &mut Request, _depot: &mut Depot, res: &mut Response) {
-        self.send(req.headers(), res).await;
+        self.send_to(req, res).await;
     }
 }
 
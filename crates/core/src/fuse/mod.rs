@@ -3,6 +3,8 @@
 pub mod flex;
 pub use flex::{FlexFactory, FlexFusewire};
 
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
 use std::sync::Arc;
 
 use async_trait::async_trait;
@@ -28,6 +30,27 @@ impl TransProto {
         matches!(self, Self::Quic)
     }
 }
+impl Display for TransProto {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tcp => f.write_str("tcp"),
+            Self::Quic => f.write_str("quic"),
+        }
+    }
+}
+impl FromStr for TransProto {
+    type Err = crate::Error;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "tcp" => Ok(Self::Tcp),
+            "quic" => Ok(Self::Quic),
+            _ => Err(crate::Error::other(format!(
+                "invalid transport protocol: `{input}`"
+            ))),
+        }
+    }
+}
 
 /// A fuse event.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -85,3 +108,17 @@ where
         Arc::new((*self)(info))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trans_proto_round_trip() {
+        for proto in [TransProto::Tcp, TransProto::Quic] {
+            assert_eq!(proto.to_string().parse::<TransProto>().unwrap(), proto);
+        }
+        assert_eq!(TransProto::Quic.to_string(), "quic");
+        assert!("udp".parse::<TransProto>().is_err());
+    }
+}
@@ -58,22 +58,35 @@
 //! ## Handle errors
 //!
 //! `Handler` in Salvo can return `Result`, only the types of `Ok` and `Err` in `Result` are implemented `Writer` trait.
+//! When the handler returns `Err`, the `Writer` of the error writes the response, so the rendering of an error type
+//! is written once instead of in every handler.
 //!
-//! Taking into account the widespread use of `anyhow`, the `Writer` implementation of `anyhow::Error` is provided by
-//! default if `anyhow` feature is enabled, and `anyhow::Error` is Mapped to `InternalServerError`.
+//! `std::io::Error` and `BoxedError` are mapped to `InternalServerError`. Taking into account the widespread use of
+//! `anyhow`, the `Writer` implementation of `anyhow::Error` is provided by default if `anyhow` feature is enabled, and
+//! `anyhow::Error` is Mapped to `InternalServerError`. The error is logged, and it is only shown in the error page in
+//! debug builds.
 //!
-//! For custom error types, you can output different error pages according to your needs.
+//! For custom error types, you can output different error pages according to your needs, the request and the depot
+//! are available in [`Writer::write`](crate::Writer::write).
 //!
 //! ```ignore
 //! use anyhow::anyhow;
 //! use salvo_core::prelude::*;
 //!
-//! struct CustomError;
+//! enum CustomError {
+//!     NotFound,
+//!     Database(String),
+//! }
 //! #[async_trait]
 //! impl Writer for CustomError {
-//!     async fn write(self, _req: &mut Request, _depot: &mut Depot, res: &mut Response) {
-//!         res.status_code(StatusCode::INTERNAL_SERVER_ERROR);
-//!         res.render("custom error");
+//!     async fn write(self, req: &mut Request, _depot: &mut Depot, res: &mut Response) {
+//!         match self {
+//!             CustomError::NotFound => res.render(StatusError::not_found().brief(format!("{} is not found", req.uri().path()))),
+//!             CustomError::Database(e) => {
+//!                 tracing::error!(error = e, "database error");
+//!                 res.render(StatusError::internal_server_error());
+//!             }
+//!         }
 //!     }
 //! }
 //!
@@ -83,7 +96,7 @@
 //! }
 //! #[handler]
 //! async fn handle_custom() -> Result<(), CustomError> {
-//!     Err(CustomError)
+//!     Err(CustomError::NotFound)
 //! }
 //!
 //! #[tokio::main]
@@ -184,6 +197,61 @@ pub trait Handler: Send + Sync + 'static {
     {
         HoopedHandler::new(self).hoop_when(hoop, filter)
     }
+
+    /// Chain another handler to run after this handler as a single handler.
+    ///
+    /// The `next` handler is not called if this handler sets a status code or a body on the
+    /// response, or calls [`FlowCtrl::skip_rest`], so the first handler can answer the request
+    /// on its own, for example to reject it.
+    ///
+    /// ```
+    /// use salvo_core::prelude::*;
+    ///
+    /// #[handler]
+    /// async fn load(depot: &mut Depot) {
+    ///     depot.insert("name", "salvo");
+    /// }
+    /// #[handler]
+    /// async fn hello(depot: &mut Depot) -> String {
+    ///     format!("Hello {}", depot.get::<&str>("name").copied().unwrap_or_default())
+    /// }
+    ///
+    /// let router = Router::new().get(load.then(hello));
+    /// ```
+    #[inline]
+    fn then<H: Handler>(self, next: H) -> Then<Self, H>
+    where
+        Self: Sized,
+    {
+        Then { first: self, next }
+    }
+}
+
+/// Handler which runs two handlers in order, created by [`Handler::then`].
+pub struct Then<A, B> {
+    first: A,
+    next: B,
+}
+#[async_trait]
+impl<A, B> Handler for Then<A, B>
+where
+    A: Handler,
+    B: Handler,
+{
+    async fn handle(
+        &self,
+        req: &mut Request,
+        depot: &mut Depot,
+        res: &mut Response,
+        ctrl: &mut FlowCtrl,
+    ) {
+        let skipped = ctrl.is_skipped;
+        self.first.handle(req, depot, res, ctrl).await;
+        if res.status_code.is_some() || !res.body.is_none() || (ctrl.is_skipped && !skipped) {
+            return;
+        }
+        self.next.handle(req, depot, res, ctrl).await;
+    }
 }
 
 /// A handler that wraps another [Handler] to enable it to be cloneable.
@@ -389,3 +457,73 @@ macro_rules! skipper_tuple_impls {
 
 crate::for_each_tuple!(handler_tuple_impls);
 crate::for_each_tuple!(skipper_tuple_impls);
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use crate::test::{ResponseExt, TestClient};
+
+    #[handler]
+    async fn load(depot: &mut Depot) {
+        depot.insert("name", "salvo");
+    }
+    #[handler]
+    async fn first(res: &mut Response) {
+        res.render("first ");
+    }
+    #[handler]
+    async fn created(res: &mut Response) {
+        res.status_code(StatusCode::CREATED);
+    }
+    #[handler]
+    async fn hello(depot: &mut Depot, res: &mut Response) {
+        let name = depot.get::<&str>("name").copied().unwrap_or_default();
+        res.render(format!("hello {name}"));
+    }
+    #[handler]
+    async fn second(res: &mut Response) {
+        res.render("second");
+    }
+    #[handler]
+    async fn forbidden(res: &mut Response) {
+        res.render(StatusError::forbidden().brief("no"));
+    }
+    #[handler]
+    async fn skip(res: &mut Response, ctrl: &mut FlowCtrl) {
+        res.render("skipped");
+        ctrl.skip_rest();
+    }
+
+    async fn access(handler: impl Handler) -> (StatusCode, String) {
+        let mut res = TestClient::get("http://127.0.0.1:5800/")
+            .send(Router::new().get(handler))
+            .await;
+        (res.status_code.unwrap(), res.take_string().await.unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_then() {
+        assert_eq!(
+            access(load.then(hello)).await,
+            (StatusCode::OK, "hello salvo".into())
+        );
+        assert_eq!(
+            access(first.then(second)).await,
+            (StatusCode::OK, "first ".into())
+        );
+        assert_eq!(
+            access(created.then(second)).await,
+            (StatusCode::CREATED, "".into())
+        );
+        let (status, _) = access(forbidden.then(second)).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(
+            access(skip.then(second)).await,
+            (StatusCode::OK, "skipped".into())
+        );
+        assert_eq!(
+            access(load.then(skip).then(second)).await,
+            (StatusCode::OK, "skipped".into())
+        );
+    }
+}
@@ -93,6 +93,18 @@ pub enum ParseError {
     #[error("serde json error: {0}")]
     SerdeJson(#[from] serde_json::error::Error),
 
+    /// MessagePack decode error.
+    #[cfg(feature = "msgpack")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "msgpack")))]
+    #[error("msgpack error: {0}")]
+    MsgPack(#[from] rmp_serde::decode::Error),
+
+    /// CBOR decode error.
+    #[cfg(feature = "cbor")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cbor")))]
+    #[error("cbor error: {0}")]
+    Cbor(#[from] ciborium::de::Error<IoError>),
+
     /// Custom error that does not fall under any other error kind.
     #[error("other error: {0}")]
     Other(BoxedError),
@@ -5,10 +5,11 @@ use std::path::{Path, PathBuf};
 
 use base64::engine::general_purpose::URL_SAFE_NO_PAD;
 use base64::engine::Engine;
+use bytes::Bytes;
 use futures_util::StreamExt;
 use http_body_util::BodyExt;
 use mime::Mime;
-use multer::{Field, Multipart};
+use multer::{Constraints, Field, Multipart, SizeLimit};
 use multimap::MultiMap;
 use rand::rngs::OsRng;
 use rand::RngCore;
@@ -18,7 +19,7 @@ use tokio::io::AsyncWriteExt;
 
 use crate::http::body::ReqBody;
 use crate::http::header::{HeaderMap, CONTENT_TYPE};
-use crate::http::ParseError;
+use crate::http::{ParseError, ParseResult};
 
 /// The extracted text fields and uploaded files from a `multipart/form-data` request.
 #[derive(Debug)]
@@ -60,11 +61,7 @@ impl FormData {
             }
             Some(ctype) if ctype.type_() == mime::MULTIPART => {
                 let mut form_data = FormData::new();
-                if let Some(boundary) = headers
-                    .get(CONTENT_TYPE)
-                    .and_then(|ct| ct.to_str().ok())
-                    .and_then(|ct| multer::parse_boundary(ct).ok())
-                {
+                if let Some(boundary) = parse_boundary(headers) {
                     let body = body.map(|f| f.map(|f| f.into_data().unwrap_or_default()));
                     let mut multipart = Multipart::new(body, boundary);
                     while let Some(mut field) = multipart.next_field().await? {
@@ -85,6 +82,91 @@ impl FormData {
         }
     }
 }
+
+/// Get the multipart boundary from the `Content-Type` header.
+pub(crate) fn parse_boundary(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(CONTENT_TYPE)
+        .and_then(|ct| ct.to_str().ok())
+        .and_then(|ct| multer::parse_boundary(ct).ok())
+}
+
+/// A streaming reader of a `multipart/form-data` body, created by
+/// [`Request::multipart_stream`](crate::Request::multipart_stream).
+pub struct MultipartStream {
+    inner: Multipart<'static>,
+}
+impl MultipartStream {
+    pub(crate) fn new(body: ReqBody, boundary: String, max_size: usize) -> Self {
+        let body = body.map(|f| f.map(|f| f.into_data().unwrap_or_default()));
+        let constraints =
+            Constraints::new().size_limit(SizeLimit::new().whole_stream(max_size as u64));
+        Self {
+            inner: Multipart::with_constraints(body, boundary, constraints),
+        }
+    }
+
+    /// Get the next field, returns `None` if there are no more fields.
+    ///
+    /// The previous field must be dropped before the next one is read.
+    #[inline]
+    pub async fn next_field(&mut self) -> ParseResult<Option<MultipartField>> {
+        Ok(self
+            .inner
+            .next_field()
+            .await?
+            .map(|inner| MultipartField { inner }))
+    }
+}
+impl std::fmt::Debug for MultipartStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MultipartStream").finish()
+    }
+}
+
+/// A field of a [`MultipartStream`], its data is read chunk by chunk.
+#[derive(Debug)]
+pub struct MultipartField {
+    inner: Field<'static>,
+}
+impl MultipartField {
+    /// Get the name of the field.
+    #[inline]
+    pub fn name(&self) -> Option<&str> {
+        self.inner.name()
+    }
+    /// Get the file name of the field, it's `None` if the field is not a file.
+    #[inline]
+    pub fn file_name(&self) -> Option<&str> {
+        self.inner.file_name()
+    }
+    /// Get the content type of the field.
+    #[inline]
+    pub fn content_type(&self) -> Option<&Mime> {
+        self.inner.content_type()
+    }
+    /// Get the headers of the field.
+    #[inline]
+    pub fn headers(&self) -> &HeaderMap {
+        self.inner.headers()
+    }
+    /// Get the next chunk of the data, returns `None` if the field is finished.
+    #[inline]
+    pub async fn chunk(&mut self) -> ParseResult<Option<Bytes>> {
+        Ok(self.inner.chunk().await?)
+    }
+    /// Read the whole data of the field.
+    #[inline]
+    pub async fn bytes(self) -> ParseResult<Bytes> {
+        Ok(self.inner.bytes().await?)
+    }
+    /// Read the whole data of the field as text.
+    #[inline]
+    pub async fn text(self) -> ParseResult<String> {
+        Ok(self.inner.text().await?)
+    }
+}
+
 impl Default for FormData {
     #[inline]
     fn default() -> Self {
@@ -5,6 +5,7 @@ pub mod form;
 mod range;
 pub mod request;
 pub mod response;
+pub mod tunnel;
 cfg_feature! {
     #![feature = "cookie"]
     pub use cookie;
@@ -74,10 +75,8 @@ pub fn parse_accept_encoding(header: &str) -> Vec<(String, u8)> {
         })
         .collect::<Vec<(String, u8)>>();
 
-    vec.sort_by(|(_, a), (_, b)| match b.cmp(a) {
-        std::cmp::Ordering::Equal => std::cmp::Ordering::Greater,
-        other => other,
-    });
+    // Stable sort, algorithms with the same quality keep the order of the header.
+    vec.sort_by(|(_, a), (_, b)| b.cmp(a));
 
     vec
 }
@@ -108,4 +107,17 @@ mod tests {
         let mime = guess_accept_mime(&req, None);
         assert_eq!(mime, "application/javascript".parse::<Mime>().unwrap());
     }
+
+    #[test]
+    fn test_parse_accept_encoding() {
+        assert_eq!(
+            parse_accept_encoding("gzip, deflate;q=0.5, br, zstd;q=0.8"),
+            vec![
+                ("gzip".to_owned(), 100),
+                ("br".to_owned(), 100),
+                ("zstd".to_owned(), 80),
+                ("deflate".to_owned(), 50)
+            ]
+        );
+    }
 }
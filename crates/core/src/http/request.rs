@@ -1,14 +1,14 @@
 //! HTTP request.
 use std::error::Error as StdError;
 use std::fmt::{self, Debug, Formatter};
-#[cfg(feature = "quinn")]
-use std::sync::Arc;
-use std::sync::OnceLock;
+use std::sync::{Arc, OnceLock};
 
 use bytes::Bytes;
 #[cfg(feature = "cookie")]
-use cookie::{Cookie, CookieJar};
-use http::header::{AsHeaderName, HeaderMap, HeaderValue, IntoHeaderName, CONTENT_TYPE};
+use cookie::{Cookie, CookieJar, Key};
+use http::header::{
+    AsHeaderName, HeaderMap, HeaderValue, IntoHeaderName, ACCEPT_LANGUAGE, CONTENT_TYPE, EXPECT,
+};
 use http::method::Method;
 pub use http::request::Parts;
 use http::uri::{Scheme, Uri};
@@ -18,11 +18,11 @@ use multimap::MultiMap;
 use parking_lot::RwLock;
 use serde::de::Deserialize;
 
-use crate::conn::SocketAddr;
+use crate::conn::{SocketAddr, TlsInfo};
 use crate::extract::{Extractible, Metadata};
 use crate::fuse::TransProto;
 use crate::http::body::ReqBody;
-use crate::http::form::{FilePart, FormData};
+use crate::http::form::{self, FilePart, FormData, MultipartStream};
 use crate::http::{Mime, ParseError, ParseResult, Response, Version};
 use crate::routing::PathParams;
 use crate::serde::{
@@ -100,6 +100,7 @@ pub struct Request {
     pub(crate) cookies: CookieJar,
 
     pub(crate) params: PathParams,
+    pub(crate) matched_path: Option<String>,
 
     // accept: Option<Vec<Mime>>,
     pub(crate) queries: OnceLock<MultiMap<String, String>>,
@@ -111,6 +112,7 @@ pub struct Request {
     pub(crate) scheme: Scheme,
     pub(crate) local_addr: SocketAddr,
     pub(crate) remote_addr: SocketAddr,
+    pub(crate) tls_info: Option<Arc<TlsInfo>>,
 
     pub(crate) secure_max_size: Option<usize>,
 }
@@ -127,6 +129,7 @@ impl Debug for Request {
             .field("body", &self.body())
             .field("local_addr", &self.local_addr)
             .field("remote_addr", &self.remote_addr)
+            .field("tls_info", &self.tls_info)
             .finish()
     }
 }
@@ -151,6 +154,7 @@ impl Request {
             #[cfg(feature = "cookie")]
             cookies: CookieJar::default(),
             params: PathParams::new(),
+            matched_path: None,
             queries: OnceLock::new(),
             form_data: tokio::sync::OnceCell::new(),
             payload: tokio::sync::OnceCell::new(),
@@ -158,6 +162,7 @@ impl Request {
             scheme: Scheme::HTTP,
             local_addr: SocketAddr::Unknown,
             remote_addr: SocketAddr::Unknown,
+            tls_info: None,
             secure_max_size: None,
         }
     }
@@ -215,11 +220,13 @@ impl Request {
             cookies,
             // accept: None,
             params: PathParams::new(),
+            matched_path: None,
             form_data: tokio::sync::OnceCell::new(),
             payload: tokio::sync::OnceCell::new(),
             // multipart: OnceLock::new(),
             local_addr: SocketAddr::Unknown,
             remote_addr: SocketAddr::Unknown,
+            tls_info: None,
             version,
             scheme,
             secure_max_size: None,
@@ -376,6 +383,9 @@ impl Request {
     }
 
     /// Get request local address reference.
+    ///
+    /// When serving several joined listeners, this is the address of the listener which accepted
+    /// the connection, so it can be used to tell e.g. an internal Unix socket from a public port.
     #[inline]
     pub fn local_addr(&self) -> &SocketAddr {
         &self.local_addr
@@ -386,6 +396,32 @@ impl Request {
         &mut self.local_addr
     }
 
+    /// Get the TLS session information of the connection, it's `None` if the connection is not
+    /// TLS.
+    ///
+    /// # Example
+    ///
+    /// Only allow the clients authenticated with a certificate, the certificate is verified by
+    /// the TLS config, such as a `WebPkiClientVerifier` of rustls:
+    ///
+    /// ```
+    /// use salvo_core::prelude::*;
+    ///
+    /// #[handler]
+    /// async fn authorize(req: &mut Request, res: &mut Response, ctrl: &mut FlowCtrl) {
+    ///     let Some(cert) = req.tls_info().and_then(|info| info.peer_certificate()) else {
+    ///         res.render(StatusError::unauthorized());
+    ///         ctrl.skip_rest();
+    ///         return;
+    ///     };
+    ///     // Parse `cert` with a crate such as `x509-parser` to authorize with its CN or SAN.
+    /// }
+    /// ```
+    #[inline]
+    pub fn tls_info(&self) -> Option<&TlsInfo> {
+        self.tls_info.as_deref()
+    }
+
     /// Returns a reference to the associated header field map.
     ///
     /// # Examples
@@ -442,6 +478,78 @@ impl Request {
         from_str_multi_val(values).map_err(Into::into)
     }
 
+    /// Get a header parsed by the [`headers`] crate, such as [`headers::Range`].
+    ///
+    /// Returns `Ok(None)` if the header is missing and `Err` if it is malformed. The
+    /// [`TypedHeader`](crate::extract::TypedHeader) extractor renders `400 Bad Request` for both.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use salvo_core::http::headers::IfModifiedSince;
+    /// use salvo_core::prelude::*;
+    ///
+    /// #[handler]
+    /// async fn show(req: &mut Request, res: &mut Response) {
+    ///     match req.typed_header::<IfModifiedSince>() {
+    ///         Ok(Some(since)) => res.render(format!("{since:?}")),
+    ///         Ok(None) => res.render("no header"),
+    ///         Err(_) => res.render(StatusError::bad_request()),
+    ///     }
+    /// }
+    /// ```
+    #[inline]
+    pub fn typed_header<H>(&self) -> Result<Option<H>, headers::Error>
+    where
+        H: headers::Header,
+    {
+        headers::HeaderMapExt::typed_try_get(&self.headers)
+    }
+
+    /// Returns `true` if the client sent `Expect: 100-continue` and waits for the `100 Continue`
+    /// interim response before it sends the body.
+    ///
+    /// The server sends `100 Continue` the first time the body is read, so a middleware can check
+    /// the request, such as the authorization or the `Content-Length`, and render an error without
+    /// reading the body, then the client doesn't send it. Use [`Request::reject_continue`] to make
+    /// sure the handlers after it can't read the body.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use salvo_core::http::header::CONTENT_LENGTH;
+    /// use salvo_core::prelude::*;
+    ///
+    /// #[handler]
+    /// async fn limit_upload(req: &mut Request, res: &mut Response, ctrl: &mut FlowCtrl) {
+    ///     let too_large = req
+    ///         .header::<u64>(CONTENT_LENGTH)
+    ///         .map_or(true, |length| length > 10 * 1024 * 1024);
+    ///     if req.expects_continue() && too_large {
+    ///         req.reject_continue();
+    ///         res.render(StatusError::payload_too_large());
+    ///         ctrl.skip_rest();
+    ///     }
+    /// }
+    /// ```
+    pub fn expects_continue(&self) -> bool {
+        self.version != Version::HTTP_10
+            && self
+                .headers
+                .get_all(EXPECT)
+                .iter()
+                .any(|value| value.as_bytes().eq_ignore_ascii_case(b"100-continue"))
+    }
+
+    /// Rejects a request which [expects `100 Continue`](Request::expects_continue), the body is
+    /// dropped without reading it, so `100 Continue` is never sent, and the connection is closed
+    /// after the response instead of receiving the body.
+    ///
+    /// The body of the request is empty after it.
+    pub fn reject_continue(&mut self) {
+        drop(self.take_body());
+    }
+
     /// Modify a header for this request.
     ///
     /// When `overwrite` is set to `true`, If the header is already present, the value will be replaced.
@@ -617,6 +725,83 @@ impl Request {
         }
     }
 
+    /// Get the best language of `supported` for the `Accept-Language` header.
+    ///
+    /// The languages of the header are tried in the order of their quality values, languages with
+    /// `q=0` are not acceptable. A language matches a supported language with the same tag, or a
+    /// supported language which is more specific, such as `en` matches `en-GB`. If there is no
+    /// such language, the tag is truncated, such as `en-US` becomes `en`, and tried again. `*`
+    /// matches the first supported language which is not unacceptable. Tags are compared
+    /// case-insensitively.
+    ///
+    /// Returns `None` if no supported language is acceptable, use `unwrap_or` to fall back to
+    /// a default language.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use salvo_core::http::*;
+    /// let mut req = Request::default();
+    /// req.headers_mut().insert("accept-language", "fr-CH, fr;q=0.9, en;q=0.8, *;q=0.5".parse().unwrap());
+    /// assert_eq!(req.preferred_language(&["en", "fr", "de"]), Some("fr"));
+    /// assert_eq!(req.preferred_language(&["de"]), Some("de"));
+    /// assert_eq!(Request::default().preferred_language(&["en"]).unwrap_or("en"), "en");
+    /// ```
+    pub fn preferred_language<'a>(&self, supported: &[&'a str]) -> Option<&'a str> {
+        let mut ranges = self
+            .headers
+            .get_all(ACCEPT_LANGUAGE)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .filter_map(|range| {
+                let mut parts = range.split(';').map(str::trim);
+                let tag = parts.next().filter(|tag| !tag.is_empty())?;
+                let quality = match parts.find_map(|p| p.strip_prefix("q=")) {
+                    Some(q) => q.parse::<f32>().ok().filter(|q| (0.0..=1.0).contains(q))?,
+                    None => 1.0,
+                };
+                Some((tag, quality))
+            })
+            .collect::<Vec<_>>();
+        // The sort is stable, so the header order is kept for the same quality.
+        ranges.sort_by(|a, b| b.1.total_cmp(&a.1));
+        let unacceptable = |lang: &str| {
+            ranges
+                .iter()
+                .any(|(tag, q)| *q == 0.0 && tag.eq_ignore_ascii_case(lang))
+        };
+        for (tag, _) in ranges.iter().filter(|(_, q)| *q > 0.0) {
+            if *tag == "*" {
+                if let Some(lang) = supported.iter().find(|lang| !unacceptable(lang)) {
+                    return Some(lang);
+                }
+                continue;
+            }
+            let mut range = *tag;
+            loop {
+                let found = supported
+                    .iter()
+                    .find(|lang| lang.eq_ignore_ascii_case(range))
+                    .or_else(|| {
+                        supported.iter().find(|lang| {
+                            lang.len() > range.len()
+                                && lang.as_bytes()[range.len()] == b'-'
+                                && lang[..range.len()].eq_ignore_ascii_case(range)
+                        })
+                    });
+                if let Some(lang) = found.filter(|lang| !unacceptable(lang)) {
+                    return Some(lang);
+                }
+                match range.rsplit_once('-') {
+                    Some((prefix, _)) => range = prefix,
+                    None => break,
+                }
+            }
+        }
+        None
+    }
+
     /// Get content type.
     #[inline]
     pub fn content_type(&self) -> Option<Mime> {
@@ -646,6 +831,45 @@ impl Request {
         {
             self.cookies.get(name.as_ref())
         }
+        /// Get a signed `Cookie` from cookies, the value is verified with `key`.
+        ///
+        /// Returns `None` if the cookie is not found or it has been tampered with.
+        /// The cookie is set with [`Response::add_signed_cookie`]. The key is usually created once
+        /// from a server secret with `Key::from` or `Key::derive_from`, and shared with `affix`.
+        ///
+        /// # Example
+        ///
+        /// ```
+        /// use salvo_core::http::cookie::Key;
+        /// use salvo_core::prelude::*;
+        ///
+        /// #[handler]
+        /// async fn show(req: &mut Request, depot: &mut Depot, res: &mut Response) {
+        ///     let key = depot.obtain::<Key>().unwrap();
+        ///     match req.signed_cookie("user_id", key) {
+        ///         Some(cookie) => res.render(cookie.value().to_owned()),
+        ///         None => res.render("anonymous"),
+        ///     }
+        /// }
+        /// ```
+        #[inline]
+        pub fn signed_cookie<T>(&self, name: T, key: &Key) -> Option<Cookie<'static>>
+        where
+            T: AsRef<str>,
+        {
+            self.cookies.signed(key).get(name.as_ref())
+        }
+        /// Get an encrypted `Cookie` from cookies, the value is decrypted and verified with `key`.
+        ///
+        /// Returns `None` if the cookie is not found or it has been tampered with.
+        /// The cookie is set with [`Response::add_private_cookie`].
+        #[inline]
+        pub fn private_cookie<T>(&self, name: T, key: &Key) -> Option<Cookie<'static>>
+        where
+            T: AsRef<str>,
+        {
+            self.cookies.private(key).get(name.as_ref())
+        }
     }
     /// Get params reference.
     #[inline]
@@ -658,6 +882,15 @@ impl Request {
         &mut self.params
     }
 
+    /// Get the path template of the matched route, such as `/users/<id>`.
+    ///
+    /// It is joined from the path filters of the routers which the request passed, so it can be
+    /// used as a low cardinality label in logs and metrics. Returns `None` if no route matched.
+    #[inline]
+    pub fn matched_path(&self) -> Option<&str> {
+        self.matched_path.as_deref()
+    }
+
     /// Get param value from params.
     #[inline]
     pub fn param<'de, T>(&'de self, key: &str) -> Option<T>
@@ -859,14 +1092,60 @@ impl Request {
             .await
     }
 
+    /// Get a copy of the request payload with default max size limit(64KB).
+    ///
+    /// The body is buffered and cached the same as [`Request::payload`], so it can be verified,
+    /// for example with the HMAC signature of a webhook, and then parsed with
+    /// [`Request::parse_json`] or [`Request::form_data`]. The returned `Bytes` shares the buffer
+    /// of the cache, so copying it is cheap.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use salvo_core::prelude::*;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Event {
+    ///     action: String,
+    /// }
+    ///
+    /// #[handler]
+    /// async fn webhook(req: &mut Request) -> Result<String, StatusError> {
+    ///     let body = req.body_bytes().await.map_err(|_| StatusError::bad_request())?;
+    ///     let _signature = req.header::<String>("x-signature");
+    ///     // verify the signature of `body` here.
+    ///     let event = req.parse_json::<Event>().await.map_err(|_| StatusError::bad_request())?;
+    ///     Ok(format!("{} bytes, {}", body.len(), event.action))
+    /// }
+    /// ```
+    #[inline]
+    pub async fn body_bytes(&mut self) -> ParseResult<Bytes> {
+        self.body_bytes_with_max_size(self.secure_max_size()).await
+    }
+
+    /// Get a copy of the request payload with max size limit.
+    ///
+    /// View [`Request::body_bytes`] for more details.
+    #[inline]
+    pub async fn body_bytes_with_max_size(&mut self, max_size: usize) -> ParseResult<Bytes> {
+        self.payload_with_max_size(max_size).await.cloned()
+    }
+
     /// Get `FormData` reference from request.
     ///
+    /// If the payload is already read, for example by [`Request::body_bytes`], the form data is
+    /// parsed from it.
+    ///
     /// *Notice: This method takes body and body's size is not limited.
     #[inline]
     pub async fn form_data(&mut self) -> ParseResult<&FormData> {
         if let Some(ctype) = self.content_type() {
             if ctype.subtype() == mime::WWW_FORM_URLENCODED || ctype.type_() == mime::MULTIPART {
-                let body = self.take_body();
+                let body = match self.payload.get() {
+                    Some(payload) => ReqBody::Once(payload.clone()),
+                    None => self.take_body(),
+                };
                 let headers = self.headers();
                 self.form_data
                     .get_or_try_init(|| async { FormData::read(headers, body).await })
@@ -879,6 +1158,54 @@ impl Request {
         }
     }
 
+    /// Get a streaming reader of the `multipart/form-data` body with default max size limit(64KB).
+    ///
+    /// Unlike [`Request::form_data`], nothing is buffered: fields are yielded one by one and the
+    /// data of each [`MultipartField`](form::MultipartField) is a stream of chunks, so large
+    /// uploads can be copied straight to their destination. Use
+    /// [`Request::multipart_stream_with_max_size`] to accept larger uploads. The body is taken
+    /// from the request, so `form_data` can not be used afterwards.
+    ///
+    /// ```no_run
+    /// use salvo_core::prelude::*;
+    /// use tokio::io::AsyncWriteExt;
+    ///
+    /// #[handler]
+    /// async fn upload(req: &mut Request) -> Result<(), salvo_core::Error> {
+    ///     let mut multipart = req.multipart_stream_with_max_size(1024 * 1024 * 1024)?;
+    ///     while let Some(mut field) = multipart.next_field().await? {
+    ///         let name = field.name().unwrap_or("upload");
+    ///         let mut file = tokio::fs::File::create(format!("/tmp/{name}")).await?;
+    ///         while let Some(chunk) = field.chunk().await? {
+    ///             file.write_all(&chunk).await?;
+    ///         }
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    #[inline]
+    pub fn multipart_stream(&mut self) -> ParseResult<MultipartStream> {
+        self.multipart_stream_with_max_size(self.secure_max_size())
+    }
+
+    /// Get a streaming reader of the `multipart/form-data` body with max size limit.
+    ///
+    /// Reading a field fails once the whole body exceeds `max_size`. The body is only taken if
+    /// the `Content-Type` is valid.
+    pub fn multipart_stream_with_max_size(
+        &mut self,
+        max_size: usize,
+    ) -> ParseResult<MultipartStream> {
+        match self.content_type() {
+            Some(ctype) if ctype.type_() == mime::MULTIPART => {
+                let boundary =
+                    form::parse_boundary(self.headers()).ok_or(ParseError::InvalidContentType)?;
+                Ok(MultipartStream::new(self.take_body(), boundary, max_size))
+            }
+            _ => Err(ParseError::NotFormData),
+        }
+    }
+
     /// Extract request as type `T` from request's different parts.
     #[inline]
     pub async fn extract<'de, T>(&'de mut self) -> ParseResult<T>
@@ -901,6 +1228,28 @@ impl Request {
     }
 
     /// Parse url params as type `T` from request.
+    ///
+    /// All the params captured by the matched routers are deserialized at once, the errors name
+    /// the field which is missing or can not be parsed, such as ``field `pid`: invalid digit found
+    /// in string``.
+    ///
+    /// ```
+    /// # use salvo_core::prelude::*;
+    /// # use serde::Deserialize;
+    /// #[derive(Deserialize)]
+    /// struct PostPath {
+    ///     uid: u64,
+    ///     pid: u64,
+    /// }
+    /// #[handler]
+    /// async fn show_post(req: &mut Request, res: &mut Response) {
+    ///     match req.parse_params::<PostPath>() {
+    ///         Ok(path) => res.render(format!("user {} post {}", path.uid, path.pid)),
+    ///         Err(e) => res.render(StatusError::bad_request().brief(e.to_string())),
+    ///     }
+    /// }
+    /// let router = Router::with_path("users/<uid>/posts/<pid>").get(show_post);
+    /// ```
     #[inline]
     pub fn parse_params<'de, T>(&'de mut self) -> ParseResult<T>
     where
@@ -965,7 +1314,7 @@ impl Request {
     {
         let ctype = self.content_type();
         if let Some(ctype) = ctype {
-            if ctype.subtype() == mime::JSON {
+            if is_json(&ctype) {
                 return self
                     .payload_with_max_size(max_size)
                     .await
@@ -983,6 +1332,73 @@ impl Request {
         Err(ParseError::InvalidContentType)
     }
 
+    cfg_feature! {
+        #![feature = "msgpack"]
+        /// Parse MessagePack body as type `T` from request with default max size limit.
+        ///
+        /// The content type should be `application/msgpack` or `application/x-msgpack`.
+        #[inline]
+        pub async fn parse_msgpack<'de, T>(&'de mut self) -> ParseResult<T>
+        where
+            T: Deserialize<'de>,
+        {
+            self.parse_msgpack_with_max_size(self.secure_max_size()).await
+        }
+        /// Parse MessagePack body as type `T` from request with max size limit.
+        #[inline]
+        pub async fn parse_msgpack_with_max_size<'de, T>(
+            &'de mut self,
+            max_size: usize,
+        ) -> ParseResult<T>
+        where
+            T: Deserialize<'de>,
+        {
+            if let Some(ctype) = self.content_type() {
+                if ctype.subtype() == "msgpack" || ctype.subtype() == "x-msgpack" {
+                    return self
+                        .payload_with_max_size(max_size)
+                        .await
+                        .and_then(|payload| {
+                            rmp_serde::from_slice::<T>(payload).map_err(ParseError::MsgPack)
+                        });
+                }
+            }
+            Err(ParseError::InvalidContentType)
+        }
+    }
+
+    cfg_feature! {
+        #![feature = "cbor"]
+        /// Parse CBOR body as type `T` from request with default max size limit.
+        ///
+        /// The content type should be `application/cbor`.
+        #[inline]
+        pub async fn parse_cbor<T>(&mut self) -> ParseResult<T>
+        where
+            T: serde::de::DeserializeOwned,
+        {
+            self.parse_cbor_with_max_size(self.secure_max_size()).await
+        }
+        /// Parse CBOR body as type `T` from request with max size limit.
+        #[inline]
+        pub async fn parse_cbor_with_max_size<T>(&mut self, max_size: usize) -> ParseResult<T>
+        where
+            T: serde::de::DeserializeOwned,
+        {
+            if let Some(ctype) = self.content_type() {
+                if ctype.subtype() == "cbor" {
+                    return self
+                        .payload_with_max_size(max_size)
+                        .await
+                        .and_then(|payload| {
+                            ciborium::from_reader::<T, _>(&payload[..]).map_err(ParseError::Cbor)
+                        });
+                }
+            }
+            Err(ParseError::InvalidContentType)
+        }
+    }
+
     /// Parse form body as type `T` from request.
     #[inline]
     pub async fn parse_form<'de, T>(&'de mut self) -> ParseResult<T>
@@ -998,7 +1414,35 @@ impl Request {
         Err(ParseError::InvalidContentType)
     }
 
-    /// Parse json body or form body as type `T` from request with default max size.
+    /// Parse body as type `T` from request with default max size, the deserializer is chosen by
+    /// the `Content-Type` header.
+    ///
+    /// - `application/json` and `application/*+json`, such as `application/merge-patch+json`,
+    ///   are parsed as JSON;
+    /// - `application/x-www-form-urlencoded` and `multipart/form-data` are parsed as form;
+    /// - `application/msgpack` and `application/x-msgpack` are parsed as MessagePack if the
+    ///   `msgpack` feature is enabled.
+    ///
+    /// So an endpoint can accept a model from both HTML forms and API clients. Other content
+    /// types return [`ParseError::InvalidContentType`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use salvo_core::prelude::*;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct NewUser {
+    ///     name: String,
+    /// }
+    ///
+    /// #[handler]
+    /// async fn create_user(req: &mut Request) -> Result<String, StatusError> {
+    ///     let user = req.parse_body::<NewUser>().await.map_err(|e| StatusError::bad_request().brief(e.to_string()))?;
+    ///     Ok(format!("created {}", user.name))
+    /// }
+    /// ```
     #[inline]
     pub async fn parse_body<'de, T>(&'de mut self) -> ParseResult<T>
     where
@@ -1007,7 +1451,10 @@ impl Request {
         self.parse_body_with_max_size(self.secure_max_size()).await
     }
 
-    /// Parse json body or form body as type `T` from request with max size.
+    /// Parse body as type `T` from request with max size, view [`Request::parse_body`] for the
+    /// supported content types.
+    ///
+    /// *Notice: The size of form body is not limited, the same as [`Request::form_data`].
     pub async fn parse_body_with_max_size<'de, T>(&'de mut self, max_size: usize) -> ParseResult<T>
     where
         T: Deserialize<'de>,
@@ -1016,16 +1463,24 @@ impl Request {
             if ctype.subtype() == mime::WWW_FORM_URLENCODED || ctype.subtype() == mime::FORM_DATA {
                 return from_str_multi_map(self.form_data().await?.fields.iter_all())
                     .map_err(ParseError::Deserialize);
-            } else if ctype.subtype() == mime::JSON {
-                return self.payload_with_max_size(max_size).await.and_then(|body| {
-                    serde_json::from_slice::<T>(body).map_err(ParseError::SerdeJson)
-                });
+            } else if is_json(&ctype) {
+                return self.parse_json_with_max_size(max_size).await;
+            }
+            #[cfg(feature = "msgpack")]
+            if ctype.subtype() == "msgpack" || ctype.subtype() == "x-msgpack" {
+                return self.parse_msgpack_with_max_size(max_size).await;
             }
         }
         Err(ParseError::InvalidContentType)
     }
 }
 
+/// Returns whether the content type is `application/json` or `application/*+json`.
+#[inline]
+fn is_json(ctype: &Mime) -> bool {
+    ctype.subtype() == mime::JSON || ctype.suffix() == Some(mime::JSON)
+}
+
 #[cfg(test)]
 mod tests {
     use serde::{Deserialize, Serialize};
@@ -1065,6 +1520,64 @@ mod tests {
         assert_eq!(man.weapons, 69);
     }
 
+    #[tokio::test]
+    async fn test_parse_queries_errors() {
+        #[derive(Deserialize, Debug)]
+        #[allow(dead_code)]
+        struct Filter {
+            page: u32,
+            tags: Vec<String>,
+            keyword: Option<String>,
+        }
+        let mut req = TestClient::get("http://127.0.0.1:5801/hello?page=x&tags=a").build();
+        let e = req.parse_queries::<Filter>().unwrap_err();
+        assert!(e.to_string().contains("field `page`"));
+        let mut req = TestClient::get("http://127.0.0.1:5801/hello?tags=a").build();
+        let e = req.parse_queries::<Filter>().unwrap_err();
+        assert!(e.to_string().contains("missing field `page`"));
+        let mut req = TestClient::get("http://127.0.0.1:5801/hello?page=1&tags=a&tags=b").build();
+        let filter = req.parse_queries::<Filter>().unwrap();
+        assert_eq!(filter.tags, vec!["a", "b"]);
+        assert!(filter.keyword.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_parse_params() {
+        use crate::prelude::*;
+        use crate::test::ResponseExt;
+
+        #[derive(Deserialize, Debug)]
+        struct PostPath {
+            uid: u64,
+            pid: u64,
+        }
+        #[handler]
+        async fn show_post(req: &mut Request) -> String {
+            match req.parse_params::<PostPath>() {
+                Ok(path) => format!("{}/{}", path.uid, path.pid),
+                Err(e) => e.to_string(),
+            }
+        }
+        let router = Router::new()
+            .push(Router::with_path("users/<uid>/posts/<pid>").get(show_post))
+            .push(Router::with_path("users/<uid>").get(show_post));
+        let service = Service::new(router);
+        let access = |url: &'static str| {
+            let service = &service;
+            async move {
+                TestClient::get(format!("http://127.0.0.1:5801/{url}"))
+                    .send(service)
+                    .await
+                    .take_string()
+                    .await
+                    .unwrap()
+            }
+        };
+        assert_eq!(access("users/1/posts/2").await, "1/2");
+        assert!(access("users/1/posts/x").await.contains("field `pid`"));
+        assert!(access("users/1").await.contains("missing field `pid`"));
+    }
+
     #[tokio::test]
     async fn test_parse_json() {
         #[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
@@ -1083,6 +1596,65 @@ mod tests {
             }
         );
     }
+    #[cfg(feature = "msgpack")]
+    #[tokio::test]
+    async fn test_parse_msgpack() {
+        #[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+        struct User {
+            name: String,
+        }
+        let user = User {
+            name: "jobs".into(),
+        };
+        let mut req = TestClient::post("http://127.0.0.1:5800/hello")
+            .add_header("content-type", "application/msgpack", true)
+            .bytes(rmp_serde::to_vec_named(&user).unwrap())
+            .build();
+        assert_eq!(req.parse_msgpack::<User>().await.unwrap(), user);
+
+        let mut req = TestClient::post("http://127.0.0.1:5800/hello")
+            .json(&user)
+            .build();
+        assert!(matches!(
+            req.parse_msgpack::<User>().await,
+            Err(ParseError::InvalidContentType)
+        ));
+        let mut req = TestClient::post("http://127.0.0.1:5800/hello")
+            .add_header("content-type", "application/msgpack", true)
+            .bytes(b"{}".to_vec())
+            .build();
+        assert!(matches!(
+            req.parse_msgpack::<User>().await,
+            Err(ParseError::MsgPack(_))
+        ));
+    }
+    #[cfg(feature = "cbor")]
+    #[tokio::test]
+    async fn test_parse_cbor() {
+        #[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+        struct User {
+            name: String,
+        }
+        let user = User {
+            name: "jobs".into(),
+        };
+        let mut body = Vec::new();
+        ciborium::into_writer(&user, &mut body).unwrap();
+        let mut req = TestClient::post("http://127.0.0.1:5800/hello")
+            .add_header("content-type", "application/cbor", true)
+            .bytes(body)
+            .build();
+        assert_eq!(req.parse_cbor::<User>().await.unwrap(), user);
+
+        let mut req = TestClient::post("http://127.0.0.1:5800/hello")
+            .add_header("content-type", "application/cbor", true)
+            .bytes(b"{}".to_vec())
+            .build();
+        assert!(matches!(
+            req.parse_cbor::<User>().await,
+            Err(ParseError::Cbor(_))
+        ));
+    }
     #[tokio::test]
     async fn test_query() {
         let req = TestClient::get(
@@ -1098,6 +1670,64 @@ mod tests {
         assert_eq!(names, vec!["rust", "25", "a", "2"]);
         assert_eq!(weapons, (98, "gun"));
     }
+    #[tokio::test]
+    async fn test_multipart_stream() {
+        let mut req = TestClient::post("http://127.0.0.1:5800/upload")
+            .add_header("content-type", "multipart/form-data; boundary=X-BOUNDARY", true)
+            .body(
+                "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"title\"\r\n\r\nhello\r\n\
+                 --X-BOUNDARY\r\nContent-Disposition: form-data; name=\"file\"; filename=\"a.txt\"\r\n\
+                 Content-Type: text/plain\r\n\r\nfile content\r\n--X-BOUNDARY--\r\n",
+            )
+            .build();
+        let mut multipart = req.multipart_stream().unwrap();
+        let field = multipart.next_field().await.unwrap().unwrap();
+        assert_eq!(field.name(), Some("title"));
+        assert_eq!(field.text().await.unwrap(), "hello");
+        let mut field = multipart.next_field().await.unwrap().unwrap();
+        assert_eq!(field.file_name(), Some("a.txt"));
+        let mut data = Vec::new();
+        while let Some(chunk) = field.chunk().await.unwrap() {
+            data.extend_from_slice(&chunk);
+        }
+        assert_eq!(data, b"file content");
+        drop(field);
+        assert!(multipart.next_field().await.unwrap().is_none());
+
+        let mut req = TestClient::post("http://127.0.0.1:5800/upload")
+            .json(&"not form")
+            .build();
+        assert!(matches!(
+            req.multipart_stream(),
+            Err(ParseError::NotFormData)
+        ));
+
+        let mut req = TestClient::post("http://127.0.0.1:5800/upload")
+            .add_header("content-type", "multipart/form-data", true)
+            .body("data")
+            .build();
+        assert!(matches!(
+            req.multipart_stream(),
+            Err(ParseError::InvalidContentType)
+        ));
+        assert_eq!(req.payload().await.unwrap().as_ref(), b"data");
+
+        let mut req = TestClient::post("http://127.0.0.1:5800/upload")
+            .add_header("content-type", "multipart/form-data; boundary=X-BOUNDARY", true)
+            .body(format!(
+                "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"title\"\r\n\r\n{}\r\n--X-BOUNDARY--\r\n",
+                "a".repeat(100)
+            ))
+            .build();
+        let mut multipart = req.multipart_stream_with_max_size(64).unwrap();
+        let result = match multipart.next_field().await {
+            Ok(Some(field)) => field.text().await.map(|_| ()),
+            Ok(None) => Ok(()),
+            Err(e) => Err(e),
+        };
+        assert!(matches!(result, Err(ParseError::Multer(_))));
+    }
+
     #[tokio::test]
     async fn test_form() {
         let mut req = TestClient::post("http://127.0.0.1:5800/hello?q=rust")
@@ -1131,4 +1761,116 @@ file content\r\n\
         let files = req.files("file1").await.unwrap();
         assert_eq!(files[0].name().unwrap(), "err.txt");
     }
+    #[tokio::test]
+    async fn test_parse_body() {
+        #[derive(Deserialize, Eq, PartialEq, Debug)]
+        struct User {
+            name: String,
+        }
+        let build = |content_type: &str, body: &str| {
+            TestClient::post("http://127.0.0.1:5800/users")
+                .add_header("content-type", content_type, true)
+                .body(body.to_owned())
+                .build()
+        };
+        let mut req = build("application/json; charset=utf-8", r#"{"name":"jobs"}"#);
+        assert_eq!(req.parse_body::<User>().await.unwrap().name, "jobs");
+        let mut req = build("application/merge-patch+json", r#"{"name":"jobs"}"#);
+        assert_eq!(req.parse_body::<User>().await.unwrap().name, "jobs");
+        let mut req = build("application/json", "");
+        assert_eq!(req.parse_body::<Option<User>>().await.unwrap(), None);
+        let mut req = build("application/x-www-form-urlencoded", "name=jobs");
+        assert_eq!(req.parse_body::<User>().await.unwrap().name, "jobs");
+        let mut req = build(
+            "multipart/form-data; boundary=X",
+            "--X\r\nContent-Disposition: form-data; name=\"name\"\r\n\r\njobs\r\n--X--\r\n",
+        );
+        assert_eq!(req.parse_body::<User>().await.unwrap().name, "jobs");
+        let mut req = build("text/plain", "jobs");
+        assert!(matches!(
+            req.parse_body::<User>().await,
+            Err(ParseError::InvalidContentType)
+        ));
+    }
+    #[tokio::test]
+    async fn test_body_bytes() {
+        #[derive(Deserialize, Eq, PartialEq, Debug)]
+        struct User {
+            name: String,
+        }
+        let mut req = TestClient::post("http://127.0.0.1:5800/hello")
+            .raw_json(r#"{"name":"jobs"}"#)
+            .build();
+        let body = req.body_bytes().await.unwrap();
+        assert_eq!(body, r#"{"name":"jobs"}"#);
+        assert_eq!(req.body_bytes().await.unwrap(), body);
+        assert_eq!(req.parse_json::<User>().await.unwrap().name, "jobs");
+
+        let mut req = TestClient::post("http://127.0.0.1:5800/hello")
+            .raw_form("name=jobs")
+            .build();
+        assert_eq!(req.body_bytes().await.unwrap(), "name=jobs");
+        assert_eq!(req.parse_form::<User>().await.unwrap().name, "jobs");
+
+        let mut req = TestClient::post("http://127.0.0.1:5800/hello")
+            .raw_json(r#"{"name":"jobs"}"#)
+            .build();
+        assert!(req.body_bytes_with_max_size(4).await.is_err());
+    }
+    #[cfg(feature = "cookie")]
+    #[test]
+    fn test_signed_and_private_cookies() {
+        let key = Key::generate();
+        let mut res = Response::new();
+        res.add_signed_cookie(Cookie::new("signed", "alice"), &key)
+            .add_private_cookie(Cookie::new("private", "bob"), &key);
+        let signed = res.cookie("signed").unwrap().value().to_owned();
+        let private = res.cookie("private").unwrap().value().to_owned();
+        assert!(signed.ends_with("alice"));
+        assert!(!private.contains("bob"));
+
+        let req = TestClient::get("http://127.0.0.1:5800/")
+            .add_header(
+                "cookie",
+                format!("signed={signed}; private={private}"),
+                true,
+            )
+            .build();
+        assert_eq!(req.signed_cookie("signed", &key).unwrap().value(), "alice");
+        assert_eq!(req.private_cookie("private", &key).unwrap().value(), "bob");
+        assert!(req.private_cookie("signed", &key).is_none());
+        assert!(req.signed_cookie("signed", &Key::generate()).is_none());
+
+        let tampered = signed.replace("alice", "admin");
+        let req = TestClient::get("http://127.0.0.1:5800/")
+            .add_header("cookie", format!("signed={tampered}"), true)
+            .build();
+        assert!(req.signed_cookie("signed", &key).is_none());
+        assert_eq!(req.cookie("signed").unwrap().value(), tampered);
+    }
+
+    #[test]
+    fn test_preferred_language() {
+        let language = |accept: &str, supported: &[&'static str]| {
+            let mut req = Request::default();
+            req.headers_mut()
+                .insert(ACCEPT_LANGUAGE, accept.parse().unwrap());
+            req.preferred_language(supported)
+        };
+        let supported = ["en", "fr", "de"];
+        assert_eq!(
+            language("de;q=0.7, fr;q=0.9, en;q=0.8", &supported),
+            Some("fr")
+        );
+        assert_eq!(language("en-US, fr;q=0.9", &supported), Some("en"));
+        assert_eq!(language("zh-Hant-TW, zh;q=0.8", &["en", "zh"]), Some("zh"));
+        assert_eq!(language("EN", &["en-GB", "en-US"]), Some("en-GB"));
+        assert_eq!(language("en-us", &["en-GB", "en-US"]), Some("en-US"));
+        assert_eq!(language("ja, *;q=0.1", &supported), Some("en"));
+        assert_eq!(language("ja, en;q=0, *;q=0.1", &supported), Some("fr"));
+        assert_eq!(language("en-US, en;q=0", &supported), None);
+        assert_eq!(language("ja, zh", &supported), None);
+        assert_eq!(language("en;q=abc, fr", &supported), Some("fr"));
+        assert_eq!(Request::default().preferred_language(&supported), None);
+    }
 }
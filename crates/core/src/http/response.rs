@@ -4,17 +4,18 @@ use std::fmt::{self, Debug, Display, Formatter};
 use std::path::PathBuf;
 
 #[cfg(feature = "cookie")]
-use cookie::{Cookie, CookieJar};
+use cookie::{Cookie, CookieJar, Key};
 use futures_util::stream::Stream;
-use http::header::{HeaderMap, HeaderValue, IntoHeaderName};
+use http::header::{HeaderMap, HeaderValue, IntoHeaderName, CONTENT_TYPE};
 pub use http::response::Parts;
 use http::{version::Version, Extensions};
 use mime::Mime;
+use serde::Serialize;
 
 use crate::fs::NamedFile;
 use crate::fuse::TransProto;
-use crate::http::{StatusCode, StatusError};
-use crate::{BoxedError, Error, Scribe};
+use crate::http::{Request, StatusCode, StatusError};
+use crate::{BoxedError, Depot, Error, Scribe};
 use bytes::Bytes;
 
 pub use crate::http::body::{BodySender, BytesFrame, ResBody};
@@ -115,6 +116,72 @@ impl Response {
         }
     }
 
+    /// Sets the status code and returns `Self`, for building a response by chaining.
+    ///
+    /// The builder methods prefixed with `with_` take and return `Self`, the built response can
+    /// be returned from a handler, it is merged into the response of the handler: the status
+    /// code, the headers and the body replace the existing ones, the cookies are added. Use the
+    /// methods taking `&mut self`, such as [`Response::status_code`], to modify the response
+    /// given to a handler.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use salvo_core::prelude::*;
+    /// use serde_json::json;
+    ///
+    /// #[handler]
+    /// async fn create() -> salvo_core::Result<Response> {
+    ///     Ok(Response::new()
+    ///         .with_status_code(StatusCode::CREATED)
+    ///         .with_header("x-request-id", "42")?
+    ///         .with_scribe(Json(json!({ "id": 1 }))))
+    /// }
+    /// ```
+    #[inline]
+    pub fn with_status_code(mut self, code: StatusCode) -> Self {
+        self.status_code = Some(code);
+        self
+    }
+
+    /// Sets a header and returns `Self`, the existing values of the header are replaced.
+    ///
+    /// An error is returned if `value` isn't a valid header value, the same as
+    /// [`Response::add_header`].
+    #[inline]
+    pub fn with_header<N, V>(mut self, name: N, value: V) -> crate::Result<Self>
+    where
+        N: IntoHeaderName,
+        V: TryInto<HeaderValue>,
+    {
+        self.add_header(name, value, true)?;
+        Ok(self)
+    }
+
+    /// Sets the `Content-Type` header and returns `Self`.
+    #[inline]
+    pub fn with_content_type(mut self, mime: Mime) -> Self {
+        if let Ok(value) = HeaderValue::from_str(mime.as_ref()) {
+            self.headers.insert(CONTENT_TYPE, value);
+        }
+        self
+    }
+
+    /// Sets the body and returns `Self`.
+    #[inline]
+    pub fn with_body(mut self, body: impl Into<ResBody>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// Renders `scribe`, such as [`Json`](crate::writing::Json) or [`Text`](crate::writing::Text),
+    /// and returns `Self`.
+    #[inline]
+    pub fn with_scribe(mut self, scribe: impl Scribe) -> Self {
+        scribe.render(&mut self);
+        self
+    }
+
     /// Get headers reference.
     #[inline]
     pub fn headers(&self) -> &HeaderMap {
@@ -156,6 +223,30 @@ impl Response {
         Ok(self)
     }
 
+    /// Insert a header of the [`headers`] crate, such as [`headers::ContentRange`], the existing
+    /// values of the header are replaced.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use salvo_core::http::headers::{CacheControl, ContentLength};
+    /// use salvo_core::http::Response;
+    ///
+    /// let mut res = Response::new();
+    /// res.typed_header(ContentLength(5))
+    ///     .typed_header(CacheControl::new().with_max_age(Duration::from_secs(60)));
+    /// ```
+    #[inline]
+    pub fn typed_header<H>(&mut self, header: H) -> &mut Self
+    where
+        H: headers::Header,
+    {
+        headers::HeaderMapExt::typed_insert(&mut self.headers, header);
+        self
+    }
+
     /// Get version.
     #[inline]
     pub fn version(&self) -> Version {
@@ -313,6 +404,24 @@ impl Response {
             self.cookies.add(cookie);
             self
         }
+        /// Helper function for add signed cookie.
+        ///
+        /// The value is signed with `key` (HMAC-SHA256), so it can be read but not tampered with
+        /// by the client. Read it with [`Request::signed_cookie`].
+        #[inline]
+        pub fn add_signed_cookie(&mut self, cookie: Cookie<'static>, key: &Key) -> &mut Self {
+            self.cookies.signed_mut(key).add(cookie);
+            self
+        }
+        /// Helper function for add encrypted cookie.
+        ///
+        /// The value is encrypted with `key` (AES-256-GCM), so it can neither be read nor
+        /// tampered with by the client. Read it with [`Request::private_cookie`].
+        #[inline]
+        pub fn add_private_cookie(&mut self, cookie: Cookie<'static>, key: &Key) -> &mut Self {
+            self.cookies.private_mut(key).add(cookie);
+            self
+        }
 
         /// Helper function for remove cookie.
         ///
@@ -391,6 +500,120 @@ impl Response {
         scribe.render(self);
     }
 
+    /// Render `value` in the format chosen by the `Accept` header of `req`.
+    ///
+    /// JSON and XML are supported by default, MessagePack and CBOR if the `msgpack` and `cbor`
+    /// features are enabled, use a [`Negotiator`](crate::writing::Negotiator) to add more
+    /// formats. JSON is used if the request has no `Accept` header, and `406 Not Acceptable` is
+    /// rendered if no format is accepted.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use salvo_core::prelude::*;
+    /// use serde::Serialize;
+    ///
+    /// #[derive(Serialize)]
+    /// struct User {
+    ///     name: String,
+    /// }
+    /// #[handler]
+    /// async fn show_user(req: &mut Request, res: &mut Response) {
+    ///     res.render_negotiated(&User { name: "jobs".into() }, req);
+    /// }
+    /// ```
+    #[inline]
+    pub fn render_negotiated<T>(&mut self, value: &T, req: &Request)
+    where
+        T: Serialize,
+    {
+        crate::writing::Negotiator::new().render(value, req, self);
+    }
+
+    /// Render the template `name` with `context` by the engine set with
+    /// [`Templates`](crate::writing::Templates), the `Content-Type` is
+    /// `text/html; charset=utf-8`.
+    ///
+    /// `500 Internal Server Error` is rendered if no engine is set or the template can't be
+    /// rendered.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use salvo_core::prelude::*;
+    /// use serde_json::json;
+    ///
+    /// #[handler]
+    /// async fn index(depot: &mut Depot, res: &mut Response) {
+    ///     res.render_template(depot, "index.html", &json!({ "title": "Home" }));
+    /// }
+    /// ```
+    #[inline]
+    pub fn render_template<T>(&mut self, depot: &Depot, name: &str, context: &T)
+    where
+        T: Serialize,
+    {
+        crate::writing::template::render_template(depot, name, context, self);
+    }
+
+    /// Redirect to `url` with `307 Temporary Redirect`, the client keeps the method and the body
+    /// of the request.
+    ///
+    /// The `url` can be absolute or relative, characters which are not allowed in a URL, such as
+    /// spaces and non-ASCII characters, are percent-encoded.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use salvo_core::prelude::*;
+    ///
+    /// #[handler]
+    /// async fn upload(res: &mut Response) {
+    ///     res.redirect_temporary("/v2/upload");
+    /// }
+    /// ```
+    #[inline]
+    pub fn redirect_temporary(&mut self, url: impl AsRef<str>) -> &mut Self {
+        self.redirect(StatusCode::TEMPORARY_REDIRECT, url.as_ref())
+    }
+
+    /// Redirect to `url` with `308 Permanent Redirect`, the client keeps the method and the body
+    /// of the request.
+    ///
+    /// The `url` is encoded like [`Response::redirect_temporary`].
+    #[inline]
+    pub fn redirect_permanent(&mut self, url: impl AsRef<str>) -> &mut Self {
+        self.redirect(StatusCode::PERMANENT_REDIRECT, url.as_ref())
+    }
+
+    /// Redirect to `url` with `302 Found`.
+    ///
+    /// Clients usually change the method of a `POST` request to `GET`, use
+    /// [`Response::redirect_see_other`] or [`Response::redirect_temporary`] to make it explicit.
+    /// The `url` is encoded like [`Response::redirect_temporary`].
+    #[inline]
+    pub fn redirect_found(&mut self, url: impl AsRef<str>) -> &mut Self {
+        self.redirect(StatusCode::FOUND, url.as_ref())
+    }
+
+    /// Redirect to `url` with `303 See Other`, the client changes the method to `GET`, it is
+    /// usually used after a form is submitted.
+    ///
+    /// The `url` is encoded like [`Response::redirect_temporary`].
+    #[inline]
+    pub fn redirect_see_other(&mut self, url: impl AsRef<str>) -> &mut Self {
+        self.redirect(StatusCode::SEE_OTHER, url.as_ref())
+    }
+
+    fn redirect(&mut self, code: StatusCode, url: &str) -> &mut Self {
+        self.status_code = Some(code);
+        self.headers.insert(
+            http::header::LOCATION,
+            crate::writing::redirect::encode_location(url),
+        );
+        self
+    }
+
     /// Render content with status code.
     #[inline]
     pub fn stuff<P>(&mut self, code: StatusCode, scribe: P)
@@ -403,8 +626,19 @@ impl Response {
 
     /// Attempts to send a file. If file not exists, not found error will occur.
     ///
+    /// The `Content-Type` is guessed from the extension of the file, conditional and `Range`
+    /// headers of the request are handled like [`NamedFile::send`], and only the headers are sent
+    /// for `HEAD` requests. The file is streamed in chunks, so large files are not read into
+    /// memory.
+    ///
+    /// # Zero-copy
+    ///
+    /// The file is always read in chunks into user space, `sendfile` is not supported, even on a
+    /// plain TCP stream. The body frames are written into the connection by hyper, which owns the
+    /// transport, so the file descriptor of the socket is not reachable from the response.
+    ///
     /// If you want more settings, you can use `NamedFile::builder` to create a new [`NamedFileBuilder`](crate::fs::NamedFileBuilder).
-    pub async fn send_file<P>(&mut self, path: P, req_headers: &HeaderMap)
+    pub async fn send_file<P>(&mut self, path: P, req: &Request)
     where
         P: Into<PathBuf> + Send,
     {
@@ -413,7 +647,7 @@ impl Response {
             self.render(StatusError::not_found());
         } else {
             match NamedFile::builder(path).build().await {
-                Ok(file) => file.send(req_headers, self).await,
+                Ok(file) => file.send_to(req, self).await,
                 Err(_) => self.render(StatusError::internal_server_error()),
             }
         }
@@ -474,6 +708,25 @@ impl Response {
     }
 
     /// Set response's body to stream.
+    ///
+    /// The body is sent with chunked transfer-encoding on HTTP/1.1 and as data frames on HTTP/2.
+    /// The stream is only polled when the connection is ready to send more data, so a slow client
+    /// applies back-pressure to the producer. If the stream yields an error, the body is aborted
+    /// and the connection is closed without completing the response.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use futures_util::{stream, StreamExt};
+    /// use salvo_core::prelude::*;
+    ///
+    /// #[handler]
+    /// async fn export(res: &mut Response) {
+    ///     res.add_header("content-type", "text/csv", true).unwrap();
+    ///     let rows = stream::iter(0..1000).map(|i| Ok::<_, std::io::Error>(format!("{i},row {i}\n")));
+    ///     res.stream(rows);
+    /// }
+    /// ```
     #[inline]
     pub fn stream<S, O, E>(&mut self, stream: S)
     where
@@ -509,6 +762,32 @@ impl Response {
     }
 }
 
+impl Scribe for Response {
+    fn render(self, res: &mut Response) {
+        let Response {
+            status_code,
+            headers,
+            #[cfg(feature = "cookie")]
+            cookies,
+            body,
+            extensions,
+            ..
+        } = self;
+        if status_code.is_some() {
+            res.status_code = status_code;
+        }
+        res.headers.extend(headers);
+        #[cfg(feature = "cookie")]
+        for cookie in cookies.delta() {
+            res.cookies.add(cookie.clone());
+        }
+        if !body.is_none() {
+            res.body = body;
+        }
+        res.extensions.extend(extensions);
+    }
+}
+
 impl Debug for Response {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         f.debug_struct("Response")
@@ -544,6 +823,76 @@ mod test {
         assert!(body.is_none());
     }
 
+    #[tokio::test]
+    async fn test_builder() {
+        use crate::prelude::*;
+        use crate::test::{ResponseExt, TestClient};
+
+        #[handler]
+        async fn set_header(res: &mut Response) {
+            res.add_header("x-powered-by", "salvo", true).unwrap();
+            res.add_header("x-request-id", "1", true).unwrap();
+        }
+        #[handler]
+        async fn create() -> crate::Result<Response> {
+            Ok(Response::new()
+                .with_status_code(StatusCode::CREATED)
+                .with_header("x-request-id", "42")?
+                .with_content_type(mime::TEXT_PLAIN)
+                .with_body("created"))
+        }
+
+        let router = Router::with_hoop(set_header).post(create);
+        let mut res = TestClient::post("http://127.0.0.1:5800/")
+            .send(router)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::CREATED));
+        assert_eq!(res.headers()["x-powered-by"], "salvo");
+        assert_eq!(res.headers()["x-request-id"], "42");
+        assert_eq!(res.headers()[CONTENT_TYPE], "text/plain");
+        assert_eq!(res.take_string().await.unwrap(), "created");
+
+        assert!(Response::new().with_header("x-request-id", "4\n2").is_err());
+
+        let res = Response::new().with_scribe(crate::writing::Json("hello"));
+        assert_eq!(
+            res.headers()[CONTENT_TYPE],
+            "application/json; charset=utf-8"
+        );
+    }
+
+    #[test]
+    fn test_typed_header() {
+        let mut res = Response::new();
+        res.typed_header(headers::ContentLength(5))
+            .typed_header(headers::ContentLength(10));
+        assert_eq!(
+            res.headers()
+                .get_all(http::header::CONTENT_LENGTH)
+                .iter()
+                .count(),
+            1
+        );
+        assert_eq!(res.headers()[http::header::CONTENT_LENGTH], "10");
+    }
+
+    #[test]
+    fn test_redirect() {
+        let mut res = Response::new();
+        res.redirect_temporary("/upload");
+        assert_eq!(res.status_code, Some(StatusCode::TEMPORARY_REDIRECT));
+        assert_eq!(res.headers()[http::header::LOCATION], "/upload");
+        res.redirect_permanent("https://salvo.rs/");
+        assert_eq!(res.status_code, Some(StatusCode::PERMANENT_REDIRECT));
+        assert_eq!(res.headers()[http::header::LOCATION], "https://salvo.rs/");
+        res.redirect_found("../a b");
+        assert_eq!(res.status_code, Some(StatusCode::FOUND));
+        assert_eq!(res.headers()[http::header::LOCATION], "../a%20b");
+        res.redirect_see_other("?page=2");
+        assert_eq!(res.status_code, Some(StatusCode::SEE_OTHER));
+        assert_eq!(res.headers()[http::header::LOCATION], "?page=2");
+    }
+
     #[tokio::test]
     async fn test_body_stream1() {
         let mut body = ResBody::Once(Bytes::from("hello"));
@@ -570,4 +919,55 @@ mod test {
 
         assert_eq!("Hello World", &result)
     }
+
+    #[cfg(feature = "http1")]
+    #[tokio::test]
+    async fn test_stream_over_http1() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio::net::TcpStream;
+
+        use crate::prelude::*;
+
+        #[handler]
+        async fn rows(res: &mut Response) {
+            res.stream(iter(["a,b\n", "c,d\n"].map(Ok::<_, std::io::Error>)));
+        }
+        #[handler]
+        async fn broken(res: &mut Response) {
+            res.stream(iter([
+                Ok("a,b\n"),
+                Err(std::io::Error::other("database gone")),
+            ]));
+        }
+        let addr = std::net::SocketAddr::from(([127, 0, 0, 1], 6895));
+        let acceptor = TcpListener::new(addr).bind().await;
+        let router = Router::new()
+            .push(Router::with_path("rows").get(rows))
+            .push(Router::with_path("broken").get(broken));
+        tokio::spawn(Server::new(acceptor).serve(router));
+
+        let request = |path: &'static str| async move {
+            let mut stream = TcpStream::connect(addr).await.unwrap();
+            stream
+                .write_all(
+                    format!("GET /{path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+                        .as_bytes(),
+                )
+                .await
+                .unwrap();
+            let mut response = Vec::new();
+            let _ = stream.read_to_end(&mut response).await;
+            String::from_utf8(response).unwrap()
+        };
+
+        let response = request("rows").await;
+        assert!(response
+            .to_lowercase()
+            .contains("transfer-encoding: chunked"));
+        assert!(response.ends_with("4\r\na,b\n\r\n4\r\nc,d\n\r\n0\r\n\r\n"));
+
+        // The terminating chunk is never sent, so the client can tell the body is incomplete.
+        let response = request("broken").await;
+        assert!(!response.ends_with("0\r\n\r\n"));
+    }
 }
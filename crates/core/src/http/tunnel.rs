@@ -0,0 +1,44 @@
+//! Tunnels of `CONNECT` requests, see [`Service::connect`](crate::Service::connect).
+use std::future::Future;
+
+use hyper::upgrade::{OnUpgrade, Upgraded};
+
+use crate::http::{Method, Request, Response, StatusCode, StatusError};
+use crate::rt::tokio::TokioIo;
+
+/// The connection of a `CONNECT` request once the tunnel is established, it implements
+/// [`AsyncRead`](tokio::io::AsyncRead) and [`AsyncWrite`](tokio::io::AsyncWrite).
+///
+/// It's not the raw `TcpStream` of the connection: the connection may be TLS, and hyper may have
+/// read the first bytes of the tunnel already, they are read from the tunnel before the rest.
+pub type Tunnel = TokioIo<Upgraded>;
+
+/// Accepts a `CONNECT` request, the status code is set to `200 OK` and `callback` is spawned with
+/// the [`Tunnel`] once the response is sent.
+///
+/// Returns `405 Method Not Allowed` if the request is not `CONNECT`, and `400 Bad Request` if the
+/// connection can't be upgraded, such as requests of [`TestClient`](crate::test::TestClient).
+pub fn accept_tunnel<F, Fut>(
+    req: &mut Request,
+    res: &mut Response,
+    callback: F,
+) -> Result<(), StatusError>
+where
+    F: FnOnce(Tunnel) -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    if req.method() != Method::CONNECT {
+        return Err(StatusError::method_not_allowed());
+    }
+    let Some(on_upgrade) = req.extensions_mut().remove::<OnUpgrade>() else {
+        return Err(StatusError::bad_request().brief("The connection can't be upgraded."));
+    };
+    res.status_code(StatusCode::OK);
+    tokio::spawn(async move {
+        match on_upgrade.await {
+            Ok(upgraded) => callback(TokioIo::new(upgraded)).await,
+            Err(e) => tracing::error!(error = ?e, "tunnel upgrade failed"),
+        }
+    });
+    Ok(())
+}
@@ -19,8 +19,11 @@
 //! | `openssl` | TLS built on [`openssl-tls`](https://crates.io/crates/openssl) | ❌ |
 //! | `native-tls` | TLS built on [`native-tls`](https://crates.io/crates/native-tls) | ❌ |
 //! | `unix` | Listener based on unix socket | ❌ |
+//! | `systemd` | Accept from the sockets passed by systemd socket activation | ❌ |
 //! | `anyhow` | Integrate with the [`anyhow`](https://crates.io/crates/anyhow) crate | ❌ |
 //! | `eyre` | Integrate with the [`eyre`](https://crates.io/crates/eyre) crate | ❌ |
+//! | `msgpack` | Support for MessagePack request and response bodies | ❌ |
+//! | `cbor` | Support for CBOR request and response bodies | ❌ |
 #![doc(html_favicon_url = "https://salvo.rs/favicon-32x32.png")]
 #![doc(html_logo_url = "https://salvo.rs/images/logo.svg")]
 #![cfg_attr(docsrs, feature(doc_cfg))]
@@ -68,7 +71,7 @@ cfg_feature! {
 }
 
 pub use self::conn::Listener;
-pub use self::depot::Depot;
+pub use self::depot::{Depot, DepotKey, DepotScope};
 pub use self::error::{BoxedError, Error};
 pub use self::extract::Extractible;
 pub use self::handler::Handler;
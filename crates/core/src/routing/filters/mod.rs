@@ -1,11 +1,12 @@
 //! Filter module
 //!
 //! This module provides filters for routing requests based on various criteria
-//! such as uri scheme, hostname, port, path, and HTTP method.
+//! such as uri scheme, hostname, port, path, HTTP method and API version.
 
 mod opts;
 mod others;
 mod path;
+mod version;
 
 use std::fmt::{self, Debug, Formatter};
 
@@ -17,6 +18,7 @@ use crate::routing::PathState;
 
 pub use others::*;
 pub use path::*;
+pub use version::{UnknownVersion, VersionFilter, VersionSource};
 
 /// Trait for filter request.
 ///
@@ -84,10 +86,61 @@ pub trait Filter: Debug + Send + Sync + 'static {
         }
     }
 
+    /// Describes the filter, it is used to list the routes and detect the conflicts between them.
+    ///
+    /// The default implementation returns [`FilterInfo::Other`] with the `Debug` output of the
+    /// filter.
+    #[inline]
+    fn describe(&self) -> FilterInfo {
+        FilterInfo::Other(format!("{self:?}"))
+    }
+
     /// Filter `Request` and returns false or true.
     async fn filter(&self, req: &mut Request, path: &mut PathState) -> bool;
 }
 
+/// Description of a [`Filter`], returned by [`Filter::describe`].
+#[non_exhaustive]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum FilterInfo {
+    /// Path filter with its raw path, such as `users/<id>`.
+    Path(String),
+    /// Method filter.
+    Method(Method),
+    /// Scheme filter.
+    Scheme(Scheme),
+    /// Host filter.
+    Host(String),
+    /// Port filter.
+    Port(u16),
+    /// Param filter with the name of the param and its regex.
+    Param {
+        /// Name of the param.
+        name: String,
+        /// Regex the param should match.
+        regex: String,
+    },
+    /// Version filter.
+    Version(u32),
+    /// Other filters, such as [`FnFilter`] and the combined filters, with their `Debug` output.
+    Other(String),
+}
+impl fmt::Display for FilterInfo {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Path(path) => write!(f, "path:{path}"),
+            Self::Method(method) => write!(f, "method:{method}"),
+            Self::Scheme(scheme) => write!(f, "scheme:{scheme}"),
+            Self::Host(host) => write!(f, "host:{host}"),
+            Self::Port(port) => write!(f, "port:{port}"),
+            Self::Param { name, regex } => write!(f, "param:{name}:{regex}"),
+            Self::Version(version) => write!(f, "version:{version}"),
+            Self::Other(info) => f.write_str(info),
+        }
+    }
+}
+
 /// `FnFilter` accepts a function as it's param, use this function to filter request.
 #[derive(Copy, Clone)]
 #[allow(missing_debug_implementations)]
@@ -129,6 +182,18 @@ pub fn port(port: u16) -> PortFilter {
     PortFilter::new(port)
 }
 
+/// Filter request by API version.
+#[inline]
+pub fn version(version: u32) -> VersionFilter {
+    VersionFilter::new(version)
+}
+
+/// Filter request by a path param which should match the regex.
+#[inline]
+pub fn param(name: impl Into<String>, regex: regex::Regex) -> ParamFilter {
+    ParamFilter::new(name, regex)
+}
+
 /// Filter request use `PathFilter`.
 #[inline]
 pub fn path(path: impl Into<String>) -> PathFilter {
@@ -283,4 +348,31 @@ mod tests {
                 .await
         );
     }
+
+    #[tokio::test]
+    async fn test_scheme_and_port() {
+        let mut req = Request::default();
+        *req.uri_mut() = "http://example.com/users".parse().unwrap();
+        let mut path_state = PathState::new("/users");
+        assert!(scheme(Scheme::HTTP).filter(&mut req, &mut path_state).await);
+        assert!(
+            !scheme(Scheme::HTTPS)
+                .filter(&mut req, &mut path_state)
+                .await
+        );
+        assert!(port(80).filter(&mut req, &mut path_state).await);
+
+        *req.uri_mut() = "https://example.com/users".parse().unwrap();
+        *req.scheme_mut() = Scheme::HTTPS;
+        assert!(
+            scheme(Scheme::HTTPS)
+                .filter(&mut req, &mut path_state)
+                .await
+        );
+        assert!(port(443).filter(&mut req, &mut path_state).await);
+
+        *req.uri_mut() = "https://[::1]:8443/users".parse().unwrap();
+        assert!(port(8443).filter(&mut req, &mut path_state).await);
+        assert!(host("[::1]").filter(&mut req, &mut path_state).await);
+    }
 }
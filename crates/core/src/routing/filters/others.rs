@@ -1,9 +1,11 @@
 use std::fmt::{self, Debug, Formatter};
 
+use regex::Regex;
+
 use crate::async_trait;
 use crate::http::uri::Scheme;
 use crate::http::{Method, Request};
-use crate::routing::{Filter, PathState};
+use crate::routing::{Filter, FilterInfo, PathState};
 
 /// Filter by request method
 #[derive(Clone, PartialEq, Eq)]
@@ -17,6 +19,11 @@ impl MethodFilter {
 
 #[async_trait]
 impl Filter for MethodFilter {
+    #[inline]
+    fn describe(&self) -> FilterInfo {
+        FilterInfo::Method(self.0.clone())
+    }
+
     #[inline]
     async fn filter(&self, req: &mut Request, _state: &mut PathState) -> bool {
         req.method() == self.0
@@ -29,17 +36,23 @@ impl Debug for MethodFilter {
     }
 }
 
-/// Filter by request uri scheme.
+/// Filter by request scheme.
+///
+/// The scheme of the request is the scheme of the request uri, or the scheme of the listener
+/// which accepted the connection, `https` for TLS listeners and `http` for the others, so
+/// HTTP/1 requests, which have no scheme in the uri, are matched too.
 #[derive(Clone, PartialEq, Eq)]
 #[non_exhaustive]
 pub struct SchemeFilter {
     /// Scheme to filter.
     pub scheme: Scheme,
-    /// When scheme is lack in request uri, use this value.
+    /// Not used any more, since the scheme of the request is always known.
+    #[deprecated(note = "the scheme of the request is always known, so it is not used")]
     pub lack: bool,
 }
 impl SchemeFilter {
     /// Create a new `SchemeFilter`.
+    #[allow(deprecated)]
     pub fn new(scheme: Scheme) -> Self {
         Self {
             scheme,
@@ -47,6 +60,8 @@ impl SchemeFilter {
         }
     }
     /// Set lack value and return `Self`.
+    #[deprecated(note = "the scheme of the request is always known, so it is not used")]
+    #[allow(deprecated)]
     pub fn lack(mut self, lack: bool) -> Self {
         self.lack = lack;
         self
@@ -55,12 +70,14 @@ impl SchemeFilter {
 
 #[async_trait]
 impl Filter for SchemeFilter {
+    #[inline]
+    fn describe(&self) -> FilterInfo {
+        FilterInfo::Scheme(self.scheme.clone())
+    }
+
     #[inline]
     async fn filter(&self, req: &mut Request, _state: &mut PathState) -> bool {
-        req.uri()
-            .scheme()
-            .map(|s| s == &self.scheme)
-            .unwrap_or(self.lack)
+        req.scheme() == &self.scheme
     }
 }
 impl Debug for SchemeFilter {
@@ -71,6 +88,15 @@ impl Debug for SchemeFilter {
 }
 
 /// Filter by request uri host.
+///
+/// The host is read from the request uri, which has it for HTTP/2 and HTTP/3 requests, or from
+/// the `Host` header. The port is ignored and the host is compared case-insensitively. A host
+/// starting with `*.`, such as `*.example.com`, matches the subdomains of `example.com`, but not
+/// `example.com` itself.
+///
+/// On TLS connections, clients send the same name in the SNI extension and in the `Host` header,
+/// so virtual hosts work on TLS too, the certificate of each host can be selected by the SNI name
+/// with the resolver of the TLS config.
 #[derive(Clone, PartialEq, Eq)]
 pub struct HostFilter {
     /// Host to filter.
@@ -95,6 +121,11 @@ impl HostFilter {
 
 #[async_trait]
 impl Filter for HostFilter {
+    #[inline]
+    fn describe(&self) -> FilterInfo {
+        FilterInfo::Host(self.host.clone())
+    }
+
     #[inline]
     async fn filter(&self, req: &mut Request, _state: &mut PathState) -> bool {
         // Http1, if `fix-http1-request-uri` feature is disabled, host is lack. so use header host instead.
@@ -107,19 +138,33 @@ impl Filter for HostFilter {
                 .get(crate::http::header::HOST)
                 .and_then(|h| h.to_str().ok())
         });
-        host.map(|h| {
-            if h.contains(':') {
-                h.rsplit_once(':')
-                    .expect("rsplit_once by ':' should not returns `None`")
-                    .0
-            } else {
-                h
-            }
-        })
-        .map(|h| h == self.host)
-        .unwrap_or(self.lack)
+        host.map(|h| self.matches(split_port(h).0))
+            .unwrap_or(self.lack)
+    }
+}
+impl HostFilter {
+    fn matches(&self, host: &str) -> bool {
+        match self.host.strip_prefix("*.") {
+            Some(domain) => host
+                .len()
+                .checked_sub(domain.len() + 1)
+                .filter(|&dot| host.as_bytes()[dot] == b'.' && dot > 0)
+                .map(|dot| host[dot + 1..].eq_ignore_ascii_case(domain))
+                .unwrap_or(false),
+            None => host.eq_ignore_ascii_case(&self.host),
+        }
     }
 }
+/// Split the host and the port, IPv6 addresses are enclosed in brackets.
+fn split_port(host: &str) -> (&str, Option<&str>) {
+    let end = if host.starts_with('[') {
+        host.find(']').map(|end| end + 1).unwrap_or(host.len())
+    } else {
+        host.rfind(':').unwrap_or(host.len())
+    };
+    let (host, port) = host.split_at(end);
+    (host, port.strip_prefix(':'))
+}
 impl Debug for HostFilter {
     #[inline]
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
@@ -127,13 +172,16 @@ impl Debug for HostFilter {
     }
 }
 
-/// Filter by request uri host.
+/// Filter by request uri port.
+///
+/// The port is read from the request uri or the `Host` header, when the host has no port, the
+/// default port of the request scheme is used, `443` for `https` and `80` for `http`.
 #[derive(Clone, PartialEq, Eq)]
 #[non_exhaustive]
 pub struct PortFilter {
     /// Port to filter.
     pub port: u16,
-    /// When port is lack in request uri, use this value.
+    /// When host is lack in request uri and headers, use this value.
     pub lack: bool,
 }
 
@@ -151,6 +199,11 @@ impl PortFilter {
 
 #[async_trait]
 impl Filter for PortFilter {
+    #[inline]
+    fn describe(&self) -> FilterInfo {
+        FilterInfo::Port(self.port)
+    }
+
     #[inline]
     async fn filter(&self, req: &mut Request, _state: &mut PathState) -> bool {
         // Http1, if `fix-http1-request-uri` feature is disabled, port is lack. so use header host instead.
@@ -163,18 +216,16 @@ impl Filter for PortFilter {
                 .get(crate::http::header::HOST)
                 .and_then(|h| h.to_str().ok())
         });
-        host.map(|h| {
-            if h.contains(':') {
-                h.rsplit_once(':')
-                    .expect("rsplit_once by ':' should not returns `None`")
-                    .1
-            } else {
-                h
-            }
-        })
-        .and_then(|p| p.parse::<u16>().ok())
-        .map(|p| p == self.port)
-        .unwrap_or(self.lack)
+        let Some(host) = host else {
+            return self.lack;
+        };
+        let port = match split_port(host).1 {
+            Some(port) => port.parse::<u16>().ok(),
+            None if req.scheme() == &Scheme::HTTPS => Some(443),
+            None if req.scheme() == &Scheme::HTTP => Some(80),
+            None => None,
+        };
+        port == Some(self.port)
     }
 }
 impl Debug for PortFilter {
@@ -183,3 +234,62 @@ impl Debug for PortFilter {
         write!(f, "port:{:?}", self.port)
     }
 }
+
+/// Filter by a path param captured by the path filters before it.
+///
+/// The filter passes when the param matches the regex anywhere, use `^` and `$` to match the
+/// whole value. It fails when the param is absent, unless it is set as [optional], such as an
+/// optional param which is not in the request path.
+///
+/// [optional]: ParamFilter::optional
+#[derive(Clone)]
+#[non_exhaustive]
+pub struct ParamFilter {
+    /// Name of the param.
+    pub name: String,
+    /// Regex the param should match.
+    pub regex: Regex,
+    /// Whether the filter passes when the param is absent. Default is `false`.
+    pub optional: bool,
+}
+impl ParamFilter {
+    /// Create a new `ParamFilter`.
+    pub fn new(name: impl Into<String>, regex: Regex) -> Self {
+        Self {
+            name: name.into(),
+            regex,
+            optional: false,
+        }
+    }
+    /// Set whether the filter passes when the param is absent and return `Self`.
+    pub fn optional(mut self, optional: bool) -> Self {
+        self.optional = optional;
+        self
+    }
+}
+
+#[async_trait]
+impl Filter for ParamFilter {
+    #[inline]
+    fn describe(&self) -> FilterInfo {
+        FilterInfo::Param {
+            name: self.name.clone(),
+            regex: self.regex.to_string(),
+        }
+    }
+
+    #[inline]
+    async fn filter(&self, _req: &mut Request, state: &mut PathState) -> bool {
+        state
+            .params
+            .get(&self.name)
+            .map(|value| self.regex.is_match(value))
+            .unwrap_or(self.optional)
+    }
+}
+impl Debug for ParamFilter {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "param:{}:{}", self.name, self.regex)
+    }
+}
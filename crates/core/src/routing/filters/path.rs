@@ -10,7 +10,7 @@ use regex::Regex;
 
 use crate::async_trait;
 use crate::http::Request;
-use crate::routing::{Filter, PathState};
+use crate::routing::{Filter, FilterInfo, PathState};
 
 /// PathWisp
 pub trait PathWisp: Send + Sync + fmt::Debug + 'static {
@@ -70,6 +70,8 @@ pub enum WispKind {
     Regex(RegexWisp),
     /// CombWisp.
     Comb(CombWisp),
+    /// OptionalWisp.
+    Optional(OptionalWisp),
 }
 impl PathWisp for WispKind {
     #[inline]
@@ -80,6 +82,7 @@ impl PathWisp for WispKind {
             Self::Chars(wisp) => wisp.validate(),
             Self::Regex(wisp) => wisp.validate(),
             Self::Comb(wisp) => wisp.validate(),
+            Self::Optional(wisp) => wisp.validate(),
         }
     }
     #[inline]
@@ -90,6 +93,7 @@ impl PathWisp for WispKind {
             Self::Chars(wisp) => wisp.detect(state),
             Self::Regex(wisp) => wisp.detect(state),
             Self::Comb(wisp) => wisp.detect(state),
+            Self::Optional(wisp) => wisp.detect(state),
         }
     }
 }
@@ -101,6 +105,7 @@ impl Debug for WispKind {
             Self::Chars(wisp) => wisp.fmt(f),
             Self::Regex(wisp) => wisp.fmt(f),
             Self::Comb(wisp) => wisp.fmt(f),
+            Self::Optional(wisp) => wisp.fmt(f),
         }
     }
 }
@@ -134,6 +139,23 @@ impl From<CombWisp> for WispKind {
         Self::Comb(wisp)
     }
 }
+impl From<OptionalWisp> for WispKind {
+    #[inline]
+    fn from(wisp: OptionalWisp) -> Self {
+        Self::Optional(wisp)
+    }
+}
+impl WispKind {
+    fn name(&self) -> Option<&String> {
+        match self {
+            Self::Named(wisp) => Some(&wisp.0),
+            Self::Chars(wisp) => Some(&wisp.name),
+            Self::Regex(wisp) => Some(&wisp.name),
+            Self::Optional(wisp) => wisp.0.name(),
+            _ => None,
+        }
+    }
+}
 
 /// RegexWispBuilder
 pub struct RegexWispBuilder(Regex);
@@ -583,6 +605,23 @@ impl PathWisp for RegexWisp {
     }
 }
 
+/// Optional wisp matches the inner wisp, or nothing if there are no more segments in the path.
+///
+/// It is written as `<name?>`, `<name?:num>` or `<name?:/regex/>`, and the param is not set when the
+/// segment is absent.
+#[derive(Debug)]
+pub struct OptionalWisp(pub Box<WispKind>);
+impl PathWisp for OptionalWisp {
+    #[inline]
+    fn validate(&self) -> Result<(), String> {
+        self.0.validate()
+    }
+    #[inline]
+    fn detect(&self, state: &mut PathState) -> bool {
+        state.pick().is_none() || self.0.detect(state)
+    }
+}
+
 /// Const wisp is used for match the const string in the path.
 #[derive(Eq, PartialEq, Debug)]
 pub struct ConstWisp(pub String);
@@ -755,6 +794,10 @@ impl PathParser {
                 self.next(true)
                     .ok_or_else(|| "char is needed after <".to_owned())?;
                 let name = self.scan_ident()?;
+                let (name, optional) = match name.strip_suffix('?') {
+                    Some(name) if !name.starts_with('*') => (name.to_owned(), true),
+                    _ => (name, false),
+                };
                 if name.is_empty() {
                     return Err("name is empty string".to_owned());
                 }
@@ -822,6 +865,11 @@ impl PathParser {
                 } else if ch == '>' {
                     wisps.push(NamedWisp(name).into());
                 }
+                if optional {
+                    if let Some(wisp) = wisps.pop() {
+                        wisps.push(OptionalWisp(Box::new(wisp)).into());
+                    }
+                }
                 if let Some(c) = self.curr() {
                     if c != '>' {
                         return Err(format!(
@@ -868,6 +916,16 @@ impl PathParser {
             }
             let mut scaned = self.scan_wisps()?;
             if scaned.len() > 1 {
+                if let Some(WispKind::Optional(wisp)) = scaned
+                    .iter()
+                    .find(|wisp| matches!(wisp, WispKind::Optional(_)))
+                {
+                    return Err(format!(
+                        "optional name `{}` must be a whole segment in url: `{}`",
+                        wisp.0.name().map(|s| &**s).unwrap_or_default(),
+                        self.path.iter().collect::<String>()
+                    ));
+                }
                 wisps.push(CombWisp::new(scaned)?.into());
             } else if let Some(wisp) = scaned.pop() {
                 wisps.push(wisp);
@@ -900,14 +958,18 @@ impl PathParser {
                 ));
             }
         }
+        let mut optional_name = None;
         for (index, wisp) in wisps.iter().enumerate() {
-            let name = match wisp {
-                WispKind::Named(wisp) => Some(&wisp.0),
-                WispKind::Chars(wisp) => Some(&wisp.name),
-                WispKind::Regex(wisp) => Some(&wisp.name),
-                _ => None,
-            };
-
+            let name = wisp.name();
+            if let WispKind::Optional(_) = wisp {
+                optional_name = name;
+            } else if let Some(optional_name) = optional_name {
+                return Err(format!(
+                    "only optional names can follow optional name `{}` in url: `{}`",
+                    optional_name,
+                    self.path.iter().collect::<String>()
+                ));
+            }
             if let Some(name) = name {
                 if name.starts_with('*') && index != wisps.len() - 1 {
                     return Err(format!(
@@ -963,9 +1025,22 @@ impl Debug for PathFilter {
 }
 #[async_trait]
 impl Filter for PathFilter {
+    #[inline]
+    fn describe(&self) -> FilterInfo {
+        FilterInfo::Path(self.raw_value.clone())
+    }
+
     #[inline]
     async fn filter(&self, _req: &mut Request, state: &mut PathState) -> bool {
-        self.detect(state)
+        if !self.detect(state) {
+            return false;
+        }
+        let template = self.raw_value.trim_matches('/');
+        if !template.is_empty() {
+            state.matched_path.push('/');
+            state.matched_path.push_str(template);
+        }
+        true
     }
 }
 impl PathFilter {
@@ -1334,4 +1409,32 @@ mod tests {
         let mut state = PathState::new("/users/12/abc");
         assert!(filter.detect(&mut state));
     }
+    #[test]
+    fn test_parse_optional() {
+        let wisps = PathParser::new("/items/<page?>").parse().unwrap();
+        assert_eq!(
+            format!("{:?}", wisps),
+            r#"[ConstWisp("items"), OptionalWisp(NamedWisp("page"))]"#
+        );
+        assert!(PathParser::new("/items/<page?:num>/<size?>")
+            .parse()
+            .is_ok());
+        assert!(PathParser::new("/items/<page?>/<size>").parse().is_err());
+        assert!(PathParser::new("/items/<page?>/<**rest>").parse().is_err());
+        assert!(PathParser::new("/items/p<page?>").parse().is_err());
+    }
+    #[test]
+    fn test_detect_optional() {
+        let filter = PathFilter::new("/items/<page?:num>");
+        let mut state = PathState::new("/items");
+        assert!(filter.detect(&mut state));
+        assert!(state.params.get("page").is_none());
+        let mut state = PathState::new("/items/");
+        assert!(filter.detect(&mut state));
+        let mut state = PathState::new("/items/3");
+        assert!(filter.detect(&mut state));
+        assert_eq!(state.params["page"], "3");
+        let mut state = PathState::new("/items/abc");
+        assert!(!filter.detect(&mut state));
+    }
 }
@@ -0,0 +1,346 @@
+use std::fmt::{self, Debug, Formatter};
+
+use crate::http::header::{HeaderName, ACCEPT};
+use crate::http::{Request, Response, StatusError};
+use crate::routing::{Filter, FilterInfo, PathState};
+use crate::{async_trait, Depot, FlowCtrl, Handler};
+
+/// Where the API version of a request is read from, used by [`VersionFilter`] and
+/// [`UnknownVersion`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum VersionSource {
+    /// The path segment at the position of the router, such as `v2` in `/v2/users`, it is
+    /// consumed when the filter passes, so the child routers match the rest of the path.
+    Path,
+    /// A header whose value is the version, such as `X-Api-Version: 2` or `X-Api-Version: v2`.
+    Header(HeaderName),
+    /// The `Accept` header, with a vendor media type such as `application/vnd.myapp.v2+json`,
+    /// or a `version` parameter such as `application/json; version=2`.
+    Accept,
+    /// A cookie whose value is the version.
+    #[cfg(feature = "cookie")]
+    Cookie(String),
+}
+impl VersionSource {
+    /// Read the version from the request, `state` is `None` outside of the routing, then the
+    /// first path segment like `v2` is read.
+    fn read(&self, req: &Request, state: Option<&PathState>) -> Option<u32> {
+        match self {
+            Self::Path => match state {
+                Some(state) if state.cursor.1 == 0 => state.pick().and_then(parse_prefixed_version),
+                Some(_) => None,
+                None => req.uri().path().split('/').find_map(parse_prefixed_version),
+            },
+            Self::Header(name) => req
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| parse_version(value.trim())),
+            Self::Accept => req
+                .headers()
+                .get_all(ACCEPT)
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .flat_map(|value| value.split(','))
+                .find_map(accept_version),
+            #[cfg(feature = "cookie")]
+            Self::Cookie(name) => req
+                .cookie(name)
+                .and_then(|cookie| parse_version(cookie.value())),
+        }
+    }
+}
+
+/// Parse a version like `2` or `v2`.
+fn parse_version(value: &str) -> Option<u32> {
+    parse_digits(value.strip_prefix(['v', 'V']).unwrap_or(value))
+}
+
+/// Parse a version like `v2`, the prefix is required, so a path segment like `2` or a media type
+/// like `vnd.myapp.2` is not a version.
+fn parse_prefixed_version(value: &str) -> Option<u32> {
+    value.strip_prefix(['v', 'V']).and_then(parse_digits)
+}
+
+fn parse_digits(digits: &str) -> Option<u32> {
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+/// Read the version of a media range, from a vendor subtype like `vnd.myapp.v2+json` or from
+/// the `version` parameter.
+fn accept_version(range: &str) -> Option<u32> {
+    let mut parts = range.split(';');
+    let media_type = parts.next()?.trim();
+    let param = parts.find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        name.trim()
+            .eq_ignore_ascii_case("version")
+            .then(|| parse_version(value.trim().trim_matches('"')))
+            .flatten()
+    });
+    if param.is_some() {
+        return param;
+    }
+    let subtype = media_type.split_once('/')?.1;
+    let subtype = subtype
+        .split_once('+')
+        .map_or(subtype, |(subtype, _)| subtype);
+    if !subtype.starts_with("vnd.") {
+        return None;
+    }
+    subtype.rsplit('.').next().and_then(parse_prefixed_version)
+}
+
+fn default_sources() -> Vec<VersionSource> {
+    vec![VersionSource::Path, VersionSource::Accept]
+}
+
+/// Filter by the API version of the request.
+///
+/// The version is read from the [sources](VersionFilter::sources) in order, the first source
+/// which has a version is used, and the filter passes if it is the version of the filter. By
+/// default the version is read from the path, such as `/v2/users`, then from the `Accept` header,
+/// such as `application/vnd.myapp.v2+json`.
+///
+/// Requests with an unknown version or without a version match none of the version routers,
+/// handle them with [`UnknownVersion`].
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::http::header::HeaderName;
+/// use salvo_core::prelude::*;
+/// use salvo_core::routing::filters::{UnknownVersion, VersionFilter, VersionSource};
+///
+/// # #[handler] async fn list_users() {}
+/// # #[handler] async fn list_users_v2() {}
+/// let router = Router::new()
+///     .push(Router::with_version(1).push(Router::with_path("users").get(list_users)))
+///     .push(Router::with_version(2).push(Router::with_path("users").get(list_users_v2)))
+///     .fallback(UnknownVersion::new([1, 2]));
+///
+/// // Read the version from the `X-Api-Version` header only.
+/// let sources = [VersionSource::Header(HeaderName::from_static("x-api-version"))];
+/// let router = Router::new()
+///     .push(Router::new().filter(VersionFilter::new(2).sources(sources)).get(list_users_v2));
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct VersionFilter {
+    /// Version to filter.
+    pub version: u32,
+    /// Sources to read the version from.
+    pub sources: Vec<VersionSource>,
+}
+impl VersionFilter {
+    /// Create a new `VersionFilter`.
+    pub fn new(version: u32) -> Self {
+        Self {
+            version,
+            sources: default_sources(),
+        }
+    }
+    /// Sets the sources to read the version from and return `Self`.
+    pub fn sources(mut self, sources: impl IntoIterator<Item = VersionSource>) -> Self {
+        self.sources = sources.into_iter().collect();
+        self
+    }
+}
+
+#[async_trait]
+impl Filter for VersionFilter {
+    #[inline]
+    fn describe(&self) -> FilterInfo {
+        FilterInfo::Version(self.version)
+    }
+
+    async fn filter(&self, req: &mut Request, state: &mut PathState) -> bool {
+        for source in &self.sources {
+            let Some(version) = source.read(req, Some(state)) else {
+                continue;
+            };
+            if version != self.version {
+                return false;
+            }
+            if *source == VersionSource::Path {
+                if let Some(segment) = state.pick().map(ToOwned::to_owned) {
+                    state.forward(segment.len());
+                    state.matched_path.push('/');
+                    state.matched_path.push_str(&segment);
+                }
+            }
+            return true;
+        }
+        false
+    }
+}
+impl Debug for VersionFilter {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "version:{}", self.version)
+    }
+}
+
+/// Handler for requests with an unknown API version or without a version.
+///
+/// It renders `406 Not Acceptable` if the version from the `Accept` header is unknown, and
+/// `400 Bad Request` if a version from another source is unknown or there is no version. Use it
+/// as the [`fallback`](crate::Router::fallback) of the router which has the version routers, with
+/// the same sources as the [`VersionFilter`]s. If the version is known, the request matched no
+/// route of the version, and `404 Not Found` is rendered.
+#[derive(Clone, Debug)]
+pub struct UnknownVersion {
+    known: Vec<u32>,
+    sources: Vec<VersionSource>,
+}
+impl UnknownVersion {
+    /// Create a new `UnknownVersion` with the known versions.
+    pub fn new(known: impl IntoIterator<Item = u32>) -> Self {
+        Self {
+            known: known.into_iter().collect(),
+            sources: default_sources(),
+        }
+    }
+    /// Sets the sources to read the version from and return `Self`.
+    pub fn sources(mut self, sources: impl IntoIterator<Item = VersionSource>) -> Self {
+        self.sources = sources.into_iter().collect();
+        self
+    }
+}
+
+#[async_trait]
+impl Handler for UnknownVersion {
+    async fn handle(
+        &self,
+        req: &mut Request,
+        _depot: &mut Depot,
+        res: &mut Response,
+        _ctrl: &mut FlowCtrl,
+    ) {
+        let found = self
+            .sources
+            .iter()
+            .find_map(|source| source.read(req, None).map(|version| (source, version)));
+        let error = match found {
+            Some((_, version)) if self.known.contains(&version) => StatusError::not_found(),
+            Some((VersionSource::Accept, _)) => {
+                StatusError::not_acceptable().brief("Unknown API version.")
+            }
+            Some(_) => StatusError::bad_request().brief("Unknown API version."),
+            None => StatusError::bad_request().brief("Missing API version."),
+        };
+        res.render(error);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use crate::test::{ResponseExt, TestClient};
+
+    #[test]
+    fn test_accept_version() {
+        assert_eq!(accept_version("application/vnd.myapp.v2+json"), Some(2));
+        assert_eq!(accept_version(" application/vnd.myapp.V3"), Some(3));
+        assert_eq!(accept_version("application/json; version=4"), Some(4));
+        assert_eq!(accept_version("application/json"), None);
+        assert_eq!(accept_version("application/vnd.myapp+json"), None);
+        assert_eq!(accept_version("application/vnd.myapp.v2x+json"), None);
+    }
+
+    #[handler]
+    async fn users(req: &mut Request) -> String {
+        format!(
+            "{} {}",
+            req.uri().path(),
+            req.param::<String>("id").unwrap_or_default()
+        )
+    }
+    #[handler]
+    async fn users_v2(req: &mut Request) -> String {
+        format!("v2 {}", req.param::<String>("id").unwrap_or_default())
+    }
+
+    #[tokio::test]
+    async fn test_version() {
+        let router = Router::new()
+            .push(Router::with_version(1).push(Router::with_path("users/<id>").get(users)))
+            .push(Router::with_version(2).push(Router::with_path("users/<id>").get(users_v2)))
+            .fallback(UnknownVersion::new([1, 2]));
+        let service = Service::new(router);
+        let access = |path: &'static str, accept: Option<&'static str>| {
+            let service = &service;
+            async move {
+                let mut client = TestClient::get(format!("http://127.0.0.1:5801{path}"));
+                if let Some(accept) = accept {
+                    client = client.add_header(ACCEPT, accept, true);
+                }
+                let mut res = client.send(service).await;
+                (res.status_code.unwrap(), res.take_string().await.unwrap())
+            }
+        };
+
+        assert_eq!(
+            access("/v1/users/7", None).await,
+            (StatusCode::OK, "/v1/users/7 7".into())
+        );
+        assert_eq!(
+            access("/v2/users/7", None).await,
+            (StatusCode::OK, "v2 7".into())
+        );
+        let accept = Some("application/vnd.myapp.v2+json");
+        assert_eq!(
+            access("/users/7", accept).await,
+            (StatusCode::OK, "v2 7".into())
+        );
+        // The path has priority over the `Accept` header.
+        assert_eq!(access("/v1/users/7", accept).await.1, "/v1/users/7 7");
+
+        assert_eq!(access("/v3/users/7", None).await.0, StatusCode::BAD_REQUEST);
+        let accept = Some("application/vnd.myapp.v3+json");
+        assert_eq!(
+            access("/users/7", accept).await.0,
+            StatusCode::NOT_ACCEPTABLE
+        );
+        assert_eq!(access("/users/7", None).await.0, StatusCode::BAD_REQUEST);
+        // The version in the path requires the `v` prefix.
+        assert_eq!(access("/1/users/7", None).await.0, StatusCode::BAD_REQUEST);
+        assert_eq!(access("/v2/posts", None).await.0, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_version_header() {
+        let sources = [VersionSource::Header(HeaderName::from_static(
+            "x-api-version",
+        ))];
+        let router = Router::new()
+            .push(
+                Router::new()
+                    .filter(VersionFilter::new(1).sources(sources.clone()))
+                    .get(users),
+            )
+            .push(
+                Router::new()
+                    .filter(VersionFilter::new(2).sources(sources.clone()))
+                    .get(users_v2),
+            )
+            .fallback(UnknownVersion::new([1, 2]).sources(sources));
+        let service = Service::new(router);
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/")
+            .add_header("x-api-version", "v2", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.take_string().await.unwrap(), "v2 ");
+        let res = TestClient::get("http://127.0.0.1:5801/")
+            .add_header("x-api-version", "9", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::BAD_REQUEST));
+    }
+}
@@ -305,6 +305,33 @@
 //! }
 //! ```
 //!
+//! A param is optional when its name ends with `?`, such as `<page?>` or `<page?:num>`, the router below matches both
+//! `/items` and `/items/3`, and `req.param::<u32>("page")` is `None` for `/items`:
+//!
+//! ```rust
+//! use salvo_core::prelude::*;
+//!
+//! # #[handler] fn list_items() {}
+//! Router::with_path("items/<page?:num>").get(list_items);
+//! ```
+//!
+//! Optional params must be whole segments at the end of the path, so a path has only one way to match. They can not
+//! be followed by normal params or wildcards like `<**rest>`, which can already match nothing. Note that
+//! `items/<page?>` also matches `/items/edit` with `page` set to `edit`, so add the `items/edit` router before it.
+//!
+//! A param can be restricted with a regex in the path, such as `<id:/\d+/>` which must match the whole segment, or with
+//! [`Router::constrain`] which checks the captured param. If the param does not match, the next router is tried:
+//!
+//! ```rust
+//! use salvo_core::prelude::*;
+//!
+//! # #[handler] fn show_image() {}
+//! # #[handler] fn download_file() {}
+//! Router::new()
+//!     .push(Router::with_path("files/<name>").constrain("name", r"\.(png|jpg)$").get(show_image))
+//!     .push(Router::with_path("files/<name>").get(download_file));
+//! ```
+//!
 //! ## Method filter
 //!
 //! Filter requests based on the `HTTP` request's `Method`, for example:
@@ -368,11 +395,31 @@
 //!
 //! You only need to register once, and then you can directly match the GUID through the simple writing method as
 //! `<id:guid>`, which simplifies the writing of the code.
+//!
+//! # Inspect routers
+//!
+//! Routers can be printed with `{}` or `{:?}` to show the tree of paths, filters, middlewares and handlers, which is
+//! useful to find out why a request is not matched. [`Router::routes`] lists every route with its full path, so you
+//! can generate a route list or check the routes in tests:
+//!
+//! ```rust
+//! use salvo_core::prelude::*;
+//!
+//! # #[handler] fn show_article() {}
+//! let router = Router::with_path("articles/<id>").get(show_article);
+//! println!("{router}");
+//! for route in router.routes() {
+//!     println!("{} {:?} -> {}", route.path, route.filters, route.goal);
+//! }
+//! ```
 
 pub mod filters;
 pub use filters::*;
+mod normalize_path;
+pub use normalize_path::NormalizePath;
 mod router;
-pub use router::Router;
+pub(crate) use router::DETECTED_METHODS;
+pub use router::{RouteInfo, Router};
 
 use std::borrow::Cow;
 use std::ops::Deref;
@@ -454,6 +501,8 @@ pub struct PathState {
     /// (row, col), row is the index of parts, col is the index of char in the part.
     pub(crate) cursor: (usize, usize),
     pub(crate) params: PathParams,
+    /// Path templates of the passed path filters, each one starts with a slash.
+    pub(crate) matched_path: String,
     pub(crate) end_slash: bool, // For rest match, we want include the last slash.
     pub(crate) once_ended: bool, // Once it has ended, used to determine whether the error code returned is 404 or 405.
 }
@@ -478,6 +527,7 @@ impl PathState {
             parts,
             cursor: (0, 0),
             params: PathParams::new(),
+            matched_path: String::new(),
             end_slash,
             once_ended: false,
         }
@@ -563,6 +613,7 @@ fn decode_url_path_safely(path: &str) -> String {
 pub struct FlowCtrl {
     catching: Option<bool>,
     is_ceased: bool,
+    pub(crate) is_skipped: bool,
     pub(crate) cursor: usize,
     pub(crate) handlers: Vec<Arc<dyn Handler>>,
 }
@@ -574,6 +625,7 @@ impl FlowCtrl {
         FlowCtrl {
             catching: None,
             is_ceased: false,
+            is_skipped: false,
             cursor: 0,
             handlers,
         }
@@ -584,6 +636,17 @@ impl FlowCtrl {
         self.cursor < self.handlers.len() // && !self.handlers.is_empty()
     }
 
+    /// Returns `true` if a handler of type `H` is in the rest handlers, so a middleware can leave
+    /// its work to a nested instance of itself.
+    #[inline]
+    pub fn has_next_of<H: Handler>(&self) -> bool {
+        self.handlers
+            .get(self.cursor..)
+            .unwrap_or_default()
+            .iter()
+            .any(|handler| Handler::type_id(&**handler) == std::any::TypeId::of::<H>())
+    }
+
     /// Call next handler. If get next handler and executed, returns `true``, otherwise returns `false`.
     ///
     /// **NOTE**: If response status code is error or is redirection, all reset handlers will be skipped.
@@ -622,7 +685,8 @@ impl FlowCtrl {
     /// Skip all reset handlers.
     #[inline]
     pub fn skip_rest(&mut self) {
-        self.cursor = self.handlers.len()
+        self.cursor = self.handlers.len();
+        self.is_skipped = true;
     }
 
     /// Check is `FlowCtrl` ceased.
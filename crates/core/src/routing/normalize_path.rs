@@ -0,0 +1,238 @@
+use std::borrow::Cow;
+
+use http::uri::{PathAndQuery, Uri};
+
+use crate::http::{Request, Response, StatusError};
+use crate::{async_trait, Depot, FlowCtrl, Handler};
+
+/// Normalize the request path, or reject suspicious request paths.
+///
+/// The path is normalized like this:
+///
+/// - repeated slashes are collapsed, `/a//b` becomes `/a/b`;
+/// - percent-encoded unreserved characters are decoded, `/%61%2e%2E` becomes `/a..`, other
+///   percent-encoded characters, such as `%2F`, are kept;
+/// - `.` and `..` segments, including encoded ones like `%2e%2e`, are resolved, `/a/./b/../c`
+///   becomes `/a/c`, `..` never goes above the root.
+///
+/// The trailing slash and the query are kept.
+///
+/// With [`NormalizePath::reject`], requests whose path has dot segments, encoded slashes or
+/// backslashes, backslashes or encoded null characters are answered with `400 Bad Request`
+/// instead, repeated slashes are still collapsed.
+///
+/// Use [`Service::normalize_path`] to normalize the path before routing, so routes and path
+/// parameters, including wildcards like `<**rest>` used to serve static files, match the
+/// normalized path. When it is used as a middleware, the route is already matched and path
+/// parameters are already parsed from the original path, only the handlers after it see the
+/// normalized [`Request::uri`]. Rejecting in the middleware still protects the handlers after it.
+///
+/// [`Service::normalize_path`]: crate::Service::normalize_path
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::prelude::*;
+/// use salvo_core::routing::NormalizePath;
+///
+/// # #[handler] async fn files() {}
+/// let router = Router::with_path("files/<**rest>").get(files);
+/// let service = Service::new(router).normalize_path(NormalizePath::new().reject(true));
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct NormalizePath {
+    reject: bool,
+}
+
+impl NormalizePath {
+    /// Create a new `NormalizePath` which rewrites the request paths.
+    #[inline]
+    pub fn new() -> Self {
+        Self { reject: false }
+    }
+
+    /// Sets whether to reject suspicious request paths instead of rewriting, default is `false`.
+    #[inline]
+    pub fn reject(mut self, reject: bool) -> Self {
+        self.reject = reject;
+        self
+    }
+
+    /// Normalize the path of the request, returns `false` if the path is rejected.
+    pub fn normalize(&self, req: &mut Request) -> bool {
+        let Some((path, suspicious)) = normalize(req.uri().path()) else {
+            return true;
+        };
+        if suspicious && self.reject {
+            return false;
+        }
+        let path_and_query = match req.uri().query() {
+            Some(query) => Cow::from(format!("{path}?{query}")),
+            None => Cow::from(path),
+        };
+        let mut parts = req.uri().clone().into_parts();
+        match PathAndQuery::try_from(path_and_query.as_ref()) {
+            Ok(path_and_query) => parts.path_and_query = Some(path_and_query),
+            Err(_) => return false,
+        }
+        match Uri::from_parts(parts) {
+            Ok(uri) => {
+                req.set_uri(uri);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+#[async_trait]
+impl Handler for NormalizePath {
+    async fn handle(
+        &self,
+        req: &mut Request,
+        _depot: &mut Depot,
+        res: &mut Response,
+        ctrl: &mut FlowCtrl,
+    ) {
+        if !self.normalize(req) {
+            res.render(StatusError::bad_request().brief("Invalid request path."));
+            ctrl.skip_rest();
+        }
+    }
+}
+
+/// Returns the normalized path and whether the path is suspicious, or `None` if the path is
+/// already normalized and not suspicious.
+fn normalize(path: &str) -> Option<(String, bool)> {
+    let mut suspicious = path.contains('\\');
+    let mut segments: Vec<String> = Vec::new();
+    for raw in path.split('/').filter(|raw| !raw.is_empty()) {
+        let segment = decode_unreserved(raw);
+        let lower = segment.to_ascii_lowercase();
+        if lower.contains("%2f") || lower.contains("%5c") || lower.contains("%00") {
+            suspicious = true;
+        }
+        match segment.as_str() {
+            "." => suspicious = true,
+            ".." => {
+                suspicious = true;
+                segments.pop();
+            }
+            _ => segments.push(segment),
+        }
+    }
+    let mut normalized = format!("/{}", segments.join("/"));
+    if path.ends_with('/') && !segments.is_empty() {
+        normalized.push('/');
+    }
+    if normalized == path && !suspicious {
+        None
+    } else {
+        Some((normalized, suspicious))
+    }
+}
+
+/// Decode the percent-encoded unreserved characters of a path segment, and uppercase the hex
+/// digits of the other percent-encoded characters.
+fn decode_unreserved(segment: &str) -> String {
+    let bytes = segment.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let byte = std::str::from_utf8(bytes.get(index + 1..index + 3).unwrap_or_default())
+            .ok()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match byte {
+            Some(byte) if bytes[index] == b'%' => {
+                if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+                    decoded.push(byte);
+                } else {
+                    decoded.push(b'%');
+                    decoded.extend(bytes[index + 1..index + 3].to_ascii_uppercase());
+                }
+                index += 3;
+            }
+            _ => {
+                decoded.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use crate::test::ResponseExt;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("/a/b"), None);
+        assert_eq!(normalize("/a/b/"), None);
+        assert_eq!(normalize("/"), None);
+        assert_eq!(normalize("/a//b"), Some(("/a/b".into(), false)));
+        assert_eq!(normalize("//a/b//"), Some(("/a/b/".into(), false)));
+        assert_eq!(normalize("/%61/%7e%2d"), Some(("/a/~-".into(), false)));
+        assert_eq!(normalize("/a%2fb"), Some(("/a%2Fb".into(), true)));
+        assert_eq!(normalize("/a/%2F"), Some(("/a/%2F".into(), true)));
+        assert_eq!(normalize("/a/./b/../c"), Some(("/a/c".into(), true)));
+        assert_eq!(normalize("/a/%2e%2E/b"), Some(("/b".into(), true)));
+        assert_eq!(
+            normalize("/../../etc/passwd"),
+            Some(("/etc/passwd".into(), true))
+        );
+        assert_eq!(normalize("/a/.."), Some(("/".into(), true)));
+        assert_eq!(normalize("/a\\..\\b"), Some(("/a\\..\\b".into(), true)));
+        assert_eq!(normalize("/a/%"), None);
+    }
+
+    #[handler]
+    async fn rest(req: &mut Request) -> String {
+        format!(
+            "{} {}",
+            req.uri().path(),
+            req.param::<String>("rest").unwrap_or_default()
+        )
+    }
+
+    /// Send the request with the raw uri, `TestClient` resolves the dot segments.
+    async fn send(service: &Service, uri: &str) -> Response {
+        let mut req = Request::default();
+        *req.uri_mut() = uri.parse().unwrap();
+        service.handle(req).await
+    }
+
+    #[tokio::test]
+    async fn test_normalize_path() {
+        let router = Router::with_path("files/<**rest>").get(rest);
+        let service = Service::new(router).normalize_path(NormalizePath::new());
+        let mut res = send(&service, "http://127.0.0.1:5801/files//a/../%2e/b?c=1").await;
+        assert_eq!(res.take_string().await.unwrap(), "/files/b b");
+
+        let res = send(&service, "http://127.0.0.1:5801/files/../../secret").await;
+        assert_eq!(res.status_code, Some(StatusCode::NOT_FOUND));
+
+        let router = Router::with_path("files/<**rest>").get(rest);
+        let service = Service::new(router).normalize_path(NormalizePath::new().reject(true));
+        let res = send(&service, "http://127.0.0.1:5801/files/a/%2e%2e/b").await;
+        assert_eq!(res.status_code, Some(StatusCode::BAD_REQUEST));
+        let mut res = send(&service, "http://127.0.0.1:5801/files//a").await;
+        assert_eq!(res.take_string().await.unwrap(), "/files/a a");
+    }
+
+    #[tokio::test]
+    async fn test_normalize_path_hoop() {
+        let router = Router::with_hoop(NormalizePath::new().reject(true))
+            .push(Router::with_path("files/<**rest>").get(rest));
+        let res = send(&Service::new(router), "http://127.0.0.1:5801/files/a/../b").await;
+        assert_eq!(res.status_code, Some(StatusCode::BAD_REQUEST));
+
+        let router = Router::with_hoop(NormalizePath::new())
+            .push(Router::with_path("files/<**rest>").get(rest));
+        let mut res = send(&Service::new(router), "http://127.0.0.1:5801/files/a/../b").await;
+        // The path parameter is parsed before the middleware normalizes the path.
+        assert_eq!(res.take_string().await.unwrap(), "/files/b a/../b");
+    }
+}
@@ -3,11 +3,24 @@ use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
 use super::filters::{self, FnFilter, PathFilter};
-use super::{DetectMatched, Filter, PathState};
+use super::{DetectMatched, Filter, FilterInfo, PathParams, PathState};
+use crate::depot::DepotScope;
 use crate::handler::{Handler, WhenHoop};
 use crate::http::uri::Scheme;
+use crate::http::Method;
 use crate::{Depot, Request};
 
+/// The methods which are detected to collect the allowed methods of a path.
+pub(crate) const DETECTED_METHODS: [Method; 7] = [
+    Method::GET,
+    Method::HEAD,
+    Method::POST,
+    Method::PUT,
+    Method::DELETE,
+    Method::PATCH,
+    Method::OPTIONS,
+];
+
 /// Route request to different handlers.
 ///
 /// View [module level documentation](index.html) for more details.
@@ -23,6 +36,9 @@ pub struct Router {
     pub hoops: Vec<Arc<dyn Handler>>,
     /// The final handler to handle request of current router.
     pub goal: Option<Arc<dyn Handler>>,
+    /// The handler to handle request which passed the filters of current router, but matched
+    /// none of its routes.
+    pub fallback: Option<Arc<dyn Handler>>,
 }
 
 impl Default for Router {
@@ -43,6 +59,7 @@ impl Router {
             filters: Vec::new(),
             hoops: Vec::new(),
             goal: None,
+            fallback: None,
         }
     }
 
@@ -84,6 +101,20 @@ impl Router {
         &self,
         req: &mut Request,
         path_state: &mut PathState,
+    ) -> Option<DetectMatched> {
+        self.detect_with_fallback(req, path_state, &mut None).await
+    }
+
+    /// Detect current router is matched for current request like [`detect`](Router::detect).
+    ///
+    /// If no route matches, `fallback` is set to the fallback of the deepest router whose filters
+    /// are passed, together with the middlewares of it and it's parents and the path params, so
+    /// the filters are not called again to find it.
+    pub(crate) async fn detect_with_fallback(
+        &self,
+        req: &mut Request,
+        path_state: &mut PathState,
+        fallback: &mut Option<(DetectMatched, PathParams)>,
     ) -> Option<DetectMatched> {
         Box::pin(async move {
             for filter in &self.filters {
@@ -91,16 +122,19 @@ impl Router {
                     return None;
                 }
             }
+            let fallback_found = fallback.is_some();
             if !self.routers.is_empty() {
                 let original_cursor = path_state.cursor;
+                let original_matched_len = path_state.matched_path.len();
                 for child in &self.routers {
-                    if let Some(dm) = child.detect(req, path_state).await {
+                    if let Some(dm) = child.detect_with_fallback(req, path_state, fallback).await {
                         return Some(DetectMatched {
                             hoops: [&self.hoops[..], &dm.hoops[..]].concat(),
                             goal: dm.goal.clone(),
                         });
                     } else {
                         path_state.cursor = original_cursor;
+                        path_state.matched_path.truncate(original_matched_len);
                     }
                 }
             }
@@ -113,11 +147,97 @@ impl Router {
                     });
                 }
             }
+            if !fallback_found {
+                if let Some((dm, _)) = fallback {
+                    // The fallback is found in the children.
+                    dm.hoops = [&self.hoops[..], &dm.hoops[..]].concat();
+                } else if let Some(goal) = &self.fallback {
+                    let dm = DetectMatched {
+                        hoops: self.hoops.clone(),
+                        goal: goal.clone(),
+                    };
+                    *fallback = Some((dm, path_state.params.clone()));
+                }
+            }
             None
         })
         .await
     }
 
+    /// Detect the methods which are allowed for the path of the request.
+    ///
+    /// The routes are detected in one pass, the method filters are not called, the methods they
+    /// filter are collected instead. Returns `None` if no route matches the path, or the allowed
+    /// methods with the middlewares of the routers which are not bound to a method.
+    pub(crate) async fn detect_allowed_methods(
+        &self,
+        req: &mut Request,
+    ) -> Option<(Vec<Method>, Vec<Arc<dyn Handler>>)> {
+        let mut path_state = PathState::new(req.uri().path());
+        let mut methods = Vec::new();
+        let hoops = self
+            .collect_allowed_methods(req, &mut path_state, None, &mut methods)
+            .await?;
+        methods.sort_by_key(|method| DETECTED_METHODS.iter().position(|m| m == method));
+        Some((methods, hoops))
+    }
+
+    async fn collect_allowed_methods(
+        &self,
+        req: &mut Request,
+        path_state: &mut PathState,
+        mut method: Option<Method>,
+        methods: &mut Vec<Method>,
+    ) -> Option<Vec<Arc<dyn Handler>>> {
+        Box::pin(async move {
+            for filter in &self.filters {
+                if let FilterInfo::Method(filtered) = filter.describe() {
+                    if method.as_ref().is_some_and(|method| *method != filtered) {
+                        return None;
+                    }
+                    method = Some(filtered);
+                } else if !filter.filter(req, path_state).await {
+                    return None;
+                }
+            }
+            let mut matched = None;
+            let original_cursor = path_state.cursor;
+            let original_matched_len = path_state.matched_path.len();
+            for child in &self.routers {
+                let hoops = child
+                    .collect_allowed_methods(req, path_state, method.clone(), methods)
+                    .await;
+                if matched.is_none() {
+                    matched = hoops;
+                }
+                path_state.cursor = original_cursor;
+                path_state.matched_path.truncate(original_matched_len);
+            }
+            if path_state.is_ended() && self.goal.is_some() {
+                let allowed: Vec<Method> = DETECTED_METHODS
+                    .into_iter()
+                    .filter(|detected| method.as_ref().map_or(true, |method| method == detected))
+                    .collect();
+                if !allowed.is_empty() {
+                    for allowed in allowed {
+                        if !methods.contains(&allowed) {
+                            methods.push(allowed);
+                        }
+                    }
+                    matched.get_or_insert_with(Vec::new);
+                }
+            }
+            // The middlewares of the routers bound to a method are not called.
+            let hoops = if method.is_some() {
+                &[][..]
+            } else {
+                &self.hoops[..]
+            };
+            matched.map(|matched| [hoops, &matched[..]].concat())
+        })
+        .await
+    }
+
     /// Insert a router at the begining of current router, shifting all routers after it to the right.
     #[inline]
     pub fn unshift(mut self, router: Router) -> Self {
@@ -144,6 +264,100 @@ impl Router {
         self
     }
 
+    /// Mount an already built router under `prefix`.
+    ///
+    /// The mounted router keeps its own filters, middlewares and path params, so a feature module
+    /// can build its router without knowing where it will be mounted:
+    ///
+    /// ```
+    /// # use salvo_core::prelude::*;
+    /// #[handler]
+    /// async fn list_users() {}
+    ///
+    /// fn admin_router() -> Router {
+    ///     Router::new().push(Router::with_path("users").get(list_users))
+    /// }
+    /// let router = Router::new().mount("/admin", admin_router());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `prefix` is not in correct format, or if a route of the mounted router is the
+    /// same as a route already in current router, for example both of them handle `GET` on
+    /// `/admin/users`. Routes with [`FnFilter`] or other custom filters are not checked.
+    pub fn mount(self, prefix: impl Into<String>, router: Router) -> Self {
+        let prefix = prefix.into();
+        let mounted = Router::with_path(prefix.clone()).push(router);
+        let existing = self
+            .routes()
+            .iter()
+            .filter_map(RouteInfo::conflict_key)
+            .collect::<Vec<_>>();
+        for route in mounted.routes() {
+            if let Some(key) = route.conflict_key() {
+                if existing.contains(&key) {
+                    panic!(
+                        "route conflict when mounting router at `{prefix}`: `{key}` is already defined"
+                    );
+                }
+            }
+        }
+        self.push(mounted)
+    }
+
+    /// Get all the routes of current router and it's descendants.
+    ///
+    /// A route is a router with goal, together with the filters and middlewares of it and it's
+    /// ancestors. The routes are listed in the order they are tried, so it can be used to print a
+    /// route list or check the routes in tests.
+    pub fn routes(&self) -> Vec<RouteInfo> {
+        let mut routes = Vec::new();
+        self.collect_routes("", &[], &[], &mut routes);
+        routes
+    }
+    fn collect_routes(
+        &self,
+        path: &str,
+        filters: &[FilterInfo],
+        hoops: &[&'static str],
+        routes: &mut Vec<RouteInfo>,
+    ) {
+        let mut path = path.to_owned();
+        let mut filters = filters.to_vec();
+        for filter in &self.filters {
+            match filter.describe() {
+                FilterInfo::Path(value) => {
+                    let value = value.trim_matches('/');
+                    if !value.is_empty() {
+                        path.push('/');
+                        path.push_str(value);
+                    }
+                }
+                info => filters.push(info),
+            }
+        }
+        let hoops = [
+            hoops,
+            &self.hoops.iter().map(|h| h.type_name()).collect::<Vec<_>>(),
+        ]
+        .concat();
+        if let Some(goal) = &self.goal {
+            routes.push(RouteInfo {
+                path: if path.is_empty() {
+                    "/".into()
+                } else {
+                    path.clone()
+                },
+                filters: filters.clone(),
+                hoops: hoops.clone(),
+                goal: goal.type_name(),
+            });
+        }
+        for router in &self.routers {
+            router.collect_routes(&path, &filters, &hoops, routes);
+        }
+    }
+
     /// Add a handler as middleware, it will run the handler in current router or it's descendants
     /// handle the request.
     #[inline]
@@ -185,6 +399,16 @@ impl Router {
         self
     }
 
+    /// Run the hoops and handlers of current router and it's descendants in a new [`Depot`] scope,
+    /// the values they insert into the depot are removed when they finish.
+    ///
+    /// View [`Depot::push_scope`] for more details.
+    #[inline]
+    pub fn depot_scope(mut self) -> Self {
+        self.hoops.insert(0, Arc::new(DepotScope));
+        self
+    }
+
     /// Create a new router and set path filter.
     ///
     /// # Panics
@@ -205,6 +429,32 @@ impl Router {
         self.filter(PathFilter::new(path))
     }
 
+    /// Add a [`ParamFilter`] to current router, the path param `name` captured by the path filters
+    /// should match `regex`, otherwise the next router is tried. The next router is tried too if
+    /// the param is absent, use [`ParamFilter::optional`] with [`Router::filter`] to accept it.
+    ///
+    /// ```
+    /// # use salvo_core::prelude::*;
+    /// # #[handler] async fn show_image() {}
+    /// # #[handler] async fn download_file() {}
+    /// Router::new()
+    ///     .push(Router::with_path("files/<name>").constrain("name", r"\.(png|jpg)$").get(show_image))
+    ///     .push(Router::with_path("files/<name>").get(download_file));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `regex` is not a valid regex.
+    ///
+    /// [`ParamFilter`]: super::filters::ParamFilter
+    #[inline]
+    pub fn constrain(self, name: impl Into<String>, regex: &str) -> Self {
+        let name = name.into();
+        let regex = regex::Regex::new(regex)
+            .unwrap_or_else(|e| panic!("invalid regex for param `{name}`: {e}"));
+        self.filter(filters::param(name, regex))
+    }
+
     /// Create a new router and set filter.
     #[inline]
     pub fn with_filter(filter: impl Filter + Sized) -> Self {
@@ -241,6 +491,32 @@ impl Router {
         self
     }
 
+    /// Sets current router's fallback handler, it handles the requests which passed the filters
+    /// of current router, but matched none of its routes.
+    ///
+    /// The status code is set to `404 Not Found`, or `405 Method Not Allowed` with an `Allow`
+    /// header if the path matched but the method not, before the fallback is called. The
+    /// middlewares of current router and it's parents are called before the fallback, the same as
+    /// the goal.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use salvo_core::prelude::*;
+    ///
+    /// #[handler]
+    /// async fn api_not_found(res: &mut Response) {
+    ///     res.render(Json(serde_json::json!({"error": "not found"})));
+    /// }
+    ///
+    /// let router = Router::new().push(Router::with_path("api").fallback(api_not_found));
+    /// ```
+    #[inline]
+    pub fn fallback<H: Handler>(mut self, fallback: H) -> Self {
+        self.fallback = Some(Arc::new(fallback));
+        self
+    }
+
     /// When you want write router chain, this function will be useful,
     /// You can write your custom logic in FnOnce.
     #[inline]
@@ -253,13 +529,37 @@ impl Router {
 
     /// Add a [`SchemeFilter`] to current router.
     ///
-    /// [`SchemeFilter`]: super::filters::HostFilter
+    /// [`SchemeFilter`]: super::filters::SchemeFilter
     #[inline]
     pub fn scheme(self, scheme: Scheme) -> Self {
         self.filter(filters::scheme(scheme))
     }
 
-    /// Add a [`HostFilter`] to current router.
+    /// Add a [`VersionFilter`] to current router, so it only matches requests for the API version.
+    ///
+    /// The version is read from the path, such as `/v2/users`, or from the `Accept` header, such
+    /// as `application/vnd.myapp.v2+json`. Use [`Router::filter`] with a [`VersionFilter`] to read
+    /// it from other sources.
+    ///
+    /// [`VersionFilter`]: super::filters::VersionFilter
+    #[inline]
+    pub fn version(self, version: u32) -> Self {
+        self.filter(filters::version(version))
+    }
+
+    /// Create a new router and set [`VersionFilter`].
+    ///
+    /// [`VersionFilter`]: super::filters::VersionFilter
+    #[inline]
+    pub fn with_version(version: u32) -> Self {
+        Router::with_filter(filters::version(version))
+    }
+
+    /// Add a [`HostFilter`] to current router, so it only matches requests for the host.
+    ///
+    /// Use `*.example.com` to match the subdomains of `example.com`. Requests for a host which
+    /// matches none of the routers can be handled by the [`fallback`](Router::fallback) of the
+    /// parent router.
     ///
     /// [`HostFilter`]: super::filters::HostFilter
     #[inline]
@@ -348,6 +648,84 @@ impl Router {
     }
 }
 
+/// A route of the router tree, returned by [`Router::routes`].
+#[non_exhaustive]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RouteInfo {
+    /// The path of the route, such as `/users/<id>`, it is `/` if there is no path filter.
+    pub path: String,
+    /// The other filters of the route, such as [`FilterInfo::Method`].
+    pub filters: Vec<FilterInfo>,
+    /// Type names of the middlewares which run before the goal.
+    pub hoops: Vec<&'static str>,
+    /// Type name of the goal.
+    pub goal: &'static str,
+}
+impl RouteInfo {
+    /// The key to compare the routes, path param names are removed so `<id>` and `<uid>` are the
+    /// same route. Returns `None` if the route has custom filters which can not be compared.
+    fn conflict_key(&self) -> Option<String> {
+        let mut filters = Vec::with_capacity(self.filters.len());
+        for filter in &self.filters {
+            match filter {
+                FilterInfo::Method(_)
+                | FilterInfo::Scheme(_)
+                | FilterInfo::Host(_)
+                | FilterInfo::Port(_)
+                | FilterInfo::Param { .. } => filters.push(filter.to_string()),
+                _ => return None,
+            }
+        }
+        filters.sort_unstable();
+        filters.dedup();
+        Some(format!(
+            "{} [{}]",
+            strip_param_names(&self.path),
+            filters.join(",")
+        ))
+    }
+}
+impl fmt::Display for RouteInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.path)?;
+        if !self.filters.is_empty() {
+            let filters = self
+                .filters
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>();
+            write!(f, " [{}]", filters.join(","))?;
+        }
+        write!(f, " -> {}", self.goal)
+    }
+}
+
+/// Remove the names of path params, `<id:num>` becomes `<:num>` and `<**rest>` becomes `<**>`.
+fn strip_param_names(path: &str) -> String {
+    let mut stripped = String::with_capacity(path.len());
+    let mut chars = path.chars().peekable();
+    while let Some(c) = chars.next() {
+        stripped.push(c);
+        if c == '<' {
+            while let Some(&c) = chars.peek() {
+                if c == '*' || c == '+' || c == '?' {
+                    stripped.push(c);
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            while chars
+                .next_if(|c| c.is_alphanumeric() || *c == '_')
+                .is_some()
+            {
+                // Skip the name.
+            }
+        }
+    }
+    stripped
+}
+
 const SYMBOL_DOWN: &str = "│";
 const SYMBOL_TEE: &str = "├";
 const SYMBOL_ELL: &str = "└";
@@ -361,16 +739,14 @@ impl Debug for Router {
                 "!NULL!".clone_into(&mut path);
             } else {
                 for filter in &router.filters {
-                    let info = format!("{filter:?}");
-                    if info.starts_with("path:") {
-                        info.split_once(':')
-                            .expect("`split_once` get `None`")
-                            .1
-                            .clone_into(&mut path)
-                    } else {
-                        let mut parts = info.splitn(2, ':').collect::<Vec<_>>();
-                        if !parts.is_empty() {
-                            others.push(parts.pop().expect("part should exists.").to_owned());
+                    match filter.describe() {
+                        FilterInfo::Path(value) => path = value,
+                        info => {
+                            let info = info.to_string();
+                            others.push(match info.split_once(':') {
+                                Some((_, value)) => value.to_owned(),
+                                None => info,
+                            });
                         }
                     }
                 }
@@ -385,10 +761,20 @@ impl Debug for Router {
                 .as_ref()
                 .map(|goal| format!(" -> {}", goal.type_name()))
                 .unwrap_or_default();
+            let hs = if router.hoops.is_empty() {
+                "".to_owned()
+            } else {
+                let hoops = router
+                    .hoops
+                    .iter()
+                    .map(|h| h.type_name())
+                    .collect::<Vec<_>>();
+                format!(" (hoops: {})", hoops.join(", "))
+            };
             if !others.is_empty() {
-                writeln!(f, "{cp}{path}[{}]{hd}", others.join(","))?;
+                writeln!(f, "{cp}{path}[{}]{hs}{hd}", others.join(","))?;
             } else {
-                writeln!(f, "{cp}{path}{hd}")?;
+                writeln!(f, "{cp}{path}{hs}{hd}")?;
             }
             let routers = router.routers();
             if !routers.is_empty() {
@@ -406,13 +792,21 @@ impl Debug for Router {
         print(f, "", true, self)
     }
 }
+/// Prints the router tree the same as `Debug`, with the path, other filters and middlewares of
+/// every router and the goals.
+impl fmt::Display for Router {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        Debug::fmt(self, f)
+    }
+}
 
 #[cfg(test)]
 mod tests {
-    use super::{PathState, Router};
-    use crate::handler;
-    use crate::test::TestClient;
-    use crate::Response;
+    use super::{FilterInfo, PathState, Router};
+    use crate::http::Method;
+    use crate::prelude::*;
+    use crate::test::{ResponseExt, TestClient};
 
     #[handler]
     async fn fake_handler(_res: &mut Response) {}
@@ -464,6 +858,298 @@ mod tests {
 "#
         );
     }
+    #[test]
+    fn test_router_routes() {
+        #[handler]
+        async fn auth() {}
+        let router = Router::new().get(fake_handler).push(
+            Router::with_path("/users").hoop(auth).push(
+                Router::with_path("<id>")
+                    .get(fake_handler)
+                    .delete(fake_handler),
+            ),
+        );
+        let routes = router.routes();
+        assert_eq!(routes.len(), 3);
+        assert_eq!(routes[0].path, "/");
+        assert!(routes[0].hoops.is_empty());
+        assert_eq!(routes[2].path, "/users/<id>");
+        assert_eq!(routes[2].filters, vec![FilterInfo::Method(Method::DELETE)]);
+        assert_eq!(routes[2].hoops, vec![auth.type_name()]);
+        assert_eq!(
+            routes[1].to_string(),
+            "/users/<id> [method:GET] -> salvo_core::routing::router::tests::fake_handler"
+        );
+        assert!(router.to_string().contains(
+            "──/users (hoops: salvo_core::routing::router::tests::test_router_routes::auth)"
+        ));
+    }
+    #[tokio::test]
+    async fn test_router_constrain() {
+        #[handler]
+        async fn image(req: &mut Request) -> String {
+            format!("image {}", req.param::<String>("name").unwrap_or_default())
+        }
+        #[handler]
+        async fn file(req: &mut Request) -> String {
+            format!("file {}", req.param::<String>("name").unwrap_or_default())
+        }
+        let router = Router::new()
+            .push(
+                Router::with_path("files/<name>")
+                    .constrain("name", r"\.(png|jpg)$")
+                    .get(image),
+            )
+            .push(Router::with_path("files/<name>").get(file));
+        let service = Service::new(router);
+        let access = |name: &'static str| {
+            let service = &service;
+            async move {
+                TestClient::get(format!("http://127.0.0.1:5801/files/{name}"))
+                    .send(service)
+                    .await
+                    .take_string()
+                    .await
+                    .unwrap()
+            }
+        };
+        assert_eq!(access("a.png").await, "image a.png");
+        assert_eq!(access("a.txt").await, "file a.txt");
+    }
+    #[tokio::test]
+    async fn test_router_constrain_absent() {
+        #[handler]
+        async fn image() -> &'static str {
+            "image"
+        }
+        #[handler]
+        async fn other() -> &'static str {
+            "other"
+        }
+        let router = Router::new()
+            .push(
+                Router::with_path("files/<name?>")
+                    .constrain("name", r"\.png$")
+                    .get(image),
+            )
+            .push(
+                Router::with_path("optional/<name?>")
+                    .filter(
+                        super::filters::param("name", regex::Regex::new(r"\.png$").unwrap())
+                            .optional(true),
+                    )
+                    .get(image),
+            )
+            .push(Router::with_path("<**>").get(other));
+        let service = Service::new(router);
+        let access = |path: &'static str| {
+            let service = &service;
+            async move {
+                TestClient::get(format!("http://127.0.0.1:5801/{path}"))
+                    .send(service)
+                    .await
+                    .take_string()
+                    .await
+                    .unwrap()
+            }
+        };
+        assert_eq!(access("files/a.png").await, "image");
+        assert_eq!(access("files").await, "other");
+        assert_eq!(access("optional").await, "image");
+        assert_eq!(access("optional/a.txt").await, "other");
+    }
+    #[tokio::test]
+    async fn test_router_fallback() {
+        #[handler]
+        async fn api_hoop(depot: &mut Depot) {
+            depot.insert("api", true);
+        }
+        #[handler]
+        async fn api_fallback(req: &mut Request, depot: &mut Depot, res: &mut Response) {
+            let api = depot.get::<bool>("api").copied().unwrap_or_default();
+            res.render(format!("api fallback {} {api}", req.uri().path()));
+        }
+        #[handler]
+        async fn page_fallback(res: &mut Response) {
+            res.render(Text::Html("<h1>Not Found</h1>"));
+        }
+        let router = Router::new()
+            .fallback(page_fallback)
+            .push(
+                Router::with_path("api")
+                    .hoop(api_hoop)
+                    .fallback(api_fallback)
+                    .push(Router::with_path("users").get(fake_handler)),
+            )
+            .push(Router::with_path("api/v2/users").get(fake_handler));
+        let service = Service::new(router);
+        let access = |method: &'static str, path: &'static str| {
+            let service = &service;
+            async move {
+                let url = format!("http://127.0.0.1:5801{path}");
+                let req = match method {
+                    "POST" => TestClient::post(url),
+                    _ => TestClient::get(url),
+                };
+                let mut res = req.send(service).await;
+                (res.status_code.unwrap(), res.take_string().await.unwrap())
+            }
+        };
+        assert_eq!(access("GET", "/api/users").await.0, StatusCode::OK);
+        assert_eq!(access("GET", "/api/v2/users").await.0, StatusCode::OK);
+        assert_eq!(
+            access("GET", "/api/articles").await,
+            (
+                StatusCode::NOT_FOUND,
+                "api fallback /api/articles true".into()
+            )
+        );
+        assert_eq!(
+            access("POST", "/api/users").await,
+            (
+                StatusCode::METHOD_NOT_ALLOWED,
+                "api fallback /api/users true".into()
+            )
+        );
+        assert_eq!(
+            access("GET", "/about").await,
+            (StatusCode::NOT_FOUND, "<h1>Not Found</h1>".into())
+        );
+    }
+    #[tokio::test]
+    async fn test_router_host() {
+        #[handler]
+        async fn api() -> &'static str {
+            "api"
+        }
+        #[handler]
+        async fn tenant() -> &'static str {
+            "tenant"
+        }
+        #[handler]
+        async fn unknown_host() -> &'static str {
+            "unknown host"
+        }
+        let router = Router::new()
+            .fallback(unknown_host)
+            .push(Router::with_host("api.example.com").get(api))
+            .push(Router::with_host("*.example.com").get(tenant));
+        let service = Service::new(router);
+        let access = |host: &'static str| {
+            let service = &service;
+            async move {
+                let mut res = TestClient::get(format!("http://{host}/"))
+                    .send(service)
+                    .await;
+                (res.status_code.unwrap(), res.take_string().await.unwrap())
+            }
+        };
+        assert_eq!(
+            access("api.example.com").await,
+            (StatusCode::OK, "api".into())
+        );
+        assert_eq!(access("API.Example.com:8080").await.1, "api");
+        assert_eq!(access("shop.example.com").await.1, "tenant");
+        assert_eq!(access("a.b.example.com").await.1, "tenant");
+        assert_eq!(
+            access("example.com").await,
+            (StatusCode::NOT_FOUND, "unknown host".into())
+        );
+        assert_eq!(access("badexample.com").await.1, "unknown host");
+        assert_eq!(access("[::1]:5801").await.1, "unknown host");
+    }
+    #[tokio::test]
+    async fn test_router_matched_path() {
+        #[handler]
+        async fn matched_path(req: &mut Request) -> String {
+            req.matched_path().unwrap_or_default().to_owned()
+        }
+        let router = Router::new().get(matched_path).push(
+            Router::with_path("/users/")
+                .push(Router::with_path("<id>").push(Router::with_path("emails").get(matched_path)))
+                .push(Router::with_path("<id>/articles/<aid>").get(matched_path)),
+        );
+        let service = Service::new(router);
+        let access = |path: &'static str| {
+            let service = &service;
+            async move {
+                TestClient::get(format!("http://127.0.0.1:5801{path}"))
+                    .send(service)
+                    .await
+                    .take_string()
+                    .await
+                    .unwrap()
+            }
+        };
+        assert_eq!(access("/").await, "/");
+        assert_eq!(access("/users/1/emails").await, "/users/<id>/emails");
+        assert_eq!(
+            access("/users/1/articles/2").await,
+            "/users/<id>/articles/<aid>"
+        );
+    }
+    #[test]
+    #[should_panic(expected = "invalid regex for param `name`")]
+    fn test_router_constrain_invalid() {
+        let _ = Router::with_path("files/<name>").constrain("name", "(png");
+    }
+    #[tokio::test]
+    async fn test_router_mount() {
+        #[handler]
+        async fn admin_hoop(depot: &mut Depot) {
+            depot.insert("admin", true);
+        }
+        #[handler]
+        async fn show_user(req: &mut Request, depot: &mut Depot) -> String {
+            format!(
+                "{}:{}",
+                req.param::<String>("id").unwrap_or_default(),
+                depot.get::<bool>("admin").copied().unwrap_or_default()
+            )
+        }
+        let admin =
+            Router::with_hoop(admin_hoop).push(Router::with_path("users/<id>").get(show_user));
+        let router = Router::new()
+            .push(Router::with_path("users/<id>").get(show_user))
+            .mount("/admin", admin);
+
+        let service = Service::new(router);
+        let content = TestClient::get("http://127.0.0.1:5801/admin/users/12")
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, "12:true");
+        let content = TestClient::get("http://127.0.0.1:5801/users/12")
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, "12:false");
+    }
+    #[test]
+    #[should_panic(expected = "`/admin/users/<> [method:GET]` is already defined")]
+    fn test_router_mount_conflict() {
+        let _ = Router::new()
+            .push(
+                Router::with_path("admin/users/<id>")
+                    .get(fake_handler)
+                    .post(fake_handler),
+            )
+            .mount("admin", Router::with_path("users/<uid>").get(fake_handler));
+    }
+    #[test]
+    fn test_router_mount_no_conflict() {
+        let _ = Router::new()
+            .push(Router::with_path("admin/users/<id>").get(fake_handler))
+            .mount("admin", Router::with_path("users/<id>").post(fake_handler))
+            .mount(
+                "admin",
+                Router::with_path("users/<id:num>").get(fake_handler),
+            );
+    }
     #[tokio::test]
     async fn test_router_detect1() {
         let router =
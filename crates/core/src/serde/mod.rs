@@ -3,9 +3,10 @@ use std::hash::Hash;
 
 pub use serde::de::value::{Error as ValError, MapDeserializer, SeqDeserializer};
 use serde::de::{
-    Deserialize, DeserializeSeed, EnumAccess, Error as DeError, IntoDeserializer, VariantAccess,
-    Visitor,
+    Deserialize, DeserializeSeed, Deserializer, EnumAccess, Error as DeError, IntoDeserializer,
+    MapAccess, VariantAccess, Visitor,
 };
+use serde::forward_to_deserialize_any;
 
 mod request;
 pub use request::from_request;
@@ -27,7 +28,7 @@ where
     let iter = input
         .into_iter()
         .map(|(k, v)| (CowValue(k.into()), CowValue(v.into())));
-    T::deserialize(MapDeserializer::new(iter))
+    T::deserialize(FieldMapDeserializer::new(iter))
 }
 
 #[inline]
@@ -45,7 +46,7 @@ where
             VecValue(v.into_iter().map(|v| CowValue(v.into()))),
         )
     });
-    T::deserialize(MapDeserializer::new(iter))
+    T::deserialize(FieldMapDeserializer::new(iter))
 }
 
 pub(crate) fn from_str_multi_val<'de, I, T, C>(input: I) -> Result<T, ValError>
@@ -67,6 +68,87 @@ where
     T::deserialize(CowValue(input.into()))
 }
 
+/// Map deserializer which adds the field name to value errors, e.g. ``field `page`: invalid digit found in string``.
+struct FieldMapDeserializer<'de, I, D> {
+    iter: I,
+    pending: Option<(Cow<'de, str>, D)>,
+}
+impl<'de, I, D> FieldMapDeserializer<'de, I, D>
+where
+    I: Iterator<Item = (CowValue<'de>, D)>,
+{
+    #[inline]
+    fn new(iter: I) -> Self {
+        Self {
+            iter,
+            pending: None,
+        }
+    }
+}
+
+impl<'de, I, D> MapAccess<'de> for FieldMapDeserializer<'de, I, D>
+where
+    I: Iterator<Item = (CowValue<'de>, D)>,
+    D: IntoDeserializer<'de, ValError>,
+{
+    type Error = ValError;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        match self.iter.next() {
+            Some((key, value)) => {
+                self.pending = Some((key.0.clone(), value));
+                seed.deserialize(key).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<T>(&mut self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let (key, value) = self
+            .pending
+            .take()
+            .ok_or_else(|| DeError::custom("value is missing"))?;
+        seed.deserialize(value.into_deserializer())
+            .map_err(|e| DeError::custom(format_args!("field `{key}`: {e}")))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> Option<usize> {
+        match self.iter.size_hint() {
+            (lower, Some(upper)) if lower == upper => Some(upper),
+            _ => None,
+        }
+    }
+}
+
+impl<'de, I, D> Deserializer<'de> for FieldMapDeserializer<'de, I, D>
+where
+    I: Iterator<Item = (CowValue<'de>, D)>,
+    D: IntoDeserializer<'de, ValError>,
+{
+    type Error = ValError;
+
+    #[inline]
+    fn deserialize_any<T>(self, visitor: T) -> Result<T::Value, Self::Error>
+    where
+        T: Visitor<'de>,
+    {
+        visitor.visit_map(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
 struct ValueEnumAccess<'de>(Cow<'de, str>);
 
 impl<'de> EnumAccess<'de> for ValueEnumAccess<'de> {
@@ -173,4 +255,24 @@ mod tests {
         let user: User = super::from_str_multi_map(map).unwrap();
         assert_eq!(user.id, 42);
     }
+
+    #[test]
+    fn test_de_str_multi_map_error_has_field() {
+        #[derive(Deserialize, Debug)]
+        #[allow(dead_code)]
+        struct Filter {
+            page: u32,
+            keyword: Option<String>,
+        }
+        let mut map = MultiMap::new();
+        map.insert("page", "x");
+        let e = super::from_str_multi_map::<_, Filter, _, _, _>(map).unwrap_err();
+        assert_eq!(e.to_string(), "field `page`: invalid digit found in string");
+
+        let mut data = HashMap::new();
+        data.insert("page", "1");
+        let filter: Filter = super::from_str_map(data).unwrap();
+        assert_eq!(filter.page, 1);
+        assert!(filter.keyword.is_none());
+    }
 }
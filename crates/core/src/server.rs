@@ -4,6 +4,7 @@ use std::io::Result as IoResult;
 #[cfg(feature = "server-handle")]
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
 #[cfg(not(any(feature = "http1", feature = "http2", feature = "quinn")))]
 compile_error!(
@@ -16,16 +17,17 @@ use hyper::server::conn::http1;
 use hyper::server::conn::http2;
 #[cfg(feature = "server-handle")]
 use tokio::{
-    time::Duration,sync::{
+    sync::{
     Notify,
     mpsc::{UnboundedReceiver, UnboundedSender}
 }};
+use tokio::task::JoinHandle;
 #[cfg(feature = "server-handle")]
 use tokio_util::sync::CancellationToken;
 
 #[cfg(feature = "quinn")]
 use crate::conn::quinn;
-use crate::conn::{Accepted, Acceptor, Holding, HttpBuilder};
+use crate::conn::{Accepted, Acceptor, Holding, HttpBuilder, SocketAddr};
 use crate::fuse::{ArcFuseFactory, FuseFactory};
 use crate::http::{HeaderValue, HttpConnection, Version};
 use crate::Service;
@@ -36,11 +38,18 @@ cfg_feature! {
     #[derive(Clone)]
     pub struct ServerHandle {
         tx_cmd: UnboundedSender<ServerCommand>,
+        local_addrs: Arc<Vec<SocketAddr>>,
     }
 }
 
 #[cfg(feature = "server-handle")]
 impl ServerHandle {
+    /// Get the local addresses that the server is bound to, see [`Server::local_addrs`].
+    #[inline]
+    pub fn local_addrs(&self) -> &[SocketAddr] {
+        &self.local_addrs
+    }
+
     /// Force stop server.
     ///
     /// Call this function will stop server immediately.
@@ -85,12 +94,30 @@ impl ServerHandle {
     }
 }
 
+cfg_feature! {
+    #![feature = "server-handle"]
+    /// Summary of a server shutdown, returned by [`Server::run_with_graceful_shutdown`].
+    #[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+    #[non_exhaustive]
+    pub struct ShutdownSummary {
+        /// Number of the connections which were closed before the drain deadline.
+        pub drained: usize,
+        /// Number of the connections which were still alive at the drain deadline and were
+        /// closed forcibly.
+        pub forcibly_closed: usize,
+    }
+}
+
 #[cfg(feature = "server-handle")]
 enum ServerCommand {
     StopForcible,
     StopGraceful(Option<Duration>),
 }
 
+/// Spawns the accept loops of the extra shards, set by [`Server::with_accept_shards`].
+type SpawnShards =
+    Box<dyn FnOnce(Option<ArcFuseFactory>, Serving) -> IoResult<Vec<JoinHandle<()>>> + Send>;
+
 /// HTTP Server.
 ///
 /// A `Server` is created to listen on a port, parse HTTP requests, and hand them off to a [`Service`].
@@ -98,6 +125,7 @@ pub struct Server<A> {
     acceptor: A,
     builder: HttpBuilder,
     fuse_factory: Option<ArcFuseFactory>,
+    spawn_shards: Option<SpawnShards>,
     #[cfg(feature = "server-handle")]
     tx_cmd: UnboundedSender<ServerCommand>,
     #[cfg(feature = "server-handle")]
@@ -130,6 +158,7 @@ impl<A: Acceptor + Send> Server<A> {
             acceptor,
             builder,
             fuse_factory: None,
+            spawn_shards: None,
             #[cfg(feature = "server-handle")]
             tx_cmd,
             #[cfg(feature = "server-handle")]
@@ -146,12 +175,45 @@ impl<A: Acceptor + Send> Server<A> {
         self
     }
 
+    /// Sets the idle timeout of keep-alive connections, by default idle connections are kept open.
+    ///
+    /// After a response is sent, the connection is closed if no new request arrives within the
+    /// timeout. It doesn't limit the time to read a request or to handle it, connections which
+    /// are streaming a response such as SSE are not idle, and connections upgraded to WebSocket
+    /// are exempt. The time before the first request is limited by the accept timeout of the
+    /// listener, such as [`TcpListener::accept_timeout`](crate::conn::TcpListener::accept_timeout).
+    ///
+    /// For HTTP/2 connections, a `GOAWAY` frame is sent when the timeout elapses.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// use salvo_core::prelude::*;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let acceptor = TcpListener::new("0.0.0.0:5800").bind().await;
+    ///     Server::new(acceptor)
+    ///         .idle_timeout(Duration::from_secs(60))
+    ///         .serve(Router::new())
+    ///         .await;
+    /// }
+    /// ```
+    #[inline]
+    pub fn idle_timeout(mut self, timeout: Duration) -> Self {
+        self.builder.idle_timeout = Some(timeout);
+        self
+    }
+
     cfg_feature! {
         #![feature = "server-handle"]
         /// Get a [`ServerHandle`] to stop server.
         pub fn handle(&self) -> ServerHandle {
             ServerHandle {
                 tx_cmd: self.tx_cmd.clone(),
+                local_addrs: Arc::new(self.local_addrs().into_iter().cloned().collect()),
             }
         }
 
@@ -178,6 +240,30 @@ impl<A: Acceptor + Send> Server<A> {
         self.acceptor.holdings()
     }
 
+    /// Get the local addresses that this server is bound to.
+    ///
+    /// They are the addresses assigned by the OS, so the real port can be read back when the
+    /// acceptor is bound to port `0`. Use [`ServerHandle::local_addrs`] once the server is running.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use salvo_core::prelude::*;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let acceptor = TcpListener::new("127.0.0.1:0").bind().await;
+    ///     let server = Server::new(acceptor);
+    ///     let port = server.local_addrs()[0].port().unwrap();
+    ///     println!("listening on port {port}");
+    ///     server.serve(Router::new()).await;
+    /// }
+    /// ```
+    #[inline]
+    pub fn local_addrs(&self) -> Vec<&SocketAddr> {
+        self.acceptor.local_addrs()
+    }
+
     cfg_feature! {
         #![feature = "http1"]
         /// Use this function to set http1 protocol.
@@ -192,6 +278,37 @@ impl<A: Acceptor + Send> Server<A> {
         pub fn http2_mut(&mut self) -> &mut http2::Builder<crate::rt::tokio::TokioExecutor> {
             &mut self.builder.http2
         }
+
+        /// Tunes HTTP/2 for high-bandwidth connections, such as large downloads or benchmarks.
+        ///
+        /// It sets the stream window to 4 MiB, the connection window to 16 MiB, the maximum frame
+        /// size to 256 KiB, the write buffer of each stream to 4 MiB and the maximum concurrent
+        /// streams to 1024. Large windows let a client buffer more data, so use it when the memory
+        /// of the server is not a concern, each setting can still be changed with
+        /// [`http2_mut`](Server::http2_mut) after it.
+        ///
+        /// # Example
+        ///
+        /// ```no_run
+        /// use salvo_core::prelude::*;
+        ///
+        /// #[tokio::main]
+        /// async fn main() {
+        ///     let acceptor = TcpListener::new("0.0.0.0:5800").bind().await;
+        ///     let mut server = Server::new(acceptor).http2_high_throughput();
+        ///     server.http2_mut().max_concurrent_streams(256);
+        ///     server.serve(Router::new()).await;
+        /// }
+        /// ```
+        pub fn http2_high_throughput(mut self) -> Self {
+            self.http2_mut()
+                .initial_stream_window_size(4 * 1024 * 1024)
+                .initial_connection_window_size(16 * 1024 * 1024)
+                .max_frame_size(256 * 1024)
+                .max_send_buf_size(4 * 1024 * 1024)
+                .max_concurrent_streams(1024);
+            self
+        }
     }
 
     cfg_feature! {
@@ -235,70 +352,88 @@ impl<A: Acceptor + Send> Server<A> {
     where
         S: Into<Service> + Send,
     {
-        async{
+        async {
+            self.serve_until_stopped(service).await.map(|_| ())
+        }
+    }
+
+    /// Serve a [`Service`] until `signal` completes, then stop the server gracefully.
+    ///
+    /// When `signal` completes, the server stops accepting new connections and waits for the
+    /// in-flight connections to finish. The connections which are still alive when
+    /// `drain_deadline` elapses are closed forcibly. If `drain_deadline` is `None`, it waits
+    /// until all connections are closed.
+    ///
+    /// Returns a [`ShutdownSummary`] with the number of the drained and forcibly closed connections.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// use salvo_core::prelude::*;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let acceptor = TcpListener::new("127.0.0.1:5800").bind().await;
+    ///     // Usually it is `tokio::signal::ctrl_c` or a `SIGTERM` handler.
+    ///     let signal = async {
+    ///         tokio::time::sleep(Duration::from_secs(60)).await;
+    ///     };
+    ///     let summary = Server::new(acceptor)
+    ///         .run_with_graceful_shutdown(Router::new(), signal, Duration::from_secs(30))
+    ///         .await
+    ///         .unwrap();
+    ///     println!("{} connections were closed forcibly", summary.forcibly_closed);
+    /// }
+    /// ```
+    #[cfg(feature = "server-handle")]
+    pub async fn run_with_graceful_shutdown<S, G>(
+        self,
+        service: S,
+        signal: G,
+        drain_deadline: impl Into<Option<Duration>>,
+    ) -> IoResult<ShutdownSummary>
+    where
+        S: Into<Service> + Send,
+        G: Future<Output = ()> + Send + 'static,
+    {
+        let handle = self.handle();
+        let drain_deadline = drain_deadline.into();
+        let signal = tokio::spawn(async move {
+            signal.await;
+            handle.stop_graceful(drain_deadline);
+        });
+        let summary = self.serve_until_stopped(service).await;
+        signal.abort();
+        summary
+    }
+
+    #[cfg(feature = "server-handle")]
+    #[allow(clippy::manual_async_fn)]
+    fn serve_until_stopped<S>(self, service: S) -> impl Future<Output=IoResult<ShutdownSummary>> + Send
+    where
+        S: Into<Service> + Send,
+    {
+        async move {
             let Self {
                 mut acceptor,
                 builder,
                 fuse_factory,
+                spawn_shards,
                 mut rx_cmd,
                 ..
             } = self;
-            let alive_connections = Arc::new(AtomicUsize::new(0));
-            let notify = Arc::new(Notify::new());
-            let force_stop_token = CancellationToken::new();
-            let graceful_stop_token = CancellationToken::new();
-
-            let mut alt_svc_h3 = None;
-            for holding in acceptor.holdings() {
-                tracing::info!("listening {}", holding);
-                if holding.http_versions.contains(&Version::HTTP_3) {
-                    if let Some(addr) = holding.local_addr.clone().into_std() {
-                        let port = addr.port();
-                        alt_svc_h3 = Some(
-                            format!(r#"h3=":{port}"; ma=2592000,h3-29=":{port}"; ma=2592000"#)
-                                .parse::<HeaderValue>()
-                                .expect("Parse alt-svc header should not failed."),
-                        );
-                    }
-                }
-            }
-
-            let service: Arc<Service> = Arc::new(service.into());
-            let builder = Arc::new(builder);
+            let serving = Serving::new(service.into(), builder, &acceptor);
+            let shards = match spawn_shards {
+                Some(spawn_shards) => spawn_shards(fuse_factory.clone(), serving.clone())?,
+                None => Vec::new(),
+            };
             loop {
                 tokio::select! {
                     accepted = acceptor.accept(fuse_factory.clone()) => {
                         match accepted {
-                            Ok(Accepted { conn, local_addr, remote_addr, http_scheme, ..}) => {
-                                alive_connections.fetch_add(1, Ordering::Release);
-
-                                let service = service.clone();
-                                let alive_connections = alive_connections.clone();
-                                let notify = notify.clone();
-                                let handler = service.hyper_handler(local_addr, remote_addr, http_scheme, conn.fusewire(), alt_svc_h3.clone());
-                                let builder = builder.clone();
-
-                                let force_stop_token = force_stop_token.clone();
-                                let graceful_stop_token = graceful_stop_token.clone();
-
-                                tokio::spawn(async move {
-                                    let conn = conn.serve(handler, builder, Some(graceful_stop_token.clone()));
-                                    tokio::select! {
-                                        _ = conn => {
-                                        },
-                                        _ = force_stop_token.cancelled() => {
-                                        }
-                                    }
-
-                                    if alive_connections.fetch_sub(1, Ordering::Acquire) == 1 {
-                                        // notify only if shutdown is initiated, to prevent notification when server is active.
-                                        // It's a valid state to have 0 alive connections when server is not shutting down.
-                                        if graceful_stop_token.is_cancelled() {
-                                            notify.notify_one();
-                                        }
-                                    }
-                                });
-                            },
+                            Ok(accepted) => serving.serve_connection(accepted),
                             Err(e) => {
                                 tracing::error!(error = ?e, "accept connection failed");
                             }
@@ -307,7 +442,7 @@ impl<A: Acceptor + Send> Server<A> {
                     Some(cmd) = rx_cmd.recv() => {
                         match cmd {
                             ServerCommand::StopGraceful(timeout) => {
-                                let graceful_stop_token = graceful_stop_token.clone();
+                                let graceful_stop_token = serving.graceful_stop_token.clone();
                                 graceful_stop_token.cancel();
                                 if let Some(timeout) = timeout {
                                     tracing::info!(
@@ -315,7 +450,7 @@ impl<A: Acceptor + Send> Server<A> {
                                         "initiate graceful stop server",
                                     );
 
-                                    let force_stop_token = force_stop_token.clone();
+                                    let force_stop_token = serving.force_stop_token.clone();
                                     tokio::spawn(async move {
                                         tokio::time::sleep(timeout).await;
                                         force_stop_token.cancel();
@@ -326,21 +461,41 @@ impl<A: Acceptor + Send> Server<A> {
                             },
                             ServerCommand::StopForcible => {
                                 tracing::info!("force stop server");
-                                force_stop_token.cancel();
+                                serving.force_stop_token.cancel();
                             },
                         }
                         break;
                     },
                 }
             }
+            // Stop accepting new connections, the already accepted ones keep being served.
+            for shard in shards {
+                let _ = shard.await;
+            }
+            acceptor.shutdown().await;
 
-            if !force_stop_token.is_cancelled() && alive_connections.load(Ordering::Acquire) > 0 {
-                tracing::info!("wait for {} connections to close.",alive_connections.load(Ordering::Acquire));
-                notify.notified().await;
+            let alive = serving.alive_connections.load(Ordering::Acquire);
+            if serving.force_stop_token.is_cancelled() {
+                tracing::info!("server stopped");
+                return Ok(ShutdownSummary {
+                    drained: 0,
+                    forcibly_closed: alive,
+                });
+            }
+            if alive > 0 {
+                tracing::info!("wait for {} connections to close.", alive);
+                serving.notify.notified().await;
             }
 
+            let forcibly_closed = serving.forcibly_closed.load(Ordering::Acquire);
+            if forcibly_closed > 0 {
+                tracing::warn!(forcibly_closed, "connections closed forcibly at the drain deadline");
+            }
             tracing::info!("server stopped");
-            Ok(())
+            Ok(ShutdownSummary {
+                drained: alive.saturating_sub(forcibly_closed),
+                forcibly_closed,
+            })
         }
     }
     /// Try to serve a [`Service`].
@@ -353,8 +508,44 @@ impl<A: Acceptor + Send> Server<A> {
             mut acceptor,
             builder,
             fuse_factory,
+            spawn_shards,
             ..
         } = self;
+        let serving = Serving::new(service.into(), builder, &acceptor);
+        if let Some(spawn_shards) = spawn_shards {
+            spawn_shards(fuse_factory.clone(), serving.clone())?;
+        }
+        loop {
+            match acceptor.accept(fuse_factory.clone()).await {
+                Ok(accepted) => serving.serve_connection(accepted),
+                Err(e) => {
+                    tracing::error!(error = ?e, "accept connection failed");
+                }
+            }
+        }
+    }
+}
+
+/// State shared by the accept loops of a server, so the connections accepted by all the
+/// shards are counted together.
+#[derive(Clone)]
+struct Serving {
+    service: Arc<Service>,
+    builder: Arc<HttpBuilder>,
+    alt_svc_h3: Option<HeaderValue>,
+    #[cfg(feature = "server-handle")]
+    alive_connections: Arc<AtomicUsize>,
+    #[cfg(feature = "server-handle")]
+    forcibly_closed: Arc<AtomicUsize>,
+    #[cfg(feature = "server-handle")]
+    notify: Arc<Notify>,
+    #[cfg(feature = "server-handle")]
+    force_stop_token: CancellationToken,
+    #[cfg(feature = "server-handle")]
+    graceful_stop_token: CancellationToken,
+}
+impl Serving {
+    fn new(service: Service, builder: HttpBuilder, acceptor: &impl Acceptor) -> Self {
         let mut alt_svc_h3 = None;
         for holding in acceptor.holdings() {
             tracing::info!("listening {}", holding);
@@ -369,36 +560,214 @@ impl<A: Acceptor + Send> Server<A> {
                 }
             }
         }
+        Self {
+            service: Arc::new(service),
+            builder: Arc::new(builder),
+            alt_svc_h3,
+            #[cfg(feature = "server-handle")]
+            alive_connections: Arc::new(AtomicUsize::new(0)),
+            #[cfg(feature = "server-handle")]
+            forcibly_closed: Arc::new(AtomicUsize::new(0)),
+            #[cfg(feature = "server-handle")]
+            notify: Arc::new(Notify::new()),
+            #[cfg(feature = "server-handle")]
+            force_stop_token: CancellationToken::new(),
+            #[cfg(feature = "server-handle")]
+            graceful_stop_token: CancellationToken::new(),
+        }
+    }
 
-        let service: Arc<Service> = Arc::new(service.into());
-        let builder = Arc::new(builder);
-        loop {
-            match acceptor.accept(fuse_factory.clone()).await {
-                Ok(Accepted { conn, local_addr, remote_addr, http_scheme, ..}) => {
+    fn serve_connection<C>(&self, accepted: Accepted<C>)
+    where
+        C: HttpConnection + Send + 'static,
+    {
+        let Accepted { conn, local_addr, remote_addr, http_scheme, ..} = accepted;
+        let handler = self.service.hyper_handler(local_addr, remote_addr, http_scheme, conn.fusewire(), self.alt_svc_h3.clone());
+        let builder = self.builder.clone();
 
-                    let service = service.clone();
-                    let handler = service.hyper_handler(local_addr, remote_addr, http_scheme, conn.fusewire(), alt_svc_h3.clone());
-                    let builder = builder.clone();
+        #[cfg(not(feature = "server-handle"))]
+        tokio::spawn(async move {
+            let _ = conn.serve(handler, builder, None).await;
+        });
 
-                    tokio::spawn(async move {
-                        let _ = conn.serve(handler, builder, None).await;
-                    });
-                },
-                Err(e) => {
-                    tracing::error!(error = ?e, "accept connection failed");
+        #[cfg(feature = "server-handle")]
+        {
+            self.alive_connections.fetch_add(1, Ordering::Release);
+
+            let alive_connections = self.alive_connections.clone();
+            let forcibly_closed = self.forcibly_closed.clone();
+            let notify = self.notify.clone();
+            let force_stop_token = self.force_stop_token.clone();
+            let graceful_stop_token = self.graceful_stop_token.clone();
+
+            tokio::spawn(async move {
+                let conn = conn.serve(handler, builder, Some(graceful_stop_token.clone()));
+                tokio::select! {
+                    _ = conn => {
+                    },
+                    _ = force_stop_token.cancelled() => {
+                        forcibly_closed.fetch_add(1, Ordering::Release);
+                    }
                 }
+
+                if alive_connections.fetch_sub(1, Ordering::Acquire) == 1 {
+                    // notify only if shutdown is initiated, to prevent notification when server is active.
+                    // It's a valid state to have 0 alive connections when server is not shutting down.
+                    if graceful_stop_token.is_cancelled() {
+                        notify.notify_one();
+                    }
+                }
+            });
+        }
+    }
+}
+
+impl<A: Acceptor + Send + 'static> Server<A> {
+    /// Sets the number of accept loops, default is `1`.
+    ///
+    /// [`Acceptor::try_shard`] is called `shards - 1` times to bind other sockets to the same
+    /// addresses, when the server starts each one is served by its own accept loop on the tokio
+    /// runtime, so accepting connections is spread over the worker threads. For
+    /// [`TcpListener`](crate::conn::TcpListener) this requires the `socket2` feature and
+    /// [`reuse_port(true)`](crate::conn::TcpListener::reuse_port), the kernel then distributes the
+    /// incoming connections between the `SO_REUSEPORT` sockets. If the acceptor can't be sharded,
+    /// [`try_serve`](Server::try_serve) returns the error.
+    ///
+    /// The shards share the state of the server and of the acceptor:
+    ///
+    /// - The [`max_connections`](crate::conn::TcpListener::max_connections) limit and
+    ///   [`TcpAcceptor::alive_connections`](crate::conn::TcpAcceptor::alive_connections) count the
+    ///   connections of all the shards.
+    /// - Stopping the server with a [`ServerHandle`] or [`run_with_graceful_shutdown`](Server::run_with_graceful_shutdown)
+    ///   stops all the accept loops, waits for the connections of all the shards and counts them
+    ///   in the [`ShutdownSummary`].
+    ///
+    /// # CPU pinning
+    ///
+    /// Pinning the accept loops to CPUs is not supported, the tokio multi-thread runtime moves
+    /// tasks between its worker threads, so a pinned accept loop would not stay on its CPU. Pin
+    /// the worker threads instead, with [`on_thread_start`](tokio::runtime::Builder::on_thread_start)
+    /// when building the runtime.
+    ///
+    /// # Example
+    ///
+    /// With the `socket2` feature:
+    ///
+    /// ```ignore
+    /// use salvo_core::prelude::*;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let acceptor = TcpListener::new("0.0.0.0:5800").reuse_port(true).bind().await;
+    ///     let shards = std::thread::available_parallelism().map_or(1, |n| n.get());
+    ///     Server::new(acceptor)
+    ///         .with_accept_shards(shards)
+    ///         .serve(Router::new())
+    ///         .await;
+    /// }
+    /// ```
+    pub fn with_accept_shards(mut self, shards: usize) -> Self {
+        if shards <= 1 {
+            self.spawn_shards = None;
+            return self;
+        }
+        let shards = (1..shards)
+            .map(|_| self.acceptor.try_shard())
+            .collect::<IoResult<Vec<_>>>();
+        self.spawn_shards = Some(Box::new(move |fuse_factory, serving| {
+            Ok(shards?
+                .into_iter()
+                .map(|shard| {
+                    tokio::spawn(accept_loop(shard, fuse_factory.clone(), serving.clone()))
+                })
+                .collect())
+        }));
+        self
+    }
+}
+
+cfg_feature! {
+    #![feature = "acme"]
+    impl Server<crate::conn::acme::AcmeAcceptor<crate::conn::tcp::TcpAcceptor>> {
+        /// Create new `Server` which listens on `0.0.0.0:443` and serves HTTPS with the
+        /// certificates issued by Let's Encrypt for `domains`.
+        ///
+        /// The certificates are issued with the TLS-ALPN-01 challenge and renewed before they are
+        /// expired, `email` is used as the contact of the ACME account. They are cached in the
+        /// `acme/letsencrypt` directory under the working directory, so they are reused after a
+        /// restart. Use [`TcpListener::acme`](crate::conn::TcpListener::acme) to set another cache
+        /// path, the HTTP-01 challenge or another directory.
+        ///
+        /// # Example
+        ///
+        /// ```no_run
+        /// use salvo_core::prelude::*;
+        ///
+        /// #[tokio::main]
+        /// async fn main() {
+        ///     let server = Server::bind_acme(["example.com".to_owned()], "admin@example.com").await;
+        ///     server.serve(Router::new()).await;
+        /// }
+        /// ```
+        pub async fn bind_acme(domains: impl IntoIterator<Item = String>, email: impl Into<String>) -> Self {
+            use crate::conn::Listener;
+
+            let email = email.into();
+            let contact = if email.starts_with("mailto:") {
+                email
+            } else {
+                format!("mailto:{email}")
+            };
+            let acceptor = crate::conn::TcpListener::new("0.0.0.0:443")
+                .acme()
+                .domains(domains.into_iter().collect::<Vec<_>>())
+                .add_contact(contact)
+                .cache_path("acme/letsencrypt")
+                .bind()
+                .await;
+            Self::new(acceptor)
+        }
+    }
+}
+
+/// Accept loop of an extra shard, it stops accepting when the server is stopped.
+async fn accept_loop<A>(mut acceptor: A, fuse_factory: Option<ArcFuseFactory>, serving: Serving)
+where
+    A: Acceptor,
+{
+    loop {
+        #[cfg(feature = "server-handle")]
+        let accepted = tokio::select! {
+            accepted = acceptor.accept(fuse_factory.clone()) => accepted,
+            _ = serving.graceful_stop_token.cancelled() => break,
+            _ = serving.force_stop_token.cancelled() => break,
+        };
+        #[cfg(not(feature = "server-handle"))]
+        let accepted = acceptor.accept(fuse_factory.clone()).await;
+        match accepted {
+            Ok(accepted) => serving.serve_connection(accepted),
+            Err(e) => {
+                tracing::error!(error = ?e, "accept connection failed");
             }
         }
     }
+    #[cfg(feature = "server-handle")]
+    acceptor.shutdown().await;
 }
 
 #[cfg(test)]
 mod tests {
     use serde::Serialize;
 
+    use crate::conn::Acceptor;
     use crate::prelude::*;
     use crate::test::{ResponseExt, TestClient};
 
+    /// Address of the first listener of the acceptor, the tests bind to ephemeral ports.
+    fn local_addr(acceptor: &impl Acceptor) -> std::net::SocketAddr {
+        acceptor.holdings()[0].local_addr.clone().into_std().unwrap()
+    }
+
     #[tokio::test]
     async fn test_server() {
         #[handler]
@@ -466,6 +835,437 @@ mod tests {
         assert!(result.contains("<code>404</code>"));
     }
 
+    #[cfg(all(feature = "http1", feature = "http2"))]
+    #[tokio::test]
+    async fn test_serve_http1_and_h2c_on_same_port() {
+        use http_body_util::{BodyExt, Empty};
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio::net::TcpStream;
+
+        use crate::rt::tokio::{TokioExecutor, TokioIo};
+
+        #[handler]
+        async fn version(req: &mut Request) -> String {
+            format!("{:?}", req.version())
+        }
+        let acceptor = TcpListener::new("127.0.0.1:0").bind().await;
+        let addr = local_addr(&acceptor);
+        tokio::spawn(Server::new(acceptor).serve(Router::new().get(version)));
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"));
+        assert!(response.ends_with("HTTP/1.1"));
+
+        // The server answers the h2c preface with an HTTP/2 SETTINGS frame.
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream.write_all(b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n").await.unwrap();
+        stream.write_all(&[0, 0, 0, 0x4, 0, 0, 0, 0, 0]).await.unwrap();
+        let mut frame_header = [0u8; 9];
+        stream.read_exact(&mut frame_header).await.unwrap();
+        assert_eq!(frame_header[3], 0x4);
+
+        let stream = TcpStream::connect(addr).await.unwrap();
+        let (mut sender, conn) = hyper::client::conn::http2::handshake(TokioExecutor::new(), TokioIo::new(stream))
+            .await
+            .unwrap();
+        tokio::spawn(conn);
+        let request = hyper::Request::builder()
+            .uri(format!("http://{addr}/"))
+            .body(Empty::<bytes::Bytes>::new())
+            .unwrap();
+        let response = sender.send_request(request).await.unwrap();
+        assert_eq!(response.version(), crate::http::Version::HTTP_2);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body, "HTTP/2.0");
+    }
+
+    #[cfg(feature = "http1")]
+    #[tokio::test]
+    async fn test_expect_continue() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio::net::TcpStream;
+
+        #[handler]
+        async fn auth(req: &mut Request, res: &mut Response, ctrl: &mut FlowCtrl) {
+            if req.expects_continue() && req.header::<String>("authorization").is_none() {
+                req.reject_continue();
+                res.render(StatusError::unauthorized());
+                ctrl.skip_rest();
+            }
+        }
+        #[handler]
+        async fn upload(req: &mut Request) -> String {
+            req.payload().await.unwrap().len().to_string()
+        }
+        let acceptor = TcpListener::new("127.0.0.1:0").bind().await;
+        let addr = local_addr(&acceptor);
+        tokio::spawn(Server::new(acceptor).serve(Router::with_hoop(auth).post(upload)));
+
+        let head = |authorization: &str| {
+            format!("POST / HTTP/1.1\r\nHost: localhost\r\nContent-Length: 5\r\nExpect: 100-continue\r\n{authorization}\r\n")
+        };
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(head("Authorization: Bearer token\r\nConnection: close\r\n").as_bytes())
+            .await
+            .unwrap();
+        let mut interim = [0u8; 25];
+        stream.read_exact(&mut interim).await.unwrap();
+        assert_eq!(&interim, b"HTTP/1.1 100 Continue\r\n\r\n");
+        stream.write_all(b"hello").await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"));
+        assert!(response.ends_with("\r\n\r\n5"));
+
+        // The final response is sent without `100 Continue`, and the connection is closed.
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream.write_all(head("").as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 401"));
+        assert!(!response.contains("100 Continue"));
+
+        let mut req = Request::default();
+        assert!(!req.expects_continue());
+        req.headers_mut().insert("expect", "100-Continue".parse().unwrap());
+        assert!(req.expects_continue());
+    }
+
+    #[cfg(feature = "http1")]
+    #[tokio::test]
+    async fn test_connect_tunnel() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio::net::TcpStream;
+
+        use crate::http::tunnel::accept_tunnel;
+
+        #[handler]
+        async fn tunnel_to(req: &mut Request, res: &mut Response) -> Result<(), StatusError> {
+            let authority = req.uri().authority().ok_or_else(StatusError::bad_request)?.to_string();
+            let mut target = TcpStream::connect(authority)
+                .await
+                .map_err(|_| StatusError::bad_gateway())?;
+            accept_tunnel(req, res, move |mut tunnel| async move {
+                let _ = tokio::io::copy_bidirectional(&mut tunnel, &mut target).await;
+            })
+        }
+        let echo = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let echo_addr = echo.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut stream, _) = echo.accept().await.unwrap();
+            let (mut reader, mut writer) = stream.split();
+            let _ = tokio::io::copy(&mut reader, &mut writer).await;
+        });
+        let acceptor = TcpListener::new("127.0.0.1:0").bind().await;
+        let addr = local_addr(&acceptor);
+        tokio::spawn(Server::new(acceptor).serve(Service::new(Router::new()).connect(tunnel_to)));
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(format!("CONNECT {echo_addr} HTTP/1.1\r\nHost: {echo_addr}\r\n\r\n").as_bytes())
+            .await
+            .unwrap();
+        let mut head = Vec::new();
+        while !head.ends_with(b"\r\n\r\n") {
+            head.push(stream.read_u8().await.unwrap());
+        }
+        assert!(head.starts_with(b"HTTP/1.1 200"));
+        stream.write_all(b"ping").await.unwrap();
+        let mut echoed = [0u8; 4];
+        stream.read_exact(&mut echoed).await.unwrap();
+        assert_eq!(&echoed, b"ping");
+    }
+
+    #[cfg(feature = "http1")]
+    #[tokio::test]
+    async fn test_http1_header_limits() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio::net::TcpStream;
+
+        use crate::conn::HttpBuilder;
+
+        static HANDLED: AtomicUsize = AtomicUsize::new(0);
+        #[handler]
+        async fn hello() -> &'static str {
+            HANDLED.fetch_add(1, Ordering::SeqCst);
+            "Hello World"
+        }
+        let acceptor = TcpListener::new("127.0.0.1:0").bind().await;
+        let addr = local_addr(&acceptor);
+        let builder = HttpBuilder::new().http1_max_headers(8).http1_max_header_size(8192);
+        tokio::spawn(Server::with_http_builder(acceptor, builder).serve(Router::new().get(hello)));
+
+        let access = |headers: String| async move {
+            let mut stream = TcpStream::connect(addr).await.unwrap();
+            stream
+                .write_all(format!("GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n{headers}\r\n").as_bytes())
+                .await
+                .unwrap();
+            let mut response = String::new();
+            let _ = stream.read_to_string(&mut response).await;
+            response
+        };
+        assert!(access("x-a: 1\r\n".into()).await.starts_with("HTTP/1.1 200"));
+        let many = (0..10).map(|i| format!("x-{i}: {i}\r\n")).collect::<String>();
+        assert!(access(many).await.starts_with("HTTP/1.1 431"));
+        let large = format!("x-large: {}\r\n", "a".repeat(10_000));
+        assert!(access(large).await.starts_with("HTTP/1.1 431"));
+        assert_eq!(HANDLED.load(Ordering::SeqCst), 1);
+    }
+
+    #[cfg(all(feature = "http1", feature = "http2"))]
+    #[tokio::test]
+    async fn test_http2_settings() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio::net::TcpStream;
+
+        let acceptor = TcpListener::new("127.0.0.1:0").bind().await;
+        let addr = local_addr(&acceptor);
+        let mut server = Server::new(acceptor).http2_high_throughput();
+        server
+            .http2_mut()
+            .max_concurrent_streams(64)
+            .max_header_list_size(8192);
+        tokio::spawn(server.serve(Router::new()));
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream.write_all(b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n").await.unwrap();
+        stream.write_all(&[0, 0, 0, 0x4, 0, 0, 0, 0, 0]).await.unwrap();
+        let mut frame_header = [0u8; 9];
+        stream.read_exact(&mut frame_header).await.unwrap();
+        assert_eq!(frame_header[3], 0x4);
+        let len = u32::from_be_bytes([0, frame_header[0], frame_header[1], frame_header[2]]) as usize;
+        let mut payload = vec![0u8; len];
+        stream.read_exact(&mut payload).await.unwrap();
+        let settings = payload
+            .chunks(6)
+            .map(|setting| {
+                let id = u16::from_be_bytes([setting[0], setting[1]]);
+                let value = u32::from_be_bytes([setting[2], setting[3], setting[4], setting[5]]);
+                (id, value)
+            })
+            .collect::<std::collections::HashMap<_, _>>();
+        // SETTINGS_MAX_CONCURRENT_STREAMS, SETTINGS_INITIAL_WINDOW_SIZE, SETTINGS_MAX_FRAME_SIZE
+        // and SETTINGS_MAX_HEADER_LIST_SIZE.
+        assert_eq!(settings.get(&0x3), Some(&64));
+        assert_eq!(settings.get(&0x4), Some(&(4 * 1024 * 1024)));
+        assert_eq!(settings.get(&0x5), Some(&(256 * 1024)));
+        assert_eq!(settings.get(&0x6), Some(&8192));
+    }
+
+    #[cfg(feature = "http1")]
+    #[tokio::test]
+    async fn test_request_local_addr_of_joined_listeners() {
+        use std::sync::atomic::{AtomicU16, Ordering};
+
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio::net::TcpStream;
+
+        static ADMIN_PORT: AtomicU16 = AtomicU16::new(0);
+        #[handler]
+        async fn admin(req: &mut Request, res: &mut Response) {
+            if req.local_addr().port() == Some(ADMIN_PORT.load(Ordering::SeqCst)) {
+                res.render("admin");
+            } else {
+                res.status_code(StatusCode::FORBIDDEN);
+            }
+        }
+        let acceptor = TcpListener::new("127.0.0.1:0")
+            .join(TcpListener::new("127.0.0.1:0"))
+            .bind()
+            .await;
+        let public_addr = acceptor.holdings()[0].local_addr.clone().into_std().unwrap();
+        let admin_addr = acceptor.holdings()[1].local_addr.clone().into_std().unwrap();
+        ADMIN_PORT.store(admin_addr.port(), Ordering::SeqCst);
+        tokio::spawn(Server::new(acceptor).serve(Router::new().get(admin)));
+
+        for (addr, status) in [(public_addr, "403"), (admin_addr, "200")] {
+            let mut stream = TcpStream::connect(addr).await.unwrap();
+            stream
+                .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+                .await
+                .unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).await.unwrap();
+            assert!(response.starts_with(&format!("HTTP/1.1 {status}")));
+        }
+    }
+
+    #[cfg(all(feature = "http1", feature = "server-handle"))]
+    #[tokio::test]
+    async fn test_local_addrs_of_ephemeral_port() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio::net::TcpStream;
+
+        #[handler]
+        async fn hello() -> &'static str {
+            "Hello World"
+        }
+        let acceptor = TcpListener::new("127.0.0.1:0").bind().await;
+        let server = Server::new(acceptor);
+        let handle = server.handle();
+        let port = server.local_addrs()[0].port().unwrap();
+        assert_ne!(port, 0);
+        assert_eq!(handle.local_addrs()[0].port(), Some(port));
+        tokio::spawn(server.serve(Router::new().get(hello)));
+
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.ends_with("Hello World"));
+        handle.stop_forcible();
+    }
+
+    #[cfg(all(feature = "http1", feature = "server-handle", feature = "socket2", target_os = "linux"))]
+    #[tokio::test]
+    async fn test_accept_shards() {
+        use std::time::Duration;
+
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio::net::TcpStream;
+
+        #[handler]
+        async fn slow(req: &mut Request) -> &'static str {
+            let millis = req.query::<u64>("millis").unwrap_or_default();
+            tokio::time::sleep(Duration::from_millis(millis)).await;
+            "done"
+        }
+        let acceptor = TcpListener::new("127.0.0.1:0").reuse_port(true).bind().await;
+        let addr = local_addr(&acceptor);
+        let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn(Server::new(acceptor).with_accept_shards(4).run_with_graceful_shutdown(
+            Router::new().get(slow),
+            async move {
+                let _ = rx.await;
+            },
+            Duration::from_secs(2),
+        ));
+
+        let request = |millis: u64| async move {
+            let mut stream = TcpStream::connect(addr).await.unwrap();
+            stream
+                .write_all(format!("GET /?millis={millis} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n").as_bytes())
+                .await
+                .unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).await.unwrap();
+            response
+        };
+        for _ in 0..16 {
+            assert!(request(0).await.ends_with("done"));
+        }
+
+        // The connections of all the shards are drained.
+        let slow_requests = (0..8).map(|_| tokio::spawn(request(300))).collect::<Vec<_>>();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        tx.send(()).unwrap();
+        for slow_request in slow_requests {
+            assert!(slow_request.await.unwrap().ends_with("done"));
+        }
+        let summary = server.await.unwrap().unwrap();
+        assert_eq!((summary.drained, summary.forcibly_closed), (8, 0));
+        assert!(TcpStream::connect(addr).await.is_err());
+    }
+
+    #[cfg(feature = "http1")]
+    #[tokio::test]
+    async fn test_idle_timeout() {
+        use std::time::Duration;
+
+        use http_body_util::{BodyExt, Empty};
+        use tokio::net::TcpStream;
+
+        use crate::rt::tokio::TokioIo;
+
+        #[handler]
+        async fn slow(req: &mut Request) -> &'static str {
+            let millis = req.query::<u64>("millis").unwrap_or_default();
+            tokio::time::sleep(Duration::from_millis(millis)).await;
+            "done"
+        }
+        let acceptor = TcpListener::new("127.0.0.1:0").bind().await;
+        let addr = local_addr(&acceptor);
+        tokio::spawn(
+            Server::new(acceptor)
+                .idle_timeout(Duration::from_millis(200))
+                .serve(Router::new().get(slow)),
+        );
+
+        let stream = TcpStream::connect(addr).await.unwrap();
+        let (mut sender, conn) = hyper::client::conn::http1::handshake(TokioIo::new(stream)).await.unwrap();
+        let conn = tokio::spawn(conn);
+        // A request which takes longer than the idle timeout, then requests within the timeout.
+        for (millis, pause) in [(400, 0), (0, 100), (0, 100), (0, 100)] {
+            tokio::time::sleep(Duration::from_millis(pause)).await;
+            let request = hyper::Request::builder()
+                .uri(format!("http://{addr}/?millis={millis}"))
+                .body(Empty::<bytes::Bytes>::new())
+                .unwrap();
+            let response = sender.send_request(request).await.unwrap();
+            let body = response.into_body().collect().await.unwrap().to_bytes();
+            assert_eq!(body, "done");
+        }
+        assert!(!conn.is_finished());
+
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        assert!(conn.is_finished());
+    }
+
+    #[cfg(all(feature = "http1", feature = "server-handle"))]
+    #[tokio::test]
+    async fn test_run_with_graceful_shutdown() {
+        use std::time::Duration;
+
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio::net::TcpStream;
+
+        #[handler]
+        async fn slow(req: &mut Request) -> &'static str {
+            let millis = req.query::<u64>("millis").unwrap_or_default();
+            tokio::time::sleep(Duration::from_millis(millis)).await;
+            "done"
+        }
+        for (millis, drain_deadline, expected) in [(300, 2000, (1, 0)), (3000, 200, (0, 1))] {
+            let acceptor = TcpListener::new("127.0.0.1:0").bind().await;
+            let addr = local_addr(&acceptor);
+            let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+            let server = tokio::spawn(Server::new(acceptor).run_with_graceful_shutdown(
+                Router::new().get(slow),
+                async move {
+                    let _ = rx.await;
+                },
+                Duration::from_millis(drain_deadline),
+            ));
+
+            let mut stream = TcpStream::connect(addr).await.unwrap();
+            stream
+                .write_all(format!("GET /?millis={millis} HTTP/1.1\r\nHost: localhost\r\n\r\n").as_bytes())
+                .await
+                .unwrap();
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            tx.send(()).unwrap();
+
+            let mut response = String::new();
+            let _ = stream.read_to_string(&mut response).await;
+            let summary = server.await.unwrap().unwrap();
+            assert_eq!((summary.drained, summary.forcibly_closed), expected);
+            assert_eq!(response.ends_with("done"), expected.0 == 1);
+        }
+    }
+
     #[test]
     fn test_regression_209() {
         #[cfg(feature = "acme")]
@@ -3,18 +3,18 @@ use std::pin::Pin;
 use std::sync::Arc;
 
 use headers::HeaderValue;
-use http::header::{ALT_SVC, CONTENT_TYPE};
+use http::header::{ALLOW, ALT_SVC, CONTENT_LENGTH, CONTENT_TYPE};
 use http::uri::Scheme;
 use hyper::service::Service as HyperService;
 use hyper::{Method, Request as HyperRequest, Response as HyperResponse};
 
 use crate::catcher::{write_error_default, Catcher};
-use crate::conn::SocketAddr;
+use crate::conn::{SocketAddr, TlsInfoSlot};
 use crate::fuse::ArcFusewire;
 use crate::handler::{Handler, WhenHoop};
 use crate::http::body::{ReqBody, ResBody};
 use crate::http::{Mime, Request, Response, StatusCode};
-use crate::routing::{FlowCtrl, PathState, Router};
+use crate::routing::{DetectMatched, FlowCtrl, NormalizePath, PathState, Router, DETECTED_METHODS};
 use crate::{async_trait, Depot};
 
 /// Service http request.
@@ -28,6 +28,16 @@ pub struct Service {
     pub hoops: Vec<Arc<dyn Handler>>,
     /// The allowed media types of this service.
     pub allowed_media_types: Arc<Vec<Mime>>,
+    /// Whether `HEAD` requests are answered by `GET` routes, default is `true`.
+    pub auto_head: bool,
+    /// Whether `OPTIONS` requests are answered with the allowed methods, default is `true`.
+    pub auto_options: bool,
+    /// Normalize the request path before routing.
+    pub normalize_path: Option<NormalizePath>,
+    /// Whether `TRACE` requests are routed, default is `true`.
+    pub allow_trace: bool,
+    /// The handler of `CONNECT` requests, they are routed if it's `None`.
+    pub connect: Option<Arc<dyn Handler>>,
 }
 
 impl Service {
@@ -42,6 +52,11 @@ impl Service {
             catcher: None,
             hoops: vec![],
             allowed_media_types: Arc::new(vec![]),
+            auto_head: true,
+            auto_options: true,
+            normalize_path: None,
+            allow_trace: true,
+            connect: None,
         }
     }
 
@@ -123,6 +138,130 @@ impl Service {
         self
     }
 
+    /// Sets whether `HEAD` requests are answered by `GET` routes, default is `true`.
+    ///
+    /// When it is enabled and no route matches a `HEAD` request, the request is routed as a `GET`
+    /// request, the handler is called with the `HEAD` method and the body it writes is dropped.
+    /// The `Content-Length` header is kept, or set from the body if its size is known. A route
+    /// added with [`Router::head`] still takes precedence, so handlers which need custom `HEAD`
+    /// behavior can be registered for it.
+    ///
+    /// The body of responses to `HEAD` requests is always dropped, even if it is disabled.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use salvo_core::prelude::*;
+    /// let service = Service::new(Router::new()).auto_head(false);
+    /// ```
+    #[inline]
+    pub fn auto_head(mut self, auto_head: bool) -> Self {
+        self.auto_head = auto_head;
+        self
+    }
+
+    /// Sets whether `OPTIONS` requests are answered with the allowed methods, default is `true`.
+    ///
+    /// When it is enabled and no route matches an `OPTIONS` request for a path which has routes
+    /// for other methods, `204 No Content` is sent with an `Allow` header listing the methods of
+    /// the path. The hoops of the path are still called before, so a CORS middleware in them
+    /// answers preflight requests itself. The precedence is:
+    ///
+    /// 1. A route added with [`Router::options`].
+    /// 2. The hoops of the path, such as a CORS middleware which skips the rest of the handlers.
+    /// 3. The automatic response.
+    ///
+    /// Only the hoops of the routers which don't filter the method are called, the hoops added
+    /// to the routes of a method are not.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use salvo_core::prelude::*;
+    /// let service = Service::new(Router::new()).auto_options(false);
+    /// ```
+    #[inline]
+    pub fn auto_options(mut self, auto_options: bool) -> Self {
+        self.auto_options = auto_options;
+        self
+    }
+
+    /// Sets the [`NormalizePath`] which normalizes the request path before routing, the request is
+    /// answered with `400 Bad Request` if the path is rejected.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use salvo_core::prelude::*;
+    /// use salvo_core::routing::NormalizePath;
+    ///
+    /// let service = Service::new(Router::new()).normalize_path(NormalizePath::new());
+    /// ```
+    #[inline]
+    pub fn normalize_path(mut self, normalize_path: NormalizePath) -> Self {
+        self.normalize_path = Some(normalize_path);
+        self
+    }
+
+    /// Answers all `TRACE` requests with `405 Method Not Allowed` without routing them, even if a
+    /// route is added for them. `TRACE` echoes the request back, disabling it is a common
+    /// hardening measure.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use salvo_core::prelude::*;
+    /// let service = Service::new(Router::new()).disable_trace();
+    /// ```
+    #[inline]
+    pub fn disable_trace(mut self) -> Self {
+        self.allow_trace = false;
+        self
+    }
+
+    /// Sets the handler of `CONNECT` requests, which are not routed then, since their target is an
+    /// authority such as `example.com:443` rather than a path. The hoops of the service are still
+    /// called before it.
+    ///
+    /// The handler establishes a tunnel with [`accept_tunnel`]:
+    ///
+    /// 1. The handler reads the target from `req.uri().authority()` and connects to it, it renders
+    ///    an error such as `502 Bad Gateway` if it fails, then the client knows that there is no
+    ///    tunnel.
+    /// 2. [`accept_tunnel`] sets the status code to `200 OK` and spawns the callback.
+    /// 3. Once the response is sent, hyper hands the connection over and the callback is called
+    ///    with the [`Tunnel`], it copies the bytes between the tunnel and the target until one of
+    ///    them is closed.
+    ///
+    /// [`accept_tunnel`]: crate::http::tunnel::accept_tunnel
+    /// [`Tunnel`]: crate::http::tunnel::Tunnel
+    ///
+    /// # Example
+    ///
+    /// A forward proxy for HTTPS, which doesn't restrict the targets:
+    ///
+    /// ```
+    /// use salvo_core::http::tunnel::accept_tunnel;
+    /// use salvo_core::prelude::*;
+    /// use tokio::net::TcpStream;
+    ///
+    /// #[handler]
+    /// async fn tunnel_to(req: &mut Request, res: &mut Response) -> Result<(), StatusError> {
+    ///     let authority = req.uri().authority().ok_or_else(StatusError::bad_request)?.to_string();
+    ///     let mut target = TcpStream::connect(authority).await.map_err(|_| StatusError::bad_gateway())?;
+    ///     accept_tunnel(req, res, move |mut tunnel| async move {
+    ///         let _ = tokio::io::copy_bidirectional(&mut tunnel, &mut target).await;
+    ///     })
+    /// }
+    ///
+    /// let service = Service::new(Router::new()).connect(tunnel_to);
+    /// ```
+    #[inline]
+    pub fn connect<H: Handler>(mut self, handler: H) -> Self {
+        self.connect = Some(Arc::new(handler));
+        self
+    }
+
     #[doc(hidden)]
     #[inline]
     pub fn hyper_handler(
@@ -141,6 +280,12 @@ impl Service {
             catcher: self.catcher.clone(),
             hoops: self.hoops.clone(),
             allowed_media_types: self.allowed_media_types.clone(),
+            auto_head: self.auto_head,
+            auto_options: self.auto_options,
+            normalize_path: self.normalize_path,
+            allow_trace: self.allow_trace,
+            connect: self.connect.clone(),
+            tls_info: None,
             fusewire,
             alt_svc_h3,
         }
@@ -189,6 +334,40 @@ impl Handler for DefaultStatusOK {
     }
 }
 
+/// Answers `OPTIONS` requests with the allowed methods of the path.
+struct AllowedMethods(HeaderValue);
+#[async_trait]
+impl Handler for AllowedMethods {
+    async fn handle(
+        &self,
+        _req: &mut Request,
+        _depot: &mut Depot,
+        res: &mut Response,
+        _ctrl: &mut FlowCtrl,
+    ) {
+        res.status_code(StatusCode::NO_CONTENT);
+        res.headers_mut().insert(ALLOW, self.0.clone());
+    }
+}
+
+/// Build the `Allow` header from the methods detected for a path, adding the methods which are
+/// answered automatically.
+fn allow_header(mut methods: Vec<Method>, auto_head: bool, auto_options: bool) -> HeaderValue {
+    if auto_head && methods.contains(&Method::GET) && !methods.contains(&Method::HEAD) {
+        methods.push(Method::HEAD);
+    }
+    if auto_options && !methods.contains(&Method::OPTIONS) {
+        methods.push(Method::OPTIONS);
+    }
+    let allow = DETECTED_METHODS
+        .iter()
+        .filter(|method| methods.contains(method))
+        .map(Method::as_str)
+        .collect::<Vec<_>>()
+        .join(", ");
+    HeaderValue::from_str(&allow).expect("methods should be valid header value")
+}
+
 #[doc(hidden)]
 #[derive(Clone)]
 pub struct HyperHandler {
@@ -199,6 +378,12 @@ pub struct HyperHandler {
     pub(crate) catcher: Option<Arc<Catcher>>,
     pub(crate) hoops: Vec<Arc<dyn Handler>>,
     pub(crate) allowed_media_types: Arc<Vec<Mime>>,
+    pub(crate) auto_head: bool,
+    pub(crate) auto_options: bool,
+    pub(crate) normalize_path: Option<NormalizePath>,
+    pub(crate) allow_trace: bool,
+    pub(crate) connect: Option<Arc<dyn Handler>>,
+    pub(crate) tls_info: Option<TlsInfoSlot>,
     pub(crate) fusewire: Option<ArcFusewire>,
     pub(crate) alt_svc_h3: Option<HeaderValue>,
 }
@@ -209,6 +394,10 @@ impl HyperHandler {
         let allowed_media_types = self.allowed_media_types.clone();
         req.local_addr = self.local_addr.clone();
         req.remote_addr = self.remote_addr.clone();
+        req.tls_info = self
+            .tls_info
+            .as_ref()
+            .and_then(|tls_info| tls_info.get().cloned());
         #[cfg(not(feature = "cookie"))]
         let mut res = Response::new();
         #[cfg(feature = "cookie")]
@@ -219,13 +408,76 @@ impl HyperHandler {
             }
         }
         let mut depot = Depot::new();
+        let rejected = self
+            .normalize_path
+            .is_some_and(|normalize_path| !normalize_path.normalize(&mut req));
+        let trace_disabled = !self.allow_trace && req.method() == Method::TRACE;
+        let connect = self
+            .connect
+            .clone()
+            .filter(|_| req.method() == Method::CONNECT);
         let mut path_state = PathState::new(req.uri().path());
         let router = self.router.clone();
+        let auto_head = self.auto_head;
+        let auto_options = self.auto_options;
 
         let hoops = self.hoops.clone();
         async move {
-            if let Some(dm) = router.detect(&mut req, &mut path_state).await {
+            let mut fallback = None;
+            let mut matched = if rejected || trace_disabled {
+                None
+            } else if let Some(connect) = connect {
+                Some(DetectMatched {
+                    hoops: vec![],
+                    goal: connect,
+                })
+            } else {
+                router
+                    .detect_with_fallback(&mut req, &mut path_state, &mut fallback)
+                    .await
+            };
+            if matched.is_none() && auto_head && req.method() == Method::HEAD {
+                // Route `HEAD` as `GET`, the handler still sees the `HEAD` method.
+                let mut get_state = PathState::new(req.uri().path());
+                *req.method_mut() = Method::GET;
+                matched = router.detect(&mut req, &mut get_state).await;
+                *req.method_mut() = Method::HEAD;
+                if matched.is_some() {
+                    path_state = get_state;
+                }
+            }
+            // The path is matched by routes of other methods, so it is `405 Method Not Allowed`
+            // rather than `404 Not Found`.
+            let mut method_not_allowed = false;
+            if matched.is_none() && path_state.once_ended {
+                if let Some((methods, hoops)) = router.detect_allowed_methods(&mut req).await {
+                    let allow = allow_header(methods, auto_head, auto_options);
+                    if auto_options && req.method() == Method::OPTIONS {
+                        matched = Some(DetectMatched {
+                            hoops,
+                            goal: Arc::new(AllowedMethods(allow)),
+                        });
+                    } else {
+                        method_not_allowed = true;
+                        res.headers_mut().insert(ALLOW, allow);
+                    }
+                }
+            }
+            if rejected {
+                res.status_code = Some(StatusCode::BAD_REQUEST);
+            } else if trace_disabled {
+                if let Some((methods, _)) = router.detect_allowed_methods(&mut req).await {
+                    res.headers_mut()
+                        .insert(ALLOW, allow_header(methods, auto_head, auto_options));
+                }
+                res.status_code = Some(StatusCode::METHOD_NOT_ALLOWED);
+            } else if let Some(dm) = matched {
                 req.params = path_state.params;
+                req.matched_path = Some(if path_state.matched_path.is_empty() {
+                    "/".into()
+                } else {
+                    path_state.matched_path
+                });
                 // Set default status code before service hoops executed.
                 // We hope all hoops in service can get the correct status code.
                 let mut ctrl = FlowCtrl::new(
@@ -242,11 +494,24 @@ impl HyperHandler {
                 if res.status_code.is_none() {
                     res.status_code = Some(StatusCode::OK);
                 }
+            } else if let Some((dm, params)) = fallback {
+                req.params = params;
+                let status_code = if method_not_allowed {
+                    StatusCode::METHOD_NOT_ALLOWED
+                } else {
+                    StatusCode::NOT_FOUND
+                };
+                res.status_code = Some(status_code);
+                let mut ctrl = FlowCtrl::new([&hoops[..], &dm.hoops[..], &[dm.goal]].concat());
+                ctrl.call_next(&mut req, &mut depot, &mut res).await;
+                if res.status_code.is_none() {
+                    res.status_code = Some(status_code);
+                }
             } else if !hoops.is_empty() {
                 req.params = path_state.params;
                 // Set default status code before service hoops executed.
                 // We hope all hoops in service can get the correct status code.
-                if path_state.once_ended {
+                if method_not_allowed {
                     res.status_code = Some(StatusCode::METHOD_NOT_ALLOWED);
                 } else {
                     res.status_code = Some(StatusCode::NOT_FOUND);
@@ -254,10 +519,10 @@ impl HyperHandler {
                 let mut ctrl = FlowCtrl::new(hoops);
                 ctrl.call_next(&mut req, &mut depot, &mut res).await;
                 // Set it to default status code again if any hoop set status code to None.
-                if res.status_code.is_none() && path_state.once_ended {
+                if res.status_code.is_none() && method_not_allowed {
                     res.status_code = Some(StatusCode::METHOD_NOT_ALLOWED);
                 }
-            } else if path_state.once_ended {
+            } else if method_not_allowed {
                 res.status_code = Some(StatusCode::METHOD_NOT_ALLOWED);
             }
 
@@ -311,9 +576,14 @@ impl HyperHandler {
                     write_error_default(&req, &mut res, None);
                 }
             }
-            #[cfg(debug_assertions)]
             if Method::HEAD == *req.method() && !res.body.is_none() {
-                tracing::warn!("request with head method should not have body: https://developer.mozilla.org/en-US/docs/Web/HTTP/Methods/HEAD");
+                // https://developer.mozilla.org/en-US/docs/Web/HTTP/Methods/HEAD
+                if !res.headers().contains_key(CONTENT_LENGTH) {
+                    if let Some(size) = res.body.size() {
+                        res.headers_mut().insert(CONTENT_LENGTH, size.into());
+                    }
+                }
+                res.body = ResBody::None;
             }
             #[cfg(feature = "quinn")]
             {
@@ -368,8 +638,9 @@ where
             .cloned()
             .unwrap_or_else(|| self.http_scheme.clone());
         // https://github.com/hyperium/hyper/issues/1310
+        // The target of `CONNECT` requests is an authority without a path, it's kept as it is.
         #[cfg(feature = "fix-http1-request-uri")]
-        if req.uri().scheme().is_none() {
+        if req.uri().scheme().is_none() && req.uri().authority().is_none() {
             if let Some(host) = req
                 .headers()
                 .get(http::header::HOST)
@@ -485,7 +756,8 @@ mod tests {
                 Router::with_path("login")
                     .post(login)
                     .push(Router::with_path("user").get(login)),
-            );
+            )
+            .push(Router::with_path("api").push(Router::with_path("users").get(hello)));
         let service = Service::new(router);
 
         let res = TestClient::get("http://127.0.0.1:5801/hello")
@@ -506,6 +778,7 @@ mod tests {
             .send(&service)
             .await;
         assert_eq!(res.status_code.unwrap(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(res.headers()["allow"], "POST, OPTIONS");
 
         let res = TestClient::get("http://127.0.0.1:5801/login2")
             .send(&service)
@@ -521,10 +794,160 @@ mod tests {
             .send(&service)
             .await;
         assert_eq!(res.status_code.unwrap(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(res.headers()["allow"], "GET, HEAD, OPTIONS");
 
         let res = TestClient::post("http://127.0.0.1:5801/login/user1")
             .send(&service)
             .await;
         assert_eq!(res.status_code.unwrap(), StatusCode::NOT_FOUND);
+
+        // The router of `/api` has no handler for any method.
+        let res = TestClient::get("http://127.0.0.1:5801/api")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code.unwrap(), StatusCode::NOT_FOUND);
+        assert!(res.headers().get("allow").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_service_auto_head() {
+        #[handler]
+        async fn hello(req: &mut Request) -> String {
+            format!("hello {}", req.method())
+        }
+        #[handler]
+        async fn custom_head(res: &mut Response) {
+            res.add_header("x-custom", "1", true).unwrap();
+        }
+        let router = std::sync::Arc::new(
+            Router::new()
+                .push(Router::with_path("hello").get(hello))
+                .push(Router::with_path("custom").get(hello).head(custom_head)),
+        );
+
+        let service = Service::new(router.clone());
+        let mut res = TestClient::head("http://127.0.0.1:5801/hello")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code.unwrap(), StatusCode::OK);
+        assert_eq!(res.headers()["content-length"], "10");
+        assert!(res.take_string().await.unwrap().is_empty());
+
+        let res = TestClient::head("http://127.0.0.1:5801/custom")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code.unwrap(), StatusCode::OK);
+        assert_eq!(res.headers()["x-custom"], "1");
+
+        let service = Service::new(router).auto_head(false);
+        let res = TestClient::head("http://127.0.0.1:5801/hello")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code.unwrap(), StatusCode::METHOD_NOT_ALLOWED);
+    }
+
+    #[tokio::test]
+    async fn test_service_auto_options() {
+        #[handler]
+        async fn hello() -> &'static str {
+            "hello"
+        }
+        #[handler]
+        async fn preflight(res: &mut Response) {
+            res.add_header("x-preflight", "1", true).unwrap();
+        }
+        let router = std::sync::Arc::new(
+            Router::new()
+                .push(Router::with_path("users").get(hello).post(hello))
+                .push(Router::with_path("custom").get(hello).options(hello))
+                .push(Router::with_path("cors").hoop(preflight).delete(hello))
+                .push(
+                    Router::with_path("bound").push(
+                        Router::with_filter(crate::routing::filters::get())
+                            .hoop(preflight)
+                            .goal(hello),
+                    ),
+                ),
+        );
+
+        let service = Service::new(router.clone());
+        let mut res = TestClient::options("http://127.0.0.1:5801/users")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code.unwrap(), StatusCode::NO_CONTENT);
+        assert_eq!(res.headers()["allow"], "GET, HEAD, POST, OPTIONS");
+        assert!(res.take_string().await.unwrap().is_empty());
+
+        let mut res = TestClient::options("http://127.0.0.1:5801/custom")
+            .send(&service)
+            .await;
+        assert_eq!(res.take_string().await.unwrap(), "hello");
+
+        let res = TestClient::options("http://127.0.0.1:5801/cors")
+            .send(&service)
+            .await;
+        assert_eq!(res.headers()["x-preflight"], "1");
+        assert_eq!(res.headers()["allow"], "DELETE, OPTIONS");
+
+        let res = TestClient::options("http://127.0.0.1:5801/bound")
+            .send(&service)
+            .await;
+        assert!(!res.headers().contains_key("x-preflight"));
+        assert_eq!(res.headers()["allow"], "GET, HEAD, OPTIONS");
+
+        let res = TestClient::options("http://127.0.0.1:5801/unknown")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code.unwrap(), StatusCode::NOT_FOUND);
+
+        let service = Service::new(router).auto_options(false);
+        let res = TestClient::options("http://127.0.0.1:5801/users")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code.unwrap(), StatusCode::METHOD_NOT_ALLOWED);
+    }
+
+    #[tokio::test]
+    async fn test_service_disable_trace_and_connect() {
+        #[handler]
+        async fn hello() -> &'static str {
+            "hello"
+        }
+        #[handler]
+        async fn tunnel(req: &mut Request, res: &mut Response) -> Result<(), StatusError> {
+            crate::http::tunnel::accept_tunnel(req, res, |_| async {})
+        }
+        let router = std::sync::Arc::new(Router::new().push(Router::with_path("any").goal(hello)));
+
+        let service = Service::new(router.clone());
+        let mut res = TestClient::trace("http://127.0.0.1:5801/any")
+            .send(&service)
+            .await;
+        assert_eq!(res.take_string().await.unwrap(), "hello");
+
+        let service = Service::new(router.clone()).disable_trace();
+        let res = TestClient::trace("http://127.0.0.1:5801/any")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code.unwrap(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(
+            res.headers()["allow"],
+            "GET, HEAD, POST, PUT, DELETE, PATCH, OPTIONS"
+        );
+        let mut res = TestClient::get("http://127.0.0.1:5801/any")
+            .send(&service)
+            .await;
+        assert_eq!(res.take_string().await.unwrap(), "hello");
+
+        // `CONNECT` requests are not routed, and the requests of `TestClient` can't be upgraded.
+        let service = Service::new(router).connect(tunnel);
+        let req = Request::from_hyper(
+            hyper::Request::connect("http://127.0.0.1:5801/any")
+                .body(crate::http::ReqBody::None)
+                .unwrap(),
+            http::uri::Scheme::HTTP,
+        );
+        let res = service.handle(req).await;
+        assert_eq!(res.status_code.unwrap(), StatusCode::BAD_REQUEST);
     }
 }
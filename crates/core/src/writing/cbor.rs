@@ -0,0 +1,109 @@
+use std::fmt::{self, Debug, Display, Formatter};
+
+use async_trait::async_trait;
+use serde::Serialize;
+
+use super::{try_set_header, Scribe};
+use crate::http::header::{HeaderValue, CONTENT_TYPE};
+use crate::http::{Response, StatusError};
+
+/// Write serializable content to response as CBOR content.
+///
+/// It will set `content-type` to `application/cbor`.
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::prelude::*;
+/// use salvo_core::writing::Cbor;
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct User {
+///    name: String,
+/// }
+/// #[handler]
+/// async fn hello(res: &mut Response) -> Cbor<User> {
+///     Cbor(User { name: "jobs".into() })
+/// }
+/// ```
+pub struct Cbor<T>(pub T);
+
+#[async_trait]
+impl<T> Scribe for Cbor<T>
+where
+    T: Serialize + Send,
+{
+    fn render(self, res: &mut Response) {
+        match to_cbor_vec(&self.0) {
+            Ok(bytes) => {
+                try_set_header(
+                    &mut res.headers,
+                    CONTENT_TYPE,
+                    HeaderValue::from_static("application/cbor"),
+                );
+                let _ = res.write_body(bytes);
+            }
+            Err(e) => {
+                tracing::error!(error = ?e, "CborContent write error");
+                res.render(StatusError::internal_server_error());
+            }
+        }
+    }
+}
+pub(crate) fn to_cbor_vec<T: Serialize + ?Sized>(
+    value: &T,
+) -> Result<Vec<u8>, ciborium::ser::Error<std::io::Error>> {
+    let mut bytes = Vec::new();
+    ciborium::into_writer(value, &mut bytes)?;
+    Ok(bytes)
+}
+impl<T: Debug> Debug for Cbor<T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_tuple("Cbor").field(&self.0).finish()
+    }
+}
+impl<T: Display> Display for Cbor<T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    use super::*;
+    use crate::test::{ResponseExt, TestClient};
+
+    #[tokio::test]
+    async fn test_write_cbor_content() {
+        #[derive(Serialize, Debug)]
+        struct User {
+            name: String,
+        }
+        #[handler]
+        async fn test() -> Cbor<User> {
+            Cbor(User {
+                name: "jobs".into(),
+            })
+        }
+
+        let router = Router::new().push(Router::with_path("test").get(test));
+        let mut res = TestClient::get("http://127.0.0.1:5800/test")
+            .send(router)
+            .await;
+        assert_eq!(
+            res.headers().get("content-type").unwrap(),
+            "application/cbor"
+        );
+        let body = res.take_bytes(None).await.unwrap();
+        assert_eq!(
+            &body[..],
+            to_cbor_vec(&User {
+                name: "jobs".into()
+            })
+            .unwrap()
+        );
+    }
+}
@@ -0,0 +1,105 @@
+use std::path::Path;
+
+use minijinja::{path_loader, Environment};
+use parking_lot::RwLock;
+
+use super::TemplateEngine;
+use crate::BoxedError;
+
+/// [`TemplateEngine`] which renders the templates with [MiniJinja](https://docs.rs/minijinja).
+///
+/// The templates are loaded from the directory when they are used, named by their paths
+/// relative to the directory, such as `partials/nav.html`. The loaded templates are cleared
+/// before every render in debug builds, so the changes are applied without a restart.
+///
+/// # Example
+///
+/// ```no_run
+/// use salvo_core::prelude::*;
+/// use salvo_core::writing::{MiniJinjaEngine, Templates};
+///
+/// let router = Router::new().hoop(Templates::new(MiniJinjaEngine::new("templates")));
+/// ```
+pub struct MiniJinjaEngine {
+    env: RwLock<Environment<'static>>,
+    reload: bool,
+}
+impl MiniJinjaEngine {
+    /// Create a new `MiniJinjaEngine` which loads the templates in `dir`.
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        let mut env = Environment::new();
+        env.set_loader(path_loader(dir));
+        Self::with_env(env)
+    }
+
+    /// Create a new `MiniJinjaEngine` with a configured `Environment`.
+    #[inline]
+    pub fn with_env(env: Environment<'static>) -> Self {
+        Self {
+            env: RwLock::new(env),
+            reload: cfg!(debug_assertions),
+        }
+    }
+
+    /// Sets whether the templates are reloaded before every render, default is `true` in debug
+    /// builds.
+    #[inline]
+    pub fn reload(mut self, reload: bool) -> Self {
+        self.reload = reload;
+        self
+    }
+
+    /// Get the mutable reference of the `Environment`, to add filters, functions or globals.
+    #[inline]
+    pub fn env_mut(&mut self) -> &mut Environment<'static> {
+        self.env.get_mut()
+    }
+}
+
+impl TemplateEngine for MiniJinjaEngine {
+    fn render(&self, name: &str, context: &serde_json::Value) -> Result<String, BoxedError> {
+        if self.reload {
+            self.env.write().clear_templates();
+        }
+        let env = self.env.read();
+        Ok(env.get_template(name)?.render(context)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_minijinja_engine() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("partials")).unwrap();
+        std::fs::write(
+            dir.path().join("base.html"),
+            "<p>{% include \"partials/nav.html\" %}{% block content %}{% endblock %}</p>",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("partials/nav.html"), "<nav></nav>").unwrap();
+        std::fs::write(
+            dir.path().join("hello.html"),
+            "{% extends \"base.html\" %}{% block content %}Hello {{ name }}{% endblock %}",
+        )
+        .unwrap();
+
+        let engine = MiniJinjaEngine::new(dir.path()).reload(true);
+        let context = json!({ "name": "jobs" });
+        assert_eq!(
+            engine.render("hello.html", &context).unwrap(),
+            "<p><nav></nav>Hello jobs</p>"
+        );
+
+        std::fs::write(dir.path().join("partials/nav.html"), "<nav>Home</nav>").unwrap();
+        assert_eq!(
+            engine.render("hello.html", &context).unwrap(),
+            "<p><nav>Home</nav>Hello jobs</p>"
+        );
+        assert!(engine.render("missing.html", &context).is_err());
+    }
+}
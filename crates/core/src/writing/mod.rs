@@ -1,17 +1,42 @@
 //! Writer trait and it's implements.
 
 mod json;
-mod redirect;
+pub(crate) mod negotiate;
+pub(crate) mod redirect;
 mod seek;
+pub(crate) mod template;
 mod text;
 
 use http::header::{AsHeaderName, IntoHeaderName};
 use http::{HeaderMap, StatusCode};
 pub use json::Json;
+pub use negotiate::{Format, Negotiator};
 pub use redirect::Redirect;
 pub use seek::ReadSeeker;
+pub use template::{TemplateEngine, Templates};
 pub use text::Text;
 
+cfg_feature! {
+    #![feature = "msgpack"]
+    mod msgpack;
+    pub use msgpack::MsgPack;
+}
+cfg_feature! {
+    #![feature = "cbor"]
+    mod cbor;
+    pub use cbor::Cbor;
+}
+cfg_feature! {
+    #![feature = "tera"]
+    mod tera;
+    pub use self::tera::TeraEngine;
+}
+cfg_feature! {
+    #![feature = "minijinja"]
+    mod minijinja;
+    pub use self::minijinja::MiniJinjaEngine;
+}
+
 use crate::http::header::{HeaderValue, CONTENT_TYPE};
 use crate::{async_trait, Depot, Request, Response};
 
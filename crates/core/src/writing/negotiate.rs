@@ -0,0 +1,385 @@
+use std::fmt::{self, Debug, Formatter};
+use std::sync::Arc;
+
+use mime::Mime;
+use serde::Serialize;
+
+use crate::http::header::{HeaderValue, ACCEPT, CONTENT_TYPE, VARY};
+use crate::http::{Request, Response, StatusError};
+use crate::BoxedError;
+
+/// A serialization format which can be chosen by a [`Negotiator`].
+///
+/// JSON and XML are supported by default, and MessagePack and CBOR if the `msgpack` and `cbor`
+/// features are enabled. Add other formats to a [`Negotiator`] with [`Negotiator::format`].
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::writing::{Format, Negotiator};
+/// use salvo_core::BoxedError;
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct User {
+///     name: String,
+/// }
+///
+/// struct PlainText;
+/// impl Format<User> for PlainText {
+///     fn media_type(&self) -> mime::Mime {
+///         mime::TEXT_PLAIN_UTF_8
+///     }
+///     fn serialize(&self, value: &User) -> Result<Vec<u8>, BoxedError> {
+///         Ok(value.name.clone().into_bytes())
+///     }
+/// }
+/// let negotiator = Negotiator::new().format(PlainText);
+/// ```
+pub trait Format<T: ?Sized>: Send + Sync {
+    /// Media type of the format, it is matched against the `Accept` header and written to the
+    /// `Content-Type` header.
+    fn media_type(&self) -> Mime;
+    /// Serialize the value to bytes.
+    fn serialize(&self, value: &T) -> Result<Vec<u8>, BoxedError>;
+}
+
+/// Renders a value in the format chosen by the `Accept` header of the request.
+///
+/// It supports the built-in formats, see [`Format`], and the formats added with
+/// [`Negotiator::format`]. Keep it in the handler which uses it, the formats of a `Negotiator`
+/// are not shared with the other negotiators.
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::prelude::*;
+/// use salvo_core::writing::Negotiator;
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct User {
+///     name: String,
+/// }
+///
+/// struct ShowUser {
+///     negotiator: Negotiator<User>,
+/// }
+/// #[async_trait]
+/// impl Handler for ShowUser {
+///     async fn handle(&self, req: &mut Request, _depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
+///         self.negotiator.render(&User { name: "jobs".into() }, req, res);
+///     }
+/// }
+/// ```
+pub struct Negotiator<T: ?Sized> {
+    formats: Vec<Arc<dyn Format<T>>>,
+}
+impl<T: ?Sized> Default for Negotiator<T> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            formats: Vec::new(),
+        }
+    }
+}
+impl<T: ?Sized> Clone for Negotiator<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            formats: self.formats.clone(),
+        }
+    }
+}
+impl<T: ?Sized> Debug for Negotiator<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Negotiator")
+            .field(
+                "formats",
+                &self
+                    .formats
+                    .iter()
+                    .map(|format| format.media_type())
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+impl<T: Serialize> Negotiator<T> {
+    /// Create a new `Negotiator` with the built-in formats.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a format and return `Self`.
+    ///
+    /// When the client accepts several formats with the same quality, the built-in formats are
+    /// preferred, then the added formats in the order they are added.
+    #[inline]
+    pub fn format(mut self, format: impl Format<T> + 'static) -> Self {
+        self.formats.push(Arc::new(format));
+        self
+    }
+
+    /// Render `value` in the format chosen by the `Accept` header of `req`.
+    ///
+    /// JSON is used if the request has no `Accept` header, and `406 Not Acceptable` is rendered
+    /// if no format is accepted.
+    pub fn render(&self, value: &T, req: &Request, res: &mut Response) {
+        res.headers_mut()
+            .append(VARY, HeaderValue::from_static("accept"));
+        let mut choices = vec![
+            Choice::Json,
+            Choice::Xml,
+            #[cfg(feature = "msgpack")]
+            Choice::MsgPack,
+            #[cfg(feature = "cbor")]
+            Choice::Cbor,
+        ];
+        choices.extend(self.formats.iter().map(|format| Choice::Custom(&**format)));
+        if !req.headers().contains_key(ACCEPT) {
+            choices[0].render(value, res);
+            return;
+        }
+        let ranges = accepted_ranges(req);
+        let mut best: Option<(&Choice<'_, T>, f32, u8)> = None;
+        for choice in &choices {
+            let Some((quality, specificity)) = choice.quality(&ranges) else {
+                continue;
+            };
+            if quality > 0.0 && best.map_or(true, |(_, q, s)| (quality, specificity) > (q, s)) {
+                best = Some((choice, quality, specificity));
+            }
+        }
+        match best {
+            Some((choice, _, _)) => choice.render(value, res),
+            None => {
+                let supported = choices
+                    .iter()
+                    .map(|choice| choice.media_type().to_string())
+                    .collect::<Vec<_>>();
+                res.render(
+                    StatusError::not_acceptable()
+                        .brief(format!("Supported media types: {}.", supported.join(", "))),
+                );
+            }
+        }
+    }
+}
+
+enum Choice<'a, T: ?Sized> {
+    Json,
+    Xml,
+    #[cfg(feature = "msgpack")]
+    MsgPack,
+    #[cfg(feature = "cbor")]
+    Cbor,
+    Custom(&'a dyn Format<T>),
+}
+impl<T: ?Sized> Debug for Choice<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.media_type())
+    }
+}
+impl<T: ?Sized> Choice<'_, T> {
+    fn media_type(&self) -> Mime {
+        match self {
+            Self::Json => mime::APPLICATION_JSON,
+            Self::Xml => "application/xml".parse().expect("mime should be valid"),
+            #[cfg(feature = "msgpack")]
+            Self::MsgPack => "application/msgpack".parse().expect("mime should be valid"),
+            #[cfg(feature = "cbor")]
+            Self::Cbor => "application/cbor".parse().expect("mime should be valid"),
+            Self::Custom(format) => format.media_type(),
+        }
+    }
+    /// Returns the quality and the specificity of the most specific range which accepts the
+    /// format, or `None` if no range accepts it.
+    fn quality(&self, ranges: &[(Mime, f32, u8)]) -> Option<(f32, u8)> {
+        let media_type = self.media_type();
+        ranges
+            .iter()
+            .filter(|(range, _, _)| {
+                (range.type_() == mime::STAR || range.type_() == media_type.type_())
+                    && (range.subtype() == mime::STAR || range.subtype() == media_type.subtype())
+            })
+            .max_by_key(|(_, _, specificity)| *specificity)
+            .map(|(_, quality, specificity)| (*quality, *specificity))
+    }
+}
+impl<T: Serialize> Choice<'_, T> {
+    fn render(&self, value: &T, res: &mut Response) {
+        let (content_type, body) = match self {
+            Self::Json => (
+                HeaderValue::from_static("application/json; charset=utf-8"),
+                serde_json::to_vec(value).map_err(BoxedError::from),
+            ),
+            Self::Xml => (
+                HeaderValue::from_static("application/xml; charset=utf-8"),
+                serde_xml_rs::to_string(value)
+                    .map(String::into_bytes)
+                    .map_err(BoxedError::from),
+            ),
+            #[cfg(feature = "msgpack")]
+            Self::MsgPack => (
+                HeaderValue::from_static("application/msgpack"),
+                rmp_serde::to_vec_named(value).map_err(BoxedError::from),
+            ),
+            #[cfg(feature = "cbor")]
+            Self::Cbor => (
+                HeaderValue::from_static("application/cbor"),
+                super::cbor::to_cbor_vec(value).map_err(BoxedError::from),
+            ),
+            Self::Custom(format) => (
+                HeaderValue::from_str(format.media_type().as_ref())
+                    .unwrap_or_else(|_| HeaderValue::from_static("application/octet-stream")),
+                format.serialize(value),
+            ),
+        };
+        match body {
+            Ok(body) => {
+                res.headers_mut().insert(CONTENT_TYPE, content_type);
+                let _ = res.write_body(body);
+            }
+            Err(e) => {
+                tracing::error!(error = ?e, format = ?self, "negotiated content write error");
+                res.render(StatusError::internal_server_error());
+            }
+        }
+    }
+}
+
+/// Returns the media ranges of the `Accept` header with their quality and specificity, the ranges
+/// with `q=0` are kept to exclude the formats they match.
+fn accepted_ranges(req: &Request) -> Vec<(Mime, f32, u8)> {
+    req.headers()
+        .get_all(ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|range| range.trim().parse::<Mime>().ok())
+        .map(|range| {
+            let quality = range
+                .get_param("q")
+                .and_then(|q| q.as_str().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            let specificity = match (range.type_() == mime::STAR, range.subtype() == mime::STAR) {
+                (true, _) => 0,
+                (false, true) => 1,
+                (false, false) => 2,
+            };
+            (range, quality, specificity)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+
+    use super::*;
+    use crate::prelude::*;
+    use crate::test::{ResponseExt, TestClient};
+
+    #[derive(Serialize)]
+    struct User {
+        name: String,
+    }
+
+    struct Csv;
+    impl Format<User> for Csv {
+        fn media_type(&self) -> Mime {
+            mime::TEXT_CSV
+        }
+        fn serialize(&self, value: &User) -> Result<Vec<u8>, BoxedError> {
+            Ok(format!("name\n{}", value.name).into_bytes())
+        }
+    }
+
+    struct ShowUser(Negotiator<User>);
+    #[async_trait]
+    impl Handler for ShowUser {
+        async fn handle(
+            &self,
+            req: &mut Request,
+            _depot: &mut Depot,
+            res: &mut Response,
+            _ctrl: &mut FlowCtrl,
+        ) {
+            let user = User {
+                name: "jobs".into(),
+            };
+            self.0.render(&user, req, res);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_render_negotiated() {
+        let router = Router::new().get(ShowUser(Negotiator::new().format(Csv)));
+        let service = Service::new(router);
+        let access = |accept: Option<&'static str>| {
+            let service = &service;
+            async move {
+                let mut req = TestClient::get("http://127.0.0.1:5801/");
+                if let Some(accept) = accept {
+                    req = req.add_header(ACCEPT, accept, true);
+                }
+                let mut res = req.send(service).await;
+                let content_type = res
+                    .headers()
+                    .get(CONTENT_TYPE)
+                    .and_then(|v| v.to_str().ok())
+                    .unwrap_or_default()
+                    .to_owned();
+                (
+                    res.status_code.unwrap(),
+                    content_type,
+                    res.take_string().await.unwrap(),
+                )
+            }
+        };
+
+        let (_, content_type, body) = access(None).await;
+        assert_eq!(content_type, "application/json; charset=utf-8");
+        assert_eq!(body, r#"{"name":"jobs"}"#);
+
+        let (_, content_type, body) =
+            access(Some("text/html, application/xml;q=0.9, */*;q=0.8")).await;
+        assert_eq!(content_type, "application/xml; charset=utf-8");
+        assert!(body.ends_with("<User><name>jobs</name></User>"));
+
+        let (_, content_type, _) = access(Some("application/xml;q=0.5, application/*")).await;
+        assert_eq!(content_type, "application/json; charset=utf-8");
+
+        let (_, content_type, body) = access(Some("text/*")).await;
+        assert_eq!(content_type, "text/csv");
+        assert_eq!(body, "name\njobs");
+
+        let (status, _, _) = access(Some("image/png, application/json;q=0")).await;
+        assert_eq!(status, StatusCode::NOT_ACCEPTABLE);
+
+        let (_, content_type, _) = access(Some("*/*, application/json;q=0")).await;
+        assert_eq!(content_type, "application/xml; charset=utf-8");
+        let (_, content_type, _) = access(Some("application/xml, */*")).await;
+        assert_eq!(content_type, "application/xml; charset=utf-8");
+    }
+
+    #[tokio::test]
+    async fn test_render_negotiated_without_custom_formats() {
+        #[handler]
+        async fn user(req: &mut Request, res: &mut Response) {
+            res.render_negotiated(
+                &User {
+                    name: "jobs".into(),
+                },
+                req,
+            );
+        }
+        let res = TestClient::get("http://127.0.0.1:5801/")
+            .add_header(ACCEPT, "text/*", true)
+            .send(Router::new().get(user))
+            .await;
+        assert_eq!(res.status_code.unwrap(), StatusCode::NOT_ACCEPTABLE);
+    }
+}
@@ -6,6 +6,9 @@ use crate::Error;
 
 /// Response that redirects the request to another location.
 ///
+/// The redirect helpers of [`Response`], such as [`Response::redirect_see_other`], don't panic
+/// and percent-encode the location instead of rejecting it.
+///
 /// # Example
 ///
 /// ```
@@ -99,6 +102,26 @@ impl Redirect {
     }
 }
 
+/// Percent-encode the characters which are not allowed in a URI reference, such as spaces and
+/// non-ASCII characters, and `%` not followed by two hex digits. Valid URLs, including relative
+/// ones like `../login`, are kept as they are.
+pub(crate) fn encode_location(url: &str) -> HeaderValue {
+    const ALLOWED: &[u8] = b"-._~:/?#[]@!$&'()*+,;=";
+    let bytes = url.as_bytes();
+    let mut encoded = String::with_capacity(bytes.len());
+    for (index, &byte) in bytes.iter().enumerate() {
+        let escaped = byte == b'%'
+            && bytes.get(index + 1).is_some_and(u8::is_ascii_hexdigit)
+            && bytes.get(index + 2).is_some_and(u8::is_ascii_hexdigit);
+        if byte.is_ascii_alphanumeric() || ALLOWED.contains(&byte) || escaped {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    HeaderValue::try_from(encoded).expect("encoded location should be a valid header value")
+}
+
 impl Scribe for Redirect {
     #[inline]
     fn render(self, res: &mut Response) {
@@ -110,3 +133,24 @@ impl Scribe for Redirect {
         res.headers_mut().insert(LOCATION, location);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_location() {
+        assert_eq!(
+            encode_location("https://salvo.rs/a?b=1#c"),
+            "https://salvo.rs/a?b=1#c"
+        );
+        assert_eq!(encode_location("../login"), "../login");
+        assert_eq!(encode_location("/a b/%E4%BD%A0"), "/a%20b/%E4%BD%A0");
+        assert_eq!(encode_location("/你"), "/%E4%BD%A0");
+        assert_eq!(encode_location("/100%"), "/100%25");
+        assert_eq!(
+            encode_location("/a\r\nSet-Cookie: a=b"),
+            "/a%0D%0ASet-Cookie:%20a=b"
+        );
+    }
+}
@@ -0,0 +1,170 @@
+use std::fmt::{self, Debug, Formatter};
+use std::sync::Arc;
+
+use serde::Serialize;
+
+use crate::http::header::{HeaderValue, CONTENT_TYPE};
+use crate::http::{Request, Response, StatusError};
+use crate::{async_trait, BoxedError, Depot, FlowCtrl, Handler};
+
+/// A template engine used by [`Response::render_template`].
+///
+/// Implement it for Askama or any other engine, then add it to the router with [`Templates`].
+/// [`TeraEngine`](super::TeraEngine) and [`MiniJinjaEngine`](super::MiniJinjaEngine) are
+/// provided by the `tera` and `minijinja` features. The context is converted to
+/// [`serde_json::Value`] before it is passed to the engine.
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::prelude::*;
+/// use salvo_core::writing::{TemplateEngine, Templates};
+/// use salvo_core::BoxedError;
+///
+/// struct Greeting;
+/// impl TemplateEngine for Greeting {
+///     fn render(&self, name: &str, context: &serde_json::Value) -> Result<String, BoxedError> {
+///         match name {
+///             "hello.html" => Ok(format!("<h1>Hello {}</h1>", context["name"].as_str().unwrap_or_default())),
+///             _ => Err(format!("template `{name}` is not found").into()),
+///         }
+///     }
+/// }
+/// let router = Router::new().hoop(Templates::new(Greeting));
+/// ```
+pub trait TemplateEngine: Send + Sync + 'static {
+    /// Render the template `name` with the context.
+    fn render(&self, name: &str, context: &serde_json::Value) -> Result<String, BoxedError>;
+}
+
+/// Middleware which sets the template engine used by [`Response::render_template`] for the
+/// requests routed through it.
+///
+/// The engine is injected into the [`Depot`], so the routers can use different engines.
+#[derive(Clone)]
+pub struct Templates {
+    engine: Arc<dyn TemplateEngine>,
+}
+impl Templates {
+    /// Create a new `Templates` with the template engine.
+    #[inline]
+    pub fn new(engine: impl TemplateEngine) -> Self {
+        Self {
+            engine: Arc::new(engine),
+        }
+    }
+}
+impl Debug for Templates {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Templates").finish_non_exhaustive()
+    }
+}
+#[async_trait]
+impl Handler for Templates {
+    async fn handle(
+        &self,
+        _req: &mut Request,
+        depot: &mut Depot,
+        _res: &mut Response,
+        _ctrl: &mut FlowCtrl,
+    ) {
+        depot.inject(self.clone());
+    }
+}
+
+pub(crate) fn render_template<T: Serialize>(
+    depot: &Depot,
+    name: &str,
+    context: &T,
+    res: &mut Response,
+) {
+    let Ok(templates) = depot.obtain::<Templates>() else {
+        tracing::error!(template = name, "template engine is not set");
+        res.render(StatusError::internal_server_error());
+        return;
+    };
+    let rendered = serde_json::to_value(context)
+        .map_err(BoxedError::from)
+        .and_then(|context| templates.engine.render(name, &context));
+    match rendered {
+        Ok(html) => {
+            res.headers_mut().insert(
+                CONTENT_TYPE,
+                HeaderValue::from_static("text/html; charset=utf-8"),
+            );
+            let _ = res.write_body(html);
+        }
+        Err(e) => {
+            tracing::error!(error = ?e, template = name, "template render error");
+            res.render(StatusError::internal_server_error());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::prelude::*;
+    use crate::test::{ResponseExt, TestClient};
+
+    struct Greeting(&'static str);
+    impl TemplateEngine for Greeting {
+        fn render(&self, name: &str, context: &serde_json::Value) -> Result<String, BoxedError> {
+            match name {
+                "hello.html" => Ok(format!(
+                    "<h1>{} {}</h1>",
+                    self.0,
+                    context["name"].as_str().unwrap_or_default()
+                )),
+                _ => Err("template is not found".into()),
+            }
+        }
+    }
+
+    #[handler]
+    async fn hello(req: &mut Request, depot: &mut Depot, res: &mut Response) {
+        let name = req.query::<String>("template").unwrap_or_default();
+        res.render_template(depot, &name, &json!({ "name": "jobs" }));
+    }
+
+    #[tokio::test]
+    async fn test_render_template() {
+        let router = Router::new()
+            .push(
+                Router::with_path("en")
+                    .hoop(Templates::new(Greeting("Hello")))
+                    .get(hello),
+            )
+            .push(
+                Router::with_path("fr")
+                    .hoop(Templates::new(Greeting("Bonjour")))
+                    .get(hello),
+            )
+            .push(Router::with_path("none").get(hello));
+        let service = Service::new(router);
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/en?template=hello.html")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::OK));
+        assert_eq!(res.headers()[CONTENT_TYPE], "text/html; charset=utf-8");
+        assert_eq!(res.take_string().await.unwrap(), "<h1>Hello jobs</h1>");
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/fr?template=hello.html")
+            .send(&service)
+            .await;
+        assert_eq!(res.take_string().await.unwrap(), "<h1>Bonjour jobs</h1>");
+
+        let res = TestClient::get("http://127.0.0.1:5801/en?template=missing.html")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::INTERNAL_SERVER_ERROR));
+
+        let res = TestClient::get("http://127.0.0.1:5801/none?template=hello.html")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::INTERNAL_SERVER_ERROR));
+    }
+}
@@ -0,0 +1,105 @@
+use std::path::Path;
+
+use parking_lot::RwLock;
+use tera::{Context, Tera};
+
+use super::TemplateEngine;
+use crate::BoxedError;
+
+/// [`TemplateEngine`] which renders the templates with [Tera](https://keats.github.io/tera/).
+///
+/// All the files in the directory and its subdirectories are loaded as templates, named by their
+/// paths relative to the directory, such as `partials/nav.html`. The templates are reloaded
+/// before every render in debug builds, so the changes are applied without a restart.
+///
+/// # Example
+///
+/// ```no_run
+/// use salvo_core::prelude::*;
+/// use salvo_core::writing::{TeraEngine, Templates};
+///
+/// let engine = TeraEngine::new("templates").expect("templates should be valid");
+/// let router = Router::new().hoop(Templates::new(engine));
+/// ```
+pub struct TeraEngine {
+    tera: RwLock<Tera>,
+    reload: bool,
+}
+impl TeraEngine {
+    /// Create a new `TeraEngine` which loads the templates in `dir`.
+    pub fn new(dir: impl AsRef<Path>) -> Result<Self, tera::Error> {
+        let glob = dir.as_ref().join("**").join("*");
+        Ok(Self::with_tera(Tera::new(&glob.to_string_lossy())?))
+    }
+
+    /// Create a new `TeraEngine` with a configured `Tera`.
+    #[inline]
+    pub fn with_tera(tera: Tera) -> Self {
+        Self {
+            tera: RwLock::new(tera),
+            reload: cfg!(debug_assertions),
+        }
+    }
+
+    /// Sets whether the templates are reloaded before every render, default is `true` in debug
+    /// builds.
+    #[inline]
+    pub fn reload(mut self, reload: bool) -> Self {
+        self.reload = reload;
+        self
+    }
+
+    /// Get the mutable reference of `Tera`, to register filters, functions or testers.
+    #[inline]
+    pub fn tera_mut(&mut self) -> &mut Tera {
+        self.tera.get_mut()
+    }
+}
+
+impl TemplateEngine for TeraEngine {
+    fn render(&self, name: &str, context: &serde_json::Value) -> Result<String, BoxedError> {
+        if self.reload {
+            self.tera.write().full_reload()?;
+        }
+        let context = Context::from_serialize(context)?;
+        Ok(self.tera.read().render(name, &context)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_tera_engine() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("partials")).unwrap();
+        std::fs::write(
+            dir.path().join("base.html"),
+            "<p>{% include \"partials/nav.html\" %}{% block content %}{% endblock %}</p>",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("partials/nav.html"), "<nav></nav>").unwrap();
+        std::fs::write(
+            dir.path().join("hello.html"),
+            "{% extends \"base.html\" %}{% block content %}Hello {{ name }}{% endblock %}",
+        )
+        .unwrap();
+
+        let engine = TeraEngine::new(dir.path()).unwrap().reload(true);
+        let context = json!({ "name": "jobs" });
+        assert_eq!(
+            engine.render("hello.html", &context).unwrap(),
+            "<p><nav></nav>Hello jobs</p>"
+        );
+
+        std::fs::write(dir.path().join("partials/nav.html"), "<nav>Home</nav>").unwrap();
+        assert_eq!(
+            engine.render("hello.html", &context).unwrap(),
+            "<p><nav>Home</nav>Hello jobs</p>"
+        );
+        assert!(engine.render("missing.html", &context).is_err());
+    }
+}
@@ -70,6 +70,27 @@ impl AllowOrigin {
         Self(OriginInner::Judge(Arc::new(f)))
     }
 
+    /// Set the allowed origins from a predicate on the `Origin` header value.
+    ///
+    /// The matching origin is mirrored back, so this can be combined with
+    /// [`Cors::allow_credentials`]. Origins which are not valid visible ASCII are rejected.
+    ///
+    /// ```
+    /// use salvo_cors::{AllowOrigin, Cors};
+    ///
+    /// let cors = Cors::new()
+    ///     .allow_credentials(true)
+    ///     .allow_origin(AllowOrigin::predicate(|origin| origin.ends_with(".salvo.rs")));
+    /// ```
+    ///
+    /// [`Cors::allow_credentials`]: super::Cors::allow_credentials
+    pub fn predicate<F>(f: F) -> Self
+    where
+        F: Fn(&str) -> bool + Send + Sync + 'static,
+    {
+        Self::judge(move |origin, _, _| origin.to_str().map(&f).unwrap_or(false))
+    }
+
     /// Allow any origin, by mirroring the request origin.
     ///
     /// See [`Cors::allow_origin`] for more details.
@@ -83,6 +104,11 @@ impl AllowOrigin {
         matches!(&self.0, OriginInner::Exact(v) if v == WILDCARD)
     }
 
+    /// Returns `true` if the header value depends on the request `Origin`.
+    pub(super) fn varies_by_origin(&self) -> bool {
+        !matches!(&self.0, OriginInner::Exact(_))
+    }
+
     pub(super) fn to_header(
         &self,
         origin: Option<&HeaderValue>,
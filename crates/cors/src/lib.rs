@@ -26,6 +26,43 @@
 //! let cors_handler = Cors::new().allow_origin(cors::Any).into_handler();
 //! ```
 //!
+//!
+//! `CorsHandler` can be attached to any router node, so different subtrees can use different
+//! settings. Preflight requests are answered directly by the handler with `204 No Content`, the
+//! rest of the handlers are skipped. The service answers `OPTIONS` requests for paths without an
+//! `OPTIONS` route automatically, after calling the hoops of the path, so the `CorsHandler` takes
+//! precedence for preflight requests. If this is disabled with `Service::auto_options`, make sure
+//! the subtree also matches `OPTIONS` requests, for example with `.options(handler::empty())`:
+//! ```
+//! use salvo_core::prelude::*;
+//! use salvo_cors::{AllowOrigin, Cors};
+//!
+//! #[handler]
+//! async fn hello() -> &'static str {
+//!     "hello"
+//! }
+//!
+//! let router = Router::new()
+//!     .push(
+//!         Router::with_path("public")
+//!             .hoop(Cors::permissive().into_handler())
+//!             .get(hello)
+//!             .options(handler::empty()),
+//!     )
+//!     .push(
+//!         Router::with_path("account")
+//!             .hoop(
+//!                 Cors::new()
+//!                     .allow_credentials(true)
+//!                     .allow_origin(AllowOrigin::predicate(|origin| origin.ends_with(".salvo.rs")))
+//!                     .max_age(600)
+//!                     .into_handler(),
+//!             )
+//!             .get(hello)
+//!             .options(handler::empty()),
+//!     );
+//! ```
+//!
 //! Read more: <https://salvo.rs>
 #![doc(html_favicon_url = "https://salvo.rs/favicon-32x32.png")]
 #![doc(html_logo_url = "https://salvo.rs/images/logo.svg")]
@@ -274,6 +311,11 @@ pub enum CallNext {
 }
 
 /// CorsHandler
+///
+/// Preflight requests (`OPTIONS` with `Origin` and `Access-Control-Request-Method`) are answered
+/// with `204 No Content` and the rest of the handlers are skipped. Other requests are passed to the
+/// next handlers according to [`CallNext`]. The `Vary` values are appended to the ones already set
+/// on the response, and `Origin` is always included when the allowed origin depends on the request.
 #[derive(Clone, Debug)]
 pub struct CorsHandler {
     cors: Cors,
@@ -295,7 +337,8 @@ impl Handler for CorsHandler {
         res: &mut Response,
         ctrl: &mut FlowCtrl,
     ) {
-        if self.call_next == CallNext::Before {
+        let is_preflight = is_preflight(req);
+        if !is_preflight && self.call_next == CallNext::Before {
             ctrl.call_next(req, depot, res).await;
         }
 
@@ -304,42 +347,76 @@ impl Handler for CorsHandler {
 
         // These headers are applied to both preflight and subsequent regular CORS requests:
         // https://fetch.spec.whatwg.org/#http-responses
-        headers.extend(self.cors.allow_origin.to_header(origin, req, depot));
-        headers.extend(self.cors.allow_credentials.to_header(origin, req, depot));
-
-        let mut vary_headers = self.cors.vary.values();
-        if let Some(first) = vary_headers.next() {
-            let mut header = match headers.entry(header::VARY) {
-                header::Entry::Occupied(_) => {
-                    unreachable!("no vary header inserted up to this point")
-                }
-                header::Entry::Vacant(v) => v.insert_entry(first),
-            };
-
-            for val in vary_headers {
-                header.append(val);
-            }
+        let allow_origin = self.cors.allow_origin.to_header(origin, req, depot);
+        let allowed = allow_origin.is_some();
+        headers.extend(allow_origin);
+        if allowed {
+            headers.extend(self.cors.allow_credentials.to_header(origin, req, depot));
         }
 
-        // Return results immediately upon preflight request
-        if req.method() == Method::OPTIONS {
+        if is_preflight {
             // These headers are applied only to preflight requests
-            headers.extend(self.cors.allow_methods.to_header(origin, req, depot));
-            headers.extend(self.cors.allow_headers.to_header(origin, req, depot));
-            headers.extend(self.cors.max_age.to_header(origin, req, depot));
+            if allowed {
+                headers.extend(self.cors.allow_methods.to_header(origin, req, depot));
+                headers.extend(self.cors.allow_headers.to_header(origin, req, depot));
+                headers.extend(self.cors.max_age.to_header(origin, req, depot));
+            }
             res.status_code = Some(StatusCode::NO_CONTENT);
-        } else {
+        } else if allowed {
             // This header is applied only to non-preflight requests
             headers.extend(self.cors.expose_headers.to_header(origin, req, depot));
         }
         res.headers_mut().extend(headers);
 
-        if self.call_next == CallNext::After {
+        // Vary is appended so the values set by other handlers are kept.
+        let mut vary = self.cors.vary.values().collect::<Vec<_>>();
+        if self.cors.allow_origin.varies_by_origin() && !vary.iter().any(|v| v == "origin") {
+            vary.push(HeaderValue::from_static("origin"));
+        }
+        append_vary(res.headers_mut(), vary);
+
+        if is_preflight {
+            // Return results immediately upon preflight request
+            ctrl.skip_rest();
+        } else if self.call_next == CallNext::After {
             ctrl.call_next(req, depot, res).await;
         }
     }
 }
 
+/// Returns `true` if the request is a CORS preflight request.
+fn is_preflight(req: &Request) -> bool {
+    req.method() == Method::OPTIONS
+        && req.headers().contains_key(header::ORIGIN)
+        && req
+            .headers()
+            .contains_key(header::ACCESS_CONTROL_REQUEST_METHOD)
+}
+
+/// Append `values` to the `Vary` header, skipping the ones already present.
+fn append_vary(headers: &mut HeaderMap, values: Vec<HeaderValue>) {
+    let exists = |headers: &HeaderMap, value: &HeaderValue| {
+        headers
+            .get_all(header::VARY)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .any(|v| {
+                let v = v.trim();
+                v == "*"
+                    || value
+                        .to_str()
+                        .map(|value| v.eq_ignore_ascii_case(value))
+                        .unwrap_or(false)
+            })
+    };
+    for value in values {
+        if !exists(headers, &value) {
+            headers.append(header::VARY, value);
+        }
+    }
+}
+
 /// Iterator over the three request headers that may be involved in a CORS preflight request.
 ///
 /// This is the default set of header names returned in the `vary` header
@@ -356,7 +433,7 @@ pub fn preflight_request_headers() -> impl Iterator<Item = HeaderName> {
 mod tests {
     use salvo_core::http::header::*;
     use salvo_core::prelude::*;
-    use salvo_core::test::TestClient;
+    use salvo_core::test::{ResponseExt, TestClient};
 
     use super::*;
 
@@ -411,4 +488,146 @@ mod tests {
         );
         assert!(headers.get(ACCESS_CONTROL_ALLOW_HEADERS).is_none());
     }
+
+    #[tokio::test]
+    async fn test_cors_per_router() {
+        #[handler]
+        async fn hello(res: &mut Response) {
+            res.add_header(VARY, "accept-encoding", true).unwrap();
+            res.render("hello");
+        }
+
+        let router = Router::new()
+            .push(
+                Router::with_path("public")
+                    .hoop(
+                        Cors::new()
+                            .allow_origin(Any)
+                            .allow_methods(Any)
+                            .into_handler(),
+                    )
+                    .get(hello)
+                    .options(hello),
+            )
+            .push(
+                Router::with_path("account")
+                    .hoop(
+                        Cors::new()
+                            .allow_credentials(true)
+                            .allow_origin(AllowOrigin::predicate(|origin| {
+                                origin.ends_with(".salvo.rs")
+                            }))
+                            .allow_methods(vec![Method::GET, Method::POST])
+                            .max_age(600)
+                            .into_handler(),
+                    )
+                    .get(hello)
+                    .options(hello),
+            );
+        let service = Service::new(router);
+
+        let preflight = |path: &str, origin: &str| {
+            TestClient::options(format!("http://127.0.0.1:5801/{path}"))
+                .add_header(ORIGIN, origin, true)
+                .add_header(ACCESS_CONTROL_REQUEST_METHOD, "POST", true)
+        };
+
+        let mut res = preflight("account", "https://app.salvo.rs")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::NO_CONTENT));
+        assert_eq!(
+            res.headers()[ACCESS_CONTROL_ALLOW_ORIGIN],
+            "https://app.salvo.rs"
+        );
+        assert_eq!(res.headers()[ACCESS_CONTROL_ALLOW_CREDENTIALS], "true");
+        assert_eq!(res.headers()[ACCESS_CONTROL_ALLOW_METHODS], "GET,POST");
+        assert_eq!(res.headers()[ACCESS_CONTROL_MAX_AGE], "600");
+        assert!(res.take_string().await.unwrap().is_empty());
+
+        let res = preflight("account", "https://evil.rs").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::NO_CONTENT));
+        assert!(res.headers().get(ACCESS_CONTROL_ALLOW_ORIGIN).is_none());
+        assert!(res.headers().get(ACCESS_CONTROL_ALLOW_METHODS).is_none());
+        assert!(res
+            .headers()
+            .get(ACCESS_CONTROL_ALLOW_CREDENTIALS)
+            .is_none());
+
+        let res = preflight("public", "https://evil.rs").send(&service).await;
+        assert_eq!(res.headers()[ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+        assert!(res.headers().get(ACCESS_CONTROL_MAX_AGE).is_none());
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/account")
+            .add_header(ORIGIN, "https://app.salvo.rs", true)
+            .send(&service)
+            .await;
+        assert_eq!(
+            res.headers()[ACCESS_CONTROL_ALLOW_ORIGIN],
+            "https://app.salvo.rs"
+        );
+        let vary = res
+            .headers()
+            .get_all(VARY)
+            .iter()
+            .map(|v| v.to_str().unwrap().to_owned())
+            .collect::<Vec<_>>();
+        assert!(vary.contains(&"accept-encoding".to_owned()));
+        assert!(vary.contains(&"origin".to_owned()));
+        assert_eq!(res.take_string().await.unwrap(), "hello");
+
+        // A plain `OPTIONS` request is not a preflight request.
+        let mut res = TestClient::options("http://127.0.0.1:5801/account")
+            .send(&service)
+            .await;
+        assert_eq!(res.take_string().await.unwrap(), "hello");
+    }
+
+    #[tokio::test]
+    async fn test_cors_auto_options() {
+        #[handler]
+        async fn hello() -> &'static str {
+            "hello"
+        }
+        let router = Router::with_hoop(Cors::permissive().into_handler())
+            .push(Router::with_path("users").get(hello).post(hello));
+        let service = Service::new(router);
+
+        let res = TestClient::options("http://127.0.0.1:5801/users")
+            .add_header(ORIGIN, "https://salvo.rs", true)
+            .add_header(ACCESS_CONTROL_REQUEST_METHOD, "POST", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::NO_CONTENT));
+        assert_eq!(res.headers()[ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+        assert!(res.headers().get(ALLOW).is_none());
+
+        let res = TestClient::options("http://127.0.0.1:5801/users")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::NO_CONTENT));
+        assert_eq!(res.headers()[ALLOW], "GET, HEAD, POST, OPTIONS");
+    }
+
+    #[tokio::test]
+    async fn test_cors_vary_origin() {
+        #[handler]
+        async fn hello() -> &'static str {
+            "hello"
+        }
+        let cors = Cors::new()
+            .allow_origin(["https://salvo.rs", "https://other.rs"])
+            .vary::<()>(Vec::<HeaderName>::new())
+            .into_handler();
+        let service = Service::new(Router::with_hoop(cors).get(hello));
+        let res = TestClient::get("http://127.0.0.1:5801/")
+            .add_header(ORIGIN, "https://other.rs", true)
+            .send(&service)
+            .await;
+        assert_eq!(
+            res.headers()[ACCESS_CONTROL_ALLOW_ORIGIN],
+            "https://other.rs"
+        );
+        assert_eq!(res.headers()[VARY], "origin");
+    }
 }
@@ -0,0 +1,120 @@
+//! Middleware for advertising an HTTP/3 endpoint with the `Alt-Svc` header.
+//!
+//! The server already adds an `Alt-Svc` header when it is listening on QUIC itself. This middleware
+//! is useful when the advertised port or max-age differs, for example behind a load balancer
+//! which terminates QUIC on port 443.
+//!
+//! Example:
+//!
+//! ```no_run
+//! use salvo_core::prelude::*;
+//! use salvo_extra::alt_svc::AltSvc;
+//!
+//! #[handler]
+//! async fn hello() -> &'static str {
+//!     "hello"
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let router = Router::new().get(hello);
+//!     let service = Service::new(router).hoop(AltSvc::new(443).max_age(86400));
+//!
+//!     let acceptor = TcpListener::new("0.0.0.0:5800").bind().await;
+//!     Server::new(acceptor).serve(service).await;
+//! }
+//! ```
+use salvo_core::handler::Skipper;
+use salvo_core::http::header::{HeaderValue, ALT_SVC};
+use salvo_core::http::{Request, Response, Version};
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler};
+
+/// Middleware which adds `Alt-Svc: h3=":{port}"; ma={max_age}` to responses.
+///
+/// Requests which already arrived over HTTP/3 are left untouched.
+pub struct AltSvc {
+    port: u16,
+    value: HeaderValue,
+    skipper: Option<Box<dyn Skipper>>,
+}
+impl AltSvc {
+    /// Create new `AltSvc` middleware advertising HTTP/3 on `port` with a max-age of one day.
+    pub fn new(port: u16) -> Self {
+        Self {
+            port,
+            value: header_value(port, 86400),
+            skipper: None,
+        }
+    }
+
+    /// Sets how many seconds the client may remember the alternative service.
+    pub fn max_age(self, max_age: u64) -> Self {
+        Self {
+            value: header_value(self.port, max_age),
+            ..self
+        }
+    }
+
+    /// Uses a closure to determine if a request should not get the `Alt-Svc` header.
+    pub fn skipper(self, skipper: impl Skipper) -> Self {
+        Self {
+            skipper: Some(Box::new(skipper)),
+            ..self
+        }
+    }
+}
+
+fn header_value(port: u16, max_age: u64) -> HeaderValue {
+    format!(r#"h3=":{port}"; ma={max_age}"#)
+        .parse()
+        .expect("alt-svc header value should be valid")
+}
+
+#[async_trait]
+impl Handler for AltSvc {
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
+        if req.version() == Version::HTTP_3
+            || self
+                .skipper
+                .as_ref()
+                .map(|skipper| skipper.skipped(req, depot))
+                .unwrap_or(false)
+        {
+            return;
+        }
+        res.headers_mut().insert(ALT_SVC, self.value.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::prelude::*;
+    use salvo_core::test::TestClient;
+
+    use super::*;
+
+    #[handler]
+    async fn hello() -> &'static str {
+        "Hello World"
+    }
+
+    #[tokio::test]
+    async fn test_alt_svc() {
+        let service = Service::new(Router::new().goal(hello)).hoop(AltSvc::new(443));
+        let res = TestClient::get("http://127.0.0.1:5800/").send(&service).await;
+        assert_eq!(res.headers().get(ALT_SVC).unwrap(), r#"h3=":443"; ma=86400"#);
+
+        let service = Service::new(Router::new().goal(hello)).hoop(AltSvc::new(8443).max_age(60));
+        let res = TestClient::get("http://127.0.0.1:5800/").send(&service).await;
+        assert_eq!(res.headers().get(ALT_SVC).unwrap(), r#"h3=":8443"; ma=60"#);
+    }
+
+    #[tokio::test]
+    async fn test_alt_svc_skip_http3() {
+        let service = Service::new(Router::new().goal(hello)).hoop(AltSvc::new(443));
+        let mut req: Request = TestClient::get("http://127.0.0.1:5800/").build();
+        *req.version_mut() = Version::HTTP_3;
+        let res = service.handle(req).await;
+        assert!(res.headers().get(ALT_SVC).is_none());
+    }
+}
@@ -27,11 +27,39 @@
 //!     Server::new(acceptor).serve(router).await;
 //! }
 //! ```
+//!
+//! A closure can be used as the validator:
+//!
+//! ```
+//! use salvo_extra::basic_auth::BasicAuth;
+//!
+//! let auth_handler = BasicAuth::new(|username: &str, password: &str| username == "root" && password == "pwd");
+//! ```
+//!
+//! The [`BasicCredentials`] and [`BearerToken`] extractors read the credentials of the
+//! `Authorization` header in handlers, and respond `401 Unauthorized` if they are missing or
+//! malformed:
+//!
+//! ```
+//! use salvo_core::prelude::*;
+//! use salvo_extra::basic_auth::{BasicCredentials, BearerToken};
+//!
+//! #[handler]
+//! async fn whoami(credentials: BasicCredentials) -> String {
+//!     credentials.username
+//! }
+//!
+//! #[handler]
+//! async fn show_token(token: BearerToken) -> String {
+//!     format!("token length: {}", token.0.len())
+//! }
+//! ```
 use std::future::Future;
 
 use base64::engine::{general_purpose, Engine};
 use salvo_core::http::header::{HeaderName, AUTHORIZATION, PROXY_AUTHORIZATION};
-use salvo_core::http::{Request, Response, StatusCode};
+use salvo_core::extract::{Extractible, Metadata};
+use salvo_core::http::{Request, Response, StatusCode, StatusError};
 use salvo_core::{async_trait, Depot, Error, FlowCtrl, Handler};
 
 /// key used when insert into depot.
@@ -42,6 +70,14 @@ pub trait BasicAuthValidator: Send + Sync {
     /// Validate is that username and password is right.
     fn validate(&self, username: &str, password: &str, depot: &mut Depot) -> impl Future<Output = bool> + Send;
 }
+impl<F> BasicAuthValidator for F
+where
+    F: Fn(&str, &str) -> bool + Send + Sync,
+{
+    async fn validate(&self, username: &str, password: &str, _depot: &mut Depot) -> bool {
+        self(username, password)
+    }
+}
 /// BasicAuthDepotExt
 pub trait BasicAuthDepotExt {
     /// Get basic auth username reference.
@@ -130,10 +166,10 @@ pub fn parse_credentials(req: &Request, header_names: &[HeaderName]) -> Result<(
         }
     }
 
-    if authorization.starts_with("Basic") {
-        if let Some((_, auth)) = authorization.split_once(' ') {
-            let auth = general_purpose::STANDARD.decode(auth).map_err(Error::other)?;
-            let auth = auth.iter().map(|&c| c as char).collect::<String>();
+    if let Some((scheme, auth)) = authorization.split_once(' ') {
+        if scheme.eq_ignore_ascii_case("Basic") {
+            let auth = general_purpose::STANDARD.decode(auth.trim()).map_err(Error::other)?;
+            let auth = String::from_utf8(auth).map_err(Error::other)?;
             if let Some((username, password)) = auth.split_once(':') {
                 return Ok((username.to_owned(), password.to_owned()));
             } else {
@@ -162,6 +198,63 @@ where
     }
 }
 
+/// Username and password of the `Authorization: Basic` header.
+///
+/// As an extractor it responds `401 Unauthorized` if the header is missing or malformed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BasicCredentials {
+    /// The username.
+    pub username: String,
+    /// The password.
+    pub password: String,
+}
+impl BasicCredentials {
+    /// Parse the credentials from the `Authorization` header, returns `None` if it is missing or malformed.
+    pub fn parse(req: &Request) -> Option<Self> {
+        parse_credentials(req, &[AUTHORIZATION])
+            .ok()
+            .map(|(username, password)| Self { username, password })
+    }
+}
+impl<'ex> Extractible<'ex> for BasicCredentials {
+    fn metadata() -> &'ex Metadata {
+        static METADATA: Metadata = Metadata::new("");
+        &METADATA
+    }
+    #[allow(refining_impl_trait)]
+    async fn extract(req: &'ex mut Request) -> Result<Self, StatusError> {
+        Self::parse(req).ok_or_else(|| StatusError::unauthorized().brief("Missing or malformed basic credentials."))
+    }
+}
+
+/// Token of the `Authorization: Bearer` header.
+///
+/// As an extractor it responds `401 Unauthorized` if the header is missing or malformed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BearerToken(pub String);
+impl BearerToken {
+    /// Parse the token from the `Authorization` header, returns `None` if it is missing or malformed.
+    pub fn parse(req: &Request) -> Option<Self> {
+        let authorization = req.headers().get(AUTHORIZATION)?.to_str().ok()?;
+        let (scheme, token) = authorization.split_once(' ')?;
+        let token = token.trim();
+        if !scheme.eq_ignore_ascii_case("Bearer") || token.is_empty() || token.contains(' ') {
+            return None;
+        }
+        Some(Self(token.to_owned()))
+    }
+}
+impl<'ex> Extractible<'ex> for BearerToken {
+    fn metadata() -> &'ex Metadata {
+        static METADATA: Metadata = Metadata::new("");
+        &METADATA
+    }
+    #[allow(refining_impl_trait)]
+    async fn extract(req: &'ex mut Request) -> Result<Self, StatusError> {
+        Self::parse(req).ok_or_else(|| StatusError::unauthorized().brief("Missing or malformed bearer token."))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use salvo_core::prelude::*;
@@ -205,4 +298,60 @@ mod tests {
             .unwrap();
         assert!(content.contains("Unauthorized"));
     }
+    #[tokio::test]
+    async fn test_basic_auth_closure() {
+        let auth_handler = BasicAuth::new(|username: &str, password: &str| username == "root" && password == "pwd");
+        let router = Router::with_hoop(auth_handler).goal(hello);
+        let service = Service::new(router);
+
+        let res = TestClient::get("http://127.0.0.1:5800/")
+            .basic_auth("root", Some("pwd"))
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::OK));
+        let res = TestClient::get("http://127.0.0.1:5800/").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::UNAUTHORIZED));
+        assert_eq!(res.headers()["www-authenticate"], r#"Basic realm="realm""#);
+    }
+
+    #[tokio::test]
+    async fn test_credentials_extractors() {
+        #[handler]
+        async fn whoami(credentials: BasicCredentials) -> String {
+            format!("{}:{}", credentials.username, credentials.password)
+        }
+        #[handler]
+        async fn show_token(token: BearerToken) -> String {
+            token.0
+        }
+        let router = Router::new()
+            .push(Router::with_path("whoami").get(whoami))
+            .push(Router::with_path("token").get(show_token));
+        let service = Service::new(router);
+        let access = |path: &'static str, authorization: &'static str| {
+            let service = &service;
+            async move {
+                let mut res = TestClient::get(format!("http://127.0.0.1:5800/{path}"))
+                    .add_header(AUTHORIZATION, authorization, true)
+                    .send(service)
+                    .await;
+                (res.status_code.unwrap(), res.take_string().await.unwrap())
+            }
+        };
+
+        // "密码" is encoded as UTF-8.
+        let (status, body) = access("whoami", "basic cm9vdDrlr4bnoIE=").await;
+        assert_eq!((status, body.as_str()), (StatusCode::OK, "root:密码"));
+        let (status, _) = access("whoami", "Basic not-base64").await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, _) = access("whoami", "Bearer abc").await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let (status, body) = access("token", "Bearer abc.def.ghi").await;
+        assert_eq!((status, body.as_str()), (StatusCode::OK, "abc.def.ghi"));
+        for authorization in ["Bearer ", "Bearer a b", "Basic cm9vdDpwd2Q="] {
+            let (status, _) = access("token", authorization).await;
+            assert_eq!(status, StatusCode::UNAUTHORIZED, "{authorization}");
+        }
+    }
 }
@@ -3,11 +3,30 @@
 //! This crate provides three handlers: [`ETag`], [`Modified`], and [`CachingHeaders`].
 //! Unless you are sure that you _don't_ want either etag or last-modified
 //! behavior, please use the combined [`CachingHeaders`] handler.
+//!
+//! The handlers can be added to the routers which should be cached only:
+//!
+//! ```no_run
+//! use salvo_core::prelude::*;
+//! use salvo_extra::caching_headers::ETag;
+//!
+//! #[handler]
+//! async fn list_articles() -> Json<Vec<String>> {
+//!     Json(vec!["hello".into()])
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let router = Router::with_path("articles").hoop(ETag::new()).get(list_articles);
+//!     let acceptor = TcpListener::new("0.0.0.0:5800").bind().await;
+//!     Server::new(acceptor).serve(router).await;
+//! }
+//! ```
 
 use etag::EntityTag;
 use salvo_core::http::header::{ETAG, IF_NONE_MATCH};
 use salvo_core::http::headers::{self, HeaderMapExt};
-use salvo_core::http::{ResBody, StatusCode};
+use salvo_core::http::{Method, ResBody, StatusCode};
 use salvo_core::{async_trait, Depot, FlowCtrl, Handler, Request, Response};
 
 /// Etag and If-None-Match header handler
@@ -20,10 +39,14 @@ use salvo_core::{async_trait, Depot, FlowCtrl, Handler, Request, Response};
 /// [`304 not modified`](https://developer.mozilla.org/en-US/docs/Web/HTTP/Status/304) status,
 /// omitting the response body.
 ///
+/// The etag is only generated for successful responses to `GET` and `HEAD`
+/// requests, and an etag already set by the handlers is kept.
+///
 /// ## Streamed bodies
 ///
 /// **Note**: This handler does not currently provide an etag trailer for
-/// streamed bodies, but may do so in the future.
+/// streamed bodies, but may do so in the future. Streamed bodies are passed
+/// through without etag.
 ///
 /// ## Strong vs weak comparison
 ///
@@ -60,14 +83,13 @@ impl Handler for ETag {
         if ctrl.is_ceased() {
             return;
         }
+        if !matches!(*req.method(), Method::GET | Method::HEAD)
+            || !res.status_code.unwrap_or(StatusCode::OK).is_success()
+        {
+            return;
+        }
 
-        let if_none_match = req
-            .headers()
-            .get(IF_NONE_MATCH)
-            .and_then(|etag| etag.to_str().ok())
-            .and_then(|etag| etag.parse::<EntityTag>().ok());
-
-        let etag = req
+        let etag = res
             .headers()
             .get(ETAG)
             .and_then(|etag| etag.to_str().ok())
@@ -105,17 +127,29 @@ impl Handler for ETag {
                 etag
             });
 
-        if let (Some(etag), Some(if_none_match)) = (etag, if_none_match) {
-            let eq = if self.strong {
-                etag.strong_eq(&if_none_match)
-            } else {
-                etag.weak_eq(&if_none_match)
-            };
-
-            if eq {
-                res.body(ResBody::None);
-                res.status_code(StatusCode::NOT_MODIFIED);
-            }
+        let Some(etag) = etag else {
+            return;
+        };
+        let mut if_none_match = req
+            .headers()
+            .get_all(IF_NONE_MATCH)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(str::trim);
+        let matched = if_none_match.any(|value| {
+            value == "*"
+                || value.parse::<EntityTag>().is_ok_and(|if_none_match| {
+                    if self.strong {
+                        etag.strong_eq(&if_none_match)
+                    } else {
+                        etag.weak_eq(&if_none_match)
+                    }
+                })
+        });
+        if matched {
+            res.body(ResBody::None);
+            res.status_code(StatusCode::NOT_MODIFIED);
         }
     }
 }
@@ -206,4 +240,63 @@ mod tests {
         assert_eq!(respone.status_code, Some(StatusCode::NOT_MODIFIED));
         assert!(respone.body.is_none());
     }
+
+    #[tokio::test]
+    async fn test_etag() {
+        #[handler]
+        async fn tagged(res: &mut Response) {
+            res.headers_mut().insert(ETAG, HeaderValue::from_static(r#""v1""#));
+            res.render("tagged");
+        }
+        #[handler]
+        async fn failed(res: &mut Response) {
+            res.render(StatusError::bad_request());
+        }
+        #[handler]
+        async fn streaming(res: &mut Response) {
+            res.stream(futures_util::stream::iter([Ok::<_, std::io::Error>("hello")]));
+        }
+        let router = Router::with_hoop(ETag::new())
+            .push(Router::with_path("hello").get(hello).post(hello))
+            .push(Router::with_path("tagged").get(tagged))
+            .push(Router::with_path("failed").get(failed))
+            .push(Router::with_path("stream").get(streaming));
+        let service = Service::new(router);
+
+        let res = TestClient::get("http://127.0.0.1:5800/hello").send(&service).await;
+        let etag = res.headers().get(ETAG).unwrap().to_str().unwrap().to_owned();
+        let res = TestClient::get("http://127.0.0.1:5800/hello")
+            .add_header(IF_NONE_MATCH, format!(r#""other", {etag}"#), true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::NOT_MODIFIED));
+        assert_eq!(res.headers()[ETAG], etag);
+        let res = TestClient::post("http://127.0.0.1:5800/hello")
+            .add_header(IF_NONE_MATCH, etag, true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::OK));
+        assert!(res.headers().get(ETAG).is_none());
+
+        let res = TestClient::get("http://127.0.0.1:5800/tagged")
+            .add_header(IF_NONE_MATCH, r#"W/"v1""#, true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::NOT_MODIFIED));
+        assert_eq!(res.headers()[ETAG], r#""v1""#);
+
+        let res = TestClient::get("http://127.0.0.1:5800/failed")
+            .add_header(IF_NONE_MATCH, "*", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::BAD_REQUEST));
+        assert!(res.headers().get(ETAG).is_none());
+
+        let res = TestClient::get("http://127.0.0.1:5800/stream")
+            .add_header(IF_NONE_MATCH, "*", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::OK));
+        assert!(res.headers().get(ETAG).is_none());
+    }
 }
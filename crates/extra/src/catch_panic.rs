@@ -1,8 +1,23 @@
 //! Middleware for catch panic in handlers.
 //!
-//! This middleware catches panics and write `500 Internal Server Error` into response.
+//! This middleware catches panics and write `500 Internal Server Error` into response, so a
+//! panicking handler doesn't drop the connection without any response.
 //! This middleware should be used as the first middleware.
 //!
+//! The panic is logged with its message. Create the middleware with
+//! [`CatchPanic::with_backtrace`] to log a backtrace too, it installs a global panic hook. Use
+//! [`CatchPanic::renderer`] to write a custom response, the request and the depot are still
+//! usable in it.
+//!
+//! # Unwind safety
+//!
+//! The handlers are called with [`AssertUnwindSafe`], so the state they touched may be left
+//! inconsistent when they panic. For example, the depot and the response can contain values
+//! written halfway, and a `std::sync::Mutex` locked by the panicking handler is poisoned. Avoid
+//! relying on such state in the renderer and in other requests. Panics can only be caught if
+//! the crate is built with `panic = "unwind"`, which is the default; with `panic = "abort"` the
+//! process is still aborted.
+//!
 //! # Example
 //!
 //! ```no_run
@@ -21,25 +36,109 @@
 //!     Server::new(acceptor).serve(router).await;
 //! }
 //! ```
+//!
+//! Write a custom response:
+//!
+//! ```
+//! use salvo_core::prelude::*;
+//! use salvo_extra::catch_panic::CatchPanic;
+//!
+//! let catch_panic = CatchPanic::new().renderer(|_req: &mut Request, _depot: &mut Depot, res: &mut Response, _message: &str| {
+//!     res.status_code(StatusCode::INTERNAL_SERVER_ERROR);
+//!     res.render(Text::Plain("Something went wrong, please try again later."));
+//! });
+//! ```
 
+use std::any::Any;
+use std::backtrace::{Backtrace, BacktraceStatus};
+use std::cell::RefCell;
+use std::fmt::{self, Debug, Formatter};
 use std::panic::AssertUnwindSafe;
+use std::sync::{Arc, Once};
 
 use futures_util::FutureExt;
 
 use salvo_core::http::{Request, Response, StatusError};
 use salvo_core::{async_trait, Depot, FlowCtrl, Error, Handler};
 
+type RendererFn = Arc<dyn Fn(&mut Request, &mut Depot, &mut Response, &str) + Send + Sync + 'static>;
+
+thread_local! {
+    static BACKTRACE: RefCell<Option<Backtrace>> = const { RefCell::new(None) };
+}
+
+/// Installs a panic hook which keeps the backtrace of the last panic of the thread, then calls
+/// the previous hook. The hook runs on the thread which polls the panicking future, so the
+/// backtrace can be taken after `catch_unwind` returns. It is only installed once.
+fn install_backtrace_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            BACKTRACE.with(|bt| *bt.borrow_mut() = Some(Backtrace::capture()));
+            previous(info);
+        }));
+    });
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "Box<dyn Any>"
+    }
+}
 
 /// Middleware for catch panic in handlers.
-/// 
+///
 /// View [module level documentation](index.html) for more details.
-#[derive(Default, Debug)]
-pub struct CatchPanic {}
+#[derive(Default, Clone)]
+pub struct CatchPanic {
+    renderer: Option<RendererFn>,
+    backtrace: bool,
+}
+impl Debug for CatchPanic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CatchPanic")
+            .field("renderer", &self.renderer.is_some())
+            .field("backtrace", &self.backtrace)
+            .finish()
+    }
+}
 impl CatchPanic {
     /// Create new `CatchPanic` middleware.
     #[inline]
     pub fn new() -> Self {
-        CatchPanic {}
+        CatchPanic {
+            renderer: None,
+            backtrace: false,
+        }
+    }
+
+    /// Create new `CatchPanic` middleware which logs the backtrace of the panics.
+    ///
+    /// It installs a global panic hook with [`std::panic::set_hook`] the first time it is called,
+    /// the hook records the backtrace, then calls the hook which was installed before, so it
+    /// affects every panic of the process. The backtrace is only captured if it is enabled by the
+    /// `RUST_BACKTRACE` or `RUST_LIB_BACKTRACE` environment variable.
+    pub fn with_backtrace() -> Self {
+        install_backtrace_hook();
+        CatchPanic {
+            renderer: None,
+            backtrace: true,
+        }
+    }
+
+    /// Sets the function which writes the response when a panic is caught, it is called with the
+    /// panic message.
+    ///
+    /// By default a `500 Internal Server Error` is rendered.
+    #[inline]
+    pub fn renderer(mut self, renderer: impl Fn(&mut Request, &mut Depot, &mut Response, &str) + Send + Sync + 'static) -> Self {
+        self.renderer = Some(Arc::new(renderer));
+        self
     }
 }
 
@@ -47,12 +146,26 @@ impl CatchPanic {
 impl Handler for CatchPanic {
     async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
         if let Err(e) = AssertUnwindSafe(ctrl.call_next(req, depot, res)).catch_unwind().await {
-            tracing::error!(error = ?e, "panic occurred");
-            res.render(
-                StatusError::internal_server_error()
-                    .brief("Panic occurred on server.")
-                    .cause(Error::other(format!("{e:#?}"))),
-            );
+            // The handlers after the panicking one are not called.
+            ctrl.skip_rest();
+            let message = panic_message(&*e);
+            let backtrace = if self.backtrace {
+                BACKTRACE.with(|bt| bt.borrow_mut().take())
+            } else {
+                None
+            };
+            match backtrace.filter(|bt| bt.status() == BacktraceStatus::Captured) {
+                Some(backtrace) => tracing::error!(panic = message, %backtrace, "panic occurred"),
+                None => tracing::error!(panic = message, "panic occurred"),
+            }
+            match &self.renderer {
+                Some(renderer) => renderer(req, depot, res, message),
+                None => res.render(
+                    StatusError::internal_server_error()
+                        .brief("Panic occurred on server.")
+                        .cause(Error::other(message.to_owned())),
+                ),
+            }
         }
     }
 }
@@ -74,15 +187,43 @@ mod tests {
         }
 
         let router = Router::new()
-            .hoop(CatchPanic::new())
+            .hoop(CatchPanic::with_backtrace())
             .push(Router::with_path("hello").get(hello));
 
-        TestClient::get("http://127.0.0.1:5801/hello")
-            .send(router)
-            .await
-            .take_string()
-            .await
-            .unwrap();
+        let mut res = TestClient::get("http://127.0.0.1:5801/hello").send(router).await;
+        assert_eq!(res.status_code, Some(StatusCode::INTERNAL_SERVER_ERROR));
+        res.take_string().await.unwrap();
         assert!(logs_contain("panic occurred"));
+        assert!(logs_contain("panic error!"));
+    }
+
+    #[tokio::test]
+    async fn test_catch_panic_renderer() {
+        #[handler]
+        async fn set_user(depot: &mut Depot) {
+            depot.insert("user", "jobs");
+        }
+        #[handler]
+        async fn panic_hoop() {
+            panic!("{} error", "formatted");
+        }
+        #[handler]
+        async fn hello() -> &'static str {
+            "hello"
+        }
+
+        let catch_panic = CatchPanic::new().renderer(|req: &mut Request, depot: &mut Depot, res: &mut Response, message: &str| {
+            let user = depot.get::<&str>("user").copied().unwrap_or_default();
+            res.status_code(StatusCode::SERVICE_UNAVAILABLE);
+            res.render(format!("{} {user}: {message}", req.uri().path()));
+        });
+        let router = Router::new()
+            .hoop(catch_panic)
+            .hoop(set_user)
+            .push(Router::with_path("hello").hoop(panic_hoop).get(hello));
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/hello").send(router).await;
+        assert_eq!(res.status_code, Some(StatusCode::SERVICE_UNAVAILABLE));
+        assert_eq!(res.take_string().await.unwrap(), "/hello jobs: formatted error");
     }
 }
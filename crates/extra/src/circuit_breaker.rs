@@ -0,0 +1,381 @@
+//! Middleware for failing fast when the handlers keep failing.
+//!
+//! [`CircuitBreaker`] counts the server errors (`5xx` responses) of the handlers after it over a
+//! rolling window. When the failure rate crosses the threshold, the circuit opens and requests get
+//! `503 Service Unavailable` with a `Retry-After` header directly, without calling the handlers.
+//! After the cool-down period the circuit is half-open, a few probe requests are let through, and
+//! the circuit closes again if they all succeed, or opens again on the first failure.
+//!
+//! Every `CircuitBreaker` has its own state, so attach one to each router which should be
+//! protected separately. Clones share the state, so a clone can be kept to report
+//! [`CircuitBreaker::state`] on a health endpoint.
+//!
+//! Example:
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use salvo_core::prelude::*;
+//! use salvo_extra::circuit_breaker::CircuitBreaker;
+//!
+//! #[handler]
+//! async fn upstream() -> &'static str {
+//!     "hello"
+//! }
+//!
+//! struct Health(CircuitBreaker);
+//! #[handler]
+//! impl Health {
+//!     async fn handle(&self, res: &mut Response) {
+//!         res.render(format!("{:?}", self.0.state()));
+//!     }
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let breaker = CircuitBreaker::new()
+//!         .failure_rate(0.5)
+//!         .min_requests(20)
+//!         .window(Duration::from_secs(30))
+//!         .cool_down(Duration::from_secs(10));
+//!     let router = Router::new()
+//!         .push(Router::with_path("health").get(Health(breaker.clone())))
+//!         .push(Router::with_path("upstream").hoop(breaker).get(upstream));
+//!
+//!     let acceptor = TcpListener::new("0.0.0.0:5800").bind().await;
+//!     Server::new(acceptor).serve(router).await;
+//! }
+//! ```
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+use salvo_core::http::header::RETRY_AFTER;
+use salvo_core::http::{Request, Response, StatusError};
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler};
+
+/// Number of buckets the rolling window is split to.
+const BUCKETS: usize = 10;
+
+/// State of a [`CircuitBreaker`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Requests are passed to the handlers and the failures are counted.
+    Closed,
+    /// Requests are rejected until the cool-down period is over.
+    Open,
+    /// A limited number of probe requests are passed to the handlers to check recovery.
+    HalfOpen,
+}
+
+#[derive(Clone, Copy, Default, Debug)]
+struct Bucket {
+    epoch: u64,
+    total: usize,
+    failures: usize,
+}
+
+#[derive(Debug)]
+struct Breaker {
+    state: CircuitState,
+    started: Instant,
+    buckets: [Bucket; BUCKETS],
+    open_until: Instant,
+    probes: usize,
+    successes: usize,
+}
+
+/// Middleware which rejects requests while the handlers after it keep failing.
+///
+/// View [module level documentation](index.html) for more details.
+#[derive(Clone, Debug)]
+pub struct CircuitBreaker {
+    failure_rate: f64,
+    min_requests: usize,
+    window: Duration,
+    cool_down: Duration,
+    probes: usize,
+    breaker: Arc<Mutex<Breaker>>,
+}
+impl Default for CircuitBreaker {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl CircuitBreaker {
+    /// Create a new `CircuitBreaker`.
+    ///
+    /// By default, the circuit opens when half of at least 10 requests in 10 seconds fail, and
+    /// half-opens after a cool-down of 30 seconds with 1 probe request.
+    pub fn new() -> Self {
+        let now = Instant::now();
+        Self {
+            failure_rate: 0.5,
+            min_requests: 10,
+            window: Duration::from_secs(10),
+            cool_down: Duration::from_secs(30),
+            probes: 1,
+            breaker: Arc::new(Mutex::new(Breaker {
+                state: CircuitState::Closed,
+                started: now,
+                buckets: Default::default(),
+                open_until: now,
+                probes: 0,
+                successes: 0,
+            })),
+        }
+    }
+
+    /// Sets the failure rate between `0.0` and `1.0` which opens the circuit.
+    #[inline]
+    pub fn failure_rate(mut self, failure_rate: f64) -> Self {
+        self.failure_rate = failure_rate.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Sets the minimum number of requests in the window before the failure rate is checked.
+    #[inline]
+    pub fn min_requests(mut self, min_requests: usize) -> Self {
+        self.min_requests = min_requests.max(1);
+        self
+    }
+
+    /// Sets the rolling window in which the requests are counted.
+    #[inline]
+    pub fn window(mut self, window: Duration) -> Self {
+        self.window = window.max(Duration::from_millis(BUCKETS as u64));
+        self
+    }
+
+    /// Sets how long the circuit stays open before probing recovery.
+    #[inline]
+    pub fn cool_down(mut self, cool_down: Duration) -> Self {
+        self.cool_down = cool_down;
+        self
+    }
+
+    /// Sets the number of probe requests in the half-open state, all of them must succeed to close
+    /// the circuit.
+    #[inline]
+    pub fn probes(mut self, probes: usize) -> Self {
+        self.probes = probes.max(1);
+        self
+    }
+
+    /// Returns the current state of the circuit.
+    pub fn state(&self) -> CircuitState {
+        let breaker = self.lock();
+        if breaker.state == CircuitState::Open && Instant::now() >= breaker.open_until {
+            CircuitState::HalfOpen
+        } else {
+            breaker.state
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Breaker> {
+        self.breaker.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn epoch(&self, breaker: &Breaker, now: Instant) -> u64 {
+        let span = self.window.as_millis() / BUCKETS as u128;
+        (now.duration_since(breaker.started).as_millis() / span) as u64
+    }
+
+    /// Returns `Err` with the time to retry after if the request is rejected.
+    fn acquire(&self) -> Result<(), Duration> {
+        let mut breaker = self.lock();
+        let now = Instant::now();
+        if breaker.state == CircuitState::Open {
+            if now < breaker.open_until {
+                return Err(breaker.open_until - now);
+            }
+            breaker.state = CircuitState::HalfOpen;
+            breaker.probes = 0;
+            breaker.successes = 0;
+        }
+        if breaker.state == CircuitState::HalfOpen {
+            if breaker.probes >= self.probes {
+                return Err(Duration::from_secs(1));
+            }
+            breaker.probes += 1;
+        }
+        Ok(())
+    }
+
+    fn record(&self, failed: bool) {
+        let mut breaker = self.lock();
+        let now = Instant::now();
+        match breaker.state {
+            CircuitState::Closed => {
+                let epoch = self.epoch(&breaker, now);
+                let bucket = &mut breaker.buckets[epoch as usize % BUCKETS];
+                if bucket.epoch != epoch {
+                    *bucket = Bucket {
+                        epoch,
+                        ..Default::default()
+                    };
+                }
+                bucket.total += 1;
+                if failed {
+                    bucket.failures += 1;
+                }
+                let (total, failures) = breaker
+                    .buckets
+                    .iter()
+                    .filter(|bucket| bucket.epoch + BUCKETS as u64 > epoch)
+                    .fold((0, 0), |(total, failures), bucket| {
+                        (total + bucket.total, failures + bucket.failures)
+                    });
+                if failed
+                    && total >= self.min_requests
+                    && failures as f64 >= total as f64 * self.failure_rate
+                {
+                    tracing::warn!(total, failures, "circuit breaker opened");
+                    breaker.state = CircuitState::Open;
+                    breaker.open_until = now + self.cool_down;
+                }
+            }
+            CircuitState::HalfOpen if failed => {
+                tracing::warn!("circuit breaker opened again after probing");
+                breaker.state = CircuitState::Open;
+                breaker.open_until = now + self.cool_down;
+            }
+            CircuitState::HalfOpen => {
+                breaker.successes += 1;
+                if breaker.successes >= self.probes {
+                    tracing::info!("circuit breaker closed");
+                    breaker.state = CircuitState::Closed;
+                    breaker.buckets = Default::default();
+                }
+            }
+            CircuitState::Open => {}
+        }
+    }
+}
+
+/// Records the outcome when dropped, so a cancelled request is counted as a failure.
+struct Outcome<'a> {
+    breaker: &'a CircuitBreaker,
+    failed: bool,
+}
+impl Drop for Outcome<'_> {
+    fn drop(&mut self) {
+        self.breaker.record(self.failed);
+    }
+}
+
+#[async_trait]
+impl Handler for CircuitBreaker {
+    async fn handle(
+        &self,
+        req: &mut Request,
+        depot: &mut Depot,
+        res: &mut Response,
+        ctrl: &mut FlowCtrl,
+    ) {
+        if let Err(retry_after) = self.acquire() {
+            let secs = (retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0)).max(1);
+            res.headers_mut().insert(RETRY_AFTER, secs.into());
+            res.render(StatusError::service_unavailable().brief("Circuit breaker is open."));
+            ctrl.skip_rest();
+            return;
+        }
+        let mut outcome = Outcome {
+            breaker: self,
+            failed: true,
+        };
+        ctrl.call_next(req, depot, res).await;
+        outcome.failed = res
+            .status_code
+            .map(|code| code.is_server_error())
+            .unwrap_or(false);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use salvo_core::prelude::*;
+    use salvo_core::test::TestClient;
+
+    use super::*;
+
+    static FAILING: AtomicBool = AtomicBool::new(true);
+
+    #[handler]
+    async fn upstream(res: &mut Response) {
+        if FAILING.load(Ordering::SeqCst) {
+            res.render(StatusError::bad_gateway());
+        } else {
+            res.render("ok");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_circuit_breaker() {
+        let breaker = CircuitBreaker::new()
+            .min_requests(4)
+            .failure_rate(0.5)
+            .cool_down(Duration::from_millis(200));
+        let service = Service::new(Router::with_hoop(breaker.clone()).get(upstream));
+        let access = || async {
+            TestClient::get("http://127.0.0.1:5801/")
+                .send(&service)
+                .await
+                .status_code
+                .unwrap()
+        };
+
+        for _ in 0..3 {
+            assert_eq!(access().await, StatusCode::BAD_GATEWAY);
+            assert_eq!(breaker.state(), CircuitState::Closed);
+        }
+        assert_eq!(access().await, StatusCode::BAD_GATEWAY);
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        let res = TestClient::get("http://127.0.0.1:5801/")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::SERVICE_UNAVAILABLE));
+        assert_eq!(res.headers()[RETRY_AFTER], "1");
+
+        // A failed probe opens the circuit again.
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        assert_eq!(access().await, StatusCode::BAD_GATEWAY);
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert_eq!(access().await, StatusCode::SERVICE_UNAVAILABLE);
+
+        FAILING.store(false, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        assert_eq!(access().await, StatusCode::OK);
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert_eq!(access().await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_circuit_breaker_rate() {
+        #[handler]
+        async fn flaky(req: &mut Request, res: &mut Response) {
+            if req.query::<bool>("fail").unwrap_or_default() {
+                res.status_code(StatusCode::INTERNAL_SERVER_ERROR);
+            }
+        }
+        let breaker = CircuitBreaker::new().min_requests(4).failure_rate(0.5);
+        let service = Service::new(Router::with_hoop(breaker.clone()).get(flaky));
+        for fail in [false, false, false, true, false, true] {
+            TestClient::get(format!("http://127.0.0.1:5801/?fail={fail}"))
+                .send(&service)
+                .await;
+            assert_eq!(breaker.state(), CircuitState::Closed);
+        }
+        TestClient::get("http://127.0.0.1:5801/?fail=true")
+            .send(&service)
+            .await;
+        TestClient::get("http://127.0.0.1:5801/?fail=true")
+            .send(&service)
+            .await;
+        assert_eq!(breaker.state(), CircuitState::Open);
+    }
+}
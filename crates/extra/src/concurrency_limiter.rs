@@ -2,6 +2,10 @@
 //! 
 //! Limit the max number of requests being concurrently processed.
 //!
+//! Requests over the limit wait for a permit. To shed load instead of queueing forever, bound the
+//! wait queue with [`MaxConcurrency::queue`], for example `max_concurrency(16).queue(32,
+//! Duration::from_secs(2))`. Rejected requests get `503 Service Unavailable` with `Retry-After`.
+//!
 //! Example:
 //! 
 //! ```no_run
@@ -68,24 +72,104 @@
 //! "#;
 //! ```
 
-use tokio::sync::Semaphore;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::{AcquireError, Semaphore, SemaphorePermit, TryAcquireError};
 
+use salvo_core::http::header::RETRY_AFTER;
 use salvo_core::http::StatusError;
 use salvo_core::http::{Request, Response};
 use salvo_core::{async_trait, Depot, FlowCtrl, Handler};
 
 /// MaxConcurrency
+///
+/// By default, requests over the limit wait until a permit is released. Use
+/// [`MaxConcurrency::queue`] to bound the number of waiting requests and the waiting time, the
+/// excess requests are rejected with `503 Service Unavailable` and a `Retry-After` header.
+///
+/// Clones share the same permits, so a clone can be kept to read [`MaxConcurrency::in_flight`]
+/// for metrics, or attached to several routers to share one limit.
+#[derive(Clone, Debug)]
 pub struct MaxConcurrency {
-    semaphore: Semaphore,
+    size: usize,
+    semaphore: Arc<Semaphore>,
+    waiting: Arc<AtomicUsize>,
+    queue: Option<(usize, Duration)>,
+    retry_after: Duration,
+}
+impl MaxConcurrency {
+    /// Bound the wait queue to `size` requests, each waiting at most `timeout` for a permit.
+    ///
+    /// With a `size` of `0`, requests over the limit are rejected immediately.
+    #[inline]
+    pub fn queue(mut self, size: usize, timeout: Duration) -> Self {
+        self.queue = Some((size, timeout));
+        self
+    }
+
+    /// Sets the `Retry-After` header of rejected requests, defaults to 1 second.
+    #[inline]
+    pub fn retry_after(mut self, retry_after: Duration) -> Self {
+        self.retry_after = retry_after;
+        self
+    }
+
+    /// Returns the number of requests being processed.
+    #[inline]
+    pub fn in_flight(&self) -> usize {
+        self.size - self.semaphore.available_permits()
+    }
+
+    /// Returns the number of requests waiting for a permit.
+    #[inline]
+    pub fn waiting(&self) -> usize {
+        self.waiting.load(Ordering::Relaxed)
+    }
+
+    /// Acquire a permit, returns `Ok(None)` if the request should be rejected.
+    async fn acquire(&self) -> Result<Option<SemaphorePermit<'_>>, AcquireError> {
+        match self.semaphore.try_acquire() {
+            Ok(permit) => return Ok(Some(permit)),
+            Err(TryAcquireError::Closed) => return self.semaphore.acquire().await.map(Some),
+            Err(TryAcquireError::NoPermits) => {}
+        }
+        let Some((size, timeout)) = self.queue else {
+            return self.semaphore.acquire().await.map(Some);
+        };
+        if self.waiting.fetch_add(1, Ordering::AcqRel) >= size {
+            self.waiting.fetch_sub(1, Ordering::AcqRel);
+            return Ok(None);
+        }
+        // Decrease the waiting count even if this future is dropped.
+        struct Waiting<'a>(&'a AtomicUsize);
+        impl Drop for Waiting<'_> {
+            fn drop(&mut self) {
+                self.0.fetch_sub(1, Ordering::AcqRel);
+            }
+        }
+        let _waiting = Waiting(&self.waiting);
+        match tokio::time::timeout(timeout, self.semaphore.acquire()).await {
+            Ok(permit) => permit.map(Some),
+            Err(_) => Ok(None),
+        }
+    }
 }
 #[async_trait]
 impl Handler for MaxConcurrency {
     #[inline]
     async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
-        match self.semaphore.acquire().await {
-            Ok(_) => {
+        match self.acquire().await {
+            Ok(Some(_permit)) => {
                 ctrl.call_next(req, depot, res).await;
             }
+            Ok(None) => {
+                res.headers_mut()
+                    .insert(RETRY_AFTER, self.retry_after.as_secs().max(1).into());
+                res.render(StatusError::service_unavailable().brief("Max concurrency reached."));
+                ctrl.skip_rest();
+            }
             Err(e) => {
                 tracing::error!(
                     "Max concurrency semaphore is never closed, acquire should never fail: {}",
@@ -100,6 +184,73 @@ impl Handler for MaxConcurrency {
 #[inline]
 pub fn max_concurrency(size: usize) -> MaxConcurrency {
     MaxConcurrency {
-        semaphore: Semaphore::new(size),
+        size,
+        semaphore: Arc::new(Semaphore::new(size)),
+        waiting: Arc::new(AtomicUsize::new(0)),
+        queue: None,
+        retry_after: Duration::from_secs(1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::prelude::*;
+    use salvo_core::test::TestClient;
+
+    use super::*;
+
+    #[handler]
+    async fn slow() -> &'static str {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        "hello"
+    }
+
+    async fn access(service: &Service) -> Response {
+        TestClient::get("http://127.0.0.1:5801/").send(service).await
+    }
+
+    async fn delayed(service: &Service, millis: u64) -> Response {
+        tokio::time::sleep(Duration::from_millis(millis)).await;
+        access(service).await
+    }
+
+    #[tokio::test]
+    async fn test_max_concurrency_shed() {
+        let limiter = max_concurrency(1)
+            .queue(0, Duration::from_secs(1))
+            .retry_after(Duration::from_secs(3));
+        let service = Service::new(Router::with_hoop(limiter.clone()).get(slow));
+
+        let (first, second) = tokio::join!(access(&service), async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            assert_eq!(limiter.in_flight(), 1);
+            access(&service).await
+        });
+        assert_eq!(first.status_code, Some(StatusCode::OK));
+        assert_eq!(second.status_code, Some(StatusCode::SERVICE_UNAVAILABLE));
+        assert_eq!(second.headers()[RETRY_AFTER], "3");
+        assert_eq!(limiter.in_flight(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_max_concurrency_queue() {
+        let limiter = max_concurrency(1).queue(1, Duration::from_secs(1));
+        let service = Service::new(Router::with_hoop(limiter.clone()).get(slow));
+
+        let (first, second, third) = tokio::join!(access(&service), delayed(&service, 30), async {
+            let res = delayed(&service, 60).await;
+            assert_eq!(limiter.waiting(), 1);
+            res
+        });
+        assert_eq!(first.status_code, Some(StatusCode::OK));
+        assert_eq!(second.status_code, Some(StatusCode::OK));
+        assert_eq!(third.status_code, Some(StatusCode::SERVICE_UNAVAILABLE));
+        assert_eq!(limiter.waiting(), 0);
+
+        let limiter = max_concurrency(1).queue(1, Duration::from_millis(50));
+        let service = Service::new(Router::with_hoop(limiter).get(slow));
+        let (first, second) = tokio::join!(access(&service), delayed(&service, 30));
+        assert_eq!(first.status_code, Some(StatusCode::OK));
+        assert_eq!(second.status_code, Some(StatusCode::SERVICE_UNAVAILABLE));
     }
 }
@@ -1,6 +1,10 @@
 //! Middleware force redirect to https.
 //!
 //! The force-https middleware can force all requests to use the HTTPS protocol.
+//! Cleartext requests are redirected with `308 Permanent Redirect` to the https url with the
+//! same path and query, so the method and body of the request are preserved. The scheme of the
+//! request is derived from the listener which accepted the connection, use
+//! [`Router::scheme`](salvo_core::Router::scheme) to route by it.
 //!
 //! If this middleware is applied to the Router, the protocol will be forced to
 //! convert only when the route is matched. If the page does not exist, it will
@@ -83,7 +87,7 @@ impl ForceHttps {
 #[async_trait]
 impl Handler for ForceHttps {
     async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
-        if req.uri().scheme() == Some(&Scheme::HTTPS)
+        if req.scheme() == &Scheme::HTTPS
             || self
                 .skipper
                 .as_ref()
@@ -108,6 +112,13 @@ impl Handler for ForceHttps {
     }
 }
 
+/// Create a middleware which redirects cleartext requests to https, it is the same as
+/// [`ForceHttps::new`].
+#[inline]
+pub fn https_redirect() -> ForceHttps {
+    ForceHttps::new()
+}
+
 fn redirect_host(host: &str, https_port: Option<u16>) -> Cow<'_, str> {
     match (host.split_once(':'), https_port) {
         (Some((host, _)), Some(port)) => Cow::Owned(format!("{host}:{port}")),
@@ -150,4 +161,20 @@ mod tests {
             Some(&"https://127.0.0.1:1234/".parse().unwrap())
         );
     }
+
+    #[tokio::test]
+    async fn test_https_redirect() {
+        let service = Service::new(Router::new().goal(hello)).hoop(https_redirect());
+        let response = TestClient::post("http://127.0.0.1:5800/users?page=2")
+            .add_header(HOST, "example.com", true)
+            .send(&service)
+            .await;
+        assert_eq!(response.status_code, Some(StatusCode::PERMANENT_REDIRECT));
+        assert_eq!(response.headers()[LOCATION], "https://example.com/users?page=2");
+
+        let mut req = TestClient::get("http://127.0.0.1:5800/").build();
+        *req.scheme_mut() = Scheme::HTTPS;
+        let response = service.handle(req).await;
+        assert_eq!(response.status_code, Some(StatusCode::OK));
+    }
 }
@@ -0,0 +1,281 @@
+//! Liveness and readiness endpoints for health checks, such as the probes of Kubernetes.
+//!
+//! [`HealthCheck`] serves two endpoints:
+//!
+//! - liveness (`/healthz` by default), it always responds `200 OK` while the process is up;
+//! - readiness (`/readyz` by default), it runs all registered probes concurrently and responds
+//!   `200 OK` if all of them are healthy, or `503 Service Unavailable` otherwise.
+//!
+//! Both endpoints respond a JSON summary, such as:
+//!
+//! ```json
+//! {"status": "unhealthy", "probes": {"database": {"status": "healthy"}, "cache": {"status": "unhealthy", "error": "connection refused"}}}
+//! ```
+//!
+//! # Example
+//!
+//! ```no_run
+//! use salvo_core::prelude::*;
+//! use salvo_extra::health_check::HealthCheck;
+//!
+//! #[handler]
+//! async fn hello() -> &'static str {
+//!     "Hello World"
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let health_check = HealthCheck::new()
+//!         .probe("database", || async { Ok(()) })
+//!         .probe("cache", || async { Err("connection refused".to_owned()) });
+//!     let router = Router::new().push(health_check.into_router()).get(hello);
+//!
+//!     let acceptor = TcpListener::new("0.0.0.0:5800").bind().await;
+//!     Server::new(acceptor).serve(router).await;
+//! }
+//! ```
+use std::fmt::{self, Debug, Formatter};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures_util::future::{join_all, BoxFuture};
+use serde_json::{json, Map, Value};
+
+use salvo_core::http::header::{HeaderValue, CACHE_CONTROL};
+use salvo_core::http::{Request, Response, StatusCode};
+use salvo_core::writing::Json;
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler, Router};
+
+type ProbeFn = Arc<dyn Fn() -> BoxFuture<'static, Result<(), String>> + Send + Sync>;
+
+/// Liveness and readiness endpoints.
+///
+/// View [module level documentation](index.html) for more details.
+#[derive(Clone)]
+pub struct HealthCheck {
+    liveness_path: String,
+    readiness_path: String,
+    timeout: Duration,
+    probes: Vec<(String, ProbeFn)>,
+}
+impl Default for HealthCheck {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl Debug for HealthCheck {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HealthCheck")
+            .field("liveness_path", &self.liveness_path)
+            .field("readiness_path", &self.readiness_path)
+            .field("timeout", &self.timeout)
+            .field(
+                "probes",
+                &self.probes.iter().map(|(name, _)| name).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+impl HealthCheck {
+    /// Create a new `HealthCheck` without probes.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            liveness_path: "healthz".into(),
+            readiness_path: "readyz".into(),
+            timeout: Duration::from_secs(5),
+            probes: vec![],
+        }
+    }
+
+    /// Sets the path of the liveness endpoint, default is `healthz`.
+    #[inline]
+    pub fn liveness_path(mut self, path: impl Into<String>) -> Self {
+        self.liveness_path = path.into();
+        self
+    }
+
+    /// Sets the path of the readiness endpoint, default is `readyz`.
+    #[inline]
+    pub fn readiness_path(mut self, path: impl Into<String>) -> Self {
+        self.readiness_path = path.into();
+        self
+    }
+
+    /// Sets how long a probe can run before it is unhealthy, default is 5 seconds.
+    #[inline]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Register a readiness probe, it returns `Err` with the reason if it is unhealthy.
+    #[inline]
+    pub fn probe<F, Fut>(mut self, name: impl Into<String>, probe: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), String>> + Send + 'static,
+    {
+        self.probes
+            .push((name.into(), Arc::new(move || Box::pin(probe()))));
+        self
+    }
+
+    /// Run all probes and returns whether all of them are healthy, and the JSON summary.
+    pub async fn check(&self) -> (bool, Value) {
+        let results = join_all(self.probes.iter().map(|(name, probe)| async move {
+            let result = match tokio::time::timeout(self.timeout, probe()).await {
+                Ok(result) => result,
+                Err(_) => Err(format!("timed out after {:?}", self.timeout)),
+            };
+            (name, result)
+        }))
+        .await;
+        let healthy = results.iter().all(|(_, result)| result.is_ok());
+        let probes = results
+            .into_iter()
+            .map(|(name, result)| {
+                let summary = match result {
+                    Ok(()) => json!({"status": "healthy"}),
+                    Err(error) => {
+                        tracing::warn!(probe = %name, error = %error, "health check probe failed");
+                        json!({"status": "unhealthy", "error": error})
+                    }
+                };
+                (name.clone(), summary)
+            })
+            .collect::<Map<_, _>>();
+        let status = if healthy { "healthy" } else { "unhealthy" };
+        (healthy, json!({"status": status, "probes": probes}))
+    }
+
+    /// Create a router serving the liveness and readiness endpoints.
+    pub fn into_router(self) -> Router {
+        Router::new()
+            .push(Router::with_path(self.liveness_path.clone()).get(Liveness))
+            .push(Router::with_path(self.readiness_path.clone()).get(self))
+    }
+}
+
+/// Handler of the liveness endpoint, it always responds `200 OK`.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Liveness;
+
+#[async_trait]
+impl Handler for Liveness {
+    async fn handle(
+        &self,
+        _req: &mut Request,
+        _depot: &mut Depot,
+        res: &mut Response,
+        _ctrl: &mut FlowCtrl,
+    ) {
+        res.headers_mut()
+            .insert(CACHE_CONTROL, HeaderValue::from_static("no-store"));
+        res.render(Json(json!({"status": "healthy"})));
+    }
+}
+
+/// The readiness endpoint.
+#[async_trait]
+impl Handler for HealthCheck {
+    async fn handle(
+        &self,
+        _req: &mut Request,
+        _depot: &mut Depot,
+        res: &mut Response,
+        _ctrl: &mut FlowCtrl,
+    ) {
+        let (healthy, summary) = self.check().await;
+        if !healthy {
+            res.status_code(StatusCode::SERVICE_UNAVAILABLE);
+        }
+        res.headers_mut()
+            .insert(CACHE_CONTROL, HeaderValue::from_static("no-store"));
+        res.render(Json(summary));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_health_check() {
+        let cache_ready = Arc::new(AtomicBool::new(false));
+        let health_check = HealthCheck::new()
+            .timeout(Duration::from_millis(100))
+            .probe("database", || async { Ok(()) })
+            .probe("cache", {
+                let cache_ready = cache_ready.clone();
+                move || {
+                    let ready = cache_ready.load(Ordering::SeqCst);
+                    async move {
+                        if ready {
+                            Ok(())
+                        } else {
+                            Err("connection refused".into())
+                        }
+                    }
+                }
+            })
+            .probe("slow", || async {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                Ok(())
+            });
+        let service = Service::new(health_check.into_router());
+        let access = |path: &'static str| {
+            let service = &service;
+            async move {
+                let mut res = TestClient::get(format!("http://127.0.0.1:5800/{path}"))
+                    .send(service)
+                    .await;
+                let summary = res.take_json::<Value>().await.unwrap();
+                (res.status_code.unwrap(), summary)
+            }
+        };
+
+        let (status, summary) = access("healthz").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(summary, json!({"status": "healthy"}));
+
+        let (status, summary) = access("readyz").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(
+            summary,
+            json!({"status": "unhealthy", "probes": {
+                "database": {"status": "healthy"},
+                "cache": {"status": "unhealthy", "error": "connection refused"},
+                "slow": {"status": "healthy"},
+            }})
+        );
+
+        cache_ready.store(true, Ordering::SeqCst);
+        let (status, summary) = access("readyz").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(summary["status"], "healthy");
+    }
+
+    #[tokio::test]
+    async fn test_health_check_timeout() {
+        let health_check = HealthCheck::new()
+            .readiness_path("ready")
+            .timeout(Duration::from_millis(10))
+            .probe("stuck", std::future::pending);
+        let (healthy, summary) = health_check.check().await;
+        assert!(!healthy);
+        assert_eq!(summary["probes"]["stuck"]["error"], "timed out after 10ms");
+
+        let res = TestClient::get("http://127.0.0.1:5800/ready")
+            .send(health_check.into_router())
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::SERVICE_UNAVAILABLE));
+    }
+}
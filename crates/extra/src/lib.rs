@@ -10,17 +10,23 @@
 //! | Feature | Description |
 //! | --- | --- |
 //! | [`affix-state`](affix_state) | Middleware for adding prefix and suffix to the request path |
+//! | [`alt-svc`](alt_svc) | Middleware for advertising HTTP/3 with the `Alt-Svc` header |
 //! | [`basic-auth`](basic_auth) | Middleware for basic authentication |
 //! | [`caching-headers`](caching_headers) | Middleware for setting caching headers |
 //! | [`catch-panic`](catch_panic) | Middleware for catching panics |
+//! | [`circuit-breaker`](circuit_breaker) | Middleware for failing fast when the handlers keep failing |
 //! | [`concurrency-limiter`](concurrency_limiter) | Middleware for limiting concurrency |
 //! | [`force-https`](force_https) | Middleware for forcing HTTPS |
+//! | [`health-check`](health_check) | Liveness and readiness endpoints for health checks |
+//! | [`locale`] | Middleware for negotiating the locale of the request |
 //! | [`logging`] | Middleware for logging requests and responses |
+//! | [`prometheus`] | Middleware for exporting Prometheus metrics |
 //! | [`request-id`](request_id) | Middleware for setting a request ID |
 //! | [`size-limiter`](size_limiter) | Middleware for limiting request size |
 //! | [`sse`] | Server-Sent Events (SSE) middleware |
 //! | [`timeout`] | Middleware for setting a timeout |
 //! | [`trailing-slash`](trailing_slash) | Middleware for handling trailing slashes |
+//! | [`trusted-proxy`](trusted_proxy) | Middleware for restoring the client address behind trusted proxies |
 //! | [`tower-compat`](tower_compat) | Adapters for `tower::Layer` and `tower::Service` |
 //! | [`websocket`] | WebSocket implementation |
 #![doc(html_favicon_url = "https://salvo.rs/favicon-32x32.png")]
@@ -40,6 +46,11 @@ cfg_feature! {
     pub mod affix_state;
 }
 
+cfg_feature! {
+    #![feature = "alt-svc"]
+    pub mod alt_svc;
+}
+
 cfg_feature! {
     #![feature = "force-https"]
     pub mod force_https;
@@ -49,10 +60,29 @@ cfg_feature! {
     pub mod catch_panic;
 }
 
+cfg_feature! {
+    #![feature = "circuit-breaker"]
+    pub mod circuit_breaker;
+}
+
+cfg_feature! {
+    #![feature = "health-check"]
+    pub mod health_check;
+}
+
+cfg_feature! {
+    #![feature = "locale"]
+    pub mod locale;
+}
+
 cfg_feature! {
     #![feature = "logging"]
     pub mod logging;
 }
+cfg_feature! {
+    #![feature = "prometheus"]
+    pub mod prometheus;
+}
 cfg_feature! {
     #![feature = "sse"]
     pub mod sse;
@@ -77,6 +107,10 @@ cfg_feature! {
     #![feature = "timeout"]
     pub mod timeout;
 }
+cfg_feature! {
+    #![feature = "trusted-proxy"]
+    pub mod trusted_proxy;
+}
 cfg_feature! {
     #![feature = "caching-headers"]
     pub mod caching_headers;
@@ -0,0 +1,155 @@
+//! Middleware for negotiating the locale of the request.
+//!
+//! [`LocaleNegotiator`] picks the best supported language for the `Accept-Language` header with
+//! [`Request::preferred_language`], or the default language if none is acceptable. The locale is
+//! stored in the [`Depot`] and the request extensions, read it with [`LocaleDepotExt::locale`] or
+//! the [`Locale`] extractor.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use salvo_core::prelude::*;
+//! use salvo_extra::locale::{Locale, LocaleNegotiator};
+//!
+//! #[handler]
+//! async fn hello(locale: Locale) -> &'static str {
+//!     match &*locale.0 {
+//!         "fr" => "Bonjour",
+//!         "de" => "Hallo",
+//!         _ => "Hello",
+//!     }
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let router = Router::new().hoop(LocaleNegotiator::new(["en", "fr", "de"])).get(hello);
+//!     let acceptor = TcpListener::new("0.0.0.0:5800").bind().await;
+//!     Server::new(acceptor).serve(router).await;
+//! }
+//! ```
+use salvo_core::extract::{Extractible, Metadata};
+use salvo_core::http::{Request, Response, StatusError};
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler};
+
+/// Key for the locale in depot.
+pub const LOCALE_KEY: &str = "::salvo::locale";
+
+/// Extesion for Depot.
+pub trait LocaleDepotExt {
+    /// Get the locale negotiated by [`LocaleNegotiator`] from depot.
+    fn locale(&self) -> Option<&str>;
+}
+
+impl LocaleDepotExt for Depot {
+    #[inline]
+    fn locale(&self) -> Option<&str> {
+        self.get::<String>(LOCALE_KEY).map(|v| &**v).ok()
+    }
+}
+
+/// Extractor for the locale negotiated by [`LocaleNegotiator`].
+///
+/// It responds `500 Internal Server Error` if `LocaleNegotiator` is not added before the handler.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Locale(pub String);
+
+impl<'ex> Extractible<'ex> for Locale {
+    fn metadata() -> &'ex Metadata {
+        static METADATA: Metadata = Metadata::new("");
+        &METADATA
+    }
+    #[allow(refining_impl_trait)]
+    async fn extract(req: &'ex mut Request) -> Result<Self, StatusError> {
+        req.extensions()
+            .get::<Locale>()
+            .cloned()
+            .ok_or_else(|| StatusError::internal_server_error().brief("Locale is not negotiated."))
+    }
+}
+
+/// Middleware for negotiating the locale of the request.
+///
+/// View [module level documentation](index.html) for more details.
+#[derive(Clone, Debug)]
+pub struct LocaleNegotiator {
+    supported: Vec<String>,
+    default_locale: String,
+}
+impl LocaleNegotiator {
+    /// Create a new `LocaleNegotiator` with the supported languages, the first one is the default.
+    pub fn new<I, S>(supported: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let supported: Vec<String> = supported.into_iter().map(Into::into).collect();
+        let default_locale = supported.first().cloned().unwrap_or_default();
+        Self {
+            supported,
+            default_locale,
+        }
+    }
+
+    /// Sets the language used when no supported language is acceptable.
+    #[inline]
+    pub fn default_locale(mut self, locale: impl Into<String>) -> Self {
+        self.default_locale = locale.into();
+        self
+    }
+
+    /// Get the best supported language for the request, or the default language.
+    pub fn negotiate(&self, req: &Request) -> String {
+        let supported = self.supported.iter().map(|s| &**s).collect::<Vec<_>>();
+        req.preferred_language(&supported)
+            .unwrap_or(&self.default_locale)
+            .to_owned()
+    }
+}
+
+#[async_trait]
+impl Handler for LocaleNegotiator {
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, _res: &mut Response, _ctrl: &mut FlowCtrl) {
+        let locale = self.negotiate(req);
+        req.extensions_mut().insert(Locale(locale.clone()));
+        depot.insert(LOCALE_KEY, locale);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    #[handler]
+    async fn hello(locale: Locale, depot: &mut Depot) -> String {
+        assert_eq!(depot.locale(), Some(&*locale.0));
+        locale.0
+    }
+
+    #[tokio::test]
+    async fn test_locale() {
+        let router = Router::with_hoop(LocaleNegotiator::new(["en", "fr", "de-DE"]).default_locale("fr")).get(hello);
+        let service = Service::new(router);
+        let access = |accept: &'static str| {
+            let service = &service;
+            async move {
+                TestClient::get("http://127.0.0.1:5801/")
+                    .add_header("accept-language", accept, true)
+                    .send(service)
+                    .await
+                    .take_string()
+                    .await
+                    .unwrap()
+            }
+        };
+        assert_eq!(access("de, en;q=0.5").await, "de-DE");
+        assert_eq!(access("en-US").await, "en");
+        assert_eq!(access("ja").await, "fr");
+
+        let router = Router::new().get(hello);
+        let res = TestClient::get("http://127.0.0.1:5801/").send(router).await;
+        assert_eq!(res.status_code, Some(StatusCode::INTERNAL_SERVER_ERROR));
+    }
+}
@@ -1,12 +1,19 @@
 //! A simple logging middleware.
 //!
+//! [`Logger`] writes an access log for every request when the response is completed. For
+//! streaming bodies, the log is written when the body ends or the connection is closed, so the
+//! latency and the body size cover the whole response.
+//!
+//! By default the log is written with `tracing`, use [`Logger::formatter`] to write it in another
+//! format, such as JSON lines, logfmt or the Apache combined log format.
+//!
 //! # Example
 //!
 //! ```no_run
 //! use salvo_core::prelude::*;
 //! use salvo_extra::logging::Logger;
-//! 
-//! 
+//!
+//!
 //! #[handler]
 //! async fn hello() -> &'static str {
 //!     "Hello World"
@@ -16,32 +23,141 @@
 //! async fn main() {
 //!     let router = Router::new().get(hello);
 //!     let service = Service::new(router).hoop(Logger::new());
-//! 
+//!
 //!     let acceptor = TcpListener::new("0.0.0.0:5800").bind().await;
 //!     Server::new(acceptor).serve(service).await;
 //! }
 //! ```
-use std::time::Instant;
+//!
+//! Write logfmt lines to stdout:
+//!
+//! ```
+//! use salvo_extra::logging::{AccessLog, Logger};
+//!
+//! let logger = Logger::new().formatter(|log: &AccessLog| {
+//!     println!(
+//!         "method={} path={} status={} latency_ms={} remote_addr={} request_id={}",
+//!         log.method,
+//!         log.uri.path(),
+//!         log.status.as_u16(),
+//!         log.latency.as_millis(),
+//!         log.remote_addr,
+//!         log.request_id.as_deref().unwrap_or("-"),
+//!     );
+//! });
+//! ```
+use std::fmt::{self, Debug, Formatter};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{ready, Context, Poll};
+use std::time::{Duration, Instant, SystemTime};
 
+use bytes::Bytes;
 use tracing::{Instrument, Level};
 
-use salvo_core::http::{Request, ResBody, Response, StatusCode};
-use salvo_core::{async_trait, Depot, FlowCtrl, Handler};
+use salvo_core::conn::SocketAddr;
+use salvo_core::http::body::{Body, Frame, SizeHint};
+use salvo_core::http::header::{HeaderName, REFERER, USER_AGENT};
+use salvo_core::http::uri::Uri;
+use salvo_core::http::{Method, Request, ResBody, Response, StatusCode, Version};
+use salvo_core::{async_trait, BoxedError, Depot, FlowCtrl, Handler};
+
+type FormatterFn = Arc<dyn Fn(&AccessLog) + Send + Sync + 'static>;
+
+/// Fields of an access log entry.
+#[non_exhaustive]
+#[derive(Clone, Debug)]
+pub struct AccessLog {
+    /// The time when the request is received by the logger.
+    pub time: SystemTime,
+    /// Remote address of the request.
+    pub remote_addr: SocketAddr,
+    /// Method of the request.
+    pub method: Method,
+    /// Uri of the request.
+    pub uri: Uri,
+    /// HTTP version of the request.
+    pub version: Version,
+    /// Request id, read from the request id header.
+    pub request_id: Option<String>,
+    /// Value of the `Referer` request header.
+    pub referer: Option<String>,
+    /// Value of the `User-Agent` request header.
+    pub user_agent: Option<String>,
+    /// Status code of the response.
+    pub status: StatusCode,
+    /// Size of the response body in bytes, `None` if it is unknown when the log is written.
+    pub body_size: Option<u64>,
+    /// Time from the logger is called until the response body is completed.
+    pub latency: Duration,
+}
 
 /// A simple logger middleware.
-#[derive(Default, Debug)]
-pub struct Logger {}
+#[derive(Clone)]
+pub struct Logger {
+    request_id_header: HeaderName,
+    formatter: Option<FormatterFn>,
+}
+impl Default for Logger {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl Debug for Logger {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Logger")
+            .field("request_id_header", &self.request_id_header)
+            .finish()
+    }
+}
 impl Logger {
     /// Create new `Logger` middleware.
     #[inline]
     pub fn new() -> Self {
-        Logger {}
+        Logger {
+            request_id_header: HeaderName::from_static("x-request-id"),
+            formatter: None,
+        }
+    }
+
+    /// Sets the header to read the request id from, default is `x-request-id`.
+    #[inline]
+    pub fn request_id_header(mut self, name: HeaderName) -> Self {
+        self.request_id_header = name;
+        self
+    }
+
+    /// Sets the function which writes the log, instead of logging it with `tracing`.
+    #[inline]
+    pub fn formatter(mut self, formatter: impl Fn(&AccessLog) + Send + Sync + 'static) -> Self {
+        self.formatter = Some(Arc::new(formatter));
+        self
+    }
+
+    fn write(&self, log: &AccessLog) {
+        match &self.formatter {
+            Some(formatter) => formatter(log),
+            None => tracing::info!(
+                status = %log.status,
+                duration = ?log.latency,
+                body_size = log.body_size,
+                request_id = log.request_id,
+                "Response"
+            ),
+        }
     }
 }
 
 #[async_trait]
 impl Handler for Logger {
-    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+    async fn handle(
+        &self,
+        req: &mut Request,
+        depot: &mut Depot,
+        res: &mut Response,
+        ctrl: &mut FlowCtrl,
+    ) {
         let span = tracing::span!(
             Level::INFO,
             "Request",
@@ -52,28 +168,122 @@ impl Handler for Logger {
         );
 
         async move {
+            let time = SystemTime::now();
             let now = Instant::now();
             ctrl.call_next(req, depot, res).await;
-            let duration = now.elapsed();
 
             let status = res.status_code.unwrap_or(match &res.body {
                 ResBody::None => StatusCode::NOT_FOUND,
                 ResBody::Error(e) => e.code,
                 _ => StatusCode::OK,
             });
-            tracing::info!(
-                %status,
-                ?duration,
-                "Response"
-            );
+            let header = |name| {
+                req.headers()
+                    .get(name)
+                    .and_then(|v| v.to_str().ok())
+                    .map(ToOwned::to_owned)
+            };
+            let log = AccessLog {
+                time,
+                remote_addr: req.remote_addr().clone(),
+                method: req.method().clone(),
+                uri: req.uri().clone(),
+                version: req.version(),
+                request_id: header(&self.request_id_header),
+                referer: header(&REFERER),
+                user_agent: header(&USER_AGENT),
+                status,
+                body_size: res.body.size(),
+                latency: now.elapsed(),
+            };
+            match res.body {
+                ResBody::Hyper(_)
+                | ResBody::Boxed(_)
+                | ResBody::Stream(_)
+                | ResBody::Channel(_) => {
+                    let span = tracing::Span::current();
+                    res.body = ResBody::Boxed(Box::pin(LoggedBody {
+                        inner: res.body.take(),
+                        logger: self.clone(),
+                        log: Some(log),
+                        started: now,
+                        sent: 0,
+                        span,
+                    }));
+                }
+                _ => self.write(&log),
+            }
         }
         .instrument(span)
         .await
     }
 }
 
+/// Body which writes the log when it is completed or dropped.
+struct LoggedBody {
+    inner: ResBody,
+    logger: Logger,
+    log: Option<AccessLog>,
+    started: Instant,
+    sent: u64,
+    span: tracing::Span,
+}
+impl LoggedBody {
+    fn finish(&mut self) {
+        if let Some(mut log) = self.log.take() {
+            log.body_size = Some(self.sent);
+            log.latency = self.started.elapsed();
+            let _enter = self.span.enter();
+            self.logger.write(&log);
+        }
+    }
+}
+impl Body for LoggedBody {
+    type Data = Bytes;
+    type Error = BoxedError;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = &mut *self;
+        match ready!(Pin::new(&mut this.inner).poll_frame(cx)) {
+            Some(Ok(frame)) => {
+                if let Some(data) = frame.data_ref() {
+                    this.sent += data.len() as u64;
+                }
+                Poll::Ready(Some(Ok(frame)))
+            }
+            Some(Err(e)) => {
+                this.finish();
+                Poll::Ready(Some(Err(e.into())))
+            }
+            None => {
+                this.finish();
+                Poll::Ready(None)
+            }
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+impl Drop for LoggedBody {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use std::sync::Mutex;
+
+    use futures_util::{stream, StreamExt};
     use salvo_core::prelude::*;
     use salvo_core::test::{ResponseExt, TestClient};
     use tracing_test::traced_test;
@@ -100,4 +310,64 @@ mod tests {
             .unwrap();
         assert!(logs_contain("duration"));
     }
+
+    #[tokio::test]
+    async fn test_log_formatter() {
+        #[handler]
+        async fn hello() -> &'static str {
+            "hello"
+        }
+        #[handler]
+        async fn streaming(res: &mut Response) {
+            let chunks = stream::iter(["a", "bc", "def"]).then(|chunk| async move {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                Ok::<_, std::io::Error>(chunk)
+            });
+            res.stream(chunks);
+        }
+
+        let logs = Arc::new(Mutex::new(Vec::<AccessLog>::new()));
+        let logger = Logger::new().formatter({
+            let logs = logs.clone();
+            move |log: &AccessLog| logs.lock().unwrap().push(log.clone())
+        });
+        let router = Router::with_hoop(logger)
+            .push(Router::with_path("hello").get(hello))
+            .push(Router::with_path("stream").get(streaming));
+        let service = Service::new(router);
+
+        TestClient::get("http://127.0.0.1:5801/hello?name=salvo")
+            .add_header("x-request-id", "abc", true)
+            .add_header("user-agent", "test", true)
+            .send(&service)
+            .await;
+        {
+            let logs = logs.lock().unwrap();
+            let log = &logs[0];
+            assert_eq!(log.method, Method::GET);
+            assert_eq!(
+                log.uri.path_and_query().unwrap().as_str(),
+                "/hello?name=salvo"
+            );
+            assert_eq!(log.status, StatusCode::OK);
+            assert_eq!(log.body_size, Some(5));
+            assert_eq!(log.request_id.as_deref(), Some("abc"));
+            assert_eq!(log.user_agent.as_deref(), Some("test"));
+        }
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/stream")
+            .send(&service)
+            .await;
+        assert_eq!(
+            logs.lock().unwrap().len(),
+            1,
+            "log is written when the body is completed"
+        );
+        assert_eq!(res.take_string().await.unwrap(), "abcdef");
+        drop(res);
+        let logs = logs.lock().unwrap();
+        assert_eq!(logs.len(), 2);
+        assert_eq!(logs[1].body_size, Some(6));
+        assert!(logs[1].latency >= Duration::from_millis(150));
+    }
 }
@@ -0,0 +1,259 @@
+//! Middleware for exporting Prometheus metrics.
+//!
+//! [`Metrics`] records these metrics for every request:
+//!
+//! - `http_requests_total`, a counter labeled by `method`, `path` and `status`;
+//! - `http_request_duration_seconds`, a histogram labeled by `method`, `path` and `status`;
+//! - `http_requests_in_flight`, a gauge labeled by `method` and `path`.
+//!
+//! The `path` label is the template of the matched route, such as `/users/<id>`, rather than the
+//! raw request path, so the number of series doesn't grow with the number of users. Requests
+//! which match no route are labeled `<unmatched>`.
+//!
+//! [`MetricsExporter`] serves the registry in the Prometheus text format.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use salvo_core::prelude::*;
+//! use salvo_extra::prometheus::Metrics;
+//!
+//! #[handler]
+//! async fn show_user(req: &mut Request) -> String {
+//!     format!("user {}", req.param::<u64>("id").unwrap_or_default())
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let metrics = Metrics::new();
+//!     let router = Router::new()
+//!         .push(Router::with_path("metrics").get(metrics.exporter()))
+//!         .push(Router::with_path("users/<id>").get(show_user));
+//!     let service = Service::new(router).hoop(metrics);
+//!
+//!     let acceptor = TcpListener::new("0.0.0.0:5800").bind().await;
+//!     Server::new(acceptor).serve(service).await;
+//! }
+//! ```
+use std::time::Instant;
+
+use ::prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts, Registry, TextEncoder,
+};
+
+use salvo_core::http::header::{HeaderValue, CONTENT_TYPE};
+use salvo_core::http::{Request, ResBody, Response, StatusCode, StatusError};
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler};
+
+const UNMATCHED_PATH: &str = "<unmatched>";
+
+/// Middleware for recording request metrics to a Prometheus registry.
+///
+/// View [module level documentation](index.html) for more details.
+#[derive(Clone, Debug)]
+pub struct Metrics {
+    registry: Registry,
+    requests: IntCounterVec,
+    duration: HistogramVec,
+    in_flight: IntGaugeVec,
+}
+impl Default for Metrics {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl Metrics {
+    /// Create a new `Metrics` with a new registry.
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_registry(Registry::new())
+            .expect("metrics should be registered to a new registry")
+    }
+
+    /// Create a new `Metrics` registering the metrics to your own registry, so they can be
+    /// exported together with the metrics of your application.
+    ///
+    /// Returns an error if the metrics are already registered to the registry.
+    pub fn with_registry(registry: Registry) -> Result<Self, ::prometheus::Error> {
+        let requests = IntCounterVec::new(
+            Opts::new("http_requests_total", "Total number of HTTP requests."),
+            &["method", "path", "status"],
+        )?;
+        let duration = HistogramVec::new(
+            HistogramOpts::new(
+                "http_request_duration_seconds",
+                "HTTP request latency in seconds.",
+            ),
+            &["method", "path", "status"],
+        )?;
+        let in_flight = IntGaugeVec::new(
+            Opts::new(
+                "http_requests_in_flight",
+                "Number of HTTP requests being served.",
+            ),
+            &["method", "path"],
+        )?;
+        registry.register(Box::new(requests.clone()))?;
+        registry.register(Box::new(duration.clone()))?;
+        registry.register(Box::new(in_flight.clone()))?;
+        Ok(Self {
+            registry,
+            requests,
+            duration,
+            in_flight,
+        })
+    }
+
+    /// Get the registry of the metrics.
+    #[inline]
+    pub fn registry(&self) -> &Registry {
+        &self.registry
+    }
+
+    /// Create a handler which serves the registry in the Prometheus text format.
+    #[inline]
+    pub fn exporter(&self) -> MetricsExporter {
+        MetricsExporter::new(self.registry.clone())
+    }
+}
+
+/// Decrements the in flight gauge when the request is completed or cancelled.
+struct InFlightGuard(::prometheus::IntGauge);
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.0.dec();
+    }
+}
+
+#[async_trait]
+impl Handler for Metrics {
+    async fn handle(
+        &self,
+        req: &mut Request,
+        depot: &mut Depot,
+        res: &mut Response,
+        ctrl: &mut FlowCtrl,
+    ) {
+        let method = req.method().as_str().to_owned();
+        let path = req.matched_path().unwrap_or(UNMATCHED_PATH).to_owned();
+        let in_flight = self.in_flight.with_label_values(&[&method, &path]);
+        in_flight.inc();
+        let _guard = InFlightGuard(in_flight);
+
+        let now = Instant::now();
+        ctrl.call_next(req, depot, res).await;
+        let elapsed = now.elapsed();
+
+        let status = res.status_code.unwrap_or(match &res.body {
+            ResBody::None => StatusCode::NOT_FOUND,
+            ResBody::Error(e) => e.code,
+            _ => StatusCode::OK,
+        });
+        let labels = [method.as_str(), path.as_str(), status.as_str()];
+        self.requests.with_label_values(&labels).inc();
+        self.duration
+            .with_label_values(&labels)
+            .observe(elapsed.as_secs_f64());
+    }
+}
+
+/// Handler which serves a Prometheus registry in the text format, usually mounted at `/metrics`.
+#[derive(Clone, Debug)]
+pub struct MetricsExporter {
+    registry: Registry,
+}
+impl MetricsExporter {
+    /// Create a new `MetricsExporter` for the registry.
+    #[inline]
+    pub fn new(registry: Registry) -> Self {
+        Self { registry }
+    }
+}
+
+#[async_trait]
+impl Handler for MetricsExporter {
+    async fn handle(
+        &self,
+        _req: &mut Request,
+        _depot: &mut Depot,
+        res: &mut Response,
+        _ctrl: &mut FlowCtrl,
+    ) {
+        let encoder = TextEncoder::new();
+        let mut body = Vec::new();
+        if let Err(e) = encoder.encode(&self.registry.gather(), &mut body) {
+            tracing::error!(error = ?e, "encode prometheus metrics failed");
+            res.render(StatusError::internal_server_error());
+            return;
+        }
+        match HeaderValue::from_str(encoder.format_type()) {
+            Ok(content_type) => {
+                res.headers_mut().insert(CONTENT_TYPE, content_type);
+            }
+            Err(e) => tracing::error!(error = ?e, "invalid prometheus content type"),
+        }
+        let _ = res.write_body(body);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    #[handler]
+    async fn show_user(req: &mut Request) -> String {
+        format!("user {}", req.param::<u64>("id").unwrap_or_default())
+    }
+
+    #[tokio::test]
+    async fn test_metrics() {
+        let metrics = Metrics::new();
+        let router = Router::new()
+            .push(Router::with_path("metrics").get(metrics.exporter()))
+            .push(Router::with_path("users/<id>").get(show_user));
+        let service = Service::new(router).hoop(metrics.clone());
+
+        for id in 1..=3 {
+            TestClient::get(format!("http://127.0.0.1:5801/users/{id}"))
+                .send(&service)
+                .await;
+        }
+        TestClient::get("http://127.0.0.1:5801/missing")
+            .send(&service)
+            .await;
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/metrics")
+            .send(&service)
+            .await;
+        assert_eq!(res.headers()[CONTENT_TYPE], "text/plain; version=0.0.4");
+        let body = res.take_string().await.unwrap();
+        assert!(
+            body.contains(r#"http_requests_total{method="GET",path="/users/<id>",status="200"} 3"#)
+        );
+        assert!(
+            body.contains(r#"http_requests_total{method="GET",path="<unmatched>",status="404"} 1"#)
+        );
+        assert!(body.contains(
+            r#"http_request_duration_seconds_count{method="GET",path="/users/<id>",status="200"} 3"#
+        ));
+        assert!(body.contains(r#"http_requests_in_flight{method="GET",path="/metrics"} 1"#));
+        assert!(body.contains(r#"http_requests_in_flight{method="GET",path="/users/<id>"} 0"#));
+        assert!(!body.contains("/users/1"));
+    }
+
+    #[test]
+    fn test_metrics_with_registry() {
+        let registry = Registry::new();
+        let metrics = Metrics::with_registry(registry.clone()).unwrap();
+        metrics
+            .requests
+            .with_label_values(&["GET", "/", "200"])
+            .inc();
+        assert_eq!(registry.gather().len(), 1);
+        assert!(Metrics::with_registry(registry).is_err());
+    }
+}
@@ -1,14 +1,22 @@
 //! Request id middleware.
 //!
+//! [`RequestId`] takes the id from the `X-Request-Id` request header, or generates a new one if it
+//! is absent. The id is set on the request header, so it is passed on by handlers which forward the
+//! request headers to downstream services, such as `salvo-proxy`. It is also stored in the
+//! [`Depot`] and the request extensions, and echoed on the response header.
+//!
+//! Use [`RequestIdExt::request_id`] or [`RequestIdDepotExt::request_id`] to read it, for example
+//! to include it in log lines with `tracing::info!(request_id = req.request_id(), "...")`.
+//!
 //! # Example
 //!
 //! ```no_run
 //! use salvo_core::prelude::*;
-//! use salvo_extra::request_id::RequestId;
+//! use salvo_extra::request_id::{RequestId, RequestIdExt};
 //!
 //! #[handler]
 //! async fn hello(req: &mut Request) -> String {
-//!     format!("Request id: {:?}", req.header::<String>("x-request-id"))
+//!     format!("Request id: {:?}", req.request_id())
 //! }
 //!
 //! #[tokio::main]
@@ -29,33 +37,56 @@ pub const REQUST_ID_KEY: &str = "::salvo::request_id";
 /// Extesion for Depot.
 pub trait RequestIdDepotExt {
     /// Get request id reference from depot.
-    fn csrf_token(&self) -> Option<&str>;
+    fn request_id(&self) -> Option<&str>;
+
+    /// Get request id reference from depot.
+    #[deprecated(note = "use `request_id` instead")]
+    fn csrf_token(&self) -> Option<&str> {
+        self.request_id()
+    }
 }
 
 impl RequestIdDepotExt for Depot {
     #[inline]
-    fn csrf_token(&self) -> Option<&str> {
+    fn request_id(&self) -> Option<&str> {
         self.get::<String>(REQUST_ID_KEY).map(|v|&**v).ok()
     }
 }
 
+/// The request id saved in the request extensions.
+#[derive(Clone, Debug)]
+struct RequestIdValue(String);
+
+/// Extesion for Request.
+pub trait RequestIdExt {
+    /// Get the request id set by [`RequestId`].
+    fn request_id(&self) -> Option<&str>;
+}
+
+impl RequestIdExt for Request {
+    #[inline]
+    fn request_id(&self) -> Option<&str> {
+        self.extensions().get::<RequestIdValue>().map(|v| &*v.0)
+    }
+}
+
 /// A middleware for generate request id.
 #[non_exhaustive]
 pub struct RequestId {
     /// The header name for request id.
     pub header_name: HeaderName,
-    /// Whether overwrite exists request id. Default is `true`
+    /// Whether overwrite exists request id. Default is `false`.
     pub overwrite: bool,
     /// The generator for request id.
     pub generator: Box<dyn IdGenerator + Send + Sync>,
 }
 
 impl RequestId {
-    /// Create new `CatchPanic` middleware.
+    /// Create new `RequestId` middleware.
     pub fn new() -> Self {
         Self {
             header_name: HeaderName::from_static("x-request-id"),
-            overwrite: true,
+            overwrite: false,
             generator: Box::new(UlidGenerator::new()),
         }
     }
@@ -66,7 +97,11 @@ impl RequestId {
         self
     }
 
-    /// Set whether overwrite exists request id. Default is `true`.
+    /// Set whether overwrite exists request id. Default is `false`.
+    ///
+    /// An incoming id longer than 128 bytes or containing other characters than ASCII letters,
+    /// digits, `-`, `_`, `.` and `:` is always replaced. Set it to `true` if the server is exposed
+    /// to clients which should not choose the id.
     pub fn overwrite(mut self, overwrite: bool) -> Self {
         self.overwrite = overwrite;
         self
@@ -115,14 +150,97 @@ impl IdGenerator for UlidGenerator {
     }
 }
 
+/// Max length of a request id taken from the request header.
+const MAX_ID_LEN: usize = 128;
+
+/// An incoming id is only kept if it is not too long and only contains ASCII letters, digits and
+/// `-`, `_`, `.`, `:`, so it can't be used to inject arbitrary content in the logs.
+fn is_valid_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_ID_LEN
+        && id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.' | b':'))
+}
+
 #[async_trait]
 impl Handler for RequestId {
-    async fn handle(&self, req: &mut Request, depot: &mut Depot, _res: &mut Response, _ctrl: &mut FlowCtrl) {
-        if !self.overwrite && req.headers().contains_key(&self.header_name) {
-            return;
-        }
-        let id = self.generator.generate(req, depot);
-        let _ = req.add_header(self.header_name.clone(), &id, true);
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
+        let incoming = req
+            .headers()
+            .get(&self.header_name)
+            .and_then(|v| v.to_str().ok())
+            .filter(|v| !self.overwrite && is_valid_id(v))
+            .map(ToOwned::to_owned);
+        let id = match incoming {
+            Some(id) => id,
+            None => {
+                let id = self.generator.generate(req, depot);
+                let _ = req.add_header(self.header_name.clone(), &id, true);
+                id
+            }
+        };
+        let _ = res.add_header(self.header_name.clone(), &id, true);
+        req.extensions_mut().insert(RequestIdValue(id.clone()));
         depot.insert(REQUST_ID_KEY, id);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    #[handler]
+    async fn hello(req: &mut Request, depot: &mut Depot) -> String {
+        assert_eq!(req.request_id(), depot.request_id());
+        req.request_id().unwrap_or_default().to_owned()
+    }
+
+    #[tokio::test]
+    async fn test_request_id() {
+        let router = Router::with_hoop(RequestId::new()).get(hello);
+        let service = Service::new(router);
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/").send(&service).await;
+        let id = res.headers()["x-request-id"].to_str().unwrap().to_owned();
+        assert_eq!(id.len(), 26);
+        assert_eq!(res.take_string().await.unwrap(), id);
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/")
+            .add_header("x-request-id", "abc-123", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.headers()["x-request-id"], "abc-123");
+        assert_eq!(res.take_string().await.unwrap(), "abc-123");
+
+        for invalid in ["a b", "<script>", &"a".repeat(129)] {
+            let res = TestClient::get("http://127.0.0.1:5801/")
+                .add_header("x-request-id", invalid, true)
+                .send(&service)
+                .await;
+            assert_eq!(res.headers()["x-request-id"].len(), 26);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_request_id_custom() {
+        let router = Router::with_hoop(
+            RequestId::new()
+                .header_name(HeaderName::from_static("x-trace-id"))
+                .overwrite(true)
+                .generator(|| "generated".to_owned()),
+        )
+        .get(hello);
+        let service = Service::new(router);
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/")
+            .add_header("x-trace-id", "abc-123", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.headers()["x-trace-id"], "generated");
+        assert_eq!(res.take_string().await.unwrap(), "generated");
+    }
+}
@@ -69,26 +69,117 @@
 //! </html>
 //! "#;
 //! ```
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use bytes::Bytes;
+use salvo_core::http::body::{Body, Frame, ReqBody, SizeHint};
 use salvo_core::http::StatusError;
-use salvo_core::http::{Body, Request, Response};
-use salvo_core::{async_trait, Depot, FlowCtrl, Handler};
+use salvo_core::http::{Request, Response};
+use salvo_core::{async_trait, BoxedError, Depot, FlowCtrl, Handler};
 
 /// MaxSize limit for request size.
+///
+/// A request whose `Content-Length` is larger than the limit is answered with
+/// `413 Payload Too Large` right away, the rest handlers are not called and no data of the body
+/// is read, so a client which sent `Expect: 100-continue` doesn't send it at all. A body without
+/// `Content-Length`, such as a chunked one, is limited while it is read, it fails as soon as the
+/// received bytes exceed the limit and the response is then `413 Payload Too Large` as well.
+///
+/// When several `MaxSize` are attached to nested routers, the most deeply nested one wins, so an
+/// upload route can allow a larger body than the rest of the application.
 pub struct MaxSize(pub u64);
+
+#[derive(Debug)]
+struct LimitState {
+    limit: AtomicU64,
+    exceeded: AtomicBool,
+}
+
+#[derive(Clone, Debug)]
+struct SizeLimit(Arc<LimitState>);
+
+struct LimitedBody {
+    inner: ReqBody,
+    state: Arc<LimitState>,
+    received: u64,
+}
+impl LimitedBody {
+    fn exceed(&self) -> Poll<Option<Result<Frame<Bytes>, BoxedError>>> {
+        self.state.exceeded.store(true, Ordering::Relaxed);
+        Poll::Ready(Some(Err(StatusError::payload_too_large().into())))
+    }
+}
+impl Body for LimitedBody {
+    type Data = Bytes;
+    type Error = BoxedError;
+
+    fn poll_frame(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Result<Frame<Bytes>, Self::Error>>> {
+        let this = self.get_mut();
+        let limit = this.state.limit.load(Ordering::Relaxed);
+        if this.received == 0 && this.inner.size_hint().lower() > limit {
+            return this.exceed();
+        }
+        match Pin::new(&mut this.inner).poll_frame(cx) {
+            Poll::Ready(Some(Ok(frame))) => {
+                if let Some(data) = frame.data_ref() {
+                    this.received += data.len() as u64;
+                    if this.received > limit {
+                        return this.exceed();
+                    }
+                }
+                Poll::Ready(Some(Ok(frame)))
+            }
+            Poll::Ready(Some(Err(e))) => Poll::Ready(Some(Err(e.into()))),
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
 #[async_trait]
 impl Handler for MaxSize {
     async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
-        let size_hint = req.body().size_hint().upper();
-        if let Some(upper) = size_hint {
-            if upper > self.0 {
-                res.render(StatusError::payload_too_large());
-                ctrl.skip_rest();
-            } else {
-                ctrl.call_next(req, depot, res).await;
-            }
+        let state = if let Some(SizeLimit(state)) = req.extensions().get::<SizeLimit>() {
+            state.limit.store(self.0, Ordering::Relaxed);
+            state.clone()
         } else {
-            res.render(StatusError::bad_request().brief("Request body size is unknown."));
+            let state = Arc::new(LimitState {
+                limit: AtomicU64::new(self.0),
+                exceeded: AtomicBool::new(false),
+            });
+            let body = LimitedBody {
+                inner: req.take_body(),
+                state: state.clone(),
+                received: 0,
+            };
+            req.replace_body(ReqBody::Boxed {
+                inner: Box::pin(body),
+                fusewire: None,
+            });
+            req.extensions_mut().insert(SizeLimit(state.clone()));
+            state
+        };
+        // The limit is final if no nested `MaxSize` overrides it, the lower bound of the size hint
+        // is the declared `Content-Length`.
+        if !ctrl.has_next_of::<MaxSize>() && req.body().size_hint().lower() > self.0 {
+            res.render(StatusError::payload_too_large());
             ctrl.skip_rest();
+            return;
+        }
+        ctrl.call_next(req, depot, res).await;
+        if state.exceeded.load(Ordering::Relaxed) {
+            res.render(StatusError::payload_too_large());
         }
     }
 }
@@ -110,6 +201,12 @@ mod tests {
         "hello"
     }
 
+    #[handler]
+    async fn read_body(req: &mut Request) -> Result<&'static str, salvo_core::Error> {
+        req.payload_with_max_size(usize::MAX).await?;
+        Ok("read")
+    }
+
     #[tokio::test]
     async fn test_size_limiter() {
         let limit_handler = MaxSize(32);
@@ -133,4 +230,76 @@ mod tests {
             .await;
         assert_eq!(res.status_code.unwrap(), StatusCode::PAYLOAD_TOO_LARGE);
     }
+
+    #[tokio::test]
+    async fn test_size_limiter_nested_override() {
+        let router = Router::new()
+            .hoop(max_size(8))
+            .push(Router::with_path("small").post(read_body))
+            .push(Router::with_path("upload").hoop(max_size(1024)).post(read_body));
+        let service = Service::new(router);
+        let body = "a".repeat(100);
+
+        let res = TestClient::post("http://127.0.0.1:5801/small")
+            .text(body.clone())
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code.unwrap(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let res = TestClient::post("http://127.0.0.1:5801/upload")
+            .text(body)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code.unwrap(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_size_limiter_rejects_before_reading() {
+        use std::sync::atomic::AtomicUsize;
+
+        static HANDLED: AtomicUsize = AtomicUsize::new(0);
+        #[handler]
+        async fn count() -> &'static str {
+            HANDLED.fetch_add(1, Ordering::SeqCst);
+            "counted"
+        }
+        let router = Router::new()
+            .hoop(max_size(8))
+            .push(Router::with_path("small").post(count))
+            .push(Router::with_path("upload").hoop(max_size(1024)).post(count));
+        let service = Service::new(router);
+        let body = "a".repeat(100);
+
+        let res = TestClient::post("http://127.0.0.1:5801/small")
+            .text(body.clone())
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code.unwrap(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(HANDLED.load(Ordering::SeqCst), 0);
+
+        let res = TestClient::post("http://127.0.0.1:5801/upload")
+            .text(body)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code.unwrap(), StatusCode::OK);
+        assert_eq!(HANDLED.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_size_limiter_without_content_length() {
+        let service = Service::new(Router::new().hoop(max_size(8)).post(read_body));
+        let stream = tokio_stream::iter(
+            ["abcd", "efgh", "ijkl"].map(|chunk| Ok::<_, std::io::Error>(Frame::data(Bytes::from(chunk)))),
+        );
+        let mut req = TestClient::post("http://127.0.0.1:5801/").build();
+        req.replace_body(ReqBody::Boxed {
+            inner: Box::pin(http_body_util::BodyExt::map_err(
+                http_body_util::StreamBody::new(stream),
+                Into::into,
+            )),
+            fusewire: None,
+        });
+        let res = service.handle(req).await;
+        assert_eq!(res.status_code.unwrap(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
 }
@@ -44,6 +44,9 @@
 //! Each field already is event which can be sent to client.
 //! The events with multiple fields can be created by combining fields using tuples.
 //!
+//! Wrap the stream in [`SseKeepAlive`] to send keep-alive comments while no event is ready, the
+//! `sse-chat` example shows a chat room built this way.
+//!
 //! See also the [EventSource](https://developer.mozilla.org/en-US/docs/Web/API/EventSource) API,
 //! which specifies the expected behavior of Server Sent Events.
 
@@ -107,7 +110,9 @@ impl SseEvent {
         Ok(self)
     }
 
-    /// Sets Server-sent event comment.`
+    /// Sets Server-sent event comment.
+    ///
+    /// A comment with multiple lines is sent as multiple comment lines.
     #[inline]
     pub fn comment<T: Into<String>>(mut self, comment: T) -> SseEvent {
         self.comment = Some(comment.into());
@@ -139,9 +144,11 @@ impl SseEvent {
 impl Display for SseEvent {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         if let Some(comment) = &self.comment {
-            ":".fmt(f)?;
-            comment.fmt(f)?;
-            f.write_char('\n')?;
+            for line in comment.split('\n') {
+                ":".fmt(f)?;
+                line.fmt(f)?;
+                f.write_char('\n')?;
+            }
         }
 
         if let Some(name) = &self.name {
@@ -201,7 +208,10 @@ impl Display for SseEvent {
     }
 }
 
-/// SseKeepAlive
+/// Event stream which sends a keep-alive comment when no event is sent for a while.
+///
+/// Proxies and load balancers often close connections which are idle for too long, the comment
+/// keeps the connection busy and is ignored by `EventSource` clients.
 #[allow(missing_debug_implementations)]
 #[pin_project]
 #[non_exhaustive]
@@ -239,6 +249,7 @@ where
     #[inline]
     pub fn max_interval(mut self, time: Duration) -> Self {
         self.max_interval = time;
+        self.alive_timer = time::sleep(time);
         self
     }
 
@@ -353,6 +364,24 @@ mod tests {
         assert!(text.contains("data:1"));
     }
 
+    #[tokio::test]
+    async fn test_sse_keep_alive_idle() {
+        use futures_util::StreamExt;
+
+        let event_stream = tokio_stream::iter(vec![1]).then(|i| async move {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            Ok::<_, Infallible>(SseEvent::default().text(i.to_string()))
+        });
+        let mut res = Response::new();
+        SseKeepAlive::new(event_stream)
+            .comment("ping")
+            .max_interval(Duration::from_millis(50))
+            .stream(&mut res);
+        let text = res.take_string().await.unwrap();
+        assert!(text.starts_with(":ping\n\n"));
+        assert!(text.ends_with("data:1\n\n"));
+    }
+
     #[tokio::test]
     async fn test_sse_json() {
         #[derive(Serialize, Debug)]
@@ -376,6 +405,12 @@ mod tests {
         super::stream(&mut res, event_stream);
         let text = res.take_string().await.unwrap();
         assert!(text.contains(":comment"));
+
+        let event_stream = tokio_stream::iter(vec![Ok::<_, Infallible>(SseEvent::default().comment("one\ntwo"))]);
+        let mut res = Response::new();
+        super::stream(&mut res, event_stream);
+        let text = res.take_string().await.unwrap();
+        assert_eq!(text, ":one\n:two\n\n");
     }
 
     #[tokio::test]
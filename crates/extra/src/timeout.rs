@@ -5,6 +5,17 @@
 //!
 //! This middleware can be used to deal with slow network attacks.
 //!
+//! `Timeout` only covers the handlers after it, so it can be attached to different routers to give
+//! different endpoints different budgets. Use [`Timeout::error`] to return another error, such as
+//! `504 Gateway Timeout` for handlers waiting on an upstream service.
+//!
+//! # Cancellation
+//!
+//! On expiry the future of the next handlers is dropped, so they stop at the `.await` point they
+//! are waiting on, and the code after it never runs. Work which is not cancel safe, such as writing
+//! several related records without a transaction, may be interrupted in the middle. Spawn such work
+//! with `tokio::spawn` if it must run to completion regardless of the timeout.
+//!
 //! # Example
 //!
 //! ```no_run
@@ -28,7 +39,11 @@
 //!     let router = Router::new()
 //!         .hoop(Timeout::new(Duration::from_secs(5)))
 //!         .push(Router::with_path("slow").get(slow))
-//!         .push(Router::with_path("fast").get(fast));
+//!         .push(
+//!             Router::with_path("fast")
+//!                 .hoop(Timeout::new(Duration::from_millis(100)).error(StatusError::gateway_timeout))
+//!                 .get(fast),
+//!         );
 //!
 //!     let acceptor = TcpListener::new("0.0.0.0:5800").bind().await;
 //!     Server::new(acceptor).serve(router).await;
@@ -127,4 +142,40 @@ mod tests {
             .unwrap();
         assert!(content.contains("hello"));
     }
+
+    #[tokio::test]
+    async fn test_timeout_per_router() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        static FINISHED: AtomicBool = AtomicBool::new(false);
+        #[handler]
+        async fn slow() -> &'static str {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            FINISHED.store(true, Ordering::SeqCst);
+            "hello"
+        }
+
+        let router = Router::new()
+            .push(
+                Router::with_path("short")
+                    .hoop(Timeout::new(Duration::from_millis(50)).error(StatusError::gateway_timeout))
+                    .get(slow),
+            )
+            .push(
+                Router::with_path("long")
+                    .hoop(Timeout::new(Duration::from_secs(5)))
+                    .get(slow),
+            );
+        let service = Service::new(router);
+
+        let res = TestClient::get("http://127.0.0.1:5801/short").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::GATEWAY_TIMEOUT));
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        assert!(!FINISHED.load(Ordering::SeqCst), "handler must be cancelled");
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/long").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::OK));
+        assert_eq!(res.take_string().await.unwrap(), "hello");
+        assert!(FINISHED.load(Ordering::SeqCst));
+    }
 }
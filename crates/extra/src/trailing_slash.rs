@@ -1,5 +1,17 @@
 //! Trailing slash middleware.
 //!
+//! The router matches `/users` and `/users/` with the same routes, this middleware makes the
+//! behavior consistent by redirecting the request to the path with or without the trailing slash,
+//! or by rewriting the request path with [`TrailingSlash::rewrite`].
+//!
+//! `301 Moved Permanently` is used to redirect by default, it is changed to
+//! `308 Permanent Redirect` for methods other than `GET` and `HEAD`, so clients don't send a
+//! `POST` request to the new location as a `GET` request without the body. `302 Found` is
+//! changed to `307 Temporary Redirect` in the same way.
+//!
+//! The middleware can be added to a part of the router tree only, so different paths can be
+//! handled differently. See the last example.
+//!
 //! # Examples
 //!
 //! - Add trailing slash:
@@ -43,12 +55,37 @@
 //!     Server::new(acceptor).serve(router).await;
 //! }
 //! ```
+//!
+//! - Rewrite the path of API requests without trailing slash, and redirect page requests to the
+//!   path with trailing slash:
+//!
+//! ```no_run
+//! use salvo_core::prelude::*;
+//! use salvo_core::handler::none_skipper;
+//! use salvo_extra::trailing_slash::{add_slash, remove_slash};
+//!
+//! #[handler]
+//! async fn hello() -> &'static str {
+//!     "Hello"
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     // The default skipper of `remove_slash` only handles paths with a file extension.
+//!     let api_slash = remove_slash().skipper(none_skipper).rewrite(true);
+//!     let router = Router::new()
+//!         .push(Router::with_path("api").hoop(api_slash).push(Router::with_path("users").get(hello)))
+//!         .push(Router::with_path("pages").hoop(add_slash()).push(Router::with_path("about").get(hello)));
+//!     let acceptor = TcpListener::new("0.0.0.0:5800").bind().await;
+//!     Server::new(acceptor).serve(router).await;
+//! }
+//! ```
 use std::borrow::Cow;
 use std::str::FromStr;
 
 use salvo_core::handler::Skipper;
 use salvo_core::http::uri::{PathAndQuery, Uri};
-use salvo_core::http::{ParseError, ResBody};
+use salvo_core::http::{Method, ParseError, ResBody};
 use salvo_core::prelude::*;
 
 /// TrailingSlashAction
@@ -87,6 +124,8 @@ pub struct TrailingSlash {
     pub skipper: Box<dyn Skipper>,
     /// Redirect code is used when redirect url.
     pub redirect_code: StatusCode,
+    /// Rewrite the request path instead of redirecting.
+    pub rewrite: bool,
 }
 impl TrailingSlash {
     /// Create new `TrailingSlash`.
@@ -99,6 +138,7 @@ impl TrailingSlash {
                 TrailingSlashAction::Remove => Box::new(default_remove_skipper),
             },
             redirect_code: StatusCode::MOVED_PERMANENTLY,
+            rewrite: false,
         }
     }
     /// Create new `TrailingSlash` and sets it's action as [`TrailingSlashAction::Add`].
@@ -119,11 +159,24 @@ impl TrailingSlash {
     }
 
     /// Sets redirect code and returns new `TrailingSlash`.
+    ///
+    /// `301` and `302` are changed to `308` and `307` for methods other than `GET` and `HEAD`.
     #[inline]
     pub fn redirect_code(mut self, redirect_code: StatusCode) -> Self {
         self.redirect_code = redirect_code;
         self
     }
+
+    /// Sets whether to rewrite the request path instead of redirecting, default is `false`.
+    ///
+    /// The path is rewritten after the route is matched, so the handlers after this middleware see
+    /// the new path in [`Request::uri`], but path parameters which are already parsed, such as
+    /// `<**rest>`, are not changed.
+    #[inline]
+    pub fn rewrite(mut self, rewrite: bool) -> Self {
+        self.rewrite = rewrite;
+        self
+    }
 }
 
 #[async_trait]
@@ -145,9 +198,19 @@ impl Handler for TrailingSlash {
                 None
             };
             if let Some(new_uri) = new_uri {
+                if self.rewrite {
+                    req.set_uri(new_uri);
+                    return;
+                }
                 ctrl.skip_rest();
                 res.body(ResBody::None);
-                match Redirect::with_status_code(self.redirect_code, new_uri) {
+                let redirect_code = match self.redirect_code {
+                    code if [Method::GET, Method::HEAD].contains(req.method()) => code,
+                    StatusCode::MOVED_PERMANENTLY => StatusCode::PERMANENT_REDIRECT,
+                    StatusCode::FOUND => StatusCode::TEMPORARY_REDIRECT,
+                    code => code,
+                };
+                match Redirect::with_status_code(redirect_code, new_uri) {
                     Ok(redirect) => {
                         res.render(redirect);
                     }
@@ -186,7 +249,7 @@ pub fn remove_slash() -> TrailingSlash {
 mod tests {
     use salvo_core::http::StatusCode;
     use salvo_core::prelude::*;
-    use salvo_core::test::TestClient;
+    use salvo_core::test::{ResponseExt, TestClient};
 
     use super::*;
 
@@ -230,4 +293,62 @@ mod tests {
             .await;
         assert_eq!(res.status_code.unwrap(), StatusCode::OK);
     }
+
+    #[tokio::test]
+    async fn test_slash_redirect_code() {
+        let router =
+            Router::with_hoop(add_slash()).push(Router::with_path("hello").get(hello).post(hello));
+        let service = Service::new(router);
+        let res = TestClient::post("http://127.0.0.1:5800/hello")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code.unwrap(), StatusCode::PERMANENT_REDIRECT);
+        assert_eq!(res.headers()["location"], "http://127.0.0.1:5800/hello/");
+
+        let router = Router::with_hoop(add_slash().redirect_code(StatusCode::FOUND))
+            .push(Router::with_path("hello").post(hello));
+        let res = TestClient::post("http://127.0.0.1:5800/hello")
+            .send(router)
+            .await;
+        assert_eq!(res.status_code.unwrap(), StatusCode::TEMPORARY_REDIRECT);
+    }
+    #[tokio::test]
+    async fn test_slash_rewrite() {
+        #[handler]
+        async fn path(req: &mut Request) -> String {
+            req.uri().to_string()
+        }
+        let router = Router::new()
+            .push(
+                Router::with_path("api")
+                    .hoop(
+                        remove_slash()
+                            .skipper(salvo_core::handler::none_skipper)
+                            .rewrite(true),
+                    )
+                    .push(Router::with_path("users").get(path)),
+            )
+            .push(
+                Router::with_path("pages")
+                    .hoop(add_slash().rewrite(true))
+                    .push(Router::with_path("about").get(path)),
+            );
+        let service = Service::new(router);
+        let mut res = TestClient::get("http://127.0.0.1:5800/api/users/?page=1")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code.unwrap(), StatusCode::OK);
+        assert_eq!(
+            res.take_string().await.unwrap(),
+            "http://127.0.0.1:5800/api/users?page=1"
+        );
+
+        let mut res = TestClient::get("http://127.0.0.1:5800/pages/about")
+            .send(&service)
+            .await;
+        assert_eq!(
+            res.take_string().await.unwrap(),
+            "http://127.0.0.1:5800/pages/about/"
+        );
+    }
 }
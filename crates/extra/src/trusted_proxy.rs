@@ -0,0 +1,286 @@
+//! Middleware for restoring the client address behind trusted reverse proxies.
+//!
+//! When the server runs behind a load balancer or reverse proxy, [`Request::remote_addr`] is the
+//! address of the proxy rather than the client. [`TrustedProxy`] replaces it with the address from
+//! the `Forwarded` or `X-Forwarded-For` header, but only when the connection comes from one of the
+//! configured proxy networks, so a client connecting directly can not spoof its address.
+//!
+//! The header is read from right to left, skipping trusted proxies, and the first address that is
+//! not trusted is taken as the client address.
+//!
+//! Example:
+//!
+//! ```no_run
+//! use salvo_core::prelude::*;
+//! use salvo_extra::trusted_proxy::{RemoteAddr, TrustedProxy};
+//!
+//! #[handler]
+//! async fn hello(remote_addr: RemoteAddr) -> String {
+//!     format!("Hello {}", remote_addr.0)
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let trusted_proxy = TrustedProxy::new()
+//!         .trust("10.0.0.0/8".parse().unwrap())
+//!         .trust("127.0.0.1".parse().unwrap());
+//!     let router = Router::with_hoop(trusted_proxy).get(hello);
+//!
+//!     let acceptor = TcpListener::new("0.0.0.0:5800").bind().await;
+//!     Server::new(acceptor).serve(router).await;
+//! }
+//! ```
+use std::convert::Infallible;
+use std::fmt::{self, Display, Formatter};
+use std::net::{IpAddr, SocketAddr as StdSocketAddr};
+use std::str::FromStr;
+
+use salvo_core::conn::SocketAddr;
+use salvo_core::extract::{Extractible, Metadata};
+use salvo_core::http::header::{HeaderName, FORWARDED};
+use salvo_core::http::{ParseError, Request, Response};
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler};
+
+/// Header name of `X-Forwarded-For`.
+pub const X_FORWARDED_FOR: HeaderName = HeaderName::from_static("x-forwarded-for");
+
+/// An IP network in CIDR notation, such as `10.0.0.0/8` or `fd00::/8`.
+///
+/// A single address without prefix length, such as `127.0.0.1`, matches only itself.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct IpCidr {
+    addr: IpAddr,
+    prefix_len: u8,
+}
+impl IpCidr {
+    /// Create a new `IpCidr`, returns `None` if `prefix_len` is too large for the address family.
+    pub fn new(addr: IpAddr, prefix_len: u8) -> Option<Self> {
+        let max_len = if addr.is_ipv4() { 32 } else { 128 };
+        (prefix_len <= max_len).then_some(Self { addr, prefix_len })
+    }
+
+    /// Check if the network contains `ip`, IPv4-mapped IPv6 addresses match IPv4 networks.
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        let ip = match (self.addr, ip) {
+            (IpAddr::V4(_), IpAddr::V6(v6)) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(ip),
+            _ => ip,
+        };
+        match (self.addr, ip) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix_len as u32).unwrap_or(0);
+                u32::from(net) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix_len as u32).unwrap_or(0);
+                u128::from(net) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+impl From<IpAddr> for IpCidr {
+    fn from(addr: IpAddr) -> Self {
+        let prefix_len = if addr.is_ipv4() { 32 } else { 128 };
+        Self { addr, prefix_len }
+    }
+}
+impl FromStr for IpCidr {
+    type Err = ParseError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let invalid = || ParseError::other(format!("invalid ip cidr: `{input}`"));
+        match input.split_once('/') {
+            Some((addr, prefix_len)) => {
+                let addr = addr.parse::<IpAddr>().map_err(|_| invalid())?;
+                let prefix_len = prefix_len.parse::<u8>().map_err(|_| invalid())?;
+                Self::new(addr, prefix_len).ok_or_else(invalid)
+            }
+            None => input.parse::<IpAddr>().map(Into::into).map_err(|_| invalid()),
+        }
+    }
+}
+impl Display for IpCidr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix_len)
+    }
+}
+
+/// Middleware which sets [`Request::remote_addr`] from the forwarding headers of trusted proxies.
+///
+/// The `Forwarded` header is preferred, `X-Forwarded-For` is used when it is absent.
+#[derive(Clone, Debug, Default)]
+pub struct TrustedProxy {
+    trusted: Vec<IpCidr>,
+}
+impl TrustedProxy {
+    /// Create new `TrustedProxy` middleware which trusts no proxy yet.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Trust proxies from the `cidr` network.
+    pub fn trust(mut self, cidr: IpCidr) -> Self {
+        self.trusted.push(cidr);
+        self
+    }
+
+    fn is_trusted(&self, ip: IpAddr) -> bool {
+        self.trusted.iter().any(|cidr| cidr.contains(ip))
+    }
+
+    /// Returns the client address according to the forwarding headers.
+    fn client_addr(&self, req: &Request) -> Option<StdSocketAddr> {
+        let peer = req.remote_addr().clone().into_std()?;
+        let hops = if req.headers().contains_key(FORWARDED) {
+            req.headers()
+                .get_all(FORWARDED)
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .flat_map(|value| value.split(','))
+                .map(forwarded_for)
+                .collect::<Vec<_>>()
+        } else {
+            req.headers()
+                .get_all(X_FORWARDED_FOR)
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .flat_map(|value| value.split(','))
+                .map(|hop| parse_node(hop.trim()))
+                .collect::<Vec<_>>()
+        };
+
+        let mut client = peer;
+        for hop in hops.into_iter().rev() {
+            if !self.is_trusted(client.ip()) {
+                break;
+            }
+            match hop {
+                Some(hop) => client = hop,
+                None => break,
+            }
+        }
+        (client != peer).then_some(client)
+    }
+}
+
+/// Returns the `for` parameter of one element of the `Forwarded` header.
+fn forwarded_for(element: &str) -> Option<StdSocketAddr> {
+    element.split(';').find_map(|pair| {
+        let (name, value) = pair.trim().split_once('=')?;
+        if name.trim().eq_ignore_ascii_case("for") {
+            parse_node(value.trim().trim_matches('"'))
+        } else {
+            None
+        }
+    })
+}
+
+/// Parse a node such as `192.0.2.43`, `192.0.2.43:47011`, `[2001:db8::1]:4711` or `2001:db8::1`.
+fn parse_node(node: &str) -> Option<StdSocketAddr> {
+    if let Ok(addr) = node.parse::<StdSocketAddr>() {
+        return Some(addr);
+    }
+    let ip = node.strip_prefix('[').and_then(|node| node.strip_suffix(']')).unwrap_or(node);
+    ip.parse::<IpAddr>().ok().map(|ip| StdSocketAddr::new(ip, 0))
+}
+
+#[async_trait]
+impl Handler for TrustedProxy {
+    async fn handle(&self, req: &mut Request, _depot: &mut Depot, _res: &mut Response, _ctrl: &mut FlowCtrl) {
+        if let Some(client) = self.client_addr(req) {
+            *req.remote_addr_mut() = client.into();
+        }
+    }
+}
+
+/// Extractor for the remote address of the request.
+///
+/// It is the address of the peer, or the client address restored by [`TrustedProxy`] when this
+/// middleware runs before the handler.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RemoteAddr(pub SocketAddr);
+
+impl<'ex> Extractible<'ex> for RemoteAddr {
+    fn metadata() -> &'ex Metadata {
+        static METADATA: Metadata = Metadata::new("");
+        &METADATA
+    }
+    #[allow(refining_impl_trait)]
+    async fn extract(req: &'ex mut Request) -> Result<Self, Infallible> {
+        Ok(Self(req.remote_addr().clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    #[test]
+    fn test_ip_cidr() {
+        let cidr: IpCidr = "10.0.0.0/8".parse().unwrap();
+        assert!(cidr.contains("10.1.2.3".parse().unwrap()));
+        assert!(!cidr.contains("11.1.2.3".parse().unwrap()));
+        assert!(cidr.contains("::ffff:10.1.2.3".parse().unwrap()));
+        let cidr: IpCidr = "fd00::/8".parse().unwrap();
+        assert!(cidr.contains("fd12::1".parse().unwrap()));
+        assert!(!cidr.contains("fe80::1".parse().unwrap()));
+        let cidr: IpCidr = "127.0.0.1".parse().unwrap();
+        assert_eq!(cidr.to_string(), "127.0.0.1/32");
+        assert!(!cidr.contains("127.0.0.2".parse().unwrap()));
+        assert!("0.0.0.0/0".parse::<IpCidr>().unwrap().contains("1.2.3.4".parse().unwrap()));
+        assert!("10.0.0.0/33".parse::<IpCidr>().is_err());
+        assert!("localhost".parse::<IpCidr>().is_err());
+    }
+
+    #[test]
+    fn test_parse_node() {
+        assert_eq!(parse_node("192.0.2.43"), Some(([192, 0, 2, 43], 0).into()));
+        assert_eq!(parse_node("192.0.2.43:47011"), Some(([192, 0, 2, 43], 47011).into()));
+        assert_eq!(parse_node("[2001:db8::1]:4711"), "[2001:db8::1]:4711".parse().ok());
+        assert_eq!(parse_node("[2001:db8::1]"), "[2001:db8::1]:0".parse().ok());
+        assert_eq!(parse_node("unknown"), None);
+        assert_eq!(
+            forwarded_for(r#"proto=http; for="[2001:db8:cafe::17]:4711";by=203.0.113.43"#),
+            "[2001:db8:cafe::17]:4711".parse().ok()
+        );
+    }
+
+    #[handler]
+    async fn remote_ip(remote_addr: RemoteAddr) -> String {
+        remote_addr.0.ip().map(|ip| ip.to_string()).unwrap_or_default()
+    }
+
+    async fn access(peer: &str, header: (&'static str, &'static str)) -> String {
+        let trusted_proxy = TrustedProxy::new()
+            .trust("127.0.0.1".parse().unwrap())
+            .trust("10.0.0.0/8".parse().unwrap());
+        let router = Router::with_hoop(trusted_proxy).get(remote_ip);
+        let mut req = TestClient::get("http://127.0.0.1:5800/")
+            .add_header(header.0, header.1, true)
+            .build();
+        *req.remote_addr_mut() = peer.parse::<StdSocketAddr>().unwrap().into();
+        Service::new(router).handle(req).await.take_string().await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_trusted_proxy() {
+        assert_eq!(access("127.0.0.1:9000", ("x-forwarded-for", "203.0.113.7")).await, "203.0.113.7");
+        assert_eq!(
+            access("127.0.0.1:9000", ("x-forwarded-for", "198.51.100.1, 203.0.113.7, 10.0.0.2")).await,
+            "203.0.113.7"
+        );
+        assert_eq!(
+            access("127.0.0.1:9000", ("forwarded", "for=203.0.113.7;proto=https, for=10.0.0.2")).await,
+            "203.0.113.7"
+        );
+        assert_eq!(access("10.0.0.2:9000", ("x-forwarded-for", "unknown")).await, "10.0.0.2");
+    }
+
+    #[tokio::test]
+    async fn test_untrusted_peer_is_kept() {
+        assert_eq!(access("198.51.100.9:9000", ("x-forwarded-for", "203.0.113.7")).await, "198.51.100.9");
+    }
+}
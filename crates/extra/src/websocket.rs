@@ -81,22 +81,26 @@ use std::borrow::Cow;
 use std::fmt::{self, Debug, Formatter};
 use std::future::Future;
 use std::pin::Pin;
-use std::task::{Context, Poll, ready};
+use std::task::{Context, Poll};
+use std::time::Duration;
 
 use futures_util::sink::{Sink, SinkExt};
 use futures_util::stream::{Stream, StreamExt};
 use futures_util::{future, FutureExt, TryFutureExt};
 use hyper::upgrade::OnUpgrade;
-use salvo_core::http::header::{SEC_WEBSOCKET_VERSION, UPGRADE};
+use salvo_core::http::header::{HeaderValue, SEC_WEBSOCKET_PROTOCOL, SEC_WEBSOCKET_VERSION, UPGRADE};
 use salvo_core::http::headers::{Connection, HeaderMapExt, SecWebsocketAccept, SecWebsocketKey, Upgrade};
 use salvo_core::http::{StatusCode, StatusError};
 use salvo_core::rt::tokio::TokioIo;
 use salvo_core::{Error, Request, Response};
+use tokio::time::{self, Instant, Sleep};
 use tokio_tungstenite::{
     tungstenite::protocol::{self, WebSocketConfig},
     WebSocketStream,
 };
 
+pub use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+
 /// Creates a WebSocket Handler.
 /// Request:
 /// - Method must be `GET`
@@ -110,9 +114,14 @@ use tokio_tungstenite::{
 /// - Header `connection: upgrade`
 /// - Header `upgrade: websocket`
 /// - Header `sec-websocket-accept` with the hash value of the received key.
+/// - Header `sec-websocket-protocol` with the selected subprotocol, if [`protocols`](Self::protocols)
+///   is set and one of them is requested.
 #[allow(missing_debug_implementations)]
 pub struct WebSocketUpgrade {
     config: Option<WebSocketConfig>,
+    keepalive: Option<(Duration, Duration)>,
+    protocols: Vec<Cow<'static, str>>,
+    require_protocol: bool,
 }
 
 impl Default for WebSocketUpgrade {
@@ -126,13 +135,23 @@ impl WebSocketUpgrade {
     /// Create new `WebSocketUpgrade`.
     #[inline]
     pub fn new() -> Self {
-        WebSocketUpgrade { config: None }
+        WebSocketUpgrade {
+            config: None,
+            keepalive: None,
+            protocols: Vec::new(),
+            require_protocol: false,
+        }
     }
 
     /// Create new `WebSocketUpgrade` with config.
     #[inline]
     pub fn with_config(config: WebSocketConfig) -> Self {
-        WebSocketUpgrade { config: Some(config) }
+        WebSocketUpgrade {
+            config: Some(config),
+            keepalive: None,
+            protocols: Vec::new(),
+            require_protocol: false,
+        }
     }
 
     /// The target minimum size of the write buffer to reach before writing the data
@@ -195,6 +214,53 @@ impl WebSocketUpgrade {
         self
     }
 
+    /// Send a `Ping` frame every `interval` and close the connection if no frame is received from
+    /// the client within `timeout`.
+    ///
+    /// Pings are sent and the timeout is checked while the [`WebSocket`] is polled for incoming
+    /// messages, so the callback should keep calling [`WebSocket::recv`]. When the timeout
+    /// elapses `recv` returns an error, followed by `None`.
+    #[inline]
+    pub fn keepalive(mut self, interval: Duration, timeout: Duration) -> Self {
+        self.keepalive = Some((interval, timeout));
+        self
+    }
+
+    /// Set the subprotocols supported by the server.
+    ///
+    /// The first protocol in the client's `sec-websocket-protocol` header which is also in this
+    /// list is selected, echoed back in the handshake response and available from
+    /// [`WebSocket::protocol`].
+    #[inline]
+    pub fn protocols<I, P>(mut self, protocols: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<Cow<'static, str>>,
+    {
+        self.protocols = protocols.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Reject the upgrade with `400 Bad Request` if the client does not request any of the
+    /// [`protocols`](Self::protocols).
+    ///
+    /// By default such clients are connected without a subprotocol.
+    #[inline]
+    pub fn require_protocol(mut self, require: bool) -> Self {
+        self.require_protocol = require;
+        self
+    }
+
+    fn select_protocol(&self, req: &Request) -> Option<String> {
+        req.headers()
+            .get_all(SEC_WEBSOCKET_PROTOCOL)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .map(str::trim)
+            .find(|requested| self.protocols.iter().any(|p| p == requested))
+            .map(ToOwned::to_owned)
+    }
 
     /// Upgrade websocket request.
     pub async fn upgrade<F, Fut>(&self, req: &mut Request, res: &mut Response, callback: F) -> Result<(), StatusError>
@@ -235,15 +301,26 @@ impl WebSocketUpgrade {
             tracing::debug!("sec_websocket_key is not exist in request headers");
             return Err(StatusError::bad_request().brief("sec_websocket_key is not exist in request headers."));
         };
+        let protocol = self.select_protocol(req);
+        if protocol.is_none() && self.require_protocol {
+            tracing::debug!("none of the requested websocket subprotocols is supported");
+            return Err(StatusError::bad_request().brief("None of the requested websocket subprotocols is supported."));
+        }
 
         res.status_code(StatusCode::SWITCHING_PROTOCOLS);
 
         res.headers_mut().typed_insert(Connection::upgrade());
         res.headers_mut().typed_insert(Upgrade::websocket());
         res.headers_mut().typed_insert(SecWebsocketAccept::from(sec_ws_key));
+        if let Some(protocol) = &protocol {
+            if let Ok(value) = HeaderValue::from_str(protocol) {
+                res.headers_mut().insert(SEC_WEBSOCKET_PROTOCOL, value);
+            }
+        }
 
         if let Some(on_upgrade) = req.extensions_mut().remove::<OnUpgrade>() {
             let config = self.config;
+            let keepalive = self.keepalive;
             tokio::spawn(async move {
                 let socket = on_upgrade
                     .and_then(move |upgraded| {
@@ -251,7 +328,12 @@ impl WebSocketUpgrade {
                         WebSocket::from_raw_socket(upgraded, protocol::Role::Server, config).map(Ok)
                     })
                     .await
-                    .expect("connection upgrade failed");
+                    .expect("connection upgrade failed")
+                    .with_protocol(protocol);
+                let socket = match keepalive {
+                    Some((interval, timeout)) => socket.with_keepalive(interval, timeout),
+                    None => socket,
+                };
                 callback(socket).await;
             });
             Ok(())
@@ -269,6 +351,16 @@ impl WebSocketUpgrade {
 /// `WebSocket`.
 pub struct WebSocket {
     inner: WebSocketStream<TokioIo<hyper::upgrade::Upgraded>>,
+    keepalive: Option<KeepAlive>,
+    protocol: Option<String>,
+}
+
+struct KeepAlive {
+    interval: Duration,
+    timeout: Duration,
+    ping_timer: Pin<Box<Sleep>>,
+    idle_timer: Pin<Box<Sleep>>,
+    expired: bool,
 }
 
 impl WebSocket {
@@ -279,10 +371,39 @@ impl WebSocket {
         config: Option<protocol::WebSocketConfig>,
     ) -> Self {
         WebSocketStream::from_raw_socket(TokioIo::new(upgraded), role, config)
-            .map(|inner| WebSocket { inner })
+            .map(|inner| WebSocket {
+                inner,
+                keepalive: None,
+                protocol: None,
+            })
             .await
     }
 
+    fn with_protocol(mut self, protocol: Option<String>) -> Self {
+        self.protocol = protocol;
+        self
+    }
+
+    fn with_keepalive(mut self, interval: Duration, timeout: Duration) -> Self {
+        self.keepalive = Some(KeepAlive {
+            interval,
+            timeout,
+            ping_timer: Box::pin(time::sleep(interval)),
+            idle_timer: Box::pin(time::sleep(timeout)),
+            expired: false,
+        });
+        self
+    }
+
+    /// Returns the subprotocol selected during the handshake.
+    ///
+    /// It is `None` unless [`WebSocketUpgrade::protocols`] is set and the client requested one of
+    /// them.
+    #[inline]
+    pub fn protocol(&self) -> Option<&str> {
+        self.protocol.as_deref()
+    }
+
     /// Receive another message.
     ///
     /// Returns `None` if the stream has closed.
@@ -300,6 +421,31 @@ impl WebSocket {
     pub async fn close(mut self) -> Result<(), Error> {
         future::poll_fn(|cx| Pin::new(&mut self).poll_close(cx)).await
     }
+
+    /// Gracefully close this websocket with a close code and reason, such as
+    /// `CloseCode::Away` and `"going away"` when the server shuts down.
+    ///
+    /// The Close frame is sent, then the messages from the peer are discarded until it replies
+    /// with its own Close frame. Returns an error if the peer does not reply within `timeout`,
+    /// the connection is dropped in both cases.
+    pub async fn close_with(
+        mut self,
+        code: impl Into<u16>,
+        reason: impl Into<Cow<'static, str>>,
+        timeout: Duration,
+    ) -> Result<(), Error> {
+        self.send(Message::close_with(code, reason)).await?;
+        let handshake = async {
+            while let Some(msg) = self.inner.next().await {
+                if msg.is_err() {
+                    break;
+                }
+            }
+        };
+        time::timeout(timeout, handshake)
+            .await
+            .map_err(|_| Error::other("websocket close handshake timed out"))
+    }
 }
 
 impl Stream for WebSocket {
@@ -307,17 +453,49 @@ impl Stream for WebSocket {
 
     #[inline]
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
-        match ready!(Pin::new(&mut self.inner).poll_next(cx)) {
-            Some(Ok(item)) => Poll::Ready(Some(Ok(Message { inner: item }))),
-            Some(Err(e)) => {
+        let this = &mut *self;
+        if this.keepalive.as_ref().map(|keepalive| keepalive.expired).unwrap_or(false) {
+            return Poll::Ready(None);
+        }
+        match Pin::new(&mut this.inner).poll_next(cx) {
+            Poll::Ready(Some(Ok(item))) => {
+                if let Some(keepalive) = &mut this.keepalive {
+                    keepalive.idle_timer.as_mut().reset(Instant::now() + keepalive.timeout);
+                }
+                return Poll::Ready(Some(Ok(Message { inner: item })));
+            }
+            Poll::Ready(Some(Err(e))) => {
                 tracing::debug!("websocket poll error: {}", e);
-                Poll::Ready(Some(Err(Error::other(e))))
+                return Poll::Ready(Some(Err(Error::other(e))));
             }
-            None => {
+            Poll::Ready(None) => {
                 tracing::debug!("websocket closed");
-                Poll::Ready(None)
+                return Poll::Ready(None);
+            }
+            Poll::Pending => {}
+        }
+
+        let Some(keepalive) = &mut this.keepalive else {
+            return Poll::Pending;
+        };
+        if keepalive.idle_timer.as_mut().poll(cx).is_ready() {
+            tracing::debug!("websocket keepalive timeout");
+            keepalive.expired = true;
+            return Poll::Ready(Some(Err(Error::other("websocket keepalive timeout"))));
+        }
+        while keepalive.ping_timer.as_mut().poll(cx).is_ready() {
+            keepalive.ping_timer.as_mut().reset(Instant::now() + keepalive.interval);
+            // If the sink is busy the ping is skipped, the next one is sent after another interval.
+            if let Poll::Ready(Ok(())) = Pin::new(&mut this.inner).poll_ready(cx) {
+                if let Err(e) = Pin::new(&mut this.inner).start_send(protocol::Message::Ping(Vec::new())) {
+                    return Poll::Ready(Some(Err(Error::other(e))));
+                }
+                if let Poll::Ready(Err(e)) = Pin::new(&mut this.inner).poll_flush(cx) {
+                    return Poll::Ready(Some(Err(Error::other(e))));
+                }
             }
         }
+        Poll::Pending
     }
 }
 
@@ -525,6 +703,154 @@ mod tests {
             .await
     }
 
+    async fn handshake(
+        addr: std::net::SocketAddr,
+        path: &str,
+        protocols: Option<&'static str>,
+    ) -> hyper::Response<hyper::body::Incoming> {
+        let stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let (mut sender, conn) = hyper::client::conn::http1::handshake(TokioIo::new(stream)).await.unwrap();
+        tokio::task::spawn(conn.with_upgrades());
+        let mut req = hyper::Request::builder()
+            .uri(format!("http://{}/{}", addr, path))
+            .header(UPGRADE, "websocket")
+            .header(CONNECTION, "Upgrade")
+            .header(SEC_WEBSOCKET_KEY, "6D69KGBOr4Re+Nj6zx9aQA==")
+            .header(SEC_WEBSOCKET_VERSION, "13");
+        if let Some(protocols) = protocols {
+            req = req.header(SEC_WEBSOCKET_PROTOCOL, protocols);
+        }
+        let req = req.body(http_body_util::Empty::<hyper::body::Bytes>::new()).unwrap();
+        sender.send_request(req).await.unwrap()
+    }
+
+    async fn connect_client(
+        res: hyper::Response<hyper::body::Incoming>,
+    ) -> tokio_tungstenite::WebSocketStream<TokioIo<hyper::upgrade::Upgraded>> {
+        assert_eq!(res.status(), StatusCode::SWITCHING_PROTOCOLS);
+        let upgraded = hyper::upgrade::on(res).await.unwrap();
+        WebSocketStream::from_raw_socket(TokioIo::new(upgraded), protocol::Role::Client, None).await
+    }
+
+    #[tokio::test]
+    async fn test_websocket_protocols() {
+        #[handler]
+        async fn connect(req: &mut Request, res: &mut Response) -> Result<(), StatusError> {
+            WebSocketUpgrade::new()
+                .protocols(["json", "cbor"])
+                .require_protocol(req.query::<bool>("require").unwrap_or_default())
+                .upgrade(req, res, |mut ws| async move {
+                    let protocol = ws.protocol().unwrap_or("none").to_owned();
+                    let _ = ws.send(Message::text(protocol)).await;
+                })
+                .await
+        }
+        let router = Router::new().goal(connect);
+        let acceptor = TcpListener::new("127.0.0.1:0").bind().await;
+        let addr = acceptor.holdings()[0].local_addr.clone().into_std().unwrap();
+        tokio::spawn(async move {
+            Server::new(acceptor).serve(router).await;
+        });
+
+        let res = handshake(addr, "", Some("xml, cbor, json")).await;
+        assert_eq!(res.headers().get(SEC_WEBSOCKET_PROTOCOL).unwrap(), "cbor");
+        let mut client = connect_client(res).await;
+        assert_eq!(client.next().await.unwrap().unwrap(), protocol::Message::text("cbor"));
+
+        let res = handshake(addr, "", Some("xml")).await;
+        assert!(res.headers().get(SEC_WEBSOCKET_PROTOCOL).is_none());
+        let mut client = connect_client(res).await;
+        assert_eq!(client.next().await.unwrap().unwrap(), protocol::Message::text("none"));
+
+        let res = handshake(addr, "?require=true", Some("xml")).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        let res = handshake(addr, "?require=true", None).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        let res = handshake(addr, "?require=true", Some("json")).await;
+        assert_eq!(res.headers().get(SEC_WEBSOCKET_PROTOCOL).unwrap(), "json");
+    }
+
+    #[tokio::test]
+    async fn test_websocket_close_with() {
+        struct Closer(tokio::sync::mpsc::UnboundedSender<bool>);
+        #[async_trait]
+        impl Handler for Closer {
+            async fn handle(&self, req: &mut Request, _depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
+                let sender = self.0.clone();
+                WebSocketUpgrade::new()
+                    .upgrade(req, res, |ws| async move {
+                        let result = ws.close_with(CloseCode::Away, "going away", Duration::from_millis(300)).await;
+                        sender.send(result.is_ok()).unwrap();
+                    })
+                    .await
+                    .unwrap();
+            }
+        }
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let router = Router::new().goal(Closer(sender));
+        let acceptor = TcpListener::new("127.0.0.1:0").bind().await;
+        let addr = acceptor.holdings()[0].local_addr.clone().into_std().unwrap();
+        tokio::spawn(async move {
+            Server::new(acceptor).serve(router).await;
+        });
+
+        // Reading the Close frame replies with a Close frame, which completes the handshake.
+        let mut client = connect_client(handshake(addr, "", None).await).await;
+        match client.next().await.unwrap().unwrap() {
+            protocol::Message::Close(Some(frame)) => {
+                assert_eq!(frame.code, CloseCode::Away);
+                assert_eq!(frame.reason, "going away");
+            }
+            msg => panic!("unexpected message: {msg:?}"),
+        }
+        assert!(client.next().await.is_none());
+        assert!(receiver.recv().await.unwrap());
+
+        // A client which never reads does not reply.
+        let _client = connect_client(handshake(addr, "", None).await).await;
+        assert!(!receiver.recv().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_websocket_keepalive() {
+        #[handler]
+        async fn connect(req: &mut Request, res: &mut Response) -> Result<(), StatusError> {
+            WebSocketUpgrade::new()
+                .keepalive(Duration::from_millis(50), Duration::from_millis(300))
+                .upgrade(req, res, |mut ws| async move {
+                    while let Some(msg) = ws.recv().await {
+                        if msg.is_err() {
+                            return;
+                        }
+                    }
+                })
+                .await
+        }
+        let router = Router::new().goal(connect);
+        let acceptor = TcpListener::new("127.0.0.1:0").bind().await;
+        let addr = acceptor.holdings()[0].local_addr.clone().into_std().unwrap();
+        tokio::spawn(async move {
+            Server::new(acceptor).serve(router).await;
+        });
+
+        // Reading replies to the pings, so the connection stays open.
+        let mut client = connect_client(handshake(addr, "", None).await).await;
+        let started = Instant::now();
+        while started.elapsed() < Duration::from_millis(500) {
+            let msg = client.next().await.unwrap().unwrap();
+            assert!(msg.is_ping());
+        }
+
+        // A client which does not answer is closed after the timeout.
+        let mut client = connect_client(handshake(addr, "", None).await).await;
+        time::sleep(Duration::from_millis(400)).await;
+        let closed = time::timeout(Duration::from_secs(1), async {
+            while let Some(Ok(_)) = client.next().await {}
+        })
+        .await;
+        assert!(closed.is_ok());
+    }
+
     #[tokio::test]
     async fn test_websocket() {
         let router = Router::new().goal(connect);
@@ -1,10 +1,15 @@
 use salvo_core::http::cookie::time::Duration;
-use salvo_core::http::cookie::{Cookie, SameSite};
+use salvo_core::http::cookie::{Cookie, Key, SameSite};
 use salvo_core::{Depot, Request, Response};
 
 use super::{Flash, FlashHandler, FlashStore};
 
 /// CookieStore is a `FlashStore` implementation that stores the flash messages in a cookie.
+///
+/// The cookie is signed, so the messages can't be spoofed by the client, cookies without a valid
+/// signature are ignored. A random key is generated by default, so the cookies written before a
+/// restart or by other instances of the app are ignored too, set the same key on every instance
+/// with [`CookieStore::signing_key`] to share them.
 #[derive(Debug)]
 #[non_exhaustive]
 pub struct CookieStore {
@@ -18,6 +23,8 @@ pub struct CookieStore {
     pub path: String,
     /// The cookie name.
     pub name: String,
+    /// The key used to sign the cookie.
+    pub signing_key: Key,
 }
 impl Default for CookieStore {
     fn default() -> Self {
@@ -34,6 +41,7 @@ impl CookieStore {
             http_only: true,
             path: "/".into(),
             name: "salvo.flash".into(),
+            signing_key: Key::generate(),
         }
     }
 
@@ -67,6 +75,12 @@ impl CookieStore {
         self
     }
 
+    /// Sets the key used to sign the cookie.
+    pub fn signing_key(mut self, key: Key) -> Self {
+        self.signing_key = key;
+        self
+    }
+
     /// Into `FlashHandler`.
     pub fn into_handler(self) -> FlashHandler<CookieStore> {
         FlashHandler::new(self)
@@ -74,7 +88,7 @@ impl CookieStore {
 }
 impl FlashStore for CookieStore {
     async fn load_flash(&self, req: &mut Request, _depot: &mut Depot) -> Option<Flash> {
-        match req.cookie(&self.name) {
+        match req.signed_cookie(&self.name, &self.signing_key) {
             None => None,
             Some(cookie) => match serde_json::from_str(cookie.value()) {
                 Ok(flash) => Some(flash),
@@ -86,14 +100,13 @@ impl FlashStore for CookieStore {
         }
     }
     async fn save_flash(&self, _req: &mut Request, _depot: &mut Depot, res: &mut Response, flash: Flash) {
-        res.add_cookie(
-            Cookie::build((self.name.clone(), serde_json::to_string(&flash).unwrap_or_default()))
-                .max_age(self.max_age)
-                .path(self.path.clone())
-                .same_site(self.same_site)
-                .http_only(self.http_only)
-                .build(),
-        );
+        let cookie = Cookie::build((self.name.clone(), serde_json::to_string(&flash).unwrap_or_default()))
+            .max_age(self.max_age)
+            .path(self.path.clone())
+            .same_site(self.same_site)
+            .http_only(self.http_only)
+            .build();
+        res.add_signed_cookie(cookie, &self.signing_key);
     }
     async fn clear_flash(&self, _depot: &mut Depot, res: &mut Response) {
         res.add_cookie(
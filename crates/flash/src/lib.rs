@@ -1,5 +1,39 @@
 //! The flash message lib for Salvo web framework.
 //!
+//! Flash messages are pushed with [`FlashDepotExt::flash`], usually before a redirect, and read
+//! with [`FlashDepotExt::incoming_flash`] on the next request, then they are cleared. They are
+//! kept by a [`FlashStore`], [`CookieStore`] keeps them in a cookie signed with
+//! [`CookieStore::signing_key`], `SessionStore` keeps them in the session.
+//!
+//! [`Flash`] is serializable, pass it to a template to iterate the messages, each message has a
+//! `level` (`"debug"`, `"info"`, `"success"`, `"warning"` or `"error"`) and a `value`:
+//!
+//! ```
+//! use salvo_core::http::cookie::Key;
+//! use salvo_core::prelude::*;
+//! use salvo_flash::{CookieStore, FlashDepotExt};
+//! use serde_json::json;
+//!
+//! #[handler]
+//! async fn save(depot: &mut Depot, res: &mut Response) {
+//!     depot.flash().success("Saved.");
+//!     res.render(Redirect::other("/"));
+//! }
+//!
+//! #[handler]
+//! async fn index(depot: &mut Depot, res: &mut Response) {
+//!     // {% for message in flashes %}<p class="{{ message.level }}">{{ message.value }}</p>{% endfor %}
+//!     let context = json!({ "flashes": depot.incoming_flash() });
+//!     res.render_template(depot, "index.html", &context);
+//! }
+//!
+//! let key = Key::generate();
+//! let router = Router::new()
+//!     .hoop(CookieStore::new().signing_key(key).into_handler())
+//!     .get(index)
+//!     .push(Router::with_path("save").post(save));
+//! ```
+//!
 //! Read more: <https://salvo.rs>
 #![doc(html_favicon_url = "https://salvo.rs/favicon-32x32.png")]
 #![doc(html_logo_url = "https://salvo.rs/images/logo.svg")]
@@ -142,17 +176,25 @@ impl FlashMessage {
 }
 
 /// Verbosity level of a flash message.
+///
+/// It is serialized in lowercase, such as `"info"`.
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum FlashLevel {
     #[allow(missing_docs)]
+    #[serde(alias = "Debug")]
     Debug = 0,
     #[allow(missing_docs)]
+    #[serde(alias = "Info")]
     Info = 1,
     #[allow(missing_docs)]
+    #[serde(alias = "Success")]
     Success = 2,
     #[allow(missing_docs)]
+    #[serde(alias = "Warning")]
     Warning = 3,
     #[allow(missing_docs)]
+    #[serde(alias = "Error")]
     Error = 4,
 }
 impl FlashLevel {
@@ -208,6 +250,12 @@ pub trait FlashDepotExt {
     fn outgoing_flash(&self) -> &Flash;
     /// Get mutable outgoing flash.
     fn outgoing_flash_mut(&mut self) -> &mut Flash;
+    /// Get mutable outgoing flash to push messages, the same as [`outgoing_flash_mut`].
+    ///
+    /// [`outgoing_flash_mut`]: FlashDepotExt::outgoing_flash_mut
+    fn flash(&mut self) -> &mut Flash {
+        self.outgoing_flash_mut()
+    }
 }
 
 impl FlashDepotExt for Depot {
@@ -361,6 +409,53 @@ mod tests {
         assert!(respone.take_string().await.unwrap().is_empty());
     }
 
+    #[cfg(feature = "cookie-store")]
+    #[tokio::test]
+    async fn test_signed_cookie_store() {
+        use salvo_core::http::cookie::Key;
+
+        let key = Key::generate();
+        let router = Router::new()
+            .hoop(CookieStore::new().signing_key(key).into_handler())
+            .push(Router::with_path("get").get(get_flash))
+            .push(Router::with_path("set").get(set_flash));
+        let service = Service::new(router);
+
+        let respone = TestClient::get("http://127.0.0.1:5800/set")
+            .send(&service)
+            .await;
+        let cookie = respone.headers().get(SET_COOKIE).unwrap();
+        let mut respone = TestClient::get("http://127.0.0.1:5800/get")
+            .add_header(COOKIE, cookie, true)
+            .send(&service)
+            .await;
+        assert!(respone
+            .take_string()
+            .await
+            .unwrap()
+            .contains("Hey there! - info"));
+
+        let spoofed = format!(
+            "salvo.flash={}",
+            serde_json::to_string(&Flash(vec![FlashMessage::error("Spoofed!")])).unwrap()
+        );
+        let mut respone = TestClient::get("http://127.0.0.1:5800/get")
+            .add_header(COOKIE, spoofed, true)
+            .send(&service)
+            .await;
+        assert!(respone.take_string().await.unwrap().is_empty());
+    }
+
+    #[cfg(feature = "cookie-store")]
+    #[test]
+    fn test_flash_level_serde() {
+        let message = serde_json::to_string(&FlashMessage::info("hello")).unwrap();
+        assert_eq!(message, r#"{"level":"info","value":"hello"}"#);
+        let message: FlashMessage =
+            serde_json::from_str(r#"{"level":"Error","value":"hello"}"#).unwrap();
+        assert_eq!(message.level, FlashLevel::Error);
+    }
+
     #[cfg(feature = "session-store")]
     #[tokio::test]
     async fn test_session_store() {
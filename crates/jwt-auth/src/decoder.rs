@@ -1,5 +1,6 @@
-use jsonwebtoken::errors::Error as JwtError;
-use jsonwebtoken::{decode, Algorithm, DecodingKey, TokenData, Validation};
+use jsonwebtoken::errors::{Error as JwtError, ErrorKind};
+use jsonwebtoken::jwk::{AlgorithmParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm};
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, TokenData, Validation};
 use serde::Deserialize;
 use std::future::Future;
 
@@ -135,3 +136,111 @@ impl JwtAuthDecoder for ConstDecoder {
         decode::<C>(token, &self.decoding_key, &self.validation)
     }
 }
+
+/// JwksDecoder will decode token with the keys of a JSON Web Key Set.
+///
+/// The key is selected by the `kid` of the token header, the `kid` can be omitted if the set has
+/// only one key. The algorithm of the token header must be one of the algorithms of the
+/// validation, and the algorithm of the key if the key specifies one. Use `OidcDecoder` of the
+/// `oidc` feature to fetch and refresh the keys from an OpenID Connect issuer instead.
+pub struct JwksDecoder {
+    jwks: JwkSet,
+    validation: Validation,
+}
+
+impl JwksDecoder {
+    /// Create a new `JwksDecoder`.
+    ///
+    /// The claims are validated with `validation`, the tokens signed with an algorithm which is
+    /// not in its algorithms are rejected.
+    pub fn new(jwks: JwkSet, validation: Validation) -> Self {
+        Self { jwks, validation }
+    }
+
+    /// Create a new `JwksDecoder` from the JSON of a JSON Web Key Set with default validation.
+    ///
+    /// The algorithms of the validation are the algorithms of the keys, or all the algorithms of
+    /// the key type for the keys which don't specify one.
+    pub fn from_json(json: &str) -> Result<Self, JwtError> {
+        let jwks: JwkSet = serde_json::from_str(json).map_err(JwtError::from)?;
+        let mut validation = Validation::default();
+        validation.algorithms = Vec::new();
+        for algorithm in jwks.keys.iter().flat_map(key_algorithms) {
+            if !validation.algorithms.contains(&algorithm) {
+                validation.algorithms.push(algorithm);
+            }
+        }
+        Ok(Self::new(jwks, validation))
+    }
+}
+
+/// Returns the signing algorithm of a key algorithm, `None` for the encryption algorithms.
+fn signing_algorithm(algorithm: KeyAlgorithm) -> Option<Algorithm> {
+    match algorithm {
+        KeyAlgorithm::HS256 => Some(Algorithm::HS256),
+        KeyAlgorithm::HS384 => Some(Algorithm::HS384),
+        KeyAlgorithm::HS512 => Some(Algorithm::HS512),
+        KeyAlgorithm::ES256 => Some(Algorithm::ES256),
+        KeyAlgorithm::ES384 => Some(Algorithm::ES384),
+        KeyAlgorithm::RS256 => Some(Algorithm::RS256),
+        KeyAlgorithm::RS384 => Some(Algorithm::RS384),
+        KeyAlgorithm::RS512 => Some(Algorithm::RS512),
+        KeyAlgorithm::PS256 => Some(Algorithm::PS256),
+        KeyAlgorithm::PS384 => Some(Algorithm::PS384),
+        KeyAlgorithm::PS512 => Some(Algorithm::PS512),
+        KeyAlgorithm::EdDSA => Some(Algorithm::EdDSA),
+        KeyAlgorithm::RSA1_5 | KeyAlgorithm::RSA_OAEP | KeyAlgorithm::RSA_OAEP_256 => None,
+    }
+}
+
+/// Returns the algorithms which can be verified with the key.
+fn key_algorithms(jwk: &Jwk) -> Vec<Algorithm> {
+    if let Some(algorithm) = jwk.common.key_algorithm {
+        return signing_algorithm(algorithm).into_iter().collect();
+    }
+    match &jwk.algorithm {
+        AlgorithmParameters::OctetKey(_) => {
+            vec![Algorithm::HS256, Algorithm::HS384, Algorithm::HS512]
+        }
+        AlgorithmParameters::RSA(_) => vec![
+            Algorithm::RS256,
+            Algorithm::RS384,
+            Algorithm::RS512,
+            Algorithm::PS256,
+            Algorithm::PS384,
+            Algorithm::PS512,
+        ],
+        AlgorithmParameters::EllipticCurve(params) => match params.curve {
+            EllipticCurve::P256 => vec![Algorithm::ES256],
+            EllipticCurve::P384 => vec![Algorithm::ES384],
+            _ => Vec::new(),
+        },
+        AlgorithmParameters::OctetKeyPair(_) => vec![Algorithm::EdDSA],
+    }
+}
+
+impl JwtAuthDecoder for JwksDecoder {
+    type Error = JwtError;
+
+    async fn decode<C>(&self, token: &str, _depot: &mut Depot) -> Result<TokenData<C>, Self::Error>
+    where
+        C: for<'de> Deserialize<'de>,
+    {
+        let header = decode_header(token)?;
+        let jwk = match (&header.kid, &*self.jwks.keys) {
+            (Some(kid), _) => self.jwks.find(kid),
+            (None, [jwk]) => Some(jwk),
+            (None, _) => None,
+        }
+        .ok_or(ErrorKind::InvalidToken)?;
+        if !self.validation.algorithms.contains(&header.alg)
+            || !key_algorithms(jwk).contains(&header.alg)
+        {
+            return Err(ErrorKind::InvalidAlgorithm.into());
+        }
+        let decoding_key = DecodingKey::from_jwk(jwk)?;
+        let mut validation = self.validation.clone();
+        validation.algorithms = vec![header.alg];
+        decode::<C>(token, &decoding_key, &validation)
+    }
+}
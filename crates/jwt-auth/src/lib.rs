@@ -113,6 +113,7 @@ pub use jsonwebtoken::{
 use serde::de::DeserializeOwned;
 use thiserror::Error;
 
+use salvo_core::http::header::{HeaderValue, WWW_AUTHENTICATE};
 use salvo_core::http::{Method, Request, Response, StatusError};
 use salvo_core::{async_trait, Depot, FlowCtrl, Handler};
 
@@ -120,7 +121,7 @@ mod finder;
 pub use finder::{CookieFinder, FormFinder, HeaderFinder, JwtTokenFinder, QueryFinder};
 
 mod decoder;
-pub use decoder::{ConstDecoder, JwtAuthDecoder};
+pub use decoder::{ConstDecoder, JwksDecoder, JwtAuthDecoder};
 
 #[macro_use]
 mod cfg;
@@ -206,7 +207,7 @@ pub enum JwtAuthState {
     Authorized,
     /// Unauthorized. Used if no jwt token is provided.
     Unauthorized,
-    /// Forbidden. Used if decoding jwt token failed.
+    /// Forbidden. Used if decoding or validating jwt token failed.
     Forbidden,
 }
 /// JwtAuthDepotExt
@@ -252,6 +253,12 @@ impl JwtAuthDepotExt for Depot {
 }
 
 /// JwtAuth, used as middleware.
+///
+/// The token is found by the finders, decoded and validated by the decoder, and the state, the
+/// token and the decoded data are stored in the depot. If the token is missing or invalid, it
+/// responds `401 Unauthorized` with a `WWW-Authenticate` challenge, unless `force_passed` is set,
+/// then the handlers decide with [`JwtAuthDepotExt::jwt_auth_state`], which is useful for routes
+/// serving both public and private content.
 #[non_exhaustive]
 pub struct JwtAuth<C, D> {
     /// Only write auth state to depot when set to `true`.
@@ -341,7 +348,11 @@ where
                     depot.insert(JWT_AUTH_STATE_KEY, JwtAuthState::Forbidden);
                     depot.insert(JWT_AUTH_ERROR_KEY, e);
                     if !self.force_passed {
-                        res.render(StatusError::forbidden());
+                        res.headers_mut().insert(
+                            WWW_AUTHENTICATE,
+                            HeaderValue::from_static(r#"Bearer error="invalid_token""#),
+                        );
+                        res.render(StatusError::unauthorized());
                         ctrl.skip_rest();
                     }
                 }
@@ -349,6 +360,8 @@ where
         } else {
             depot.insert(JWT_AUTH_STATE_KEY, JwtAuthState::Unauthorized);
             if !self.force_passed {
+                res.headers_mut()
+                    .insert(WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
                 res.render(StatusError::unauthorized());
                 ctrl.skip_rest();
             }
@@ -436,7 +449,111 @@ mod tests {
             &EncodingKey::from_secret(b"ABCDEFG"),
         )
         .unwrap();
-        let content = access(&service, &token).await;
-        assert!(content.contains("Forbidden"));
+        let res = TestClient::get("http://127.0.0.1:5801/hello")
+            .add_header("Authorization", format!("Bearer {}", token), true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::UNAUTHORIZED));
+        assert_eq!(
+            res.headers()["www-authenticate"],
+            r#"Bearer error="invalid_token""#
+        );
+        let res = TestClient::get("http://127.0.0.1:5801/hello")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::UNAUTHORIZED));
+        assert_eq!(res.headers()["www-authenticate"], "Bearer");
+    }
+
+    #[tokio::test]
+    async fn test_jwks_decoder() {
+        // The key is "secret" encoded with base64url.
+        let decoder = JwksDecoder::from_json(
+            r#"{"keys": [
+                {"kty": "oct", "kid": "k1", "alg": "HS256", "k": "c2VjcmV0"},
+                {"kty": "oct", "kid": "k2", "alg": "HS512", "k": "b3RoZXI"}
+            ]}"#,
+        )
+        .unwrap();
+        let auth_handler: JwtAuth<JwtClaims, _> = JwtAuth::new(decoder).force_passed(true);
+
+        #[handler]
+        async fn whoami(depot: &mut Depot) -> String {
+            match depot.jwt_auth_state() {
+                JwtAuthState::Authorized => depot
+                    .jwt_auth_data::<JwtClaims>()
+                    .unwrap()
+                    .claims
+                    .user
+                    .clone(),
+                state => format!("{state:?}"),
+            }
+        }
+        let service = Service::new(Router::with_hoop(auth_handler).get(whoami));
+        let claim = JwtClaims {
+            user: "root".into(),
+            exp: (OffsetDateTime::now_utc() + Duration::days(1)).unix_timestamp(),
+        };
+        let encode = |kid: Option<&str>, alg: Algorithm| {
+            let mut header = jsonwebtoken::Header::new(alg);
+            header.kid = kid.map(ToOwned::to_owned);
+            jsonwebtoken::encode(&header, &claim, &EncodingKey::from_secret(b"secret")).unwrap()
+        };
+        let access = |token: Option<String>| {
+            let service = &service;
+            async move {
+                let mut req = TestClient::get("http://127.0.0.1:5801/");
+                if let Some(token) = token {
+                    req = req.add_header("Authorization", format!("Bearer {token}"), true);
+                }
+                req.send(service).await.take_string().await.unwrap()
+            }
+        };
+
+        assert_eq!(
+            access(Some(encode(Some("k1"), Algorithm::HS256))).await,
+            "root"
+        );
+        assert_eq!(
+            access(Some(encode(Some("k1"), Algorithm::HS384))).await,
+            "Forbidden"
+        );
+        assert_eq!(
+            access(Some(encode(Some("k2"), Algorithm::HS512))).await,
+            "Forbidden"
+        );
+        assert_eq!(
+            access(Some(encode(None, Algorithm::HS256))).await,
+            "Forbidden"
+        );
+        assert_eq!(access(None).await, "Unauthorized");
+    }
+
+    #[tokio::test]
+    async fn test_jwks_decoder_allowed_algorithms() {
+        let jwks = serde_json::from_str(r#"{"keys": [{"kty": "oct", "k": "c2VjcmV0"}]}"#).unwrap();
+        let decoder = JwksDecoder::new(jwks, jsonwebtoken::Validation::new(Algorithm::HS256));
+        let claim = JwtClaims {
+            user: "root".into(),
+            exp: (OffsetDateTime::now_utc() + Duration::days(1)).unix_timestamp(),
+        };
+        let encode = |alg: Algorithm| {
+            let header = jsonwebtoken::Header::new(alg);
+            jsonwebtoken::encode(&header, &claim, &EncodingKey::from_secret(b"secret")).unwrap()
+        };
+        let mut depot = Depot::new();
+        let data = decoder
+            .decode::<JwtClaims>(&encode(Algorithm::HS256), &mut depot)
+            .await
+            .unwrap();
+        assert_eq!(data.claims.user, "root");
+        let err = decoder
+            .decode::<JwtClaims>(&encode(Algorithm::HS512), &mut depot)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.kind(),
+            &jsonwebtoken::errors::ErrorKind::InvalidAlgorithm
+        );
     }
 }
@@ -423,11 +423,38 @@ impl OpenApi {
     /// Consusmes the [`OpenApi`] and informations from a [`Router`] with base path.
     pub fn merge_router_with_base(mut self, router: &Router, base: impl AsRef<str>) -> Self {
         let mut node = NormNode::new(router, Default::default());
-        self.merge_norm_node(&mut node, base.as_ref());
+        self.merge_norm_node(&mut node, base.as_ref(), false);
         self
     }
 
-    fn merge_norm_node(&mut self, node: &mut NormNode, base_path: &str) {
+    /// Consusmes the [`OpenApi`] and informations from a [`Router`], including the handlers
+    /// which are not endpoints.
+    ///
+    /// The operations of plain handlers only contain the methods and the path parameter names,
+    /// which is still useful for client generation before the handlers are annotated with
+    /// [`endpoint`](crate::endpoint). Keep the handlers serving the documentation out of `router`,
+    /// or they are documented too.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use salvo_core::prelude::*;
+    /// use salvo_oapi::OpenApi;
+    ///
+    /// #[handler]
+    /// async fn show_user() {}
+    ///
+    /// let router = Router::with_path("users/<id>").get(show_user);
+    /// let doc = OpenApi::new("test api", "0.0.1").merge_router_including_handlers(&router);
+    /// assert!(doc.paths.contains_key("/users/{id}"));
+    /// ```
+    pub fn merge_router_including_handlers(mut self, router: &Router) -> Self {
+        let mut node = NormNode::new(router, Default::default());
+        self.merge_norm_node(&mut node, "/", true);
+        self
+    }
+
+    fn merge_norm_node(&mut self, node: &mut NormNode, base_path: &str, include_handlers: bool) {
         fn join_path(a: &str, b: &str) -> String {
             if a.is_empty() {
                 b.to_owned()
@@ -455,12 +482,25 @@ impl OpenApi {
             })
             .collect::<Vec<_>>();
         if let Some(handler_type_id) = &node.handler_type_id {
-            if let Some(creator) = crate::EndpointRegistry::find(handler_type_id) {
-                let Endpoint {
-                    mut operation,
-                    mut components,
-                    ..
-                } = (creator)();
+            let endpoint = match crate::EndpointRegistry::find(handler_type_id) {
+                Some(creator) => Some((creator)()),
+                None if include_handlers => Some(Endpoint::new(
+                    Operation::new().parameters(path_parameter_names.iter().map(|name| {
+                        Parameter::new(name)
+                            .parameter_in(ParameterIn::Path)
+                            .required(Required::True)
+                            .schema(Object::with_type(BasicType::String))
+                    })),
+                    Components::new(),
+                )),
+                None => None,
+            };
+            if let Some(Endpoint {
+                mut operation,
+                mut components,
+                ..
+            }) = endpoint
+            {
                 operation.tags.extend(node.metadata.tags.iter().cloned());
                 operation
                     .securities
@@ -517,7 +557,7 @@ impl OpenApi {
             }
         }
         for child in &mut node.children {
-            self.merge_norm_node(child, &path);
+            self.merge_norm_node(child, &path, include_handlers);
         }
     }
 }
@@ -1225,6 +1265,44 @@ mod tests {
         assert_eq!(deserialize_result.unwrap(), Required::False);
     }
 
+    #[test]
+    fn test_merge_router_including_handlers() {
+        #[handler]
+        async fn plain() {}
+
+        #[salvo_oapi::endpoint]
+        async fn annotated(id: PathParam<u64>) -> String {
+            id.to_string()
+        }
+
+        let router = Router::new()
+            .push(Router::with_path("users/<id>/files/<**rest>").get(plain))
+            .push(Router::with_path("items/<id>").delete(annotated))
+            .push(Router::with_path("status").goal(plain));
+        let doc = OpenApi::new("test api", "0.0.1").merge_router(&router);
+        assert_eq!(doc.paths.keys().collect::<Vec<_>>(), ["/items/{id}"]);
+
+        let doc = OpenApi::new("test api", "0.0.1").merge_router_including_handlers(&router);
+        let paths = serde_json::to_value(&doc.paths).unwrap();
+        assert_eq!(
+            paths["/users/{id}/files/{rest}"]["get"]["parameters"],
+            json!([
+                {"name": "id", "in": "path", "required": true, "schema": {"type": "string"}},
+                {"name": "rest", "in": "path", "required": true, "schema": {"type": "string"}},
+            ])
+        );
+        assert_eq!(
+            paths["/items/{id}"]["delete"]["parameters"][0]["schema"]["type"],
+            "integer"
+        );
+        let methods = paths["/status"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .collect::<Vec<_>>();
+        assert_eq!(methods, ["get", "patch", "post", "put"]);
+    }
+
     #[tokio::test]
     async fn test_openapi_handle() {
         let doc = OpenApi::new("pet api", "0.1.0");
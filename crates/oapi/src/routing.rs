@@ -35,7 +35,8 @@ impl NormNode {
                 .extend(metadata.securities.iter().cloned());
         }
 
-        let regex = Regex::new(r#"<([^/:>]+)(:[^>]*)?>"#).expect("invalid regex");
+        // `<id:num>`, `<**rest>` and `<id?>` are all documented as `{id}` and `{rest}`.
+        let regex = Regex::new(r#"<\**([^/:>?]+)[^>]*>"#).expect("invalid regex");
         for filter in router.filters() {
             let info = format!("{filter:?}");
             if info.starts_with("path:") {
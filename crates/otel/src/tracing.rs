@@ -1,19 +1,41 @@
-use opentelemetry::trace::{FutureExt, Span, SpanKind, TraceContextExt, Tracer};
+use opentelemetry::trace::{FutureExt, Span, SpanKind, Status, TraceContextExt, Tracer};
 use opentelemetry::{global, Context, KeyValue};
-use opentelemetry_http::HeaderExtractor;
+use opentelemetry_http::{HeaderExtractor, HeaderInjector};
 use opentelemetry_semantic_conventions::{resource, trace};
-use salvo_core::http::headers::{self, HeaderMap, HeaderMapExt, HeaderName, HeaderValue};
+use salvo_core::http::headers::{self, HeaderMapExt, HeaderName};
+use salvo_core::http::ResBody;
 use salvo_core::prelude::*;
 
 /// Middleware for tracing with OpenTelemetry.
+///
+/// Every request is wrapped in a server span named after the method and the matched route, such
+/// as `GET /users/<id>`. The W3C trace context is extracted from the `traceparent` request header
+/// with the global propagator, so the span joins the trace of the caller, and the context of the
+/// span is injected into the response headers. Handlers run inside the context of the span, so
+/// they can get it with [`Context::current`] to propagate it to outgoing requests.
+///
+/// The response status is recorded on the span, and server errors set the span status to error.
+/// The request id header, which is set by the `RequestId` middleware of `salvo_extra`, is recorded
+/// as the `http.request.header.x-request-id` attribute, so logs and traces can be correlated.
+/// Add `RequestId` before `Tracing` for this.
 pub struct Tracing<T> {
     tracer: T,
+    request_id_header: HeaderName,
 }
 
 impl<T> Tracing<T> {
     /// Create `Tracing` middleware with `tracer`.
     pub fn new(tracer: T) -> Self {
-        Self { tracer }
+        Self {
+            tracer,
+            request_id_header: HeaderName::from_static("x-request-id"),
+        }
+    }
+
+    /// Sets the header to read the request id from, default is `x-request-id`.
+    pub fn request_id_header(mut self, name: HeaderName) -> Self {
+        self.request_id_header = name;
+        self
     }
 }
 
@@ -32,16 +54,8 @@ where
     ) {
         let remote_addr = req.remote_addr().to_string();
 
-        //TODO: Will remove after opentelemetry_http updated
-        let mut headers = HeaderMap::with_capacity(req.headers().len());
-        headers.extend(req.headers().into_iter().map(|(name, value)| {
-            let name = HeaderName::from_bytes(name.as_ref()).expect("Invalid header name");
-            let value = HeaderValue::from_bytes(value.as_ref()).expect("Invalid header value");
-            (name, value)
-        }));
-
         let parent_cx = global::get_text_map_propagator(|propagator| {
-            propagator.extract(&HeaderExtractor(&headers))
+            propagator.extract(&HeaderExtractor(req.headers()))
         });
 
         let mut attributes = Vec::new();
@@ -64,9 +78,26 @@ where
             trace::NETWORK_PROTOCOL_VERSION,
             format!("{:?}", req.version()),
         ));
+        if let Some(route) = req.matched_path() {
+            attributes.push(KeyValue::new(trace::HTTP_ROUTE, route.to_owned()));
+        }
+        if let Some(request_id) = req
+            .headers()
+            .get(&self.request_id_header)
+            .and_then(|v| v.to_str().ok())
+        {
+            attributes.push(KeyValue::new(
+                format!("http.request.header.{}", self.request_id_header),
+                request_id.to_owned(),
+            ));
+        }
+        let span_name = match req.matched_path() {
+            Some(route) => format!("{} {}", req.method(), route),
+            None => req.method().to_string(),
+        };
         let mut span = self
             .tracer
-            .span_builder(format!("{} {}", req.method(), req.uri()))
+            .span_builder(span_name)
             .with_kind(SpanKind::Server)
             .with_attributes(attributes)
             .start_with_context(&self.tracer, &parent_cx);
@@ -92,14 +123,107 @@ where
                 trace::HTTP_RESPONSE_STATUS_CODE,
                 status.as_u16() as i64,
             ));
+            if status.is_server_error() {
+                span.set_attribute(KeyValue::new(trace::ERROR_TYPE, status.as_str().to_owned()));
+                let description = if let ResBody::Error(e) = &res.body {
+                    e.to_string()
+                } else {
+                    status.to_string()
+                };
+                span.set_status(Status::error(description));
+            }
             if let Some(content_length) = res.headers().typed_get::<headers::ContentLength>() {
                 span.set_attribute(KeyValue::new(
                     "http.response.header.content-length",
                     content_length.0 as i64,
                 ));
             }
+            global::get_text_map_propagator(|propagator| {
+                propagator.inject_context(&cx, &mut HeaderInjector(res.headers_mut()))
+            });
         }
         .with_context(Context::current_with_span(span))
         .await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use opentelemetry::trace::{SpanId, TraceId, TracerProvider as _};
+    use opentelemetry_sdk::propagation::TraceContextPropagator;
+    use opentelemetry_sdk::testing::trace::InMemorySpanExporter;
+    use opentelemetry_sdk::trace::TracerProvider;
+    use salvo_core::test::TestClient;
+
+    use super::*;
+
+    #[handler]
+    async fn show_user(req: &mut Request) -> Result<&'static str, StatusError> {
+        match req.param::<u64>("id") {
+            Some(_) => Ok("user"),
+            None => Err(StatusError::internal_server_error().brief("invalid id")),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tracing() {
+        global::set_text_map_propagator(TraceContextPropagator::new());
+        let exporter = InMemorySpanExporter::default();
+        let provider = TracerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+        let router = Router::with_hoop(Tracing::new(provider.tracer("test")))
+            .push(Router::with_path("users/<id>").get(show_user));
+        let service = Service::new(router);
+
+        let res = TestClient::get("http://127.0.0.1:5801/users/1")
+            .add_header(
+                "traceparent",
+                "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+                true,
+            )
+            .add_header("x-request-id", "abc", true)
+            .send(&service)
+            .await;
+        let traceparent = res.headers()["traceparent"].to_str().unwrap();
+        assert!(traceparent.starts_with("00-4bf92f3577b34da6a3ce929d0e0e4736-"));
+
+        TestClient::get("http://127.0.0.1:5801/users/abc")
+            .send(&service)
+            .await;
+
+        let spans = exporter.get_finished_spans().unwrap();
+        assert_eq!(spans.len(), 2);
+        let span = &spans[0];
+        assert_eq!(span.name, "GET /users/<id>");
+        assert_eq!(span.span_kind, SpanKind::Server);
+        assert_eq!(
+            span.span_context.trace_id(),
+            TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").unwrap()
+        );
+        assert_eq!(
+            span.parent_span_id,
+            SpanId::from_hex("00f067aa0ba902b7").unwrap()
+        );
+        let attribute = |span: &opentelemetry_sdk::export::trace::SpanData, key: &str| {
+            span.attributes
+                .iter()
+                .find(|kv| kv.key.as_str() == key)
+                .map(|kv| kv.value.to_string())
+        };
+        assert_eq!(
+            attribute(span, trace::HTTP_ROUTE).as_deref(),
+            Some("/users/<id>")
+        );
+        assert_eq!(
+            attribute(span, "http.request.header.x-request-id").as_deref(),
+            Some("abc")
+        );
+        assert_eq!(span.status, Status::Unset);
+        assert_eq!(
+            attribute(&spans[1], trace::HTTP_RESPONSE_STATUS_CODE).as_deref(),
+            Some("500")
+        );
+        assert!(matches!(spans[1].status, Status::Error { .. }));
+    }
+}
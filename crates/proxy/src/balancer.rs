@@ -0,0 +1,288 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use salvo_core::Error;
+
+use crate::{UpstreamOutcome, Upstreams};
+
+/// Strategy to choose an upstream of a [`UpstreamPool`].
+pub trait LoadBalancer: Send + Sync + 'static {
+    /// Choose one of the `candidates`, returns its index in `candidates`.
+    ///
+    /// The candidates are the healthy upstreams of the pool, or all the upstreams if none of
+    /// them is healthy, it is never empty.
+    fn choose(&self, candidates: &[&Upstream]) -> usize;
+}
+
+/// Chooses the upstreams in turn.
+#[derive(Default, Debug)]
+pub struct RoundRobin {
+    next: AtomicUsize,
+}
+impl RoundRobin {
+    /// Create a new `RoundRobin`.
+    #[inline]
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+impl LoadBalancer for RoundRobin {
+    fn choose(&self, candidates: &[&Upstream]) -> usize {
+        self.next.fetch_add(1, Ordering::Relaxed) % candidates.len()
+    }
+}
+
+/// Chooses the upstream with the fewest active requests, the first one for ties.
+#[derive(Default, Clone, Copy, Debug)]
+pub struct LeastConnections;
+impl LoadBalancer for LeastConnections {
+    fn choose(&self, candidates: &[&Upstream]) -> usize {
+        candidates
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, upstream)| upstream.active())
+            .map(|(index, _)| index)
+            .unwrap_or_default()
+    }
+}
+
+/// An upstream of a [`UpstreamPool`] and its state.
+#[derive(Debug)]
+pub struct Upstream {
+    url: String,
+    active: AtomicUsize,
+    consecutive_failures: AtomicUsize,
+    total_requests: AtomicUsize,
+    total_failures: AtomicUsize,
+    ejected_until: Mutex<Option<Instant>>,
+}
+impl Upstream {
+    fn new(url: String) -> Self {
+        Self {
+            url,
+            active: AtomicUsize::new(0),
+            consecutive_failures: AtomicUsize::new(0),
+            total_requests: AtomicUsize::new(0),
+            total_failures: AtomicUsize::new(0),
+            ejected_until: Mutex::new(None),
+        }
+    }
+    /// Url of the upstream.
+    #[inline]
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+    /// Number of the requests waiting for the response of the upstream.
+    #[inline]
+    pub fn active(&self) -> usize {
+        self.active.load(Ordering::Relaxed)
+    }
+    /// Number of the failed requests since the last successful one.
+    #[inline]
+    pub fn consecutive_failures(&self) -> usize {
+        self.consecutive_failures.load(Ordering::Relaxed)
+    }
+    /// Number of the requests sent to the upstream.
+    #[inline]
+    pub fn total_requests(&self) -> usize {
+        self.total_requests.load(Ordering::Relaxed)
+    }
+    /// Number of the failed requests, including the ones answered with a `5xx` status code.
+    #[inline]
+    pub fn total_failures(&self) -> usize {
+        self.total_failures.load(Ordering::Relaxed)
+    }
+    /// Returns `false` if the upstream is ejected from the pool and the cooldown hasn't elapsed.
+    pub fn is_healthy(&self) -> bool {
+        let ejected_until = self.ejected_until.lock().unwrap_or_else(|e| e.into_inner());
+        ejected_until.map_or(true, |until| until <= Instant::now())
+    }
+}
+
+/// Upstreams balanced by a [`LoadBalancer`], with passive health checks.
+///
+/// An upstream is ejected from the pool when a number of requests in a row fail to be sent to it,
+/// time out or are answered with a `5xx` status code, it is added back after the cooldown, and
+/// ejected again if the next request fails. The state of the upstreams can be read with
+/// [`UpstreamPool::upstreams`], for example to expose metrics. Use `Arc<UpstreamPool>` to share it
+/// with the proxy.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::time::Duration;
+///
+/// use salvo_core::prelude::*;
+/// use salvo_proxy::{HyperClient, LeastConnections, Proxy, UpstreamPool};
+///
+/// let pool = Arc::new(
+///     UpstreamPool::new(["http://10.0.0.1:8000", "http://10.0.0.2:8000"], LeastConnections)
+///         .max_failures(3)
+///         .cooldown(Duration::from_secs(30)),
+/// );
+/// let proxy = Proxy::new(pool.clone(), HyperClient::https_or_http()).retries(1);
+/// let router = Router::with_path("<**rest>").goal(proxy);
+/// for upstream in pool.upstreams() {
+///     println!("{} active: {}, healthy: {}", upstream.url(), upstream.active(), upstream.is_healthy());
+/// }
+/// ```
+#[derive(Debug)]
+pub struct UpstreamPool<B = RoundRobin> {
+    upstreams: Vec<Upstream>,
+    balancer: B,
+    max_failures: usize,
+    cooldown: Duration,
+}
+impl<B> UpstreamPool<B>
+where
+    B: LoadBalancer,
+{
+    /// Create a new `UpstreamPool` with the upstream urls and the load balancer.
+    pub fn new<I, S>(urls: I, balancer: B) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            upstreams: urls
+                .into_iter()
+                .map(|url| Upstream::new(url.into()))
+                .collect(),
+            balancer,
+            max_failures: 3,
+            cooldown: Duration::from_secs(30),
+        }
+    }
+
+    /// Sets how many failed requests in a row eject an upstream, default is `3`, `0` disables the
+    /// health checks.
+    #[inline]
+    pub fn max_failures(mut self, max_failures: usize) -> Self {
+        self.max_failures = max_failures;
+        self
+    }
+
+    /// Sets how long an ejected upstream is not used, default is 30 seconds.
+    #[inline]
+    pub fn cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Get the upstreams and their state.
+    #[inline]
+    pub fn upstreams(&self) -> &[Upstream] {
+        &self.upstreams
+    }
+}
+
+impl<B> Upstreams for UpstreamPool<B>
+where
+    B: LoadBalancer,
+{
+    type Error = Error;
+
+    async fn elect(&self) -> Result<&str, Self::Error> {
+        if self.upstreams.is_empty() {
+            return Err(Error::other("upstreams is empty"));
+        }
+        let mut candidates = self
+            .upstreams
+            .iter()
+            .filter(|upstream| upstream.is_healthy())
+            .collect::<Vec<_>>();
+        if candidates.is_empty() {
+            candidates = self.upstreams.iter().collect();
+        }
+        let upstream = candidates[self.balancer.choose(&candidates).min(candidates.len() - 1)];
+        upstream.active.fetch_add(1, Ordering::Relaxed);
+        upstream.total_requests.fetch_add(1, Ordering::Relaxed);
+        Ok(&upstream.url)
+    }
+
+    fn report(&self, url: &str, outcome: UpstreamOutcome) {
+        let Some(upstream) = self.upstreams.iter().find(|upstream| upstream.url == url) else {
+            return;
+        };
+        let _ = upstream
+            .active
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |active| {
+                active.checked_sub(1)
+            });
+        if outcome == UpstreamOutcome::Cancelled {
+            return;
+        }
+        if outcome == UpstreamOutcome::Success {
+            upstream.consecutive_failures.store(0, Ordering::Relaxed);
+            return;
+        }
+        upstream.total_failures.fetch_add(1, Ordering::Relaxed);
+        let failures = upstream
+            .consecutive_failures
+            .fetch_add(1, Ordering::Relaxed)
+            + 1;
+        if self.max_failures > 0 && failures >= self.max_failures {
+            tracing::warn!(upstream = url, failures, cooldown = ?self.cooldown, "upstream ejected");
+            *upstream
+                .ejected_until
+                .lock()
+                .unwrap_or_else(|e| e.into_inner()) = Some(Instant::now() + self.cooldown);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_round_robin() {
+        let pool = UpstreamPool::new(["a", "b", "c"], RoundRobin::new());
+        let mut elected = Vec::new();
+        for _ in 0..4 {
+            let url = pool.elect().await.unwrap();
+            elected.push(url.to_owned());
+            pool.report(url, UpstreamOutcome::Success);
+        }
+        assert_eq!(elected, ["a", "b", "c", "a"]);
+        assert_eq!(pool.upstreams()[0].total_requests(), 2);
+        assert_eq!(pool.upstreams()[0].active(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_least_connections() {
+        let pool = UpstreamPool::new(["a", "b"], LeastConnections);
+        assert_eq!(pool.elect().await.unwrap(), "a");
+        assert_eq!(pool.elect().await.unwrap(), "b");
+        pool.report("a", UpstreamOutcome::Success);
+        assert_eq!(pool.elect().await.unwrap(), "a");
+        assert_eq!(pool.upstreams()[1].active(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_ejection() {
+        let pool = UpstreamPool::new(["a", "b"], LeastConnections)
+            .max_failures(2)
+            .cooldown(Duration::from_millis(100));
+        pool.report("a", UpstreamOutcome::Failure);
+        assert!(pool.upstreams()[0].is_healthy());
+        pool.report("a", UpstreamOutcome::ServerError);
+        assert!(!pool.upstreams()[0].is_healthy());
+        assert_eq!(pool.upstreams()[0].total_failures(), 2);
+        for _ in 0..3 {
+            assert_eq!(pool.elect().await.unwrap(), "b");
+        }
+
+        // All the upstreams are used if none of them is healthy.
+        pool.report("b", UpstreamOutcome::Failure);
+        pool.report("b", UpstreamOutcome::Failure);
+        assert_eq!(pool.elect().await.unwrap(), "a");
+
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        assert!(pool.upstreams()[0].is_healthy());
+        pool.report("a", UpstreamOutcome::Success);
+        assert_eq!(pool.upstreams()[0].consecutive_failures(), 0);
+    }
+}
@@ -16,6 +16,8 @@ pub struct HyperClient {
 }
 
 impl Default for HyperClient {
+    /// Create a new `HyperClient` which only connects to the upstreams over HTTPS, use
+    /// [`HyperClient::https_or_http`] to allow plain HTTP.
     fn default() -> Self {
         let https = HttpsConnectorBuilder::new()
             .with_native_roots()
@@ -34,7 +36,8 @@ where
     U: Upstreams,
     U::Error: Into<BoxedError>,
 {
-    /// Create new `Proxy` which use default hyper util client.
+    /// Create new `Proxy` which use default hyper util client, it only connects to the upstreams
+    /// over HTTPS.
     pub fn use_hyper_client(upstreams: U) -> Self {
         Proxy::new(upstreams, HyperClient::default())
     }
@@ -45,6 +48,22 @@ impl HyperClient {
     pub fn new(inner: HyperUtilClient<HttpsConnector<HttpConnector>, ReqBody>) -> Self {
         Self { inner }
     }
+
+    /// Create a new `HyperClient` which connects to the upstreams over HTTPS or plain HTTP.
+    ///
+    /// The requests to `http://` upstreams are sent unencrypted, only use it for upstreams in a
+    /// trusted network.
+    pub fn https_or_http() -> Self {
+        let https = HttpsConnectorBuilder::new()
+            .with_native_roots()
+            .expect("no native root CA certificates found")
+            .https_or_http()
+            .enable_http1()
+            .build();
+        Self {
+            inner: HyperUtilClient::builder(TokioExecutor::new()).build(https),
+        }
+    }
 }
 
 impl Client for HyperClient {
@@ -30,6 +30,27 @@
 //!     Server::new(acceptor).serve(router).await;
 //! }
 //! ```
+//!
+//! The request is forwarded with its method, headers and body stream, the hop-by-hop headers,
+//! such as `Connection` and `Transfer-Encoding`, are removed, and the response is streamed back.
+//! The upstream url is joined with the last path parameter, so the prefix the proxy is mounted on
+//! is stripped: with `Router::with_path("api/<**rest>")`, `/api/users?page=2` is forwarded to
+//! `<upstream>/users?page=2`, use [`Proxy::url_path_getter`] to rewrite the path in other ways.
+//! Connections to the upstreams are pooled by the client.
+//!
+//! ```
+//! use std::time::Duration;
+//!
+//! use salvo_core::prelude::*;
+//! use salvo_proxy::{HyperClient, Proxy};
+//!
+//! let proxy = Proxy::new("http://127.0.0.1:8000", HyperClient::https_or_http())
+//!     .timeout(Duration::from_secs(30))
+//!     .retries(2)
+//!     .rewrite_location("http://127.0.0.1:8000/", "https://example.com/api/")
+//!     .rewrite_cookie_domain("127.0.0.1", "example.com");
+//! let router = Router::with_path("api/<**rest>").goal(proxy);
+//! ```
 #![doc(html_favicon_url = "https://salvo.rs/favicon-32x32.png")]
 #![doc(html_logo_url = "https://salvo.rs/images/logo.svg")]
 #![cfg_attr(docsrs, feature(doc_cfg))]
@@ -37,17 +58,25 @@
 use std::convert::Infallible;
 use std::error::Error as StdError;
 use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
 
+use hyper::body::Body;
 use hyper::upgrade::OnUpgrade;
 use percent_encoding::{utf8_percent_encode, CONTROLS};
-use salvo_core::http::header::{HeaderMap, HeaderName, HeaderValue, CONNECTION, HOST, UPGRADE};
+use salvo_core::http::header::{
+    HeaderMap, HeaderName, HeaderValue, CONNECTION, HOST, LOCATION, SET_COOKIE, UPGRADE,
+};
 use salvo_core::http::uri::Uri;
-use salvo_core::http::{ReqBody, ResBody, StatusCode};
+use salvo_core::http::{Method, ReqBody, ResBody, StatusCode};
 use salvo_core::{async_trait, BoxedError, Depot, Error, FlowCtrl, Handler, Request, Response};
 
 #[macro_use]
 mod cfg;
 
+mod balancer;
+pub use balancer::{LeastConnections, LoadBalancer, RoundRobin, Upstream, UpstreamPool};
+
 cfg_feature! {
     #![feature = "hyper-client"]
     mod hyper_client;
@@ -83,12 +112,57 @@ pub trait Client: Send + Sync + 'static {
     ) -> impl Future<Output = Result<HyperResponse, Self::Error>> + Send;
 }
 
+/// Result of a request sent to an upstream, reported by [`Upstreams::report`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum UpstreamOutcome {
+    /// The upstream responded with a status code which is not `5xx`.
+    Success,
+    /// The upstream responded with a `5xx` status code.
+    ServerError,
+    /// The request failed to be sent to the upstream or timed out.
+    Failure,
+    /// The request was dropped before the response was received, such as when the client
+    /// disconnected, it is not a failure of the upstream.
+    Cancelled,
+}
+
+/// Reports the outcome of a request to the elected upstream when it is dropped, so the upstream
+/// is reported as [`UpstreamOutcome::Cancelled`] if the request is dropped while it is pending.
+struct Elected<'a, U: Upstreams> {
+    upstreams: &'a U,
+    upstream: &'a str,
+    outcome: Option<UpstreamOutcome>,
+}
+impl<U: Upstreams> Drop for Elected<'_, U> {
+    fn drop(&mut self) {
+        let outcome = self.outcome.unwrap_or(UpstreamOutcome::Cancelled);
+        self.upstreams.report(self.upstream, outcome);
+    }
+}
+
 /// Upstreams trait.
 pub trait Upstreams: Send + Sync + 'static {
     /// Error type.
     type Error: StdError + Send + Sync + 'static;
     /// Elect a upstream to process current request.
     fn elect(&self) -> impl Future<Output = Result<&str, Self::Error>> + Send;
+    /// Report the result of a request sent to the elected upstream, it is called once for each
+    /// elected upstream, when the response headers are received, the request failed or it was
+    /// dropped.
+    fn report(&self, _upstream: &str, _outcome: UpstreamOutcome) {}
+}
+impl<T> Upstreams for Arc<T>
+where
+    T: Upstreams,
+{
+    type Error = T::Error;
+    fn elect(&self) -> impl Future<Output = Result<&str, Self::Error>> + Send {
+        (**self).elect()
+    }
+    fn report(&self, upstream: &str, outcome: UpstreamOutcome) {
+        (**self).report(upstream, outcome)
+    }
 }
 impl Upstreams for &'static str {
     type Error = Infallible;
@@ -159,6 +233,16 @@ where
     pub url_path_getter: UrlPartGetter,
     /// Url query getter.
     pub url_query_getter: UrlPartGetter,
+    /// Timeout of a request to the upstream, until the response headers are received.
+    pub timeout: Option<Duration>,
+    /// How many times a failed request is retried.
+    pub retries: usize,
+    /// Whether to retry the requests with a non-idempotent method, such as `POST`.
+    pub retry_non_idempotent: bool,
+    /// Prefixes of the `Location` header to rewrite, and their replacements.
+    pub location_rewrites: Vec<(String, String)>,
+    /// Domains of the `Set-Cookie` headers to rewrite, and their replacements.
+    pub cookie_domain_rewrites: Vec<(String, String)>,
 }
 
 impl<U, C> Proxy<U, C>
@@ -174,6 +258,11 @@ where
             client,
             url_path_getter: Box::new(default_url_path_getter),
             url_query_getter: Box::new(default_url_query_getter),
+            timeout: None,
+            retries: 0,
+            retry_non_idempotent: false,
+            location_rewrites: Vec::new(),
+            cookie_domain_rewrites: Vec::new(),
         }
     }
 
@@ -197,6 +286,56 @@ where
         self
     }
 
+    /// Sets the timeout of a request to the upstream, until the response headers are received,
+    /// `504 Gateway Timeout` is rendered if it expires.
+    #[inline]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets how many times a request is retried if it can't be sent to the upstream or it times
+    /// out, default is `0`.
+    ///
+    /// Only the requests with an idempotent method, such as `GET` and `PUT`, and without a body or
+    /// with a body which is already read into memory are retried. The upstream is elected again
+    /// for each retry, so an [`UpstreamPool`] retries the next upstream.
+    #[inline]
+    pub fn retries(mut self, retries: usize) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Sets whether to retry the requests with a non-idempotent method, such as `POST`, default
+    /// is `false`.
+    ///
+    /// The upstream may have processed a failed or timed out request, enable it only if the
+    /// upstreams can handle the same request twice.
+    #[inline]
+    pub fn retry_non_idempotent(mut self, retry: bool) -> Self {
+        self.retry_non_idempotent = retry;
+        self
+    }
+
+    /// Rewrites the `Location` header of the response if it starts with `from`, `from` is
+    /// replaced with `to`.
+    ///
+    /// It is used to fix the redirects of the upstream, for example from
+    /// `http://127.0.0.1:8000/login` to `https://example.com/api/login`.
+    #[inline]
+    pub fn rewrite_location(mut self, from: impl Into<String>, to: impl Into<String>) -> Self {
+        self.location_rewrites.push((from.into(), to.into()));
+        self
+    }
+
+    /// Rewrites the `Domain` attribute of the `Set-Cookie` headers of the response if it is
+    /// `from`, the attribute is removed if `to` is empty.
+    #[inline]
+    pub fn rewrite_cookie_domain(mut self, from: impl Into<String>, to: impl Into<String>) -> Self {
+        self.cookie_domain_rewrites.push((from.into(), to.into()));
+        self
+    }
+
     /// Get upstreams list.
     #[inline]
     pub fn upstreams(&self) -> &U {
@@ -219,12 +358,13 @@ where
         &mut self.client
     }
 
-    async fn build_proxied_request(
+    fn build_proxied_request(
         &self,
-        req: &mut Request,
+        req: &Request,
         depot: &Depot,
+        upstream: &str,
+        body: ReqBody,
     ) -> Result<HyperRequest, Error> {
-        let upstream = self.upstreams.elect().await.map_err(Error::other)?;
         if upstream.is_empty() {
             tracing::error!("upstreams is empty");
             return Err(Error::other("upstreams is empty"));
@@ -254,10 +394,16 @@ where
         let mut build = hyper::Request::builder()
             .method(req.method())
             .uri(&forward_url);
+        let upgrading = get_upgrade_type(req.headers()).is_some();
+        let connection_headers = connection_headers(req.headers());
         for (key, value) in req.headers() {
-            if key != HOST {
-                build = build.header(key, value);
+            if key == HOST
+                || (!(upgrading && (key == CONNECTION || key == UPGRADE))
+                    && is_hop_by_hop(key, &connection_headers))
+            {
+                continue;
             }
+            build = build.header(key, value);
         }
         if let Some(host) = forward_url
             .host()
@@ -283,7 +429,116 @@ where
         //     // shouldn't happen...
         //     Err(_) => panic!("Invalid header name: {}", x_forwarded_for_header_name),
         // }
-        build.body(req.take_body()).map_err(Error::other)
+        build.body(body).map_err(Error::other)
+    }
+
+    /// Send the request to the upstream, retry it if it is possible.
+    async fn send(&self, req: &mut Request, depot: &Depot) -> Result<HyperResponse, StatusCode> {
+        let mut body = req.take_body();
+        let mut upgraded = req.extensions_mut().remove::<OnUpgrade>();
+        let mut retries =
+            if upgraded.is_none() && (self.retry_non_idempotent || is_idempotent(req.method())) {
+                self.retries
+            } else {
+                0
+            };
+        loop {
+            let replayed = if retries > 0 {
+                replay_body(&body)
+            } else {
+                None
+            };
+            if replayed.is_none() {
+                retries = 0;
+            }
+            let attempt_body = std::mem::replace(&mut body, replayed.unwrap_or_default());
+            let upstream = match self.upstreams.elect().await {
+                Ok(upstream) => upstream,
+                Err(e) => {
+                    tracing::error!(error = ?e, "elect upstream failed");
+                    return Err(StatusCode::INTERNAL_SERVER_ERROR);
+                }
+            };
+            let mut elected = Elected {
+                upstreams: &self.upstreams,
+                upstream,
+                outcome: None,
+            };
+            let proxied_request =
+                match self.build_proxied_request(req, depot, upstream, attempt_body) {
+                    Ok(proxied_request) => proxied_request,
+                    Err(e) => {
+                        tracing::error!(error = ?e, "build proxied request failed");
+                        elected.outcome = Some(UpstreamOutcome::Failure);
+                        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+                    }
+                };
+            let executed = self.client.execute(proxied_request, upgraded.take());
+            let executed = match self.timeout {
+                Some(timeout) => tokio::time::timeout(timeout, executed).await.ok(),
+                None => Some(executed.await),
+            };
+            let status = match executed {
+                Some(Ok(response)) => {
+                    let outcome = if response.status().is_server_error() {
+                        UpstreamOutcome::ServerError
+                    } else {
+                        UpstreamOutcome::Success
+                    };
+                    elected.outcome = Some(outcome);
+                    return Ok(response);
+                }
+                Some(Err(e)) => {
+                    tracing::error!(error = ?e, uri = ?req.uri(), upstream, "get response data failed: {}", e);
+                    StatusCode::BAD_GATEWAY
+                }
+                None => {
+                    tracing::error!(uri = ?req.uri(), upstream, timeout = ?self.timeout, "upstream timed out");
+                    StatusCode::GATEWAY_TIMEOUT
+                }
+            };
+            elected.outcome = Some(UpstreamOutcome::Failure);
+            drop(elected);
+            if retries == 0 {
+                return Err(status);
+            }
+            retries -= 1;
+        }
+    }
+
+    fn rewritten_location(&self, value: &HeaderValue) -> Option<HeaderValue> {
+        let location = value.to_str().ok()?;
+        self.location_rewrites.iter().find_map(|(from, to)| {
+            let rest = location.strip_prefix(from.as_str())?;
+            HeaderValue::from_str(&format!("{to}{rest}")).ok()
+        })
+    }
+
+    fn rewritten_cookie(&self, value: &HeaderValue) -> Option<HeaderValue> {
+        let cookie = value.to_str().ok()?;
+        let mut rewritten = false;
+        let attrs = cookie
+            .split(';')
+            .filter_map(|attr| {
+                let Some((name, domain)) = attr.split_once('=') else {
+                    return Some(attr.to_owned());
+                };
+                if !name.trim().eq_ignore_ascii_case("domain") {
+                    return Some(attr.to_owned());
+                }
+                let domain = domain.trim().trim_start_matches('.');
+                let (_, to) = self
+                    .cookie_domain_rewrites
+                    .iter()
+                    .find(|(from, _)| from.trim_start_matches('.').eq_ignore_ascii_case(domain))?;
+                rewritten = true;
+                (!to.is_empty()).then(|| format!(" Domain={to}"))
+            })
+            .collect::<Vec<_>>();
+        if !rewritten {
+            return None;
+        }
+        HeaderValue::from_str(&attrs.join(";")).ok()
     }
 }
 
@@ -301,44 +556,105 @@ where
         res: &mut Response,
         _ctrl: &mut FlowCtrl,
     ) {
-        match self.build_proxied_request(req, depot).await {
-            Ok(proxied_request) => {
-                match self
-                    .client
-                    .execute(proxied_request, req.extensions_mut().remove())
-                    .await
-                {
-                    Ok(response) => {
-                        let (
-                            salvo_core::http::response::Parts {
-                                status,
-                                // version,
-                                headers,
-                                // extensions,
-                                ..
-                            },
-                            body,
-                        ) = response.into_parts();
-                        res.status_code(status);
-                        for (name, value) in headers {
-                            if let Some(name) = name {
-                                res.headers.insert(name, value);
-                            }
+        match self.send(req, depot).await {
+            Ok(response) => {
+                let (
+                    salvo_core::http::response::Parts {
+                        status,
+                        // version,
+                        mut headers,
+                        // extensions,
+                        ..
+                    },
+                    body,
+                ) = response.into_parts();
+                if status != StatusCode::SWITCHING_PROTOCOLS {
+                    let connection_headers = connection_headers(&headers);
+                    let names = headers.keys().cloned().collect::<Vec<_>>();
+                    for name in names {
+                        if is_hop_by_hop(&name, &connection_headers) {
+                            headers.remove(name);
                         }
-                        res.body(body);
-                    }
-                    Err(e) => {
-                        tracing::error!( error = ?e, uri = ?req.uri(), "get response data failed: {}", e);
-                        res.status_code(StatusCode::INTERNAL_SERVER_ERROR);
                     }
                 }
+                res.status_code(status);
+                for name in headers.keys() {
+                    res.headers.remove(name);
+                }
+                for (name, value) in &headers {
+                    let rewritten = if name == LOCATION {
+                        self.rewritten_location(value)
+                    } else if name == SET_COOKIE {
+                        self.rewritten_cookie(value)
+                    } else {
+                        None
+                    };
+                    res.headers
+                        .append(name, rewritten.unwrap_or_else(|| value.clone()));
+                }
+                res.body(body);
             }
-            Err(e) => {
-                tracing::error!(error = ?e, "build proxied request failed");
+            Err(status) => {
+                res.status_code(status);
             }
         }
     }
 }
+
+/// Headers which are meaningful only for a single connection, they are not forwarded.
+const HOP_BY_HOP_HEADERS: [&str; 9] = [
+    "connection",
+    "keep-alive",
+    "proxy-authenticate",
+    "proxy-authorization",
+    "proxy-connection",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+];
+
+/// Headers listed in the `Connection` header, they are hop-by-hop headers too.
+fn connection_headers(headers: &HeaderMap) -> Vec<String> {
+    headers
+        .get_all(CONNECTION)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|name| name.trim().to_ascii_lowercase())
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+fn is_hop_by_hop(name: &HeaderName, connection_headers: &[String]) -> bool {
+    HOP_BY_HOP_HEADERS.contains(&name.as_str())
+        || connection_headers
+            .iter()
+            .any(|header| header == name.as_str())
+}
+
+fn is_idempotent(method: &Method) -> bool {
+    [
+        Method::GET,
+        Method::HEAD,
+        Method::OPTIONS,
+        Method::TRACE,
+        Method::PUT,
+        Method::DELETE,
+    ]
+    .contains(method)
+}
+
+/// Returns a copy of the body for another attempt, or `None` if the body is a stream which
+/// can't be read again.
+fn replay_body(body: &ReqBody) -> Option<ReqBody> {
+    match body {
+        ReqBody::Once(bytes) => Some(ReqBody::Once(bytes.clone())),
+        body if body.is_end_stream() => Some(ReqBody::None),
+        _ => None,
+    }
+}
+
 #[inline]
 #[allow(dead_code)]
 fn get_upgrade_type(headers: &HeaderMap) -> Option<&str> {
@@ -370,6 +686,24 @@ fn get_upgrade_type(headers: &HeaderMap) -> Option<&str> {
 mod tests {
     use super::*;
 
+    /// Url of an upstream which is bound to an ephemeral port.
+    #[cfg(feature = "hyper-client")]
+    fn upstream_url(acceptor: &impl salvo_core::conn::Acceptor) -> String {
+        let addr = acceptor.holdings()[0]
+            .local_addr
+            .clone()
+            .into_std()
+            .unwrap();
+        format!("http://{addr}")
+    }
+
+    /// Url of a port which nothing listens on.
+    #[cfg(feature = "hyper-client")]
+    fn dead_url() -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        format!("http://{}", listener.local_addr().unwrap())
+    }
+
     #[test]
     fn test_encode_url_path() {
         let path = "/test/path";
@@ -377,6 +711,184 @@ mod tests {
         assert_eq!(encoded_path, "/test/path");
     }
 
+    #[cfg(feature = "hyper-client")]
+    #[tokio::test]
+    async fn test_proxy_upstream() {
+        use salvo_core::prelude::*;
+        use salvo_core::test::{ResponseExt, TestClient};
+
+        #[handler]
+        async fn echo(req: &mut Request, res: &mut Response) {
+            res.add_header("keep-alive", "timeout=5", true).unwrap();
+            res.add_header(SET_COOKIE, "a=1; Path=/; Domain=127.0.0.1", false)
+                .unwrap();
+            res.add_header(SET_COOKIE, "b=2; Domain=.other.com", false)
+                .unwrap();
+            res.render(format!(
+                "{} {} {}",
+                req.uri().path_and_query().unwrap(),
+                req.headers().contains_key("x-secret"),
+                req.headers().contains_key("x-keep")
+            ));
+        }
+        #[handler]
+        async fn redirect(req: &mut Request, res: &mut Response) {
+            let port = req.local_addr().port().unwrap_or_default();
+            res.redirect_found(format!("http://127.0.0.1:{port}/login"));
+        }
+        #[handler]
+        async fn slow() {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+        }
+
+        let acceptor = TcpListener::new("127.0.0.1:0").bind().await;
+        let url = upstream_url(&acceptor);
+        let upstream = Router::new()
+            .push(Router::with_path("echo").get(echo))
+            .push(Router::with_path("redirect").get(redirect))
+            .push(Router::with_path("slow").get(slow));
+        tokio::spawn(Server::new(acceptor).serve(upstream));
+
+        let proxy = Proxy::new(url.clone(), HyperClient::https_or_http())
+            .timeout(Duration::from_millis(500))
+            .retries(1)
+            .rewrite_location(format!("{url}/"), "https://example.com/api/")
+            .rewrite_cookie_domain("127.0.0.1", "example.com");
+        let service = Service::new(Router::with_path("api/<**rest>").goal(proxy));
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/api/echo?page=2")
+            .add_header(CONNECTION, "x-secret", true)
+            .add_header("x-secret", "1", true)
+            .add_header("x-keep", "1", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::OK));
+        assert!(!res.headers().contains_key("keep-alive"));
+        let cookies = res
+            .headers()
+            .get_all(SET_COOKIE)
+            .iter()
+            .map(|value| value.to_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            cookies,
+            ["a=1; Path=/; Domain=example.com", "b=2; Domain=.other.com"]
+        );
+        assert_eq!(res.take_string().await.unwrap(), "/echo?page=2 false true");
+
+        let res = TestClient::get("http://127.0.0.1:5801/api/redirect")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::FOUND));
+        assert_eq!(res.headers()[LOCATION], "https://example.com/api/login");
+
+        let res = TestClient::get("http://127.0.0.1:5801/api/slow")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::GATEWAY_TIMEOUT));
+
+        let service = Service::new(
+            Router::with_path("<**rest>")
+                .goal(Proxy::new(dead_url(), HyperClient::https_or_http())),
+        );
+        let res = TestClient::get("http://127.0.0.1:5801/echo")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::BAD_GATEWAY));
+    }
+
+    #[cfg(feature = "hyper-client")]
+    #[tokio::test]
+    async fn test_proxy_pool() {
+        use salvo_core::prelude::*;
+        use salvo_core::test::{ResponseExt, TestClient};
+
+        #[handler]
+        async fn hello() -> &'static str {
+            "hello"
+        }
+
+        let acceptor = TcpListener::new("127.0.0.1:0").bind().await;
+        let url = upstream_url(&acceptor);
+        tokio::spawn(Server::new(acceptor).serve(Router::new().get(hello).post(hello)));
+
+        // Nothing listens on the first upstream.
+        let urls = [dead_url(), url];
+        let pool = Arc::new(UpstreamPool::new(urls.clone(), RoundRobin::new()).max_failures(1));
+        let proxy = Proxy::new(pool.clone(), HyperClient::https_or_http()).retries(1);
+        let service = Service::new(Router::with_path("<**rest>").goal(proxy));
+        for _ in 0..3 {
+            let mut res = TestClient::get("http://127.0.0.1:5801/")
+                .send(&service)
+                .await;
+            assert_eq!(res.take_string().await.unwrap(), "hello");
+        }
+        let dead = &pool.upstreams()[0];
+        assert!(!dead.is_healthy());
+        assert_eq!(dead.total_requests(), 1);
+        assert_eq!(pool.upstreams()[1].total_requests(), 3);
+
+        // Non-idempotent requests are not retried by default.
+        let pool = Arc::new(UpstreamPool::new(urls, RoundRobin::new()));
+        let proxy = Proxy::new(pool.clone(), HyperClient::https_or_http()).retries(1);
+        let service = Service::new(Router::with_path("<**rest>").goal(proxy));
+        let res = TestClient::post("http://127.0.0.1:5801/")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::BAD_GATEWAY));
+
+        let proxy = Proxy::new(pool, HyperClient::https_or_http())
+            .retries(1)
+            .retry_non_idempotent(true);
+        let service = Service::new(Router::with_path("<**rest>").goal(proxy));
+        let mut res = TestClient::post("http://127.0.0.1:5801/")
+            .send(&service)
+            .await;
+        assert_eq!(res.take_string().await.unwrap(), "hello");
+    }
+
+    #[cfg(feature = "hyper-client")]
+    #[tokio::test]
+    async fn test_proxy_pool_cancelled() {
+        use salvo_core::prelude::*;
+        use salvo_core::test::TestClient;
+
+        #[handler]
+        async fn slow() {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+        }
+
+        let acceptor = TcpListener::new("127.0.0.1:0").bind().await;
+        let url = upstream_url(&acceptor);
+        tokio::spawn(Server::new(acceptor).serve(Router::new().get(slow)));
+
+        let pool = Arc::new(UpstreamPool::new([url], LeastConnections).max_failures(1));
+        let proxy = Proxy::new(pool.clone(), HyperClient::https_or_http());
+        let service = Service::new(Router::with_path("<**rest>").goal(proxy));
+        // The pending request is dropped, as if the client disconnected.
+        let sent = tokio::time::timeout(
+            Duration::from_millis(200),
+            TestClient::get("http://127.0.0.1:5801/").send(&service),
+        )
+        .await;
+        assert!(sent.is_err());
+        let upstream = &pool.upstreams()[0];
+        assert_eq!(upstream.active(), 0);
+        assert_eq!(upstream.total_requests(), 1);
+        assert_eq!(upstream.total_failures(), 0);
+        assert!(upstream.is_healthy());
+    }
+
+    #[test]
+    fn test_replay_body() {
+        assert!(replay_body(&ReqBody::None).is_some());
+        assert!(
+            matches!(replay_body(&ReqBody::Once("hello".into())), Some(ReqBody::Once(bytes)) if bytes == "hello")
+        );
+        assert!(is_idempotent(&Method::PUT));
+        assert!(!is_idempotent(&Method::POST));
+    }
+
     #[test]
     fn test_get_upgrade_type() {
         let mut headers = HeaderMap::new();
@@ -4,6 +4,9 @@ use time::OffsetDateTime;
 use super::{BasicQuota, RateGuard};
 
 /// Fixed window implement.
+///
+/// At most `limit` requests of [`BasicQuota`] are allowed in every period, the window starts with
+/// the first request and the count is reset when the period is over.
 #[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct FixedGuard {
     reset: OffsetDateTime,
@@ -31,18 +34,12 @@ impl FixedGuard {
 impl RateGuard for FixedGuard {
     type Quota = BasicQuota;
     async fn verify(&mut self, quota: &Self::Quota) -> bool {
-        if self.quota.is_none() || OffsetDateTime::now_utc() > self.reset || self.quota.as_ref() != Some(quota) {
-            if self.quota.as_ref() != Some(quota) {
-                let mut quota = quota.clone();
-                if quota.limit == 0 {
-                    quota.limit = 1;
-                }
-                self.quota = Some(quota);
-            }
+        if self.quota.as_ref() != Some(quota) || OffsetDateTime::now_utc() > self.reset {
+            self.quota = Some(quota.clone());
             self.reset = OffsetDateTime::now_utc() + quota.period;
             self.count = 1;
             true
-        } else if self.count < quota.limit {
+        } else if self.count < quota.limit.max(1) {
             self.count += 1;
             true
         } else {
@@ -51,7 +48,7 @@ impl RateGuard for FixedGuard {
     }
 
     async fn remaining(&self, quota: &Self::Quota) -> usize {
-        quota.limit - self.count
+        quota.limit.saturating_sub(self.count)
     }
 
     async fn reset(&self, _: &Self::Quota) -> i64 {
@@ -4,11 +4,39 @@
 //! from a particular IP or id within a time period.
 //!
 //! [`RateIssuer`] is used to issue a key to request, your can define your custom `RateIssuer`.
-//! If you want just identify user by IP address, you can use [`RemoteIpIssuer`].
+//! If you want just identify user by IP address, you can use [`RemoteIpIssuer`]. A closure
+//! `Fn(&mut Request, &Depot) -> Option<Key>` is also a `RateIssuer`, for example to limit by API key.
 //!
 //! [`QuotaGetter`] is used to get quota for every key.
 //!
-//! [`RateGuard`] is strategy to verify is the request exceeded quota.
+//! [`RateGuard`] is strategy to verify is the request exceeded quota, [`FixedGuard`] implements a
+//! fixed window and [`SlidingGuard`] implements a sliding window.
+//!
+//! [`RateStore`] is used to save the guard of every key, [`MokaStore`] is an in-memory store.
+//! Implement `RateStore` to share the state between servers, for example in Redis.
+//!
+//! When the quota is exceeded, the response is `429 Too Many Requests` with a `Retry-After` header.
+//! Use [`RateLimiter::add_headers`] to also add the `X-RateLimit-Limit`, `X-RateLimit-Remaining`
+//! and `X-RateLimit-Reset` headers to every response.
+//!
+//! ```
+//! use salvo_core::prelude::*;
+//! use salvo_rate_limiter::{BasicQuota, FixedGuard, MokaStore, RateLimiter};
+//!
+//! #[handler]
+//! async fn hello() -> &'static str {
+//!     "Hello World"
+//! }
+//!
+//! let limiter = RateLimiter::new(
+//!     FixedGuard::new(),
+//!     MokaStore::new(),
+//!     |req: &mut Request, _: &Depot| req.header::<String>("x-api-key"),
+//!     BasicQuota::per_minute(60),
+//! )
+//! .add_headers(true);
+//! let router = Router::new().hoop(limiter).get(hello);
+//! ```
 //!
 //! Read more: <https://salvo.rs>
 #![doc(html_favicon_url = "https://salvo.rs/favicon-32x32.png")]
@@ -22,8 +50,9 @@ use std::hash::Hash;
 
 use salvo_core::conn::SocketAddr;
 use salvo_core::handler::{none_skipper, Skipper};
-use salvo_core::http::{HeaderValue, Request, Response, StatusCode, StatusError};
+use salvo_core::http::{header, HeaderValue, Request, Response, StatusCode, StatusError};
 use salvo_core::{async_trait, Depot, FlowCtrl, Handler};
+use time::OffsetDateTime;
 
 mod quota;
 pub use quota::{BasicQuota, CelledQuota, QuotaGetter};
@@ -104,6 +133,9 @@ pub trait RateGuard: Clone + Send + Sync + 'static {
 }
 
 /// `RateStore` is used to store rate limit data.
+///
+/// The guard is loaded before and saved after every request, `refer` is the initial guard used for
+/// new keys.
 pub trait RateStore: Send + Sync + 'static {
     /// Error type for RateStore.
     type Error: StdError;
@@ -232,6 +264,10 @@ where
             );
         }
         if !verified {
+            let retry_after =
+                guard.reset(&quota).await - OffsetDateTime::now_utc().unix_timestamp();
+            res.headers_mut()
+                .insert(header::RETRY_AFTER, retry_after.max(1).into());
             res.status_code(StatusCode::TOO_MANY_REQUESTS);
             ctrl.skip_rest();
         }
@@ -424,4 +460,73 @@ mod tests {
         assert_eq!(respone.status_code, Some(StatusCode::OK));
         assert_eq!(respone.take_string().await.unwrap(), "Limited page");
     }
+
+    #[tokio::test]
+    async fn test_rate_limit_headers() {
+        let limiter = RateLimiter::new(
+            FixedGuard::new(),
+            MokaStore::new(),
+            |req: &mut Request, _: &Depot| req.header::<String>("x-api-key"),
+            BasicQuota::set_seconds(2, 10),
+        )
+        .add_headers(true);
+        let service = Service::new(Router::with_hoop(limiter).get(limited));
+        let access = |key: &'static str| {
+            TestClient::get("http://127.0.0.1:5800/")
+                .add_header("x-api-key", key, true)
+                .send(&service)
+        };
+
+        let res = access("key1").await;
+        assert_eq!(res.status_code, Some(StatusCode::OK));
+        assert_eq!(res.headers()["x-ratelimit-limit"], "2");
+        assert_eq!(res.headers()["x-ratelimit-remaining"], "1");
+        assert!(res.headers().get(header::RETRY_AFTER).is_none());
+        let res = access("key1").await;
+        assert_eq!(res.headers()["x-ratelimit-remaining"], "0");
+
+        let res = access("key1").await;
+        assert_eq!(res.status_code, Some(StatusCode::TOO_MANY_REQUESTS));
+        let retry_after = res.headers()[header::RETRY_AFTER]
+            .to_str()
+            .unwrap()
+            .parse::<i64>()
+            .unwrap();
+        assert!((1..=11).contains(&retry_after));
+
+        let res = access("key2").await;
+        assert_eq!(res.status_code, Some(StatusCode::OK));
+
+        let res = TestClient::get("http://127.0.0.1:5800/")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::BAD_REQUEST));
+    }
+
+    #[tokio::test]
+    async fn test_sliding_window() {
+        let limiter = RateLimiter::new(
+            SlidingGuard::new(),
+            MokaStore::new(),
+            UserIssuer,
+            CelledQuota::new(2, 4, time::Duration::milliseconds(800)),
+        );
+        let service = Service::new(Router::with_hoop(limiter).get(limited));
+        let access = || async {
+            TestClient::get("http://127.0.0.1:5800/?user=user1")
+                .send(&service)
+                .await
+                .status_code
+                .unwrap()
+        };
+
+        assert_eq!(access().await, StatusCode::OK);
+        tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+        assert_eq!(access().await, StatusCode::OK);
+        assert_eq!(access().await, StatusCode::TOO_MANY_REQUESTS);
+        // The first request leaves the window, but the second one is still in it.
+        tokio::time::sleep(tokio::time::Duration::from_millis(400)).await;
+        assert_eq!(access().await, StatusCode::OK);
+        assert_eq!(access().await, StatusCode::TOO_MANY_REQUESTS);
+    }
 }
@@ -3,6 +3,10 @@ use time::{Duration, OffsetDateTime};
 use super::{CelledQuota, RateGuard};
 
 /// Sliding window implement.
+///
+/// The period of [`CelledQuota`] is split into `cells` cells, and the requests of the cells which
+/// are still in the window are counted, so a burst at the end of a window and another burst at the
+/// start of the next window can not exceed the limit. Rejected requests are not counted.
 #[derive(Clone, Debug)]
 pub struct SlidingGuard {
     cell_inst: OffsetDateTime,
@@ -34,43 +38,38 @@ impl SlidingGuard {
 impl RateGuard for SlidingGuard {
     type Quota = CelledQuota;
     async fn verify(&mut self, quota: &Self::Quota) -> bool {
-        if self.quota.is_none() || self.quota.as_ref() != Some(quota) {
-            let mut quota = quota.clone();
-            if quota.limit == 0 {
-                quota.limit = 1;
-            }
-            if quota.cells == 0 {
-                quota.cells = 1;
-            }
-            if quota.cells > quota.limit {
-                quota.cells = quota.limit;
-            }
+        if self.quota.as_ref() != Some(quota) {
+            // Keep the original quota so the comparison above still matches on next requests.
+            let cells = quota.cells.clamp(1, quota.limit.max(1));
             self.cell_inst = OffsetDateTime::now_utc();
-            self.cell_span = quota.period / (quota.cells as u32);
-            self.counts = vec![0; quota.cells];
+            self.cell_span = quota.period / (cells as u32);
+            self.counts = vec![0; cells];
             self.head = 0;
             self.counts[0] = 1;
-            self.quota = Some(quota);
+            self.quota = Some(quota.clone());
             return true;
         }
-        let mut delta = OffsetDateTime::now_utc() - self.cell_inst;
-        if delta > quota.period {
-            self.counts = vec![0; quota.cells];
+        // `cell_inst` is the start time of the cell at `head`, move forward to the cell of now.
+        let now = OffsetDateTime::now_utc();
+        let mut delta = now - self.cell_inst;
+        if delta >= quota.period {
+            self.counts.iter_mut().for_each(|count| *count = 0);
             self.head = 0;
-            self.counts[0] = 1;
-            self.cell_inst = OffsetDateTime::now_utc();
-            return true;
+            self.cell_inst = now;
         } else {
-            while delta > self.cell_span {
+            while delta >= self.cell_span {
                 delta -= self.cell_span;
                 self.head = (self.head + 1) % self.counts.len();
                 self.counts[self.head] = 0;
+                self.cell_inst += self.cell_span;
             }
-            self.head = (self.head + 1) % self.counts.len();
+        }
+        if self.counts.iter().sum::<usize>() < quota.limit.max(1) {
             self.counts[self.head] += 1;
-            self.cell_inst = OffsetDateTime::now_utc();
+            true
+        } else {
+            false
         }
-        self.counts.iter().cloned().sum::<usize>() <= quota.limit
     }
 
     async fn remaining(&self, quota: &Self::Quota) -> usize {
@@ -81,7 +80,13 @@ impl RateGuard for SlidingGuard {
     }
 
     async fn reset(&self, quota: &Self::Quota) -> i64 {
-        (self.cell_inst + quota.period).unix_timestamp()
+        // The oldest cell which still has requests leaves the window first.
+        let len = self.counts.len();
+        let age = (0..len)
+            .rev()
+            .find(|age| self.counts[(self.head + len - age) % len] > 0)
+            .unwrap_or_default();
+        (self.cell_inst - self.cell_span * (age as u32) + quota.period).unix_timestamp()
     }
 
     async fn limit(&self, quota: &Self::Quota) -> usize {
@@ -19,21 +19,30 @@
 //! | `openssl` | TLS built on [`openssl-tls`](https://crates.io/crates/openssl) | ❌ |
 //! | `native-tls` | TLS built on [`native-tls`](https://crates.io/crates/native-tls) | ❌ |
 //! | `unix` | Listener based on unix socket | ❌ |
+//! | `systemd` | Accept from the sockets passed by systemd socket activation | ❌ |
 //! | `tower-compat` | Adapters for `tower::Layer` and `tower::Service` | ❌ |
 //! | `anyhow` | Integrate with the [`anyhow`](https://crates.io/crates/anyhow) crate | ❌ |
 //! | `eyre` | Integrate with the [`eyre`](https://crates.io/crates/eyre) crate | ❌ |
+//! | `msgpack` | Support for MessagePack request and response bodies | ❌ |
+//! | `cbor` | Support for CBOR request and response bodies | ❌ |
 //! | `affix-state` | Middleware for adding prefix and suffix to the request path | ❌ |
+//! | `alt-svc` | Middleware for advertising HTTP/3 with the `Alt-Svc` header | ❌ |
 //! | `craft` | Generate handlers or endpoints with shared data | ❌ |
 //! | `basic-auth` | Middleware for basic authentication | ❌ |
 //! | `caching-headers` | Middleware for setting caching headers | ❌ |
 //! | `catch-panic` | Middleware for catching panics | ❌ |
+//! | `circuit-breaker` | Middleware for failing fast when the handlers keep failing | ❌ |
 //! | `concurrency-limiter` | Middleware for limiting concurrency | ❌ |
 //! | `force-https` | Middleware for forcing HTTPS | ❌ |
+//! | `health-check` | Liveness and readiness endpoints for health checks | ❌ |
+//! | `locale` | Middleware for negotiating the locale of the request | ❌ |
 //! | `logging` | Middleware for logging requests and responses | ❌ |
+//! | `prometheus` | Middleware for exporting Prometheus metrics | ❌ |
 //! | `request-id` | Middleware for setting a request ID | ❌ |
 //! | `size-limiter` | Middleware for limiting request size | ❌ |
 //! | `sse` | Server-Sent Events (SSE) middleware | ❌ |
 //! | `timeout` | Middleware for setting a timeout | ❌ |
+//! | `trusted-proxy` | Middleware for restoring the client address behind trusted proxies | ❌ |
 //! | `trailing-slash` | Middleware for handling trailing slashes | ❌ |
 //! | `websocket` | WebSocket implementation | ❌ |
 #![doc(html_favicon_url = "https://salvo.rs/favicon-32x32.png")]
@@ -53,6 +62,11 @@ cfg_feature! {
     // #[doc(no_inline)]
     pub use salvo_extra::affix_state;
 }
+cfg_feature! {
+    #![feature ="alt-svc"]
+    // #[doc(no_inline)]
+    pub use salvo_extra::alt_svc;
+}
 cfg_feature! {
     #![feature ="basic-auth"]
     // #[doc(no_inline)]
@@ -68,16 +82,36 @@ cfg_feature! {
     // #[doc(no_inline)]
     pub use salvo_extra::catch_panic;
 }
+cfg_feature! {
+    #![feature ="circuit-breaker"]
+    // #[doc(no_inline)]
+    pub use salvo_extra::circuit_breaker;
+}
 cfg_feature! {
     #![feature ="force-https"]
     // #[doc(no_inline)]
     pub use salvo_extra::force_https;
 }
+cfg_feature! {
+    #![feature ="health-check"]
+    // #[doc(no_inline)]
+    pub use salvo_extra::health_check;
+}
+cfg_feature! {
+    #![feature ="locale"]
+    // #[doc(no_inline)]
+    pub use salvo_extra::locale;
+}
 cfg_feature! {
     #![feature ="logging"]
     // #[doc(no_inline)]
     pub use salvo_extra::logging;
 }
+cfg_feature! {
+    #![feature ="prometheus"]
+    // #[doc(no_inline)]
+    pub use salvo_extra::prometheus;
+}
 cfg_feature! {
     #![feature ="concurrency-limiter"]
     // #[doc(no_inline)]
@@ -103,6 +137,11 @@ cfg_feature! {
     // #[doc(no_inline)]
     pub use salvo_extra::timeout;
 }
+cfg_feature! {
+    #![feature ="trusted-proxy"]
+    // #[doc(no_inline)]
+    pub use salvo_extra::trusted_proxy;
+}
 cfg_feature! {
     #![feature ="websocket"]
     // #[doc(no_inline)]
@@ -186,6 +225,10 @@ pub mod prelude {
         #![feature ="affix-state"]
         pub use salvo_extra::affix_state;
     }
+    cfg_feature! {
+        #![feature ="alt-svc"]
+        pub use salvo_extra::alt_svc::AltSvc;
+    }
     cfg_feature! {
         #![feature ="basic-auth"]
         pub use salvo_extra::basic_auth::{BasicAuth, BasicAuthDepotExt, BasicAuthValidator};
@@ -211,6 +254,10 @@ pub mod prelude {
         #![feature ="csrf"]
         pub use salvo_csrf::CsrfDepotExt;
     }
+    cfg_feature! {
+        #![feature ="circuit-breaker"]
+        pub use salvo_extra::circuit_breaker::CircuitBreaker;
+    }
     cfg_feature! {
         #![feature ="force-https"]
         pub use salvo_extra::force_https::ForceHttps;
@@ -219,10 +266,22 @@ pub mod prelude {
         #![feature ="jwt-auth"]
         pub use salvo_jwt_auth::{JwtAuthDepotExt, JwtAuth, JwtAuthState};
     }
+    cfg_feature! {
+        #![feature ="health-check"]
+        pub use salvo_extra::health_check::HealthCheck;
+    }
+    cfg_feature! {
+        #![feature ="locale"]
+        pub use salvo_extra::locale::{Locale, LocaleDepotExt, LocaleNegotiator};
+    }
     cfg_feature! {
         #![feature ="logging"]
         pub use salvo_extra::logging::Logger;
     }
+    cfg_feature! {
+        #![feature ="prometheus"]
+        pub use salvo_extra::prometheus::Metrics;
+    }
     cfg_feature! {
         #![feature ="proxy"]
         pub use salvo_proxy::Proxy;
@@ -255,13 +314,17 @@ pub mod prelude {
         #![feature ="tower-compat"]
         pub use salvo_extra::tower_compat::{TowerServiceCompat, TowerLayerCompat};
     }
+    cfg_feature! {
+        #![feature ="trusted-proxy"]
+        pub use salvo_extra::trusted_proxy::{RemoteAddr, TrustedProxy};
+    }
     cfg_feature! {
         #![feature ="websocket"]
         pub use salvo_extra::websocket::WebSocketUpgrade;
     }
     cfg_feature! {
         #![feature ="request-id"]
-        pub use salvo_extra::request_id::RequestId;
+        pub use salvo_extra::request_id::{RequestId, RequestIdExt};
     }
     cfg_feature! {
         #![feature ="serve-static"]
@@ -1,16 +1,18 @@
 //! serve static dir
 
+use std::borrow::Cow;
 use std::collections::{HashMap, HashSet};
 use std::ffi::OsStr;
 use std::fmt::{self, Display, Formatter, Write};
 use std::fs::Metadata;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime};
 
-use salvo_core::fs::NamedFile;
+use salvo_core::fs::{EtagStrategy, NamedFile};
 use salvo_core::handler::Handler;
-use salvo_core::http::header::ACCEPT_ENCODING;
+use salvo_core::http::header::{ACCEPT_ENCODING, VARY};
+use salvo_core::http::headers::CacheControl;
 use salvo_core::http::{self, HeaderValue, Request, Response, StatusCode, StatusError};
 use salvo_core::writing::Text;
 use salvo_core::{async_trait, Depot, FlowCtrl, IntoVecString};
@@ -73,6 +75,15 @@ impl From<CompressionAlgo> for HeaderValue {
     }
 }
 
+/// Precompressed variations are looked up in this order, the first one accepted by the client and
+/// existing on disk is served.
+const PRECOMPRESSED_PRIORITY: [CompressionAlgo; 4] = [
+    CompressionAlgo::Brotli,
+    CompressionAlgo::Zstd,
+    CompressionAlgo::Gzip,
+    CompressionAlgo::Deflate,
+];
+
 /// Trait for collecting static roots.
 pub trait StaticRoots {
     /// Collect all static roots.
@@ -140,15 +151,24 @@ pub struct StaticDir {
     exclude_filters: Vec<Box<dyn Fn(&str) -> bool + Send + Sync>>,
     /// Auto list the directory if default file not found.
     pub auto_list: bool,
+    /// Serve precompressed variations of files, default is true.
+    pub precompressed: bool,
     /// Compressed variations.
     ///
     /// The key is the compression algorithm, and the value is the file extension.
     /// If the compression file exists, it will serve the compressed file instead of the original file.
+    /// Brotli is preferred, then Zstd, Gzip and Deflate.
     pub compressed_variations: HashMap<CompressionAlgo, Vec<String>>,
     /// Default file names list.
     pub defaults: Vec<String>,
     /// Fallback file name. This is used when the requested file is not found.
     pub fallback: Option<String>,
+    /// How the `ETag` of served files is computed.
+    pub etag_strategy: EtagStrategy,
+    /// `Cache-Control` header of served files.
+    pub cache_control: Option<CacheControl>,
+    /// Serve fingerprinted files, such as `app.3f2a9c1b.js`, with long-lived immutable caching.
+    pub immutable_fingerprinted: bool,
 }
 impl StaticDir {
     /// Create new `StaticDir`.
@@ -166,9 +186,13 @@ impl StaticDir {
             include_dot_files: false,
             exclude_filters: vec![],
             auto_list: false,
+            precompressed: true,
             compressed_variations,
             defaults: vec![],
             fallback: None,
+            etag_strategy: EtagStrategy::default(),
+            cache_control: None,
+            immutable_fingerprinted: false,
         }
     }
 
@@ -192,12 +216,27 @@ impl StaticDir {
     }
 
     /// Sets auto_list.
+    ///
+    /// A directory without default file is listed with the names, sizes and modified times of
+    /// its entries, as HTML, JSON or XML according to the `Accept` header. Dot files and
+    /// excluded files are not listed.
     #[inline]
     pub fn auto_list(mut self, auto_list: bool) -> Self {
         self.auto_list = auto_list;
         self
     }
 
+    /// Sets whether to serve precompressed variations of files.
+    ///
+    /// When it is enabled and the client accepts the encoding, a sidecar file such as `app.js.br`
+    /// or `app.js.gz` is served with `Content-Encoding` instead of `app.js`, avoiding compressing
+    /// it on every request. The original file is served if no accepted variation exists.
+    #[inline]
+    pub fn precompressed(mut self, precompressed: bool) -> Self {
+        self.precompressed = precompressed;
+        self
+    }
+
     /// Sets compressed_variations.
     #[inline]
     pub fn compressed_variation<A>(mut self, algo: A, exts: &str) -> Self
@@ -236,6 +275,38 @@ impl StaticDir {
         self
     }
 
+    /// Sets how the `ETag` of served files is computed.
+    ///
+    /// The default is [`EtagStrategy::Metadata`].
+    #[inline]
+    pub fn etag_strategy(mut self, etag_strategy: EtagStrategy) -> Self {
+        self.etag_strategy = etag_strategy;
+        self
+    }
+
+    /// Sets the `Cache-Control` header of served files.
+    #[inline]
+    pub fn cache_control(mut self, cache_control: CacheControl) -> Self {
+        self.cache_control = Some(cache_control);
+        self
+    }
+
+    /// Serve fingerprinted files with `Cache-Control: public, immutable, max-age=31536000`.
+    ///
+    /// A file is fingerprinted when its name has a hex hash segment of at least 8 characters with
+    /// a letter, such as `app.3f2a9c1b.js` or `chunk-5d41402a.css`. Other files
+    /// are served with [`StaticDir::cache_control`] if it is set.
+    #[inline]
+    pub fn immutable_fingerprinted(mut self, immutable_fingerprinted: bool) -> Self {
+        self.immutable_fingerprinted = immutable_fingerprinted;
+        self
+    }
+
+    #[inline]
+    fn is_excluded(&self, path: &str) -> bool {
+        self.exclude_filters.iter().any(|filter| filter(path))
+    }
+
     #[inline]
     fn is_compressed_ext(&self, ext: &str) -> bool {
         for exts in self.compressed_variations.values() {
@@ -313,11 +384,8 @@ impl Handler for StaticDir {
         let rel_path = format_url_path_safely(rel_path);
         let mut files: HashMap<String, Metadata> = HashMap::new();
         let mut dirs: HashMap<String, Metadata> = HashMap::new();
-        let is_dot_file = Path::new(&rel_path)
-            .file_name()
-            .and_then(|s| s.to_str())
-            .map(|s| s.starts_with('.'))
-            .unwrap_or(false);
+        // Files inside dot directories, such as `.git/config`, are hidden as well.
+        let is_dot_file = rel_path.split('/').any(|part| part.starts_with('.'));
         let mut abs_path = None;
         if self.include_dot_files || !is_dot_file {
             for root in &self.roots {
@@ -326,10 +394,8 @@ impl Handler for StaticDir {
                 if !Path::new(&raw_path).starts_with(root) {
                     continue;
                 }
-                for filter in &self.exclude_filters {
-                    if filter(&raw_path) {
-                        continue;
-                    }
+                if self.is_excluded(&raw_path) {
+                    continue;
                 }
                 let path = Path::new(&raw_path);
                 if path.is_dir() {
@@ -361,10 +427,8 @@ impl Handler for StaticDir {
         if abs_path.is_none() && !fallback.is_empty() {
             for root in &self.roots {
                 let raw_path = join_path!(root, fallback);
-                for filter in &self.exclude_filters {
-                    if filter(&raw_path) {
-                        continue;
-                    }
+                if self.is_excluded(&raw_path) {
+                    continue;
                 }
                 let path = Path::new(&raw_path);
                 if path.is_file() {
@@ -383,6 +447,16 @@ impl Handler for StaticDir {
         };
 
         if abs_path.is_file() {
+            let cache_control = if self.immutable_fingerprinted && is_fingerprinted(&abs_path) {
+                Some(
+                    CacheControl::new()
+                        .with_public()
+                        .with_immutable()
+                        .with_max_age(Duration::from_secs(365 * 24 * 60 * 60)),
+                )
+            } else {
+                self.cache_control.clone()
+            };
             let ext = abs_path
                 .extension()
                 .and_then(|s| s.to_str())
@@ -392,50 +466,54 @@ impl Handler for StaticDir {
                 .map(|ext| self.is_compressed_ext(ext))
                 .unwrap_or(false);
             let mut content_encoding = None;
-            let named_path = if !is_compressed_ext {
-                if !self.compressed_variations.is_empty() {
-                    let mut new_abs_path = None;
-                    let header = req
-                        .headers()
-                        .get(ACCEPT_ENCODING)
-                        .and_then(|v| v.to_str().ok())
-                        .unwrap_or_default();
-                    let accept_algos = http::parse_accept_encoding(header)
-                        .into_iter()
-                        .filter_map(|(algo, _level)| {
-                            if let Ok(algo) = algo.parse::<CompressionAlgo>() {
-                                Some(algo)
-                            } else {
-                                None
-                            }
-                        })
-                        .collect::<HashSet<_>>();
-                    for (algo, exts) in &self.compressed_variations {
-                        if accept_algos.contains(algo) {
-                            for zip_ext in exts {
+            let named_path = if self.precompressed
+                && !is_compressed_ext
+                && !self.compressed_variations.is_empty()
+            {
+                // The response depends on `Accept-Encoding` even if the original file is sent.
+                res.headers_mut()
+                    .insert(VARY, HeaderValue::from_static("accept-encoding"));
+                let header = req
+                    .headers()
+                    .get(ACCEPT_ENCODING)
+                    .and_then(|v| v.to_str().ok())
+                    .unwrap_or_default();
+                let accept_algos = http::parse_accept_encoding(header)
+                    .into_iter()
+                    .filter(|(_, q)| *q > 0)
+                    .filter_map(|(algo, _)| algo.parse::<CompressionAlgo>().ok())
+                    .collect::<HashSet<_>>();
+                let compressed = PRECOMPRESSED_PRIORITY
+                    .iter()
+                    .filter(|algo| accept_algos.contains(algo))
+                    .find_map(|algo| {
+                        self.compressed_variations
+                            .get(algo)?
+                            .iter()
+                            .find_map(|zip_ext| {
                                 let mut path = abs_path.clone();
-                                path.as_mut_os_string().push(&*format!(".{}", zip_ext));
-                                if path.is_file() {
-                                    new_abs_path = Some(path);
-                                    content_encoding = Some(algo.to_string());
-                                    break;
-                                }
-                            }
-                        }
+                                path.as_mut_os_string().push(format!(".{zip_ext}"));
+                                path.is_file().then_some((path, *algo))
+                            })
+                    });
+                match compressed {
+                    Some((path, algo)) => {
+                        content_encoding = Some(algo.to_string());
+                        path
                     }
-                    new_abs_path.unwrap_or(abs_path)
-                } else {
-                    abs_path
+                    None => abs_path,
                 }
             } else {
                 abs_path
             };
 
             let builder = {
-                let mut builder = NamedFile::builder(named_path).content_type(
-                    mime_infer::from_ext(ext.as_deref().unwrap_or_default())
-                        .first_or_octet_stream(),
-                );
+                let mut builder = NamedFile::builder(named_path)
+                    .content_type(
+                        mime_infer::from_ext(ext.as_deref().unwrap_or_default())
+                            .first_or_octet_stream(),
+                    )
+                    .etag_strategy(self.etag_strategy);
                 if let Some(content_encoding) = content_encoding {
                     builder = builder.content_encoding(content_encoding);
                 }
@@ -445,8 +523,13 @@ impl Handler for StaticDir {
                 builder
             };
             if let Ok(named_file) = builder.build().await {
-                let headers = req.headers();
-                named_file.send(headers, res).await;
+                named_file.send_to(req, res).await;
+                let status = res.status_code.unwrap_or(StatusCode::OK);
+                if let Some(cache_control) = cache_control {
+                    if status.is_success() || status == StatusCode::NOT_MODIFIED {
+                        res.typed_header(cache_control);
+                    }
+                }
             } else {
                 res.render(StatusError::internal_server_error().brief("Read file failed."));
             }
@@ -457,10 +540,8 @@ impl Handler for StaticDir {
                     let file_name = entry.file_name().to_string_lossy().to_string();
                     if self.include_dot_files || !file_name.starts_with('.') {
                         let raw_path = join_path!(&abs_path, &file_name);
-                        for filter in &self.exclude_filters {
-                            if filter(&raw_path) {
-                                continue;
-                            }
+                        if self.is_excluded(&raw_path) {
+                            continue;
                         }
                         if let Ok(metadata) = entry.metadata().await {
                             if metadata.is_dir() {
@@ -496,6 +577,18 @@ impl Handler for StaticDir {
     }
 }
 
+/// Returns true if the file name has a content hash segment, such as `app.3f2a9c1b.js`.
+fn is_fingerprinted(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        return false;
+    };
+    name.split(['.', '-', '_']).skip(1).any(|part| {
+        part.len() >= 8
+            && part.chars().all(|c| c.is_ascii_hexdigit())
+            && part.chars().any(|c| c.is_ascii_alphabetic())
+    })
+}
+
 #[inline]
 fn list_json(current: &CurrentInfo) -> String {
     json!(current).to_string()
@@ -510,25 +603,43 @@ fn list_xml(current: &CurrentInfo) -> String {
             let _ = write!(
                 ftxt,
                 "<dir><name>{}</name><modified>{}</modified><link>{}</link></dir>",
-                dir.name,
+                escape_markup(&dir.name),
                 dir.modified.format(&format).expect("format time failed"),
-                encode_url_path(&dir.name),
+                escape_markup(&encode_url_path(&dir.name)),
             );
         }
         for file in &current.files {
             let _ = write!(
                 ftxt,
                 "<file><name>{}</name><modified>{}</modified><size>{}</size><link>{}</link></file>",
-                file.name,
+                escape_markup(&file.name),
                 file.modified.format(&format).expect("format time failed"),
                 file.size,
-                encode_url_path(&file.name),
+                escape_markup(&encode_url_path(&file.name)),
             );
         }
     }
     ftxt.push_str("</list>");
     ftxt
 }
+/// Escapes file names for HTML and XML listings.
+fn escape_markup(value: &str) -> Cow<'_, str> {
+    if !value.contains(['&', '<', '>', '"', '\'']) {
+        return Cow::Borrowed(value);
+    }
+    let mut escaped = String::with_capacity(value.len() + 8);
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    Cow::Owned(escaped)
+}
 fn human_size(bytes: u64) -> String {
     let units = ["B", "KB", "MB", "GB", "TB", "PB", "EB", "ZB", "YB"];
     let mut index = 0;
@@ -558,8 +669,12 @@ fn list_html(current: &CurrentInfo) -> String {
             HOME_ICON,
             segments
                 .map(|seg| {
-                    link = format!("{link}/{seg}");
-                    format!("/<a href=\"{link}\">{seg}</a>")
+                    link = format!("{link}/{}", encode_url_path(seg));
+                    format!(
+                        "/<a href=\"{}\">{}</a>",
+                        escape_markup(&link),
+                        escape_markup(seg)
+                    )
                 })
                 .collect::<Vec<_>>()
                 .join("")
@@ -571,7 +686,7 @@ fn list_html(current: &CurrentInfo) -> String {
         <meta name="viewport" content="width=device-width">
         <title>{}</title>
         <style>{}</style></head><body><header><h3>Index of: {}</h3></header><hr/>"#,
-        current.path,
+        escape_markup(&current.path),
         HTML_STYLE,
         header_links(&current.path)
     );
@@ -592,8 +707,8 @@ fn list_html(current: &CurrentInfo) -> String {
                 ftxt,
                 r#"<tr><td>{}</td><td><a href="./{}/">{}</a></td><td>{}</td><td></td></tr>"#,
                 DIR_ICON,
-                encode_url_path(&dir.name),
-                dir.name,
+                escape_markup(&encode_url_path(&dir.name)),
+                escape_markup(&dir.name),
                 dir.modified.format(&format).expect("format time failed"),
             );
         }
@@ -602,8 +717,8 @@ fn list_html(current: &CurrentInfo) -> String {
                 ftxt,
                 r#"<tr><td>{}</td><td><a href="./{}">{}</a></td><td>{}</td><td>{}</td></tr>"#,
                 FILE_ICON,
-                encode_url_path(&file.name),
-                file.name,
+                escape_markup(&encode_url_path(&file.name)),
+                escape_markup(&file.name),
                 file.modified.format(&format).expect("format time failed"),
                 human_size(file.size)
             );
@@ -660,7 +775,9 @@ const HOME_ICON: &str = r#"<svg aria-hidden="true" data-icon="home" viewBox="0 0
 
 #[cfg(test)]
 mod tests {
-    use crate::dir::human_size;
+    use std::path::Path;
+
+    use crate::dir::{human_size, is_fingerprinted};
 
     #[tokio::test]
     async fn test_convert_bytes_to_units() {
@@ -683,4 +800,24 @@ mod tests {
         assert_eq!("1 PB", human_size(unit * unit * unit * unit * unit));
         assert_eq!("1 PB", human_size(unit * unit * unit * unit * unit - 1));
     }
+
+    #[test]
+    fn test_is_fingerprinted() {
+        for name in [
+            "app.3f2a9c1b.js",
+            "chunk-5d41402a.css",
+            "logo.1a2b3c4d5e.svg",
+        ] {
+            assert!(is_fingerprinted(Path::new(name)), "{name}");
+        }
+        for name in [
+            "index.html",
+            "jquery.min.js",
+            "3f2a9c1b.js",
+            "app.abcdefgh.js",
+            "app.js",
+        ] {
+            assert!(!is_fingerprinted(Path::new(name)), "{name}");
+        }
+    }
 }
@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 
-use salvo_core::fs::{NamedFile, NamedFileBuilder};
+use salvo_core::fs::{EtagStrategy, NamedFile, NamedFileBuilder};
 use salvo_core::http::{Request, Response, StatusError};
 use salvo_core::{async_trait, Depot, FlowCtrl, Handler, Writer};
 
@@ -25,6 +25,14 @@ impl StaticFile {
     pub fn chunk_size(self, size: u64) -> Self {
         Self(self.0.buffer_size(size))
     }
+
+    /// Sets how the `ETag` of the file is computed.
+    ///
+    /// The default is [`EtagStrategy::Metadata`].
+    #[inline]
+    pub fn etag_strategy(self, etag_strategy: EtagStrategy) -> Self {
+        Self(self.0.etag_strategy(etag_strategy))
+    }
 }
 
 #[async_trait]
@@ -97,6 +97,7 @@ pub(crate) fn redirect_to_dir_url(req_uri: &Uri, res: &mut Response) {
 
 #[cfg(test)]
 mod tests {
+    use salvo_core::http::headers::CacheControl;
     use salvo_core::prelude::*;
     use salvo_core::test::{ResponseExt, TestClient};
 
@@ -221,6 +222,148 @@ mod tests {
         assert_eq!(response.status_code.unwrap(), StatusCode::NOT_FOUND);
     }
 
+    #[cfg(feature = "content-hash")]
+    #[tokio::test]
+    async fn test_serve_static_etag() {
+        let router = Router::new()
+            .push(
+                Router::with_path("file.txt").get(
+                    StaticFile::new("test/static/test1.txt")
+                        .etag_strategy(salvo_core::fs::EtagStrategy::ContentHash),
+                ),
+            )
+            .push(Router::with_path("dir/<**path>").get(StaticDir::new("test/static")));
+        let service = Service::new(router);
+
+        for url in [
+            "http://127.0.0.1:5801/file.txt",
+            "http://127.0.0.1:5801/dir/test1.txt",
+        ] {
+            let response = TestClient::get(url).send(&service).await;
+            let etag = response.headers().get("etag").unwrap().clone();
+            let response = TestClient::get(url)
+                .add_header("if-none-match", etag, true)
+                .send(&service)
+                .await;
+            assert_eq!(response.status_code.unwrap(), StatusCode::NOT_MODIFIED);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_serve_static_dir_listing() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join(".git")).unwrap();
+        std::fs::write(dir.path().join(".git/config"), "secret").unwrap();
+        std::fs::write(dir.path().join(".env"), "secret").unwrap();
+        std::fs::write(dir.path().join("debug.log"), "log").unwrap();
+        std::fs::write(dir.path().join("<b>&amp.txt"), "bold").unwrap();
+        let router = Router::with_path("<*path>").get(
+            StaticDir::new(dir.path().to_path_buf())
+                .auto_list(true)
+                .exclude(|path| path.ends_with(".log")),
+        );
+        let service = Service::new(router);
+
+        let content = TestClient::get("http://127.0.0.1:5801/")
+            .add_header("accept", "text/html", true)
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert!(content.contains("&lt;b&gt;&amp;amp.txt"));
+        assert!(!content.contains("<b>"));
+        assert!(!content.contains(".git") && !content.contains(".env"));
+        assert!(!content.contains("debug.log"));
+
+        for path in [".env", ".git/config", "debug.log"] {
+            let response = TestClient::get(format!("http://127.0.0.1:5801/{path}"))
+                .send(&service)
+                .await;
+            assert_eq!(response.status_code.unwrap(), StatusCode::NOT_FOUND);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_serve_precompressed() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("app.js"), "plain").unwrap();
+        std::fs::write(dir.path().join("app.js.gz"), "gzip").unwrap();
+        std::fs::write(dir.path().join("app.js.br"), "brotli").unwrap();
+        let root = dir.path().to_path_buf();
+
+        async fn access(router: Router, accept_encoding: &str) -> (Option<String>, String) {
+            let mut response = TestClient::get("http://127.0.0.1:5801/app.js")
+                .add_header("accept-encoding", accept_encoding, true)
+                .send(router)
+                .await;
+            assert_eq!(response.headers().get("vary").unwrap(), "accept-encoding");
+            assert!(response
+                .headers()
+                .get("content-type")
+                .unwrap()
+                .to_str()
+                .unwrap()
+                .contains("javascript"));
+            let encoding = response
+                .headers()
+                .get("content-encoding")
+                .map(|v| v.to_str().unwrap().to_owned());
+            let body = response.take_bytes(None).await.unwrap();
+            (encoding, String::from_utf8(body.to_vec()).unwrap())
+        }
+        let router = || Router::with_path("<*path>").get(StaticDir::new(root.clone()));
+
+        let (encoding, body) = access(router(), "gzip, br").await;
+        assert_eq!((encoding.as_deref(), body.as_str()), (Some("br"), "brotli"));
+        let (encoding, body) = access(router(), "gzip, br;q=0").await;
+        assert_eq!((encoding.as_deref(), body.as_str()), (Some("gzip"), "gzip"));
+        let (encoding, body) = access(router(), "zstd").await;
+        assert_eq!((encoding, body.as_str()), (None, "plain"));
+
+        let router =
+            Router::with_path("<*path>").get(StaticDir::new(root.clone()).precompressed(false));
+        let response = TestClient::get("http://127.0.0.1:5801/app.js")
+            .add_header("accept-encoding", "br", true)
+            .send(router)
+            .await;
+        assert!(!response.headers().contains_key("content-encoding"));
+    }
+
+    #[tokio::test]
+    async fn test_serve_cache_control() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("index.html"), "index").unwrap();
+        std::fs::write(dir.path().join("app.3f2a9c1b.js"), "app").unwrap();
+        std::fs::write(dir.path().join("report.20240101.pdf"), "report").unwrap();
+        let router = Router::with_path("<*path>").get(
+            StaticDir::new(dir.path().to_path_buf())
+                .cache_control(CacheControl::new().with_no_cache())
+                .immutable_fingerprinted(true),
+        );
+        let service = Service::new(router);
+
+        let response = TestClient::get("http://127.0.0.1:5801/app.3f2a9c1b.js")
+            .send(&service)
+            .await;
+        assert_eq!(
+            response.headers()["cache-control"],
+            "public, immutable, max-age=31536000"
+        );
+        let response = TestClient::get("http://127.0.0.1:5801/index.html")
+            .send(&service)
+            .await;
+        assert_eq!(response.headers()["cache-control"], "no-cache");
+        let response = TestClient::get("http://127.0.0.1:5801/report.20240101.pdf")
+            .send(&service)
+            .await;
+        assert_eq!(response.headers()["cache-control"], "no-cache");
+        let response = TestClient::get("http://127.0.0.1:5801/missing.3f2a9c1b.js")
+            .send(&service)
+            .await;
+        assert!(!response.headers().contains_key("cache-control"));
+    }
+
     #[cfg(feature = "embed")]
     #[tokio::test]
     async fn test_serve_embed_files() {
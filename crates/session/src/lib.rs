@@ -18,6 +18,10 @@ external-datastore-backed session storage. For a list of currently
 available session stores, see [the documentation for
 async-session](https://github.com/http-rs/async-session).
 
+[`MemoryStore`] keeps sessions in memory and is useful for tests and development,
+[`RedisStore`] keeps sessions in Redis when the `redis-store` feature is enabled, and other
+stores can be added by implementing [`SessionStore`].
+
 ## Security
 
 Although each session store may have different security implications,
@@ -49,6 +53,24 @@ adversary were able to tamper with the expiry of a cookie, salvo
 sessions would still check the expiry on the contained session before
 using it
 
+The expiry is sliding by default: every request extends the session and its cookie by
+`session_ttl`. Disable `sliding_expiry` on the builder to expire sessions `session_ttl` after
+they are created.
+
+### Cookie attributes
+
+The session cookie is `HttpOnly` and `SameSite=Lax` by default, so it is not readable by scripts
+and not sent with cross-site subrequests and form posts. It is `Secure` when the request is served
+over https, use `cookie_secure` on the builder to force it, for example behind a TLS terminating
+proxy. A cookie with `SameSite=None` is always `Secure`.
+
+### Session rotation
+
+Regenerate the session id when the privilege of the user changes, such as on login, to prevent
+session fixation. Call [`Session::regenerate`] on the session in the depot, or set a new session
+with [`SessionDepotExt::set_session`]. The session stored with the old id is destroyed and a new
+cookie is sent.
+
 ### If anything goes wrong with the above process
 
 If there are any failures in the above session retrieval process, a
@@ -71,6 +93,13 @@ Read more: <https://salvo.rs>
 
 pub use async_session::{CookieStore, MemoryStore, Session, SessionStore};
 
+#[cfg(feature = "redis-store")]
+#[cfg_attr(docsrs, doc(cfg(feature = "redis-store")))]
+mod redis_store;
+#[cfg(feature = "redis-store")]
+#[cfg_attr(docsrs, doc(cfg(feature = "redis-store")))]
+pub use redis_store::RedisStore;
+
 use std::fmt::{self, Formatter};
 use std::time::Duration;
 
@@ -124,8 +153,10 @@ pub struct HandlerBuilder<S> {
     cookie_name: String,
     cookie_domain: Option<String>,
     session_ttl: Option<Duration>,
+    sliding_expiry: bool,
     save_unchanged: bool,
     same_site_policy: SameSite,
+    cookie_secure: Option<bool>,
     key: Key,
     fallback_keys: Vec<Key>,
 }
@@ -137,7 +168,9 @@ impl<S: SessionStore> fmt::Debug for HandlerBuilder<S> {
             .field("cookie_name", &self.cookie_name)
             .field("cookie_domain", &self.cookie_domain)
             .field("session_ttl", &self.session_ttl)
+            .field("sliding_expiry", &self.sliding_expiry)
             .field("same_site_policy", &self.same_site_policy)
+            .field("cookie_secure", &self.cookie_secure)
             .field("key", &"..")
             .field("fallback_keys", &"..")
             .field("save_unchanged", &self.save_unchanged)
@@ -159,7 +192,9 @@ where
             cookie_name: "salvo.session.id".into(),
             cookie_domain: None,
             same_site_policy: SameSite::Lax,
+            cookie_secure: None,
             session_ttl: Some(Duration::from_secs(24 * 60 * 60)),
+            sliding_expiry: true,
             key: Key::from(secret),
             fallback_keys: vec![],
         }
@@ -185,6 +220,16 @@ where
         self
     }
 
+    /// Sets whether every request extends the session by the session ttl.
+    ///
+    /// The default for this value is true. When it is disabled, the session
+    /// expires the session ttl after it is created.
+    #[inline]
+    pub fn sliding_expiry(mut self, sliding_expiry: bool) -> Self {
+        self.sliding_expiry = sliding_expiry;
+        self
+    }
+
     /// Sets the name of the cookie that the session is stored with or in.
     ///
     /// If you are running multiple tide applications on the same
@@ -221,6 +266,16 @@ where
         self
     }
 
+    /// Sets the `Secure` attribute of the session cookie.
+    ///
+    /// By default the cookie is secure when the request uri scheme is https.
+    /// Set it explicitly when the server is behind a TLS terminating proxy.
+    #[inline]
+    pub fn cookie_secure(mut self, secure: bool) -> Self {
+        self.cookie_secure = Some(secure);
+        self
+    }
+
     /// Sets the domain of the cookie.
     #[inline]
     pub fn cookie_domain(mut self, cookie_domain: impl AsRef<str>) -> Self {
@@ -250,7 +305,9 @@ where
             cookie_name,
             cookie_domain,
             session_ttl,
+            sliding_expiry,
             same_site_policy,
+            cookie_secure,
             key,
             fallback_keys,
         } = self;
@@ -268,7 +325,9 @@ where
             cookie_name,
             cookie_domain,
             session_ttl,
+            sliding_expiry,
             same_site_policy,
+            cookie_secure,
             hmac,
             fallback_hmacs,
         })
@@ -282,8 +341,10 @@ pub struct SessionHandler<S> {
     cookie_name: String,
     cookie_domain: Option<String>,
     session_ttl: Option<Duration>,
+    sliding_expiry: bool,
     save_unchanged: bool,
     same_site_policy: SameSite,
+    cookie_secure: Option<bool>,
     hmac: Hmac<Sha256>,
     fallback_hmacs: Vec<Hmac<Sha256>>,
 }
@@ -296,7 +357,9 @@ impl<S: SessionStore> fmt::Debug for SessionHandler<S> {
             .field("cookie_name", &self.cookie_name)
             .field("cookie_domain", &self.cookie_domain)
             .field("session_ttl", &self.session_ttl)
+            .field("sliding_expiry", &self.sliding_expiry)
             .field("same_site_policy", &self.same_site_policy)
+            .field("cookie_secure", &self.cookie_secure)
             .field("key", &"..")
             .field("fallback_keys", &"..")
             .field("save_unchanged", &self.save_unchanged)
@@ -318,10 +381,13 @@ where
         let cookie = req.cookies().get(&self.cookie_name);
         let cookie_value = cookie.and_then(|cookie| self.verify_signature(cookie.value()).ok());
 
-        let mut session = self.load_or_create(cookie_value).await;
+        let loaded = self.load(cookie_value.clone()).await;
+        let mut session = loaded.clone().unwrap_or_default();
 
         if let Some(ttl) = self.session_ttl {
-            session.expire_in(ttl);
+            if self.sliding_expiry || session.expiry().is_none() {
+                session.expire_in(ttl);
+            }
         }
 
         depot.set_session(session);
@@ -332,6 +398,15 @@ where
         }
 
         let session = depot.take_session().expect("session should exist in depot");
+        let rotated = loaded.filter(|loaded| loaded.id() != session.id());
+        if let Some(loaded) = rotated.clone() {
+            if let Err(e) = self.store.destroy_session(loaded).await {
+                tracing::error!(error = ?e, "unable to destroy rotated session");
+            }
+        }
+        let secure_cookie = self
+            .cookie_secure
+            .unwrap_or_else(|| req.uri().scheme() == Some(&Scheme::HTTPS));
         if session.is_destroyed() {
             if let Err(e) = self.store.destroy_session(session).await {
                 tracing::error!(error = ?e, "unable to destroy session");
@@ -339,9 +414,14 @@ where
             res.remove_cookie(&self.cookie_name);
         } else if self.save_unchanged || session.data_changed() {
             match self.store.store_session(session).await {
-                Ok(cookie_value) => {
+                Ok(new_cookie_value) => {
+                    // Refresh the cookie expiry if the store does not issue a new cookie.
+                    let cookie_value = new_cookie_value.or_else(|| {
+                        (self.sliding_expiry && self.session_ttl.is_some() && rotated.is_none())
+                            .then_some(cookie_value)
+                            .flatten()
+                    });
                     if let Some(cookie_value) = cookie_value {
-                        let secure_cookie = req.uri().scheme() == Some(&Scheme::HTTPS);
                         let cookie = self.build_cookie(secure_cookie, cookie_value);
                         res.add_cookie(cookie);
                     }
@@ -363,15 +443,13 @@ where
         HandlerBuilder::new(store, secret)
     }
     #[inline]
-    async fn load_or_create(&self, cookie_value: Option<String>) -> Session {
+    async fn load(&self, cookie_value: Option<String>) -> Option<Session> {
         let session = match cookie_value {
             Some(cookie_value) => self.store.load_session(cookie_value).await.ok().flatten(),
             None => None,
         };
 
-        session
-            .and_then(|session| session.validate())
-            .unwrap_or_default()
+        session.and_then(|session| session.validate())
     }
     // the following is reused verbatim from
     // https://github.com/SergioBenitez/cookie-rs/blob/master/src/secure/signed.rs#L51-L66
@@ -409,7 +487,7 @@ where
         let mut cookie = Cookie::build((self.cookie_name.clone(), cookie_value))
             .http_only(true)
             .same_site(self.same_site_policy)
-            .secure(secure)
+            .secure(secure || self.same_site_policy == SameSite::None)
             .path(self.cookie_path.clone())
             .build();
 
@@ -542,4 +620,68 @@ mod tests {
             .await;
         assert_eq!(respone.take_string().await.unwrap(), "home");
     }
+    #[tokio::test]
+    async fn test_session_rotation() {
+        #[handler]
+        pub async fn login(depot: &mut Depot) {
+            let session = depot.session_mut().unwrap();
+            session.regenerate();
+            session.insert("username", "salvo").unwrap();
+        }
+
+        #[handler]
+        pub async fn home(depot: &mut Depot, res: &mut Response) {
+            let username = depot.session().and_then(|s| s.get::<String>("username"));
+            res.render(username.unwrap_or_else(|| "guest".into()));
+        }
+
+        let store = MemoryStore::new();
+        let session_handler = SessionHandler::builder(
+            store.clone(),
+            b"secretabsecretabsecretabsecretabsecretabsecretabsecretabsecretab",
+        )
+        .cookie_secure(true)
+        .build()
+        .unwrap();
+        let router = Router::new()
+            .hoop(session_handler)
+            .get(home)
+            .push(Router::with_path("login").post(login));
+        let service = Service::new(router);
+        let cookie_of = |res: &Response| {
+            let cookie = res.headers().get(SET_COOKIE).unwrap().to_str().unwrap();
+            assert!(cookie.contains("HttpOnly") && cookie.contains("Secure"));
+            assert!(cookie.contains("SameSite=Lax") && cookie.contains("Expires"));
+            cookie.split(';').next().unwrap().to_owned()
+        };
+
+        let res = TestClient::get("http://127.0.0.1:5800/")
+            .send(&service)
+            .await;
+        let guest_cookie = cookie_of(&res);
+        let res = TestClient::post("http://127.0.0.1:5800/login")
+            .add_header(COOKIE, &guest_cookie, true)
+            .send(&service)
+            .await;
+        let user_cookie = cookie_of(&res);
+        assert_ne!(guest_cookie, user_cookie);
+        assert_eq!(store.count().await, 1);
+
+        let mut res = TestClient::get("http://127.0.0.1:5800/")
+            .add_header(COOKIE, &user_cookie, true)
+            .send(&service)
+            .await;
+        assert_eq!(
+            cookie_of(&res),
+            user_cookie,
+            "sliding expiry refreshes the cookie"
+        );
+        assert_eq!(res.take_string().await.unwrap(), "salvo");
+
+        let mut res = TestClient::get("http://127.0.0.1:5800/")
+            .add_header(COOKIE, &guest_cookie, true)
+            .send(&service)
+            .await;
+        assert_eq!(res.take_string().await.unwrap(), "guest");
+    }
 }
@@ -0,0 +1,138 @@
+use std::fmt::{self, Debug, Formatter};
+
+use async_session::{async_trait, serde_json, Result, Session, SessionStore};
+use redis::aio::ConnectionManager;
+use redis::{AsyncCommands, Client, IntoConnectionInfo, RedisResult};
+
+/// Session store backed by Redis.
+///
+/// Sessions are stored as JSON strings with the Redis expiry set to the session expiry, so stale
+/// sessions are cleaned up by Redis itself. The keys are the session ids with a prefix,
+/// `session:` by default, and [`SessionStore::clear_store`] only deletes the keys with this prefix.
+///
+/// The store keeps a [`ConnectionManager`], which is shared by its clones and reconnects when
+/// the connection is lost.
+///
+/// # Example
+///
+/// ```no_run
+/// use salvo_session::{RedisStore, SessionHandler};
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let store = RedisStore::open("redis://127.0.0.1/")
+///     .await
+///     .unwrap()
+///     .prefix("myapp:session:");
+/// let handler = SessionHandler::builder(
+///     store,
+///     b"secretabsecretabsecretabsecretabsecretabsecretabsecretabsecretab",
+/// )
+/// .build()
+/// .unwrap();
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct RedisStore {
+    connection: ConnectionManager,
+    prefix: String,
+}
+
+impl Debug for RedisStore {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RedisStore")
+            .field("prefix", &self.prefix)
+            .finish()
+    }
+}
+
+impl RedisStore {
+    /// Create a new `RedisStore` from a Redis client, it connects to Redis.
+    #[inline]
+    pub async fn new(client: Client) -> RedisResult<Self> {
+        Ok(Self {
+            connection: ConnectionManager::new(client).await?,
+            prefix: "session:".into(),
+        })
+    }
+
+    /// Create a new `RedisStore` connecting to the url, such as `redis://127.0.0.1/`.
+    #[inline]
+    pub async fn open(info: impl IntoConnectionInfo) -> RedisResult<Self> {
+        Self::new(Client::open(info)?).await
+    }
+
+    /// Sets the prefix of the keys, so several applications can share a Redis database.
+    ///
+    /// The default is `session:`.
+    #[inline]
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    fn key(&self, id: &str) -> String {
+        format!("{}{id}", self.prefix)
+    }
+
+    fn connection(&self) -> ConnectionManager {
+        self.connection.clone()
+    }
+}
+
+#[async_trait]
+impl SessionStore for RedisStore {
+    async fn load_session(&self, cookie_value: String) -> Result<Option<Session>> {
+        let id = Session::id_from_cookie_value(&cookie_value)?;
+        let record: Option<String> = self.connection().get(self.key(&id)).await?;
+        match record {
+            Some(value) => Ok(serde_json::from_str::<Session>(&value)?.validate()),
+            None => Ok(None),
+        }
+    }
+
+    async fn store_session(&self, session: Session) -> Result<Option<String>> {
+        let key = self.key(session.id());
+        let value = serde_json::to_string(&session)?;
+        let mut conn = self.connection();
+        match session.expires_in() {
+            Some(ttl) => {
+                conn.set_ex::<_, _, ()>(key, value, ttl.as_secs().max(1))
+                    .await?
+            }
+            None => conn.set::<_, _, ()>(key, value).await?,
+        }
+        session.reset_data_changed();
+        Ok(session.into_cookie_value())
+    }
+
+    async fn destroy_session(&self, session: Session) -> Result {
+        self.connection()
+            .del::<_, ()>(self.key(session.id()))
+            .await?;
+        Ok(())
+    }
+
+    async fn clear_store(&self) -> Result {
+        let mut conn = self.connection();
+        let pattern = format!("{}*", self.prefix);
+        let mut cursor = 0u64;
+        loop {
+            let (next, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+                .arg(cursor)
+                .arg("MATCH")
+                .arg(&pattern)
+                .arg("COUNT")
+                .arg(100)
+                .query_async(&mut conn)
+                .await?;
+            if !keys.is_empty() {
+                conn.del::<_, ()>(keys).await?;
+            }
+            if next == 0 {
+                return Ok(());
+            }
+            cursor = next;
+        }
+    }
+}
@@ -0,0 +1,25 @@
+use salvo::prelude::*;
+use salvo::writing::{Templates, TeraEngine};
+use serde_json::json;
+
+#[handler]
+async fn hello(req: &mut Request, depot: &mut Depot, res: &mut Response) {
+    let name = req.param::<&str>("name").unwrap_or("World");
+    res.render_template(depot, "hello.html", &json!({ "name": name }));
+}
+
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::fmt().init();
+
+    // The templates are reloaded before every render in debug builds.
+    let templates = concat!(env!("CARGO_MANIFEST_DIR"), "/templates");
+    let engine = TeraEngine::new(templates).expect("templates should be valid");
+
+    let router = Router::new()
+        .hoop(Templates::new(engine))
+        .get(hello)
+        .push(Router::with_path("hello/<name>").get(hello));
+    let acceptor = TcpListener::new("0.0.0.0:5800").bind().await;
+    Server::new(acceptor).serve(router).await;
+}